@@ -374,10 +374,17 @@ pub struct NativeOptions {
     /// persisted (only if the "persistence" feature is enabled).
     pub persist_window: bool,
 
-    /// The folder where `eframe` will store the app state. If not set, eframe will use a default
+    /// The file where `eframe` will store the app state. If not set, eframe will use a default
     /// data storage path for each target system.
+    ///
+    /// Not used by [`StorageBackend::Custom`].
     pub persistence_path: Option<std::path::PathBuf>,
 
+    /// How `eframe` stores the app state (only if the "persistence" feature is enabled).
+    ///
+    /// Defaults to [`StorageBackend::Ron`].
+    pub storage_backend: StorageBackend,
+
     /// Controls whether to apply dithering to minimize banding artifacts.
     ///
     /// Dithering assumes an sRGB output and thus will apply noise to any input value that lies between
@@ -415,6 +422,8 @@ impl Clone for NativeOptions {
 
             persistence_path: self.persistence_path.clone(),
 
+            storage_backend: self.storage_backend.clone(),
+
             #[cfg(target_os = "android")]
             android_app: self.android_app.clone(),
 
@@ -458,6 +467,8 @@ impl Default for NativeOptions {
 
             persistence_path: None,
 
+            storage_backend: StorageBackend::default(),
+
             dithering: true,
 
             #[cfg(target_os = "android")]
@@ -466,6 +477,36 @@ impl Default for NativeOptions {
     }
 }
 
+/// Creates a [`Storage`] from the app id, see [`StorageBackend::Custom`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type StorageCreator = std::sync::Arc<dyn Fn(&str) -> Option<Box<dyn Storage>>>;
+
+/// How a native app persists its state, see [`NativeOptions::storage_backend`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Default)]
+pub enum StorageBackend {
+    /// A single [RON](https://github.com/ron-rs/ron) file, `app.ron`,
+    /// that is rewritten on each save.
+    ///
+    /// Simple, but slow if you store a lot of state.
+    #[default]
+    Ron,
+
+    /// An append-only log, `app.ron.log`, where each save only adds the keys that changed.
+    ///
+    /// It is compacted once it grows too large.
+    /// This is faster for apps that store a lot of state, and more robust against crashes.
+    ///
+    /// The first time it is used, it starts from the state in `app.ron`,
+    /// so switching from [`Self::Ron`] keeps the saved state.
+    AppendLog,
+
+    /// Create your own [`Storage`] from the app id.
+    ///
+    /// Use [`crate::storage_dir`] to find where to put it.
+    Custom(StorageCreator),
+}
+
 // ----------------------------------------------------------------------------
 
 /// Options when using `eframe` in a web page.
@@ -887,6 +928,15 @@ pub trait Storage {
 
     /// write-to-disk or similar
     fn flush(&mut self);
+
+    /// Keep a copy of a value that could not be loaded, e.g. after a breaking format change,
+    /// so that it isn't lost when the key is overwritten.
+    ///
+    /// The default implementation just logs a warning.
+    fn store_backup(&self, key: &str, value: &str) {
+        _ = value;
+        log::warn!("Discarding value of {key:?} that failed to load: this storage has no backups");
+    }
 }
 
 /// Get and deserialize the [RON](https://github.com/ron-rs/ron) stored at the given key.
@@ -899,7 +949,8 @@ pub fn get_value<T: serde::de::DeserializeOwned>(storage: &dyn Storage, key: &st
             Ok(value) => Some(value),
             Err(err) => {
                 // This happens on when we break the format, e.g. when updating egui.
-                log::debug!("Failed to decode RON: {err}");
+                log::warn!("Failed to decode RON of {key:?}: {err}");
+                storage.store_backup(key, &value);
                 None
             }
         })
@@ -915,5 +966,278 @@ pub fn set_value<T: serde::Serialize>(storage: &mut dyn Storage, key: &str, valu
     }
 }
 
+/// A migration from one version of a stored value to the next, see [`Migrations`].
+#[cfg(feature = "ron")]
+type Migration = Box<dyn Fn(&str) -> Result<String, String>>;
+
+/// The schema version of a value in [`Storage`], and how to migrate older versions of it.
+///
+/// Use with [`get_versioned_value`] and [`set_versioned_value`].
+///
+/// Values stored with [`set_value`] count as version 0.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct LayoutV1 {
+///     sidebar_width: f32,
+/// }
+///
+/// #[derive(serde::Deserialize, serde::Serialize)]
+/// struct Layout {
+///     sidebar_width: f32,
+///     sidebar_open: bool,
+/// }
+///
+/// let migrations = eframe::Migrations::new(2).add(1, |old: LayoutV1| Layout {
+///     sidebar_width: old.sidebar_width,
+///     sidebar_open: true,
+/// });
+///
+/// # fn load(storage: &dyn eframe::Storage, migrations: &eframe::Migrations) {
+/// let layout: Option<Layout> = eframe::get_versioned_value(storage, "layout", migrations);
+/// # }
+/// ```
+#[cfg(feature = "ron")]
+pub struct Migrations {
+    version: u32,
+
+    /// Migrations from the key version to the next one.
+    steps: std::collections::BTreeMap<u32, Migration>,
+}
+
+#[cfg(feature = "ron")]
+impl Migrations {
+    /// The current version of the value, as written by [`set_versioned_value`].
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            steps: Default::default(),
+        }
+    }
+
+    /// Migrate a value from `from_version` to `from_version + 1`.
+    ///
+    /// Versions without a migration are assumed to be compatible with the next version.
+    #[must_use]
+    pub fn add<Old, New>(
+        mut self,
+        from_version: u32,
+        migrate: impl Fn(Old) -> New + 'static,
+    ) -> Self
+    where
+        Old: serde::de::DeserializeOwned,
+        New: serde::Serialize,
+    {
+        self.steps.insert(
+            from_version,
+            Box::new(move |ron| {
+                let old = ron::from_str(ron).map_err(|err| err.to_string())?;
+                ron::ser::to_string(&migrate(old)).map_err(|err| err.to_string())
+            }),
+        );
+        self
+    }
+
+    /// The current version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Migrate RON of version `from_version` to the current version.
+    fn migrate(&self, mut ron: String, from_version: u32) -> Result<String, String> {
+        if self.version < from_version {
+            return Err(format!(
+                "stored version {from_version} is newer than {}",
+                self.version
+            ));
+        }
+        for (version, migration) in self.steps.range(from_version..self.version) {
+            ron = migration(&ron).map_err(|err| format!("migration from {version}: {err}"))?;
+        }
+        Ok(ron)
+    }
+}
+
+#[cfg(feature = "ron")]
+fn version_key(key: &str) -> String {
+    format!("{key}.version")
+}
+
+/// Get the value at the given key, migrating it to the current version.
+///
+/// Values that can't be migrated or deserialized are passed to [`Storage::store_backup`].
+///
+/// See [`Migrations`].
+#[cfg(feature = "ron")]
+pub fn get_versioned_value<T: serde::de::DeserializeOwned>(
+    storage: &dyn Storage,
+    key: &str,
+    migrations: &Migrations,
+) -> Option<T> {
+    profiling::function_scope!(key);
+    let value = storage.get_string(key)?;
+    let version = storage
+        .get_string(&version_key(key))
+        .and_then(|version| version.parse().ok())
+        .unwrap_or(0);
+
+    let result = migrations
+        .migrate(value.clone(), version)
+        .and_then(|ron| ron::from_str(&ron).map_err(|err| err.to_string()));
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!("Failed to load version {version} of {key:?}: {err}");
+            storage.store_backup(key, &value);
+            None
+        }
+    }
+}
+
+/// Store the value with the given key, tagged with the current version of the [`Migrations`].
+///
+/// Load it with [`get_versioned_value`].
+#[cfg(feature = "ron")]
+pub fn set_versioned_value<T: serde::Serialize>(
+    storage: &mut dyn Storage,
+    key: &str,
+    migrations: &Migrations,
+    value: &T,
+) {
+    set_value(storage, key, value);
+    storage.set_string(&version_key(key), migrations.version().to_string());
+}
+
 /// [`Storage`] key used for app
 pub const APP_KEY: &str = "app";
+
+#[cfg(all(test, feature = "ron"))]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStorage {
+        kv: HashMap<String, String>,
+        backups: RefCell<Vec<(String, String)>>,
+    }
+
+    impl Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.kv.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.kv.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+
+        fn store_backup(&self, key: &str, value: &str) {
+            self.backups
+                .borrow_mut()
+                .push((key.to_owned(), value.to_owned()));
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct V0 {
+        width: f32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct V1 {
+        size: (f32, f32),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct V2 {
+        size: (f32, f32),
+        open: bool,
+    }
+
+    fn migrations() -> Migrations {
+        Migrations::new(2)
+            .add(0, |old: V0| V1 {
+                size: (old.width, 100.0),
+            })
+            .add(1, |old: V1| V2 {
+                size: old.size,
+                open: true,
+            })
+    }
+
+    #[test]
+    fn test_migrate_unversioned_value() {
+        let mut storage = MemoryStorage::default();
+        set_value(&mut storage, "layout", &V0 { width: 42.0 });
+
+        let value: Option<V2> = get_versioned_value(&storage, "layout", &migrations());
+        assert_eq!(
+            value,
+            Some(V2 {
+                size: (42.0, 100.0),
+                open: true
+            })
+        );
+        assert!(storage.backups.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_versioned_roundtrip() {
+        let mut storage = MemoryStorage::default();
+        let value = V2 {
+            size: (1.0, 2.0),
+            open: false,
+        };
+        set_versioned_value(&mut storage, "layout", &migrations(), &value);
+        assert_eq!(
+            get_versioned_value(&storage, "layout", &migrations()),
+            Some(value)
+        );
+
+        // Only the migrations from the stored version are run:
+        set_versioned_value(
+            &mut storage,
+            "layout",
+            &Migrations::new(1),
+            &V1 { size: (3.0, 4.0) },
+        );
+        assert_eq!(
+            get_versioned_value(&storage, "layout", &migrations()),
+            Some(V2 {
+                size: (3.0, 4.0),
+                open: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_backup_on_failure() {
+        let mut storage = MemoryStorage::default();
+        storage.set_string("layout", "garbage".to_owned());
+        assert_eq!(get_value::<V0>(&storage, "layout"), None);
+        assert_eq!(
+            get_versioned_value::<V2>(&storage, "layout", &migrations()),
+            None
+        );
+
+        // Stored by a newer version of the app:
+        set_versioned_value(
+            &mut storage,
+            "layout",
+            &Migrations::new(3),
+            &V0 { width: 1.0 },
+        );
+        assert_eq!(
+            get_versioned_value::<V2>(&storage, "layout", &migrations()),
+            None
+        );
+
+        let backups = storage.backups.borrow();
+        assert_eq!(backups.len(), 3);
+        assert_eq!(backups[0], ("layout".to_owned(), "garbage".to_owned()));
+        assert_eq!(backups[2], ("layout".to_owned(), "(width:1.0)".to_owned()));
+    }
+}
@@ -2,7 +2,6 @@
 
 use web_time::Instant;
 
-use std::path::PathBuf;
 use winit::event_loop::ActiveEventLoop;
 
 use raw_window_handle::{HasDisplayHandle as _, HasWindowHandle as _};
@@ -128,22 +127,46 @@ fn largest_monitor_point_size(egui_zoom_factor: f32, event_loop: &ActiveEventLoo
 // ----------------------------------------------------------------------------
 
 /// For loading/saving app state and/or egui memory to disk.
-pub fn create_storage(_app_name: &str) -> Option<Box<dyn epi::Storage>> {
+///
+/// Uses [`epi::NativeOptions::storage_backend`] and [`epi::NativeOptions::persistence_path`].
+pub fn create_storage(
+    native_options: &epi::NativeOptions,
+    app_name: &str,
+) -> Option<Box<dyn epi::Storage>> {
     #[cfg(feature = "persistence")]
-    if let Some(storage) = super::file_storage::FileStorage::from_app_id(_app_name) {
-        return Some(Box::new(storage));
-    }
-    None
-}
+    {
+        use super::{file_storage::FileStorage, log_storage::LogStorage};
 
-#[expect(clippy::unnecessary_wraps)]
-pub fn create_storage_with_file(_file: impl Into<PathBuf>) -> Option<Box<dyn epi::Storage>> {
-    #[cfg(feature = "persistence")]
-    return Some(Box::new(
-        super::file_storage::FileStorage::from_ron_filepath(_file),
-    ));
+        let app_id = native_options
+            .viewport
+            .app_id
+            .as_deref()
+            .unwrap_or(app_name);
+        let path = native_options.persistence_path.clone();
+
+        match &native_options.storage_backend {
+            epi::StorageBackend::Ron => {
+                if let Some(path) = path {
+                    Some(Box::new(FileStorage::from_ron_filepath(path)))
+                } else {
+                    FileStorage::from_app_id(app_id).map(|s| Box::new(s) as _)
+                }
+            }
+            epi::StorageBackend::AppendLog => {
+                if let Some(path) = path {
+                    Some(Box::new(LogStorage::from_filepath(path)))
+                } else {
+                    LogStorage::from_app_id(app_id).map(|s| Box::new(s) as _)
+                }
+            }
+            epi::StorageBackend::Custom(create_storage) => create_storage(app_id),
+        }
+    }
     #[cfg(not(feature = "persistence"))]
-    None
+    {
+        _ = (native_options, app_name);
+        None
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -344,8 +367,14 @@ impl EpiIntegration {
             }
             if _app.persist_egui_memory() {
                 profiling::scope!("egui_memory");
-                self.egui_ctx
-                    .memory(|mem| epi::set_value(storage, STORAGE_EGUI_MEMORY_KEY, mem));
+                self.egui_ctx.memory(|mem| {
+                    epi::set_versioned_value(
+                        storage,
+                        STORAGE_EGUI_MEMORY_KEY,
+                        &epi::Migrations::new(egui::Memory::SERIALIZATION_VERSION),
+                        mem,
+                    );
+                });
             }
             {
                 profiling::scope!("App::save");
@@ -383,7 +412,11 @@ pub fn load_egui_memory(_storage: Option<&dyn epi::Storage>) -> Option<egui::Mem
     profiling::function_scope!();
     #[cfg(feature = "persistence")]
     {
-        epi::get_value(_storage?, STORAGE_EGUI_MEMORY_KEY)
+        epi::get_versioned_value(
+            _storage?,
+            STORAGE_EGUI_MEMORY_KEY,
+            &epi::Migrations::new(egui::Memory::SERIALIZATION_VERSION),
+        )
     }
     #[cfg(not(feature = "persistence"))]
     None
@@ -160,6 +160,10 @@ impl crate::Storage for FileStorage {
         }
     }
 
+    fn store_backup(&self, key: &str, value: &str) {
+        write_backup(&self.ron_filepath, key, value);
+    }
+
     fn flush(&mut self) {
         if self.dirty {
             profiling::scope!("FileStorage::flush");
@@ -190,45 +194,93 @@ impl crate::Storage for FileStorage {
     }
 }
 
-fn save_to_disk(file_path: &PathBuf, kv: &HashMap<String, String>) {
+fn save_to_disk(file_path: &Path, kv: &HashMap<String, String>) {
     profiling::function_scope!();
 
+    let result = write_atomically(file_path, |writer| {
+        profiling::scope!("ron::serialize");
+        ron::Options::default()
+            .to_io_writer_pretty(writer, &kv, Default::default())
+            .map_err(std::io::Error::other)
+    });
+    match result {
+        Ok(()) => log::trace!("Persisted to {:?}", file_path),
+        Err(err) => log::warn!("Failed to save app state to {file_path:?}: {err}"),
+    }
+}
+
+/// Write to a temporary file first, and then rename it to `file_path`,
+/// so that a crash while writing doesn't leave a half-written file behind.
+pub(super) fn write_atomically(
+    file_path: &Path,
+    write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> std::io::Result<()>,
+) -> std::io::Result<()> {
     if let Some(parent_dir) = file_path.parent() {
         if !parent_dir.exists() {
-            if let Err(err) = std::fs::create_dir_all(parent_dir) {
-                log::warn!("Failed to create directory {parent_dir:?}: {err}");
-            }
+            std::fs::create_dir_all(parent_dir)?;
         }
     }
 
-    match std::fs::File::create(file_path) {
-        Ok(file) => {
-            let mut writer = std::io::BufWriter::new(file);
-            let config = Default::default();
-
-            profiling::scope!("ron::serialize");
-            if let Err(err) = ron::Options::default()
-                .to_io_writer_pretty(&mut writer, &kv, config)
-                .and_then(|_| writer.flush().map_err(|err| err.into()))
-            {
-                log::warn!("Failed to serialize app state: {}", err);
-            } else {
-                log::trace!("Persisted to {:?}", file_path);
-            }
-        }
-        Err(err) => {
-            log::warn!("Failed to create file {file_path:?}: {err}");
-        }
+    let mut tmp_path = file_path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    drop(writer);
+    std::fs::rename(&tmp_path, file_path)
+}
+
+/// Save a value that failed to load next to `file_path`, see [`crate::Storage::store_backup`].
+pub(super) fn write_backup(file_path: &Path, key: &str, value: &str) {
+    let key: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let backup_path = backup_path(file_path, &key);
+    log::warn!("Backing up value that failed to load to {backup_path:?}");
+    if let Err(err) = std::fs::write(&backup_path, value) {
+        log::warn!("Failed to write backup {backup_path:?}: {err}");
     }
 }
 
+/// A new path next to `file_path` to back up (part of) it to,
+/// e.g. `app.ron` -> `app.egui.backup-1700000000.ron`.
+///
+/// If there already is a backup from the same second, a counter is added,
+/// e.g. `app.egui.backup-1700000000-1.ron`.
+pub(super) fn backup_path(file_path: &Path, label: &str) -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let stem = file_path
+        .file_stem()
+        .map_or_else(|| "app".into(), |stem| stem.to_string_lossy());
+    let name = if label.is_empty() {
+        format!("{stem}.backup-{timestamp}")
+    } else {
+        format!("{stem}.{label}.backup-{timestamp}")
+    };
+
+    let mut path = file_path.with_file_name(format!("{name}.ron"));
+    let mut counter = 0;
+    while path.exists() {
+        counter += 1;
+        path = file_path.with_file_name(format!("{name}-{counter}.ron"));
+    }
+    path
+}
+
 // ----------------------------------------------------------------------------
 
-fn read_ron<T>(ron_path: impl AsRef<Path>) -> Option<T>
+pub(super) fn read_ron<T>(ron_path: impl AsRef<Path>) -> Option<T>
 where
     T: serde::de::DeserializeOwned,
 {
     profiling::function_scope!();
+    let ron_path = ron_path.as_ref();
     match std::fs::File::open(ron_path) {
         Ok(file) => {
             let reader = std::io::BufReader::new(file);
@@ -236,6 +288,13 @@ where
                 Ok(value) => Some(value),
                 Err(err) => {
                     log::warn!("Failed to parse RON: {}", err);
+
+                    // Don't lose the data when we overwrite the file on the next save:
+                    let backup_path = backup_path(ron_path, "");
+                    log::warn!("Backing up {ron_path:?} to {backup_path:?}");
+                    if let Err(err) = std::fs::copy(ron_path, &backup_path) {
+                        log::warn!("Failed to back up {ron_path:?}: {err}");
+                    }
                     None
                 }
             }
@@ -265,4 +324,23 @@ mod tests {
             assert_eq!(directories_storage_dir(app_id), storage_dir(app_id));
         }
     }
+
+    #[test]
+    #[expect(clippy::disallowed_methods)] // `temp_dir`, since `tempfile` isn't a dependency
+    fn backups_in_the_same_second_dont_overwrite_each_other() {
+        let dir =
+            std::env::temp_dir().join(format!("eframe_test_backup_path_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("app.ron");
+
+        let mut backups = vec![];
+        for i in 0..3 {
+            let backup = backup_path(&file_path, "egui");
+            assert!(!backups.contains(&backup), "{backup:?} was already used");
+            std::fs::write(&backup, format!("backup {i}")).unwrap();
+            backups.push(backup);
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
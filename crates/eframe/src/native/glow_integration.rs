@@ -196,17 +196,7 @@ impl<'app> GlowWinitApp<'app> {
     ) -> Result<&mut GlowWinitRunning<'app>> {
         profiling::function_scope!();
 
-        let storage = if let Some(file) = &self.native_options.persistence_path {
-            epi_integration::create_storage_with_file(file)
-        } else {
-            epi_integration::create_storage(
-                self.native_options
-                    .viewport
-                    .app_id
-                    .as_ref()
-                    .unwrap_or(&self.app_name),
-            )
-        };
+        let storage = epi_integration::create_storage(&self.native_options, &self.app_name);
 
         let egui_ctx = create_egui_context(storage.as_deref());
 
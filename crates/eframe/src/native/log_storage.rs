@@ -0,0 +1,430 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write as _,
+    path::PathBuf,
+};
+
+use super::file_storage::{backup_path, read_ron, storage_dir, write_atomically, write_backup};
+
+/// Compact the log when it is this many times larger than the live data.
+const COMPACTION_FACTOR: u64 = 4;
+
+/// Never compact logs smaller than this.
+const MIN_COMPACTION_SIZE: u64 = 64 * 1024;
+
+/// A key-value store backed by an append-only log file on disk.
+///
+/// Unlike [`super::file_storage::FileStorage`], which rewrites the whole file on each save,
+/// [`crate::Storage::flush`] only appends the keys that changed since the last flush.
+/// This makes saving cheap even if the app stores a lot of state.
+///
+/// Each change is one line of [RON](https://github.com/ron-rs/ron),
+/// so a crash in the middle of a write loses at most that change.
+/// Once the log has grown much larger than the data in it, it is compacted
+/// by writing the current state to a temporary file and renaming it over the log.
+/// Like with [`super::file_storage::FileStorage`], the writing happens on a background thread.
+///
+/// When there is no log yet, the state is imported from the `.ron` file of
+/// [`super::file_storage::FileStorage`] next to it (e.g. `app.ron` for `app.ron.log`),
+/// so switching backends keeps the saved state.
+///
+/// Select it with [`crate::StorageBackend::AppendLog`].
+pub struct LogStorage {
+    log_path: PathBuf,
+    kv: HashMap<String, String>,
+
+    /// Keys changed since the last flush.
+    dirty: BTreeSet<String>,
+
+    /// `None` while it is saving on a background thread.
+    writer: Option<LogWriter>,
+    last_save_join_handle: Option<std::thread::JoinHandle<LogWriter>>,
+}
+
+impl Drop for LogStorage {
+    fn drop(&mut self) {
+        if let Some(join_handle) = self.last_save_join_handle.take() {
+            profiling::scope!("wait_for_save");
+            join_handle.join().ok();
+        }
+    }
+}
+
+impl LogStorage {
+    /// Store the state in this log file.
+    pub(crate) fn from_filepath(log_path: impl Into<PathBuf>) -> Self {
+        profiling::function_scope!();
+        let log_path: PathBuf = log_path.into();
+        log::debug!("Loading app state from {:?}…", log_path);
+
+        let (kv, log_size, needs_compaction) = match std::fs::read(&log_path) {
+            Ok(bytes) => {
+                let log = String::from_utf8_lossy(&bytes);
+                let (kv, is_corrupt) = parse_log(&log);
+                if is_corrupt {
+                    log::warn!("Ignoring corrupt entries in {log_path:?}");
+                    let backup_path = backup_path(&log_path, "");
+                    if let Err(err) = std::fs::copy(&log_path, &backup_path) {
+                        log::warn!("Failed to back up {log_path:?}: {err}");
+                    }
+                }
+                (kv, bytes.len() as u64, is_corrupt)
+            }
+            Err(_err) => {
+                // File probably doesn't exist.
+                // If the app used `FileStorage` before, start from its state,
+                // and write all of it to the log on the first flush:
+                let ron_path = log_path.with_extension("");
+                let imported = if log_path.extension() == Some("log".as_ref()) {
+                    read_ron(&ron_path)
+                } else {
+                    None
+                };
+                if let Some(kv) = imported {
+                    log::info!("Importing app state from {ron_path:?}");
+                    (kv, 0, true)
+                } else {
+                    (HashMap::default(), 0, false)
+                }
+            }
+        };
+
+        Self {
+            writer: Some(LogWriter {
+                log_path: log_path.clone(),
+                log_size,
+                needs_compaction,
+            }),
+            log_path,
+            kv,
+            dirty: Default::default(),
+            last_save_join_handle: None,
+        }
+    }
+
+    /// Find a good place to put the files that the OS likes.
+    pub fn from_app_id(app_id: &str) -> Option<Self> {
+        profiling::function_scope!();
+        if let Some(data_dir) = storage_dir(app_id) {
+            if let Err(err) = std::fs::create_dir_all(&data_dir) {
+                log::warn!(
+                    "Saving disabled: Failed to create app path at {:?}: {}",
+                    data_dir,
+                    err
+                );
+                None
+            } else {
+                Some(Self::from_filepath(data_dir.join("app.ron.log")))
+            }
+        } else {
+            log::warn!("Saving disabled: Failed to find path to data_dir.");
+            None
+        }
+    }
+
+    /// Wait for the previous save to complete, and get back the [`LogWriter`].
+    fn wait_for_save(&mut self) {
+        if let Some(join_handle) = self.last_save_join_handle.take() {
+            let writer = join_handle.join().unwrap_or_else(|_| {
+                // We don't know how much made it to disk, so start over:
+                LogWriter::rewrite(self.log_path.clone())
+            });
+            self.writer = Some(writer);
+        }
+    }
+}
+
+impl crate::Storage for LogStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.kv.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.kv.get(key) != Some(&value) {
+            self.kv.insert(key.to_owned(), value);
+            self.dirty.insert(key.to_owned());
+        }
+    }
+
+    fn store_backup(&self, key: &str, value: &str) {
+        write_backup(&self.log_path, key, value);
+    }
+
+    fn flush(&mut self) {
+        if self
+            .last_save_join_handle
+            .as_ref()
+            .is_some_and(|join_handle| join_handle.is_finished())
+        {
+            self.wait_for_save();
+        }
+        let needs_compaction = self
+            .writer
+            .as_ref()
+            .is_some_and(|writer| writer.needs_compaction);
+        if self.dirty.is_empty() && !needs_compaction {
+            return;
+        }
+        profiling::scope!("LogStorage::flush");
+
+        self.wait_for_save();
+        let Some(writer) = self.writer.take() else {
+            return;
+        };
+
+        let live_size = self
+            .kv
+            .iter()
+            .map(|(key, value)| (key.len() + value.len()) as u64)
+            .sum();
+        let compact = writer.should_compact(live_size);
+        let entries: Vec<(String, String)> = if compact {
+            let mut entries: Vec<(String, String)> = self
+                .kv
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            entries.sort();
+            entries
+        } else {
+            self.dirty
+                .iter()
+                .filter_map(|key| Some((key.clone(), self.kv.get(key)?.clone())))
+                .collect()
+        };
+        self.dirty.clear();
+
+        let result = std::thread::Builder::new()
+            .name("eframe_persist".to_owned())
+            .spawn(move || writer.save(&entries, compact));
+        match result {
+            Ok(join_handle) => {
+                self.last_save_join_handle = Some(join_handle);
+            }
+            Err(err) => {
+                log::warn!("Failed to spawn thread to save app state: {err}");
+                self.writer = Some(LogWriter::rewrite(self.log_path.clone()));
+            }
+        }
+    }
+}
+
+/// Writes to the log file, on a background thread.
+struct LogWriter {
+    log_path: PathBuf,
+
+    /// Current size of the log file.
+    log_size: u64,
+
+    /// Rewrite the log on the next flush, e.g. because its last line was only partially written.
+    needs_compaction: bool,
+}
+
+impl LogWriter {
+    /// A writer that replaces the log with the current state on the next flush.
+    fn rewrite(log_path: PathBuf) -> Self {
+        Self {
+            log_path,
+            log_size: 0,
+            needs_compaction: true,
+        }
+    }
+
+    /// `live_size` is the size of all keys and values.
+    fn should_compact(&self, live_size: u64) -> bool {
+        self.needs_compaction
+            || (MIN_COMPACTION_SIZE < self.log_size
+                && COMPACTION_FACTOR * live_size < self.log_size)
+    }
+
+    /// Append the entries to the log, or replace the log with them if `compact` is set.
+    fn save(mut self, entries: &[(String, String)], compact: bool) -> Self {
+        profiling::function_scope!();
+
+        let result = if compact {
+            self.compact(entries)
+        } else {
+            self.append(entries)
+        };
+
+        match result {
+            Ok(()) => {
+                log::trace!("Persisted to {:?}", self.log_path);
+            }
+            Err(err) => {
+                // We don't know how much made it to disk, so start over next time:
+                log::warn!("Failed to save app state to {:?}: {err}", self.log_path);
+                self.needs_compaction = true;
+            }
+        }
+        self
+    }
+
+    fn append(&mut self, entries: &[(String, String)]) -> std::io::Result<()> {
+        let mut records = String::new();
+        for (key, value) in entries {
+            records += &record(key, value)?;
+        }
+
+        if let Some(parent_dir) = self.log_path.parent() {
+            if !parent_dir.exists() {
+                std::fs::create_dir_all(parent_dir)?;
+            }
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        writer.write_all(records.as_bytes())?;
+        writer.flush()?;
+        writer.get_ref().sync_data()?;
+
+        self.log_size += records.len() as u64;
+        Ok(())
+    }
+
+    /// Replace the log with one that only has these entries.
+    fn compact(&mut self, entries: &[(String, String)]) -> std::io::Result<()> {
+        profiling::function_scope!();
+        let mut log = String::new();
+        for (key, value) in entries {
+            log += &record(key, value)?;
+        }
+        write_atomically(&self.log_path, |writer| writer.write_all(log.as_bytes()))?;
+
+        self.log_size = log.len() as u64;
+        self.needs_compaction = false;
+        Ok(())
+    }
+}
+
+/// One line of the log.
+fn record(key: &str, value: &str) -> std::io::Result<String> {
+    let mut line = ron::to_string(&(key, value)).map_err(std::io::Error::other)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Replay the log, where later lines overwrite earlier ones.
+///
+/// Also returns `true` if any line could not be parsed,
+/// e.g. because the app crashed while writing it.
+fn parse_log(log: &str) -> (HashMap<String, String>, bool) {
+    let mut kv = HashMap::default();
+    let mut is_corrupt = !log.is_empty() && !log.ends_with('\n');
+    for line in log.lines().filter(|line| !line.is_empty()) {
+        match ron::from_str::<(String, String)>(line) {
+            Ok((key, value)) => {
+                kv.insert(key, value);
+            }
+            Err(err) => {
+                log::debug!("Failed to parse log entry: {err}");
+                is_corrupt = true;
+            }
+        }
+    }
+    (kv, is_corrupt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_log() {
+        let log = [
+            record("app", "(counter: 1)").unwrap(),
+            record("egui", "multi\nline \"value\"").unwrap(),
+            record("app", "(counter: 2)").unwrap(),
+        ]
+        .concat();
+        assert_eq!(log.lines().count(), 3);
+
+        let (kv, is_corrupt) = parse_log(&log);
+        assert!(!is_corrupt);
+        assert_eq!(kv.len(), 2);
+        assert_eq!(kv["app"], "(counter: 2)");
+        assert_eq!(kv["egui"], "multi\nline \"value\"");
+    }
+
+    #[test]
+    fn test_partially_written_log() {
+        let first = record("app", "(counter: 1)").unwrap();
+        let second = record("app", "(counter: 2)").unwrap();
+        let log = format!("{first}{}", &second[..second.len() / 2]);
+
+        let (kv, is_corrupt) = parse_log(&log);
+        assert!(
+            is_corrupt,
+            "The next append must not continue the broken line"
+        );
+        assert_eq!(kv["app"], "(counter: 1)");
+    }
+
+    #[test]
+    #[expect(clippy::disallowed_methods)] // `temp_dir`, since `tempfile` isn't a dependency
+    fn test_save_and_load() {
+        use crate::Storage as _;
+
+        let log_path = std::env::temp_dir().join(format!(
+            "eframe_test_log_storage_{}.ron.log",
+            std::process::id()
+        ));
+        std::fs::remove_file(&log_path).ok();
+
+        let mut storage = LogStorage::from_filepath(&log_path);
+        storage.set_string("app", "(counter: 1)".to_owned());
+        storage.set_string("egui", "()".to_owned());
+        storage.flush();
+        storage.set_string("app", "(counter: 2)".to_owned());
+        storage.flush(); // Waits for the first save
+        drop(storage); // Waits for the second save
+
+        let storage = LogStorage::from_filepath(&log_path);
+        assert_eq!(storage.get_string("app").as_deref(), Some("(counter: 2)"));
+        assert_eq!(storage.get_string("egui").as_deref(), Some("()"));
+        let writer = storage.writer.as_ref().unwrap();
+        assert!(!writer.needs_compaction);
+        assert_eq!(
+            std::fs::read_to_string(&log_path).unwrap().lines().count(),
+            3
+        );
+        drop(storage);
+
+        std::fs::remove_file(&log_path).ok();
+    }
+
+    #[test]
+    #[expect(clippy::disallowed_methods)] // `temp_dir`, since `tempfile` isn't a dependency
+    fn test_import_from_ron() {
+        use super::super::file_storage::FileStorage;
+        use crate::Storage as _;
+
+        let dir = std::env::temp_dir().join(format!(
+            "eframe_test_log_storage_import_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ron_path = dir.join("app.ron");
+        let log_path = dir.join("app.ron.log");
+
+        let mut ron_storage = FileStorage::from_ron_filepath(&ron_path);
+        ron_storage.set_string("app", "(counter: 1)".to_owned());
+        ron_storage.flush();
+        drop(ron_storage); // Waits for the save
+
+        let mut storage = LogStorage::from_filepath(&log_path);
+        assert_eq!(storage.get_string("app").as_deref(), Some("(counter: 1)"));
+        storage.flush();
+        drop(storage);
+
+        // The log now has the state on its own:
+        std::fs::remove_file(&ron_path).unwrap();
+        let storage = LogStorage::from_filepath(&log_path);
+        assert_eq!(storage.get_string("app").as_deref(), Some("(counter: 1)"));
+        drop(storage);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -7,6 +7,10 @@ pub mod run;
 #[cfg(feature = "persistence")]
 pub mod file_storage;
 
+/// Append-log file storage which can be used by native backends.
+#[cfg(feature = "persistence")]
+pub mod log_storage;
+
 pub(crate) mod winit_integration;
 
 #[cfg(feature = "glow")]
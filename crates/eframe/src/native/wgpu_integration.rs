@@ -389,17 +389,7 @@ impl WinitApp for WgpuWinitApp<'_> {
             self.recreate_window(event_loop, running);
             running
         } else {
-            let storage = if let Some(file) = &self.native_options.persistence_path {
-                epi_integration::create_storage_with_file(file)
-            } else {
-                epi_integration::create_storage(
-                    self.native_options
-                        .viewport
-                        .app_id
-                        .as_ref()
-                        .unwrap_or(&self.app_name),
-                )
-            };
+            let storage = epi_integration::create_storage(&self.native_options, &self.app_name);
             let egui_ctx = winit_integration::create_egui_context(storage.as_deref());
             let (window, builder) = create_window(
                 &egui_ctx,
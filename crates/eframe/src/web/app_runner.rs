@@ -50,7 +50,7 @@ impl AppRunner {
             },
             cpu_usage: None,
         };
-        let storage = LocalStorage::default();
+        let storage = super::storage::LocalStorage::default();
 
         egui_ctx.set_os(egui::os::OperatingSystem::from_user_agent(
             &super::user_agent().unwrap_or_default(),
@@ -372,20 +372,3 @@ impl AppRunner {
         }
     }
 }
-
-// ----------------------------------------------------------------------------
-
-#[derive(Default)]
-struct LocalStorage {}
-
-impl epi::Storage for LocalStorage {
-    fn get_string(&self, key: &str) -> Option<String> {
-        super::storage::local_storage_get(key)
-    }
-
-    fn set_string(&mut self, key: &str, value: String) {
-        super::storage::local_storage_set(key, &value);
-    }
-
-    fn flush(&mut self) {}
-}
@@ -12,17 +12,42 @@ pub fn local_storage_set(key: &str, value: &str) {
     local_storage().map(|storage| storage.set_item(key, value));
 }
 
+/// [`crate::Storage`] backed by local storage.
+#[derive(Default)]
+pub(crate) struct LocalStorage {}
+
+impl crate::Storage for LocalStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        local_storage_get(key)
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        local_storage_set(key, &value);
+    }
+
+    fn flush(&mut self) {}
+
+    fn store_backup(&self, key: &str, value: &str) {
+        let backup_key = format!("{key}.backup");
+        log::warn!("Backing up {key:?} to {backup_key:?}");
+        local_storage_set(&backup_key, value);
+    }
+}
+
+#[cfg(feature = "persistence")]
+const EGUI_MEMORY_KEY: &str = "egui_memory_ron";
+
+#[cfg(feature = "persistence")]
+fn egui_memory_migrations() -> crate::Migrations {
+    crate::Migrations::new(egui::Memory::SERIALIZATION_VERSION)
+}
+
 #[cfg(feature = "persistence")]
 pub(crate) fn load_memory(ctx: &egui::Context) {
-    if let Some(memory_string) = local_storage_get("egui_memory_ron") {
-        match ron::from_str(&memory_string) {
-            Ok(memory) => {
-                ctx.memory_mut(|m| *m = memory);
-            }
-            Err(err) => {
-                log::warn!("Failed to parse memory RON: {err}");
-            }
-        }
+    if let Some(memory) =
+        crate::get_versioned_value(&LocalStorage {}, EGUI_MEMORY_KEY, &egui_memory_migrations())
+    {
+        ctx.memory_mut(|m| *m = memory);
     }
 }
 
@@ -31,14 +56,14 @@ pub(crate) fn load_memory(_: &egui::Context) {}
 
 #[cfg(feature = "persistence")]
 pub(crate) fn save_memory(ctx: &egui::Context) {
-    match ctx.memory(ron::to_string) {
-        Ok(ron) => {
-            local_storage_set("egui_memory_ron", &ron);
-        }
-        Err(err) => {
-            log::warn!("Failed to serialize memory as RON: {err}");
-        }
-    }
+    ctx.memory(|memory| {
+        crate::set_versioned_value(
+            &mut LocalStorage {},
+            EGUI_MEMORY_KEY,
+            &egui_memory_migrations(),
+            memory,
+        );
+    });
 }
 
 #[cfg(not(feature = "persistence"))]
@@ -0,0 +1,418 @@
+//! Keyframe and spring animations, see [`Track`] and [`Spring`].
+//!
+//! These are played with [`crate::Context::animate_track`] and [`crate::Context::animate_spring`].
+//! For animating a single `bool` or `f32`, see [`crate::Context::animate_bool`] and [`crate::Context::animate_value_with_time`].
+
+use emath::easing::Easing;
+
+use crate::{Color32, Pos2, Rect, Vec2, pos2, vec2};
+
+/// A value that can be animated, like an `f32`, [`Vec2`], [`Color32`] or [`Rect`].
+///
+/// Animations work on up to four `f32` components, which are interpolated separately.
+pub trait Animatable: Copy + PartialEq + Send + Sync + 'static {
+    /// The components of the value. Unused components should be zero.
+    fn to_components(self) -> [f32; 4];
+
+    fn from_components(components: [f32; 4]) -> Self;
+
+    /// Interpolate between `self` (at `t = 0`) and `other` (at `t = 1`).
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let (a, b) = (self.to_components(), other.to_components());
+        Self::from_components(std::array::from_fn(|i| emath::lerp(a[i]..=b[i], t)))
+    }
+}
+
+impl Animatable for f32 {
+    fn to_components(self) -> [f32; 4] {
+        [self, 0.0, 0.0, 0.0]
+    }
+
+    fn from_components([x, ..]: [f32; 4]) -> Self {
+        x
+    }
+}
+
+impl Animatable for Vec2 {
+    fn to_components(self) -> [f32; 4] {
+        [self.x, self.y, 0.0, 0.0]
+    }
+
+    fn from_components([x, y, ..]: [f32; 4]) -> Self {
+        vec2(x, y)
+    }
+}
+
+impl Animatable for Pos2 {
+    fn to_components(self) -> [f32; 4] {
+        [self.x, self.y, 0.0, 0.0]
+    }
+
+    fn from_components([x, y, ..]: [f32; 4]) -> Self {
+        pos2(x, y)
+    }
+}
+
+impl Animatable for Rect {
+    fn to_components(self) -> [f32; 4] {
+        [self.min.x, self.min.y, self.max.x, self.max.y]
+    }
+
+    fn from_components([min_x, min_y, max_x, max_y]: [f32; 4]) -> Self {
+        Self::from_min_max(pos2(min_x, min_y), pos2(max_x, max_y))
+    }
+}
+
+/// Interpolated in gamma space, like [`Color32::lerp_to_gamma`].
+impl Animatable for Color32 {
+    fn to_components(self) -> [f32; 4] {
+        self.to_array().map(f32::from)
+    }
+
+    fn from_components(components: [f32; 4]) -> Self {
+        let [r, g, b, a] = components.map(|c| c.round().clamp(0.0, 255.0) as u8);
+        Self::from_rgba_premultiplied(r, g, b, a)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// What a [`Track`] does when it reaches its last keyframe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stop at the last keyframe.
+    #[default]
+    Once,
+
+    /// Jump back to the start value and play again, forever.
+    Loop,
+
+    /// Play backwards to the start value, then forwards again, forever.
+    PingPong,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Segment<T> {
+    to: T,
+    duration: f32,
+    easing: Easing,
+}
+
+/// A sequence of keyframes for a value, played with [`crate::Context::animate_track`].
+///
+/// The track starts at a value and moves to each keyframe in turn, optionally after a delay.
+/// To sequence several tracks, e.g. for an onboarding tour,
+/// delay each one by the [`Self::total_duration`] of the ones before it.
+///
+/// ```
+/// # use egui::{Color32, animation::{Repeat, Track}, emath::easing::Easing};
+/// # egui::__run_test_ui(|ui| {
+/// let slide_in = Track::new(-200.0)
+///     .delay(0.5)
+///     .to(0.0, 0.4)
+///     .easing(Easing::CubicOut);
+/// let pulse = Track::new(Color32::GRAY)
+///     .delay(slide_in.total_duration())
+///     .to(Color32::WHITE, 0.3)
+///     .hold(0.2)
+///     .to(Color32::GRAY, 0.3)
+///     .repeat(Repeat::Loop);
+///
+/// let offset = ui.ctx().animate_track(ui.id().with("slide_in"), &slide_in);
+/// let color = ui.ctx().animate_track(ui.id().with("pulse"), &pulse);
+/// # let _ = (offset, color);
+/// # });
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    start: T,
+    delay: f32,
+    segments: Vec<Segment<T>>,
+    repeat: Repeat,
+}
+
+impl<T: Animatable> Track<T> {
+    /// A track that starts at this value.
+    pub fn new(start: T) -> Self {
+        Self {
+            start,
+            delay: 0.0,
+            segments: Vec::new(),
+            repeat: Repeat::Once,
+        }
+    }
+
+    /// Wait this many seconds before playing the first keyframe.
+    ///
+    /// The delay is only applied once, and not when looping.
+    #[inline]
+    pub fn delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Move to `value` over `duration` seconds, linearly unless you call [`Self::easing`].
+    #[inline]
+    pub fn to(mut self, value: T, duration: f32) -> Self {
+        self.segments.push(Segment {
+            to: value,
+            duration,
+            easing: Easing::Linear,
+        });
+        self
+    }
+
+    /// Set the easing of the last keyframe added with [`Self::to`].
+    #[inline]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        if let Some(segment) = self.segments.last_mut() {
+            segment.easing = easing;
+        }
+        self
+    }
+
+    /// Keep the current value for `duration` seconds.
+    #[inline]
+    pub fn hold(self, duration: f32) -> Self {
+        let value = self.end_value();
+        self.to(value, duration)
+    }
+
+    #[inline]
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// The value of the last keyframe.
+    pub fn end_value(&self) -> T {
+        self.segments
+            .last()
+            .map_or(self.start, |segment| segment.to)
+    }
+
+    /// How long it takes to play all the keyframes once, not counting the delay.
+    pub fn duration(&self) -> f32 {
+        self.segments.iter().map(|segment| segment.duration).sum()
+    }
+
+    /// The delay plus the [`Self::duration`].
+    pub fn total_duration(&self) -> f32 {
+        self.delay + self.duration()
+    }
+
+    /// The value at `time` seconds after the track started.
+    pub fn sample(&self, time: f32) -> T {
+        self.sample_and_next_change(time).0
+    }
+
+    /// Has the track stopped changing at `time` seconds after it started?
+    ///
+    /// Tracks that repeat never finish.
+    pub fn is_finished(&self, time: f32) -> bool {
+        self.sample_and_next_change(time).1.is_none()
+    }
+
+    /// The value at `time`, and in how many seconds it will start changing,
+    /// which is zero while it is changing, and `None` if it never will.
+    pub(crate) fn sample_and_next_change(&self, time: f32) -> (T, Option<f32>) {
+        let duration = self.duration();
+        let time = time - self.delay;
+        if time < 0.0 {
+            return (self.start, Some(-time));
+        }
+        if duration <= 0.0 {
+            return (self.end_value(), None);
+        }
+
+        let (time, backwards) = match self.repeat {
+            Repeat::Once => {
+                if duration <= time {
+                    return (self.end_value(), None);
+                }
+                (time, false)
+            }
+            Repeat::Loop => (time % duration, false),
+            Repeat::PingPong => {
+                let time = time % (2.0 * duration);
+                if duration < time {
+                    (2.0 * duration - time, true)
+                } else {
+                    (time, false)
+                }
+            }
+        };
+
+        let mut from = self.start;
+        let mut segment_start = 0.0;
+        for segment in &self.segments {
+            let segment_end = segment_start + segment.duration;
+            if time < segment_end {
+                let t = (time - segment_start) / segment.duration;
+                let value = from.lerp(segment.to, segment.easing.apply(t));
+                let next_change = if from == segment.to {
+                    // Holding:
+                    if backwards {
+                        time - segment_start
+                    } else {
+                        segment_end - time
+                    }
+                } else {
+                    0.0
+                };
+                return (value, Some(next_change));
+            }
+            from = segment.to;
+            segment_start = segment_end;
+        }
+        (from, Some(0.0))
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A damped spring, that pulls a value towards a target, played with [`crate::Context::animate_spring`].
+///
+/// Unlike a [`Track`], a spring keeps its velocity when the target changes, so it feels natural
+/// for values that follow the user, like the position of something being dragged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spring {
+    /// How strongly the spring pulls towards the target.
+    pub stiffness: f32,
+
+    /// How quickly the spring stops oscillating.
+    pub damping: f32,
+
+    /// Heavier values move slower and overshoot more.
+    pub mass: f32,
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self {
+            stiffness: 170.0,
+            damping: 26.0,
+            mass: 1.0,
+        }
+    }
+}
+
+impl Spring {
+    /// Values closer than this to the target, and moving slower than this, are at rest.
+    const REST_THRESHOLD: f32 = 0.01;
+
+    /// The longest time step of the simulation, for stability.
+    const MAX_STEP: f32 = 1.0 / 120.0;
+
+    pub fn new(stiffness: f32, damping: f32) -> Self {
+        Self {
+            stiffness,
+            damping,
+            mass: 1.0,
+        }
+    }
+
+    #[inline]
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Advance the simulation by `dt` seconds.
+    ///
+    /// Returns `true` if the value has come to rest at the target.
+    pub(crate) fn step(
+        &self,
+        value: &mut [f32; 4],
+        velocity: &mut [f32; 4],
+        target: [f32; 4],
+        dt: f32,
+    ) -> bool {
+        let steps = (dt / Self::MAX_STEP).ceil().max(1.0);
+        let dt = dt / steps;
+        for _ in 0..steps as usize {
+            for i in 0..4 {
+                let force = self.stiffness * (target[i] - value[i]) - self.damping * velocity[i];
+                velocity[i] += force / self.mass * dt;
+                value[i] += velocity[i] * dt;
+            }
+        }
+
+        let at_rest = (0..4).all(|i| {
+            (target[i] - value[i]).abs() < Self::REST_THRESHOLD
+                && velocity[i].abs() < Self::REST_THRESHOLD
+        });
+        if at_rest || !value.iter().chain(velocity.iter()).all(|x| x.is_finite()) {
+            *value = target;
+            *velocity = [0.0; 4];
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyframes() {
+        let track = Track::new(0.0)
+            .delay(1.0)
+            .to(10.0, 1.0)
+            .hold(1.0)
+            .to(0.0, 2.0);
+        assert_eq!(track.total_duration(), 5.0);
+
+        assert_eq!(track.sample_and_next_change(0.5), (0.0, Some(0.5)));
+        assert_eq!(track.sample_and_next_change(1.5), (5.0, Some(0.0)));
+        assert_eq!(track.sample_and_next_change(2.25), (10.0, Some(0.75)));
+        assert_eq!(track.sample_and_next_change(4.0), (5.0, Some(0.0)));
+        assert_eq!(track.sample_and_next_change(5.0), (0.0, None));
+        assert!(track.is_finished(100.0));
+    }
+
+    #[test]
+    fn test_repeat() {
+        let track = Track::new(Vec2::ZERO).to(vec2(4.0, 8.0), 2.0);
+
+        let looping = track.clone().repeat(Repeat::Loop);
+        assert_eq!(looping.sample(3.0), vec2(2.0, 4.0));
+        assert!(!looping.is_finished(100.0));
+
+        let ping_pong = track.repeat(Repeat::PingPong);
+        assert_eq!(ping_pong.sample(1.0), vec2(2.0, 4.0));
+        assert_eq!(ping_pong.sample(3.5), vec2(1.0, 2.0));
+        assert_eq!(ping_pong.sample(4.5), vec2(1.0, 2.0));
+    }
+
+    #[test]
+    fn test_color_and_rect() {
+        let color = Track::new(Color32::BLACK).to(Color32::WHITE, 1.0);
+        assert_eq!(color.sample(0.5), Color32::from_gray(128));
+
+        let from = Rect::from_min_size(Pos2::ZERO, vec2(10.0, 10.0));
+        let to = Rect::from_min_size(pos2(10.0, 0.0), vec2(20.0, 10.0));
+        let rect = Track::new(from).to(to, 1.0);
+        assert_eq!(
+            rect.sample(0.5),
+            Rect::from_min_max(pos2(5.0, 0.0), pos2(20.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn test_spring_comes_to_rest() {
+        let spring = Spring::default();
+        let (mut value, mut velocity) = ([0.0; 4], [0.0; 4]);
+        let target = [100.0, -50.0, 0.0, 0.0];
+
+        let mut frames = 0;
+        while !spring.step(&mut value, &mut velocity, target, 1.0 / 60.0) {
+            frames += 1;
+            assert!(frames < 600, "The spring should settle within ten seconds");
+        }
+        assert_eq!(value, target);
+        assert_eq!(velocity, [0.0; 4]);
+    }
+}
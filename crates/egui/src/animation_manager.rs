@@ -1,12 +1,20 @@
 use crate::{
     Id, IdMap, InputState,
+    animation::Spring,
     emath::{NumExt as _, remap_clamp},
+    style::{WidgetTransition, WidgetVisuals},
 };
 
 #[derive(Clone, Default)]
 pub(crate) struct AnimationManager {
     bools: IdMap<BoolAnim>,
     values: IdMap<ValueAnim>,
+    visuals: IdMap<VisualsAnim>,
+
+    /// When each [`crate::animation::Track`] started playing.
+    tracks: IdMap<f64>,
+
+    springs: IdMap<SpringAnim>,
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +33,23 @@ struct ValueAnim {
     toggle_time: f64,
 }
 
+#[derive(Clone, Debug)]
+struct VisualsAnim {
+    from_visuals: WidgetVisuals,
+
+    to_visuals: WidgetVisuals,
+
+    /// When did the target visuals last change?
+    toggle_time: f64,
+}
+
+#[derive(Clone, Debug)]
+struct SpringAnim {
+    value: [f32; 4],
+    velocity: [f32; 4],
+    last_tick: f64,
+}
+
 impl AnimationManager {
     /// See [`crate::Context::animate_bool`] for documentation
     pub fn animate_bool(
@@ -107,4 +132,142 @@ impl AnimationManager {
             }
         }
     }
+
+    /// See [`crate::Context::animate_widget_visuals`] for documentation
+    pub fn animate_visuals(
+        &mut self,
+        input: &InputState,
+        transition: WidgetTransition,
+        id: Id,
+        target: WidgetVisuals,
+    ) -> WidgetVisuals {
+        match self.visuals.get_mut(&id) {
+            None => {
+                self.visuals.insert(
+                    id,
+                    VisualsAnim {
+                        from_visuals: target,
+                        to_visuals: target,
+                        toggle_time: -f64::INFINITY, // long time ago
+                    },
+                );
+                target
+            }
+            Some(anim) => {
+                let time_since_toggle = (input.time - anim.toggle_time) as f32;
+                let t = (time_since_toggle / transition.duration).clamp(0.0, 1.0);
+                // Not every easing lands exactly on 1.0 at the end (e.g. `SinOut`),
+                // so snap to the target once the transition is over:
+                let current = if t.is_finite() && t < 1.0 {
+                    anim.from_visuals
+                        .lerp(&anim.to_visuals, transition.easing.apply(t))
+                } else {
+                    anim.to_visuals
+                };
+                if anim.to_visuals != target {
+                    // Start the new transition from where the previous one is now:
+                    anim.from_visuals = current;
+                    anim.to_visuals = target;
+                    anim.toggle_time = input.time;
+                }
+                current
+            }
+        }
+    }
+
+    /// When the track with this id started playing, which is now if it wasn't playing before.
+    pub fn track_start_time(&mut self, input: &InputState, id: Id) -> f64 {
+        *self.tracks.entry(id).or_insert(input.time)
+    }
+
+    /// See [`crate::Context::animate_spring`] for documentation.
+    ///
+    /// Also returns `true` if the spring is at rest.
+    pub fn animate_spring(
+        &mut self,
+        input: &InputState,
+        spring: &Spring,
+        id: Id,
+        target: [f32; 4],
+    ) -> ([f32; 4], bool) {
+        match self.springs.get_mut(&id) {
+            None => {
+                self.springs.insert(
+                    id,
+                    SpringAnim {
+                        value: target,
+                        velocity: [0.0; 4],
+                        last_tick: input.time,
+                    },
+                );
+                (target, true)
+            }
+            Some(anim) => {
+                let elapsed = ((input.time - anim.last_tick) as f32).at_most(input.stable_dt);
+                anim.last_tick = input.time;
+                let at_rest = spring.step(&mut anim.value, &mut anim.velocity, target, elapsed);
+                (anim.value, at_rest)
+            }
+        }
+    }
+
+    /// Forget the state of all animations with this id.
+    pub fn reset(&mut self, id: Id) {
+        let Self {
+            bools,
+            values,
+            visuals,
+            tracks,
+            springs,
+        } = self;
+        bools.remove(&id);
+        values.remove(&id);
+        visuals.remove(&id);
+        tracks.remove(&id);
+        springs.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Visuals, emath::easing::Easing};
+
+    /// Animates the visuals of one widget, with the input time set to the given time.
+    fn visuals_animator(easing: Easing) -> impl FnMut(f64, WidgetVisuals) -> WidgetVisuals {
+        let transition = WidgetTransition::duration(0.25).easing(easing);
+        let id = Id::new("button");
+        let mut manager = AnimationManager::default();
+        let mut input = InputState::default();
+        move |time, target| {
+            input.time = time;
+            manager.animate_visuals(&input, transition, id, target)
+        }
+    }
+
+    #[test]
+    fn test_visuals_transition() {
+        let widgets = Visuals::dark().widgets;
+        let (inactive, hovered) = (widgets.inactive, widgets.hovered);
+        let mut animate = visuals_animator(Easing::Linear);
+
+        assert_eq!(animate(0.0, inactive), inactive);
+        assert_eq!(animate(1.0, hovered), inactive, "Starts at the old look");
+        assert_eq!(animate(1.125, hovered), inactive.lerp(&hovered, 0.5));
+        assert_eq!(animate(1.25, hovered), hovered);
+
+        // Changing the target midway continues from the current look:
+        animate(2.0, inactive);
+        let quarter = animate(2.0625, hovered);
+        assert_eq!(quarter, hovered.lerp(&inactive, 0.25));
+        assert_eq!(animate(2.125, hovered), quarter.lerp(&hovered, 0.25));
+
+        // Every easing ends exactly at the target, even if it doesn't quite reach 1.0:
+        for easing in Easing::ALL {
+            let mut animate = visuals_animator(easing);
+            animate(0.0, inactive);
+            animate(1.0, hovered);
+            assert_eq!(animate(1.25, hovered), hovered, "{easing:?}");
+        }
+    }
 }
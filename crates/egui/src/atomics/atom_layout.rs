@@ -372,7 +372,7 @@ impl<'atom> AllocatedAtomLayout<'atom> {
             gap,
         } = self;
 
-        let inner_rect = response.rect - self.frame.total_margin();
+        let inner_rect = response.rect - frame.total_margin();
 
         ui.painter().add(frame.paint(inner_rect));
 
@@ -0,0 +1,1016 @@
+//! Dock tabs into a tree of splits and tab stacks, that the user can rearrange by dragging.
+//!
+//! See [`DockArea`].
+
+use std::hash::Hash;
+
+use crate::{
+    Align2, CentralPanel, Color32, Context, CornerRadius, CursorIcon, DragAndDrop, Frame, Id,
+    LayerId, Order, Pos2, Rect, Response, Sense, Shape, Stroke, TextStyle, TextWrapMode, Ui,
+    UiBuilder, Vec2, WidgetInfo, WidgetText, WidgetType, Window, pos2,
+    util::id_type_map::SerializableAny, vec2,
+};
+
+/// Provides the contents of the tabs of a [`DockArea`].
+pub trait DockViewer {
+    /// Identifies a tab, e.g. an enum or a file path.
+    ///
+    /// Each tab should only be in the [`DockTree`] once.
+    /// With the `persistence` feature, this needs to be serializable.
+    type Tab: SerializableAny + PartialEq + Hash;
+
+    /// The title shown in the tab.
+    fn title(&mut self, tab: &Self::Tab) -> WidgetText;
+
+    /// Show the contents of the tab.
+    fn ui(&mut self, ui: &mut Ui, tab: &Self::Tab);
+
+    /// Can the user close this tab?
+    ///
+    /// Closed tabs are reported in [`DockResponse::closed`].
+    fn closeable(&mut self, _tab: &Self::Tab) -> bool {
+        true
+    }
+}
+
+/// How a [`DockNode::Split`] divides its space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SplitDirection {
+    /// `first` to the left, `second` to the right.
+    Horizontal,
+
+    /// `first` on top, `second` below.
+    Vertical,
+}
+
+/// A node in a [`DockTree`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DockNode<Tab> {
+    /// Two nodes next to each other.
+    Split {
+        direction: SplitDirection,
+
+        /// How much of the space goes to `first`, in `0..=1`.
+        fraction: f32,
+
+        first: Box<DockNode<Tab>>,
+        second: Box<DockNode<Tab>>,
+    },
+
+    /// A stack of tabs, of which one is shown.
+    Tabs {
+        tabs: Vec<Tab>,
+
+        /// Index of the shown tab.
+        active: usize,
+    },
+}
+
+impl<Tab> DockNode<Tab> {
+    /// A stack of tabs, showing the first one.
+    pub fn tabs(tabs: Vec<Tab>) -> Self {
+        Self::Tabs { tabs, active: 0 }
+    }
+
+    /// `left` and `right` next to each other. `fraction` of the width goes to `left`.
+    pub fn horizontal(left: Self, right: Self, fraction: f32) -> Self {
+        Self::Split {
+            direction: SplitDirection::Horizontal,
+            fraction,
+            first: Box::new(left),
+            second: Box::new(right),
+        }
+    }
+
+    /// `top` above `bottom`. `fraction` of the height goes to `top`.
+    pub fn vertical(top: Self, bottom: Self, fraction: f32) -> Self {
+        Self::Split {
+            direction: SplitDirection::Vertical,
+            fraction,
+            first: Box::new(top),
+            second: Box::new(bottom),
+        }
+    }
+
+    fn child_mut(&mut self, index: usize) -> Option<&mut Self> {
+        match self {
+            Self::Split { first, second, .. } => Some(if index == 0 { first } else { second }),
+            Self::Tabs { .. } => None,
+        }
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut Self> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&index, rest)) => self.child_mut(index)?.node_mut(rest),
+        }
+    }
+
+    fn for_each_tab<'a>(
+        &'a self,
+        path: &mut Vec<usize>,
+        f: &mut impl FnMut(&[usize], usize, &'a Tab),
+    ) {
+        match self {
+            Self::Split { first, second, .. } => {
+                for (index, child) in [first, second].into_iter().enumerate() {
+                    path.push(index);
+                    child.for_each_tab(path, f);
+                    path.pop();
+                }
+            }
+            Self::Tabs { tabs, .. } => {
+                for (index, tab) in tabs.iter().enumerate() {
+                    f(path, index, tab);
+                }
+            }
+        }
+    }
+
+    /// Remove empty tab stacks, and splits with only one side left.
+    fn simplified(self) -> Option<Self> {
+        match self {
+            Self::Split {
+                direction,
+                fraction,
+                first,
+                second,
+            } => match (first.simplified(), second.simplified()) {
+                (Some(first), Some(second)) => Some(Self::Split {
+                    direction,
+                    fraction,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (first, second) => first.or(second),
+            },
+            Self::Tabs { tabs, active } => (!tabs.is_empty()).then(|| Self::Tabs {
+                active: active.min(tabs.len() - 1),
+                tabs,
+            }),
+        }
+    }
+}
+
+/// The layout of a [`DockArea`]: a tree of splits and tab stacks, plus floating tabs.
+///
+/// The [`DockArea`] stores this in [`crate::Memory`], so it is remembered between frames
+/// (and between runs, with the `persistence` feature).
+/// Use [`Self::load`] and [`Self::store`] to change it from code, e.g. to open a new tab.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DockTree<Tab> {
+    /// `None` when all tabs are closed or floating.
+    pub root: Option<DockNode<Tab>>,
+
+    /// Tabs shown in their own [`Window`].
+    pub floating: Vec<Tab>,
+}
+
+impl<Tab> Default for DockTree<Tab> {
+    fn default() -> Self {
+        Self {
+            root: None,
+            floating: Vec::new(),
+        }
+    }
+}
+
+impl<Tab: SerializableAny + PartialEq> DockTree<Tab> {
+    pub fn new(root: DockNode<Tab>) -> Self {
+        Self {
+            root: Some(root),
+            floating: Vec::new(),
+        }
+    }
+
+    /// Load the tree of the [`DockArea`] with this id.
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.insert_persisted(id, self));
+    }
+
+    /// All docked and floating tabs.
+    pub fn tabs(&self) -> Vec<&Tab> {
+        let mut tabs = Vec::new();
+        if let Some(root) = &self.root {
+            root.for_each_tab(&mut vec![], &mut |_, _, tab| tabs.push(tab));
+        }
+        tabs.extend(&self.floating);
+        tabs
+    }
+
+    pub fn contains(&self, tab: &Tab) -> bool {
+        self.tabs().contains(&tab)
+    }
+
+    /// Add a tab to the first tab stack, and show it.
+    pub fn push_tab(&mut self, tab: Tab) {
+        let mut node = self.root.as_mut();
+        while let Some(DockNode::Split { first, .. }) = node {
+            node = Some(first);
+        }
+        match node {
+            Some(DockNode::Tabs { tabs, active }) => {
+                tabs.push(tab);
+                *active = tabs.len() - 1;
+            }
+            _ => self.root = Some(DockNode::tabs(vec![tab])),
+        }
+    }
+
+    /// Show this tab in its stack.
+    ///
+    /// Returns `false` if the tab is not docked.
+    pub fn focus_tab(&mut self, tab: &Tab) -> bool {
+        let Some((path, index)) = self.locations(tab).into_iter().next() else {
+            return false;
+        };
+        if let Some(DockNode::Tabs { active, .. }) = self.node_mut(&path) {
+            *active = index;
+        }
+        true
+    }
+
+    /// Remove the tab, wherever it is.
+    ///
+    /// Returns `false` if it wasn't there.
+    pub fn remove_tab(&mut self, tab: &Tab) -> bool {
+        let len = self.floating.len();
+        self.floating.retain(|t| t != tab);
+        let mut removed = self.floating.len() != len;
+
+        let locations = self.locations(tab);
+        removed |= !locations.is_empty();
+        for (path, index) in locations.into_iter().rev() {
+            self.remove_at(&path, index);
+        }
+        self.simplify();
+        removed
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut DockNode<Tab>> {
+        self.root.as_mut()?.node_mut(path)
+    }
+
+    /// Where this tab is docked, in tree order.
+    fn locations(&self, tab: &Tab) -> Vec<(Vec<usize>, usize)> {
+        let mut locations = Vec::new();
+        if let Some(root) = &self.root {
+            root.for_each_tab(&mut vec![], &mut |path, index, t| {
+                if t == tab {
+                    locations.push((path.to_vec(), index));
+                }
+            });
+        }
+        locations
+    }
+
+    fn remove_at(&mut self, path: &[usize], index: usize) {
+        if let Some(DockNode::Tabs { tabs, active }) = self.node_mut(path) {
+            tabs.remove(index);
+            if index < *active || tabs.len() <= *active {
+                *active = active.saturating_sub(1);
+            }
+        }
+    }
+
+    fn simplify(&mut self) {
+        self.root = self.root.take().and_then(DockNode::simplified);
+    }
+
+    /// Move a tab to the drop target.
+    fn drop_tab(&mut self, tab: &Tab, target: &DropTarget) {
+        let new_location = if let Some(node) = self.node_mut(&target.path) {
+            match target.zone {
+                DropZone::Insert(index) => {
+                    let DockNode::Tabs { tabs, active } = node else {
+                        return;
+                    };
+                    let index = index.min(tabs.len());
+                    tabs.insert(index, tab.clone());
+                    *active = index;
+                    (target.path.clone(), index)
+                }
+                DropZone::Split(side) => {
+                    let old = std::mem::replace(node, DockNode::tabs(vec![]));
+                    let new = DockNode::tabs(vec![tab.clone()]);
+                    let (new_index, split) = match side {
+                        Side::Left => (0, DockNode::horizontal(new, old, 0.5)),
+                        Side::Right => (1, DockNode::horizontal(old, new, 0.5)),
+                        Side::Top => (0, DockNode::vertical(new, old, 0.5)),
+                        Side::Bottom => (1, DockNode::vertical(old, new, 0.5)),
+                    };
+                    *node = split;
+                    let mut path = target.path.clone();
+                    path.push(new_index);
+                    (path, 0)
+                }
+            }
+        } else {
+            self.root = Some(DockNode::tabs(vec![tab.clone()]));
+            (vec![], 0)
+        };
+
+        // Remove the tab from where it was dragged from:
+        self.floating.retain(|t| t != tab);
+        let old_locations = self.locations(tab);
+        for (path, index) in old_locations.into_iter().rev() {
+            if (&path, index) != (&new_location.0, new_location.1) {
+                self.remove_at(&path, index);
+            }
+        }
+        self.simplify();
+        self.focus_tab(tab);
+    }
+
+    /// Undock a tab into its own window.
+    fn float_tab(&mut self, tab: Tab) {
+        for (path, index) in self.locations(&tab).into_iter().rev() {
+            self.remove_at(&path, index);
+        }
+        self.simplify();
+        if !self.floating.contains(&tab) {
+            self.floating.push(tab);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DropZone {
+    /// Into the tab stack, at this index.
+    Insert(usize),
+
+    /// Split the tab stack, and put the tab on this side.
+    Split(Side),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DropTarget {
+    /// Path to a tab stack, or empty if the dock is empty.
+    path: Vec<usize>,
+    zone: DropZone,
+}
+
+/// What is being dragged.
+struct DockPayload<Tab> {
+    dock_id: Id,
+    tab: Tab,
+}
+
+/// What happened in a [`DockArea`] this frame.
+pub struct DockResponse<Tab> {
+    /// The whole dock area.
+    pub response: Response,
+
+    /// Tabs the user closed this frame.
+    pub closed: Vec<Tab>,
+}
+
+/// Tabs that can be docked into splits and tab stacks, like in an IDE.
+///
+/// The user can rearrange the tabs by dragging them:
+/// onto a tab bar to move them into that stack,
+/// onto the side of a tab stack to split it,
+/// or outside the dock area to show them in a floating [`Window`].
+/// The splits can be resized by dragging the line between them.
+///
+/// The layout is a [`DockTree`], stored in [`crate::Memory`] under [`Self::id`].
+///
+/// ```
+/// use egui::dock::{DockArea, DockNode, DockTree, DockViewer};
+///
+/// struct Viewer;
+///
+/// impl DockViewer for Viewer {
+///     type Tab = String;
+///
+///     fn title(&mut self, tab: &String) -> egui::WidgetText {
+///         tab.as_str().into()
+///     }
+///
+///     fn ui(&mut self, ui: &mut egui::Ui, tab: &String) {
+///         ui.label(format!("Contents of {tab}"));
+///     }
+/// }
+///
+/// # egui::__run_test_ui(|ui| {
+/// let default_tree = DockTree::new(DockNode::horizontal(
+///     DockNode::tabs(vec!["Files".to_owned()]),
+///     DockNode::tabs(vec!["main.rs".to_owned(), "lib.rs".to_owned()]),
+///     0.25,
+/// ));
+/// DockArea::new("dock")
+///     .default_tree(default_tree)
+///     .show_inside(ui, &mut Viewer);
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct DockArea<Tab> {
+    id: Id,
+    default_tree: Option<DockTree<Tab>>,
+}
+
+impl<Tab: SerializableAny + PartialEq + Hash> DockArea<Tab> {
+    pub fn new(id_salt: impl Hash) -> Self {
+        Self {
+            id: Id::new(id_salt),
+            default_tree: None,
+        }
+    }
+
+    /// The layout to use until the user changes it.
+    ///
+    /// Default: empty.
+    #[inline]
+    pub fn default_tree(mut self, default_tree: DockTree<Tab>) -> Self {
+        self.default_tree = Some(default_tree);
+        self
+    }
+
+    /// Where the [`DockTree`] is stored, see [`DockTree::load`].
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Fill the remaining space of the screen, like a [`CentralPanel`].
+    pub fn show(self, ctx: &Context, viewer: &mut impl DockViewer<Tab = Tab>) -> DockResponse<Tab> {
+        CentralPanel::default()
+            .frame(Frame::central_panel(&ctx.style()).inner_margin(0))
+            .show(ctx, |ui| self.show_inside(ui, viewer))
+            .inner
+    }
+
+    /// Fill the remaining space of the [`Ui`].
+    pub fn show_inside(
+        self,
+        ui: &mut Ui,
+        viewer: &mut impl DockViewer<Tab = Tab>,
+    ) -> DockResponse<Tab> {
+        let Self { id, default_tree } = self;
+        let ctx = ui.ctx().clone();
+
+        let mut tree = DockTree::load(&ctx, id).unwrap_or_else(|| default_tree.unwrap_or_default());
+
+        let rect = ui.available_rect_before_wrap();
+        let response = ui.allocate_rect(rect, Sense::hover());
+
+        let dragged =
+            DragAndDrop::payload::<DockPayload<Tab>>(&ctx).filter(|payload| payload.dock_id == id);
+
+        let mut pass = DockPass {
+            dock_id: id,
+            viewer,
+            is_dragging: dragged.is_some(),
+            drop_target: None,
+            closed: Vec::new(),
+        };
+
+        if let Some(root) = &mut tree.root {
+            pass.show_node(ui, root, rect, &mut vec![]);
+        } else if pass.is_dragging && ui.rect_contains_pointer(rect) {
+            paint_drop_preview(ui, rect);
+            pass.drop_target = Some(DropTarget {
+                path: vec![],
+                zone: DropZone::Insert(0),
+            });
+        }
+
+        pass.show_floating(&ctx, &tree);
+
+        let DockPass {
+            viewer,
+            drop_target,
+            closed,
+            ..
+        } = pass;
+
+        if let (Some(payload), Some(pointer)) = (dragged, ctx.pointer_latest_pos()) {
+            paint_dragged_tab(&ctx, id, pointer, viewer.title(&payload.tab));
+
+            if ctx.input(|i| i.pointer.any_released()) {
+                DragAndDrop::clear_payload(&ctx);
+                if let Some(target) = drop_target {
+                    tree.drop_tab(&payload.tab, &target);
+                } else if !rect.contains(pointer) {
+                    let window_id = floating_window_id(id, &payload.tab);
+                    ctx.data_mut(|d| d.insert_temp(window_id, pointer));
+                    tree.float_tab(payload.tab.clone());
+                }
+            }
+        }
+
+        for tab in &closed {
+            tree.remove_tab(tab);
+        }
+
+        tree.store(&ctx, id);
+
+        DockResponse { response, closed }
+    }
+}
+
+fn floating_window_id(dock_id: Id, tab: &impl Hash) -> Id {
+    dock_id.with("floating").with(tab)
+}
+
+/// State while showing a [`DockArea`].
+struct DockPass<'v, V: DockViewer> {
+    dock_id: Id,
+    viewer: &'v mut V,
+
+    /// Is one of our tabs being dragged?
+    is_dragging: bool,
+
+    drop_target: Option<DropTarget>,
+    closed: Vec<V::Tab>,
+}
+
+impl<V: DockViewer> DockPass<'_, V> {
+    fn show_node(
+        &mut self,
+        ui: &mut Ui,
+        node: &mut DockNode<V::Tab>,
+        rect: Rect,
+        path: &mut Vec<usize>,
+    ) {
+        match node {
+            DockNode::Split {
+                direction,
+                fraction,
+                first,
+                second,
+            } => {
+                let direction = *direction;
+                let separator_id = self.dock_id.with("separator").with(&*path);
+
+                // Read the resize interaction first, to avoid frame latency:
+                if let Some(response) = ui.ctx().read_response(separator_id) {
+                    if let (true, Some(pointer)) =
+                        (response.dragged(), response.interact_pointer_pos())
+                    {
+                        *fraction = match direction {
+                            SplitDirection::Horizontal => (pointer.x - rect.left()) / rect.width(),
+                            SplitDirection::Vertical => (pointer.y - rect.top()) / rect.height(),
+                        };
+                    }
+                }
+
+                let min_size = 2.0 * ui.spacing().interact_size.y;
+                let (length, gap) = match direction {
+                    SplitDirection::Horizontal => (rect.width(), ui.spacing().item_spacing.x),
+                    SplitDirection::Vertical => (rect.height(), ui.spacing().item_spacing.y),
+                };
+                let min_fraction = (min_size / length).min(0.5);
+                *fraction = fraction.clamp(min_fraction, 1.0 - min_fraction);
+
+                let split = rect.min + (*fraction * length) * direction_vec(direction);
+                let (first_rect, second_rect, separator) = match direction {
+                    SplitDirection::Horizontal => (
+                        rect.with_max_x(split.x - 0.5 * gap),
+                        rect.with_min_x(split.x + 0.5 * gap),
+                        Rect::from_x_y_ranges(split.x..=split.x, rect.y_range()),
+                    ),
+                    SplitDirection::Vertical => (
+                        rect.with_max_y(split.y - 0.5 * gap),
+                        rect.with_min_y(split.y + 0.5 * gap),
+                        Rect::from_x_y_ranges(rect.x_range(), split.y..=split.y),
+                    ),
+                };
+
+                path.push(0);
+                self.show_node(ui, first, first_rect, path);
+                path.pop();
+                path.push(1);
+                self.show_node(ui, second, second_rect, path);
+                path.pop();
+
+                // On top of the contents:
+                let grab_radius = ui.style().interaction.resize_grab_radius_side;
+                let response =
+                    ui.interact(separator.expand(grab_radius), separator_id, Sense::drag());
+                if response.hovered() || response.dragged() {
+                    ui.ctx().set_cursor_icon(match direction {
+                        SplitDirection::Horizontal => CursorIcon::ResizeHorizontal,
+                        SplitDirection::Vertical => CursorIcon::ResizeVertical,
+                    });
+                }
+                let stroke = if response.dragged() {
+                    ui.visuals().widgets.active.fg_stroke
+                } else if response.hovered() {
+                    ui.visuals().widgets.hovered.fg_stroke
+                } else {
+                    ui.visuals().widgets.noninteractive.bg_stroke
+                };
+                ui.painter()
+                    .line_segment([separator.min, separator.max], stroke);
+            }
+
+            DockNode::Tabs { tabs, active } => {
+                let bar_height = ui.spacing().interact_size.y + ui.spacing().button_padding.y;
+                let bar_rect = rect.with_max_y(rect.top() + bar_height);
+                let content_rect = rect.with_min_y(bar_rect.bottom());
+
+                let tab_rects = self.tab_bar(ui, bar_rect, tabs, active);
+
+                if let Some(tab) = tabs.get(*active) {
+                    self.tab_contents(ui, content_rect, tab);
+                }
+
+                if self.is_dragging && ui.rect_contains_pointer(rect) {
+                    self.drop_zone(ui, path, bar_rect, content_rect, &tab_rects);
+                }
+            }
+        }
+    }
+
+    /// Returns the rects of the tabs.
+    fn tab_bar(
+        &mut self,
+        ui: &Ui,
+        bar_rect: Rect,
+        tabs: &[V::Tab],
+        active: &mut usize,
+    ) -> Vec<Rect> {
+        let visuals = ui.visuals().clone();
+        ui.painter()
+            .rect_filled(bar_rect, CornerRadius::ZERO, visuals.extreme_bg_color);
+
+        let painter = ui
+            .painter()
+            .with_clip_rect(bar_rect.intersect(ui.clip_rect()));
+        let padding = ui.spacing().button_padding;
+        let close_size = ui.spacing().icon_width;
+
+        let mut x = bar_rect.left();
+        let mut tab_rects = Vec::with_capacity(tabs.len());
+        for (index, tab) in tabs.iter().enumerate() {
+            let title = self.viewer.title(tab);
+            let text = title.text().to_owned();
+            let galley = title.into_galley(
+                ui,
+                Some(TextWrapMode::Extend),
+                f32::INFINITY,
+                TextStyle::Button,
+            );
+            let closeable = self.viewer.closeable(tab);
+            let close_width = if closeable {
+                close_size + padding.x
+            } else {
+                0.0
+            };
+
+            let width = galley.size().x + 2.0 * padding.x + close_width;
+            let rect = Rect::from_min_size(pos2(x, bar_rect.top()), vec2(width, bar_rect.height()));
+            x += width;
+            tab_rects.push(rect);
+
+            let tab_id = self.dock_id.with("tab").with(tab);
+            let response = ui.interact(rect, tab_id, Sense::click_and_drag());
+            let is_active = index == *active;
+            response.widget_info(|| {
+                WidgetInfo::selected(WidgetType::SelectableLabel, true, is_active, &text)
+            });
+            if response.clicked() {
+                *active = index;
+            }
+            if response.drag_started() {
+                DragAndDrop::set_payload(
+                    ui.ctx(),
+                    DockPayload {
+                        dock_id: self.dock_id,
+                        tab: tab.clone(),
+                    },
+                );
+            }
+
+            let fill = if is_active {
+                visuals.panel_fill
+            } else if response.hovered() {
+                visuals.widgets.hovered.weak_bg_fill
+            } else {
+                Color32::TRANSPARENT
+            };
+            let corner_radius = visuals.widgets.inactive.corner_radius;
+            painter.rect_filled(
+                rect,
+                CornerRadius {
+                    sw: 0,
+                    se: 0,
+                    ..corner_radius
+                },
+                fill,
+            );
+            let text_color = if is_active {
+                visuals.strong_text_color()
+            } else {
+                visuals.widgets.inactive.text_color()
+            };
+            let text_pos = pos2(
+                rect.left() + padding.x,
+                rect.center().y - 0.5 * galley.size().y,
+            );
+            painter.galley(text_pos, galley, text_color);
+
+            if closeable && (is_active || response.hovered()) {
+                let close_rect = Rect::from_center_size(
+                    pos2(rect.right() - padding.x - 0.5 * close_size, rect.center().y),
+                    Vec2::splat(close_size),
+                );
+                let close_response = ui.interact(close_rect, tab_id.with("close"), Sense::click());
+                close_response.widget_info(|| {
+                    WidgetInfo::labeled(WidgetType::Button, true, format!("Close {text}"))
+                });
+                if close_response.clicked() {
+                    self.closed.push(tab.clone());
+                }
+                let stroke = ui.style().interact(&close_response).fg_stroke;
+                let cross = close_rect.shrink(0.3 * close_size);
+                painter.line_segment([cross.left_top(), cross.right_bottom()], stroke);
+                painter.line_segment([cross.right_top(), cross.left_bottom()], stroke);
+            }
+        }
+        tab_rects
+    }
+
+    fn tab_contents(&mut self, ui: &mut Ui, rect: Rect, tab: &V::Tab) {
+        ui.painter()
+            .rect_filled(rect, CornerRadius::ZERO, ui.visuals().panel_fill);
+
+        let margin = ui.spacing().window_margin;
+        let mut content_ui = ui.new_child(
+            UiBuilder::new()
+                .id_salt(self.dock_id.with(tab))
+                .max_rect(rect - margin),
+        );
+        content_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+        self.viewer.ui(&mut content_ui, tab);
+    }
+
+    /// Show where a dragged tab would go, if dropped here.
+    fn drop_zone(
+        &mut self,
+        ui: &Ui,
+        path: &[usize],
+        bar_rect: Rect,
+        content_rect: Rect,
+        tab_rects: &[Rect],
+    ) {
+        let Some(pointer) = ui.ctx().pointer_latest_pos() else {
+            return;
+        };
+        if ui.ctx().layer_id_at(pointer) != Some(ui.layer_id()) {
+            return; // e.g. a floating window is in the way
+        }
+
+        let (zone, preview) = if bar_rect.contains(pointer) {
+            let index = tab_rects
+                .iter()
+                .filter(|rect| rect.center().x < pointer.x)
+                .count();
+            let x = tab_rects.get(index).map_or_else(
+                || tab_rects.last().map_or(bar_rect.left(), |r| r.right()),
+                |r| r.left(),
+            );
+            let preview = Rect::from_x_y_ranges(x - 1.0..=x + 1.0, bar_rect.y_range());
+            (DropZone::Insert(index), preview)
+        } else {
+            let rel = (pointer - content_rect.min) / content_rect.size();
+            let distances = [
+                (Side::Left, rel.x),
+                (Side::Right, 1.0 - rel.x),
+                (Side::Top, rel.y),
+                (Side::Bottom, 1.0 - rel.y),
+            ];
+            let (side, distance) = distances
+                .into_iter()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((Side::Left, 1.0));
+            if distance < 0.25 {
+                let c = content_rect.center();
+                let preview = match side {
+                    Side::Left => content_rect.with_max_x(c.x),
+                    Side::Right => content_rect.with_min_x(c.x),
+                    Side::Top => content_rect.with_max_y(c.y),
+                    Side::Bottom => content_rect.with_min_y(c.y),
+                };
+                (DropZone::Split(side), preview)
+            } else {
+                (DropZone::Insert(tab_rects.len()), content_rect)
+            }
+        };
+
+        paint_drop_preview(ui, preview);
+        self.drop_target = Some(DropTarget {
+            path: path.to_vec(),
+            zone,
+        });
+    }
+
+    /// Show each floating tab in its own window.
+    fn show_floating(&mut self, ctx: &Context, tree: &DockTree<V::Tab>) {
+        for tab in tree.floating.clone() {
+            let window_id = floating_window_id(self.dock_id, &tab);
+            let mut window = Window::new(self.viewer.title(&tab))
+                .id(window_id)
+                .title_bar(false)
+                .frame(Frame::window(&ctx.style()).inner_margin(0))
+                .default_size(vec2(320.0, 240.0));
+            if let Some(pos) = ctx.data_mut(|d| d.remove_temp::<Pos2>(window_id)) {
+                window = window.current_pos(pos);
+            }
+            window.show(ctx, |ui| {
+                let rect = ui.available_rect_before_wrap();
+                let bar_height = ui.spacing().interact_size.y + ui.spacing().button_padding.y;
+                let bar_rect = rect.with_max_y(rect.top() + bar_height);
+                self.tab_bar(ui, bar_rect, std::slice::from_ref(&tab), &mut 0);
+                self.tab_contents(ui, rect.with_min_y(bar_rect.bottom()), &tab);
+                ui.allocate_rect(rect, Sense::hover());
+            });
+        }
+    }
+}
+
+fn direction_vec(direction: SplitDirection) -> Vec2 {
+    match direction {
+        SplitDirection::Horizontal => Vec2::X,
+        SplitDirection::Vertical => Vec2::Y,
+    }
+}
+
+fn paint_drop_preview(ui: &Ui, rect: Rect) {
+    let color = ui.visuals().selection.bg_fill;
+    ui.painter()
+        .rect_filled(rect, CornerRadius::ZERO, color.gamma_multiply(0.5));
+    ui.painter().rect_stroke(
+        rect,
+        CornerRadius::ZERO,
+        Stroke::new(1.0, color),
+        crate::StrokeKind::Inside,
+    );
+}
+
+/// Show the title of the dragged tab next to the pointer.
+fn paint_dragged_tab(ctx: &Context, dock_id: Id, pointer: Pos2, title: WidgetText) {
+    let painter = ctx.layer_painter(LayerId::new(Order::Tooltip, dock_id.with("dragged")));
+    let style = ctx.style();
+    let galley = title.into_galley_impl(
+        ctx,
+        &style,
+        crate::text::TextWrapping::no_max_width(),
+        TextStyle::Button.into(),
+        crate::Align::Center,
+    );
+    let rect = Align2::LEFT_TOP
+        .anchor_size(pointer + vec2(12.0, 12.0), galley.size())
+        .expand2(style.spacing.button_padding);
+    painter.add(Shape::rect_filled(
+        rect,
+        style.visuals.widgets.inactive.corner_radius,
+        style.visuals.panel_fill,
+    ));
+    painter.rect_stroke(
+        rect,
+        style.visuals.widgets.inactive.corner_radius,
+        style.visuals.window_stroke,
+        crate::StrokeKind::Outside,
+    );
+    painter.galley(
+        rect.min + style.spacing.button_padding,
+        galley,
+        style.visuals.text_color(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> DockTree<char> {
+        DockTree::new(DockNode::horizontal(
+            DockNode::tabs(vec!['a', 'b']),
+            DockNode::tabs(vec!['c']),
+            0.5,
+        ))
+    }
+
+    #[test]
+    fn test_move_tab_between_stacks() {
+        let mut tree = tree();
+        tree.drop_tab(
+            &'c',
+            &DropTarget {
+                path: vec![0],
+                zone: DropZone::Insert(1),
+            },
+        );
+        assert_eq!(
+            tree.root,
+            Some(DockNode::Tabs {
+                tabs: vec!['a', 'c', 'b'],
+                active: 1,
+            }),
+            "The empty stack and its split should be removed"
+        );
+    }
+
+    #[test]
+    fn test_reorder_tabs() {
+        let mut tree = tree();
+        tree.drop_tab(
+            &'a',
+            &DropTarget {
+                path: vec![0],
+                zone: DropZone::Insert(2),
+            },
+        );
+        assert_eq!(
+            tree.root,
+            Some(DockNode::horizontal(
+                DockNode::Tabs {
+                    tabs: vec!['b', 'a'],
+                    active: 1,
+                },
+                DockNode::tabs(vec!['c']),
+                0.5,
+            ))
+        );
+    }
+
+    #[test]
+    fn test_split_stack() {
+        let mut tree = tree();
+        tree.drop_tab(
+            &'a',
+            &DropTarget {
+                path: vec![1],
+                zone: DropZone::Split(Side::Bottom),
+            },
+        );
+        assert_eq!(
+            tree.root,
+            Some(DockNode::horizontal(
+                DockNode::tabs(vec!['b']),
+                DockNode::vertical(DockNode::tabs(vec!['c']), DockNode::tabs(vec!['a']), 0.5),
+                0.5,
+            ))
+        );
+
+        // Splitting a stack with only the dragged tab does nothing:
+        let before = tree.clone();
+        tree.drop_tab(
+            &'b',
+            &DropTarget {
+                path: vec![0],
+                zone: DropZone::Split(Side::Left),
+            },
+        );
+        assert_eq!(tree, before);
+    }
+
+    #[test]
+    fn test_float_and_close() {
+        let mut tree = tree();
+        tree.float_tab('c');
+        assert_eq!(tree.root, Some(DockNode::tabs(vec!['a', 'b'])));
+        assert_eq!(tree.floating, vec!['c']);
+
+        tree.drop_tab(
+            &'c',
+            &DropTarget {
+                path: vec![],
+                zone: DropZone::Split(Side::Top),
+            },
+        );
+        assert!(tree.floating.is_empty());
+        assert_eq!(
+            tree.root,
+            Some(DockNode::vertical(
+                DockNode::tabs(vec!['c']),
+                DockNode::tabs(vec!['a', 'b']),
+                0.5,
+            ))
+        );
+
+        assert!(tree.remove_tab(&'a'));
+        assert!(tree.remove_tab(&'b'));
+        assert!(!tree.remove_tab(&'b'));
+        assert_eq!(tree.root, Some(DockNode::tabs(vec!['c'])));
+        assert_eq!(tree.tabs(), vec![&'c']);
+    }
+}
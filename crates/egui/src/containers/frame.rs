@@ -1,10 +1,15 @@
 //! Frame container
 
+use std::sync::Arc;
+
 use crate::{
     InnerResponse, Response, Sense, Style, Ui, UiBuilder, UiKind, UiStackInfo, epaint,
     layers::ShapeIdx,
 };
-use epaint::{Color32, CornerRadius, Margin, MarginF32, Rect, Shadow, Shape, Stroke};
+use epaint::{
+    Color32, CornerRadius, DashPattern, Margin, MarginF32, Rect, RectShape, Shadow, Shape, Stroke,
+    StrokeKind,
+};
 
 /// A frame around some content, including margin, colors, etc.
 ///
@@ -90,7 +95,7 @@ use epaint::{Color32, CornerRadius, Margin, MarginF32, Rect, Shadow, Shape, Stro
 ///
 /// Note that you cannot change the margins after calling `begin`.
 #[doc(alias = "border")]
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[must_use = "You should call .show()"]
 pub struct Frame {
@@ -115,6 +120,13 @@ pub struct Frame {
     #[doc(alias = "border")]
     pub stroke: Stroke,
 
+    /// If set, the [`Self::stroke`] is painted as dashes following this pattern.
+    ///
+    /// Like in CSS, the [`Self::fill`] shows through the gaps between the dashes.
+    ///
+    /// Since most frames are not dashed, this is optional and in an `Arc`.
+    pub stroke_dash: Option<Arc<DashPattern>>,
+
     /// The rounding of the _outer_ corner of the [`Self::stroke`]
     /// (or, if there is no stroke, the outer corner of [`Self::fill`]).
     ///
@@ -144,7 +156,7 @@ pub struct Frame {
 fn frame_size() {
     assert_eq!(
         std::mem::size_of::<Frame>(),
-        32,
+        40,
         "Frame changed size! If it shrank - good! Update this test. If it grew - bad! Try to find a way to avoid it."
     );
     assert!(
@@ -153,6 +165,21 @@ fn frame_size() {
     );
 }
 
+#[test]
+fn frame_stroke_dash() {
+    let content_rect = Rect::from_min_size(epaint::Pos2::ZERO, epaint::Vec2::splat(20.0));
+    let frame = Frame::NONE
+        .stroke(Stroke::new(2.0, Color32::WHITE))
+        .stroke_dash(DashPattern::new(4.0, 2.0));
+    let Shape::Rect(rect_shape) = frame.paint(content_rect) else {
+        panic!("Expected a single RectShape");
+    };
+    assert_eq!(
+        rect_shape.stroke_dash.as_deref(),
+        Some(&DashPattern::new(4.0, 2.0))
+    );
+}
+
 /// ## Constructors
 impl Frame {
     /// No colors, no margins, no border.
@@ -161,6 +188,7 @@ impl Frame {
     pub const NONE: Self = Self {
         inner_margin: Margin::ZERO,
         stroke: Stroke::NONE,
+        stroke_dash: None,
         fill: Color32::TRANSPARENT,
         corner_radius: CornerRadius::ZERO,
         outer_margin: Margin::ZERO,
@@ -273,6 +301,15 @@ impl Frame {
         self
     }
 
+    /// Paint the [`Self::stroke`] as dashes following the given pattern.
+    ///
+    /// The dashes follow the rounded corners of the frame.
+    #[inline]
+    pub fn stroke_dash(mut self, stroke_dash: DashPattern) -> Self {
+        self.stroke_dash = Some(Arc::new(stroke_dash));
+        self
+    }
+
     /// The rounding of the _outer_ corner of the [`Self::stroke`]
     /// (or, if there is no stroke, the outer corner of [`Self::fill`]).
     ///
@@ -401,7 +438,7 @@ impl Frame {
 
         let content_ui = ui.new_child(
             UiBuilder::new()
-                .ui_stack_info(UiStackInfo::new(UiKind::Frame).with_frame(self))
+                .ui_stack_info(UiStackInfo::new(UiKind::Frame).with_frame(self.clone()))
                 .max_rect(max_content_rect),
         );
 
@@ -435,25 +472,29 @@ impl Frame {
             inner_margin: _,
             fill,
             stroke,
+            stroke_dash,
             corner_radius,
             outer_margin: _,
             shadow,
-        } = *self;
+        } = self;
 
         let widget_rect = self.widget_rect(content_rect);
 
-        let frame_shape = Shape::Rect(epaint::RectShape::new(
-            widget_rect,
-            corner_radius,
-            fill,
-            stroke,
-            epaint::StrokeKind::Inside,
-        ));
-
-        if shadow == Default::default() {
+        let frame_shape = Shape::Rect(RectShape {
+            stroke_dash: stroke_dash.clone(),
+            ..RectShape::new(
+                widget_rect,
+                *corner_radius,
+                *fill,
+                *stroke,
+                StrokeKind::Inside,
+            )
+        });
+
+        if *shadow == Default::default() {
             frame_shape
         } else {
-            let shadow = shadow.as_shape(widget_rect, corner_radius);
+            let shadow = shadow.as_shape(widget_rect, *corner_radius);
             Shape::Vec(vec![Shape::from(shadow), frame_shape])
         }
     }
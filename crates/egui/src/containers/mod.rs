@@ -6,6 +6,7 @@ pub(crate) mod area;
 pub mod close_tag;
 pub mod collapsing_header;
 mod combo_box;
+pub mod dock;
 pub mod frame;
 pub mod menu;
 pub mod modal;
@@ -23,6 +24,7 @@ pub use {
     area::{Area, AreaState},
     collapsing_header::{CollapsingHeader, CollapsingResponse},
     combo_box::*,
+    dock::{DockArea, DockTree, DockViewer},
     frame::Frame,
     modal::{Modal, ModalResponse},
     old_popup::*,
@@ -289,9 +289,10 @@ impl SidePanel {
         panel_ui.set_clip_rect(panel_rect); // If we overflow, don't do so visibly (#4475)
 
         let frame = frame.unwrap_or_else(|| Frame::side_top_panel(ui.style()));
+        let inner_margin = frame.inner_margin;
         let inner_response = frame.show(&mut panel_ui, |ui| {
             ui.set_min_height(ui.max_rect().height()); // Make sure the frame fills the full height
-            ui.set_min_width((width_range.min - frame.inner_margin.sum().x).at_least(0.0));
+            ui.set_min_width((width_range.min - inner_margin.sum().x).at_least(0.0));
             add_contents(ui)
         });
 
@@ -785,9 +786,10 @@ impl TopBottomPanel {
         panel_ui.expand_to_include_rect(panel_rect);
         panel_ui.set_clip_rect(panel_rect); // If we overflow, don't do so visibly (#4475)
 
+        let inner_margin = frame.inner_margin;
         let inner_response = frame.show(&mut panel_ui, |ui| {
             ui.set_min_width(ui.max_rect().width()); // Make the frame fill full width
-            ui.set_min_height((height_range.min - frame.inner_margin.sum().y).at_least(0.0));
+            ui.set_min_height((height_range.min - inner_margin.sum().y).at_least(0.0));
             add_contents(ui)
         });
 
@@ -440,8 +440,9 @@ impl Window<'_> {
             fade_out,
         } = self;
 
-        let header_color =
-            frame.map_or_else(|| ctx.style().visuals.widgets.open.weak_bg_fill, |f| f.fill);
+        let header_color = frame
+            .as_ref()
+            .map_or_else(|| ctx.style().visuals.widgets.open.weak_bg_fill, |f| f.fill);
         let mut window_frame = frame.unwrap_or_else(|| Frame::window(&ctx.style()));
 
         let is_explicitly_closed = matches!(open, Some(false));
@@ -511,7 +512,7 @@ impl Window<'_> {
                 possible,
                 area_layer_id,
                 last_frame_outer_rect,
-                window_frame,
+                window_frame.clone(),
             )
         });
 
@@ -520,7 +521,7 @@ impl Window<'_> {
                 + vec2(0.0, title_bar_height_with_margin + title_content_spacing);
 
             resize_response(
-                resize_interaction,
+                &resize_interaction,
                 ctx,
                 margins,
                 area_layer_id,
@@ -540,7 +541,7 @@ impl Window<'_> {
         let content_inner = {
             ctx.with_accessibility_parent(area.id(), || {
                 // BEGIN FRAME --------------------------------
-                let mut frame = window_frame.begin(&mut area_content_ui);
+                let mut frame = window_frame.clone().begin(&mut area_content_ui);
 
                 let show_close_button = open.is_some();
 
@@ -552,7 +553,7 @@ impl Window<'_> {
                         title,
                         show_close_button,
                         collapsible,
-                        window_frame,
+                        window_frame.clone(),
                         title_bar_height_with_margin,
                     );
                     resize.min_size.x = resize.min_size.x.at_least(title_bar.inner_rect.width()); // Prevent making window smaller than title bar width
@@ -593,7 +594,7 @@ impl Window<'_> {
                     &possible,
                     outer_rect,
                     &window_frame,
-                    resize_interaction,
+                    &resize_interaction,
                 );
 
                 // END FRAME --------------------------------
@@ -637,7 +638,7 @@ impl Window<'_> {
 
                 collapsing.store(ctx);
 
-                paint_frame_interaction(&area_content_ui, outer_rect, resize_interaction);
+                paint_frame_interaction(&area_content_ui, outer_rect, &resize_interaction);
 
                 content_inner
             })
@@ -664,7 +665,7 @@ fn paint_resize_corner(
     possible: &PossibleInteractions,
     outer_rect: Rect,
     window_frame: &Frame,
-    i: ResizeInteraction,
+    i: &ResizeInteraction,
 ) {
     let cr = window_frame.corner_radius;
 
@@ -746,7 +747,7 @@ impl PossibleInteractions {
 }
 
 /// Resizing the window edges.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 struct ResizeInteraction {
     /// Outer rect (outside the stroke)
     outer_rect: Rect,
@@ -821,14 +822,14 @@ impl ResizeInteraction {
 }
 
 fn resize_response(
-    resize_interaction: ResizeInteraction,
+    resize_interaction: &ResizeInteraction,
     ctx: &Context,
     margins: Vec2,
     area_layer_id: LayerId,
     area: &mut area::Prepared,
     resize_id: Id,
 ) {
-    let Some(mut new_rect) = move_and_resize_window(ctx, &resize_interaction) else {
+    let Some(mut new_rect) = move_and_resize_window(ctx, resize_interaction) else {
         return;
     };
 
@@ -1028,7 +1029,7 @@ fn resize_interaction(
 }
 
 /// Fill in parts of the window frame when we resize by dragging that part
-fn paint_frame_interaction(ui: &Ui, rect: Rect, interaction: ResizeInteraction) {
+fn paint_frame_interaction(ui: &Ui, rect: Rect, interaction: &ResizeInteraction) {
     use epaint::tessellator::path::add_circle_quadrant;
 
     let visuals = if interaction.any_dragged() {
@@ -1194,7 +1195,7 @@ impl TitleBar {
         collapsing: &mut CollapsingState,
         collapsible: bool,
     ) {
-        let window_frame = self.window_frame;
+        let window_frame = &self.window_frame;
         let title_inner_rect = self.inner_rect;
 
         if false {
@@ -1872,6 +1872,21 @@ impl Context {
         });
 
         if update_fonts {
+            #[cfg(feature = "system_fonts")]
+            if let Some(system_fallback) = &font_definitions.system_fallback {
+                // Characters that were missing while the system fonts were being scanned
+                // are looked up again in the next pass, so make sure there is one:
+                let ctx = self.clone();
+                let cause = RepaintCause::new();
+                system_fallback.on_scan_finished(move || {
+                    ctx.write(|ctx| {
+                        for viewport_id in ctx.all_viewport_ids() {
+                            ctx.request_repaint(viewport_id, cause.clone());
+                        }
+                    });
+                });
+            }
+
             self.memory_mut(|mem| mem.new_font_definitions = Some(font_definitions));
         }
     }
@@ -2548,16 +2563,15 @@ impl ContextImpl {
             .map(|v| v.input.pixels_per_point.into())
             .collect();
         self.fonts.retain(|pixels_per_point, _| {
-            if active_pixels_per_point.contains(pixels_per_point) {
-                true
-            } else {
-                #[cfg(feature = "log")]
+            let keep = active_pixels_per_point.contains(pixels_per_point);
+            #[cfg(feature = "log")]
+            if !keep {
                 log::trace!(
                     "Freeing Fonts with pixels_per_point={} because it is no longer needed",
                     pixels_per_point.into_inner()
                 );
-                false
             }
+            keep
         });
 
         platform_output.num_completed_passes += 1;
@@ -2991,6 +3005,101 @@ impl Context {
         animated_value
     }
 
+    /// Smoothly animate the look of a widget when it changes, e.g. from inactive to hovered.
+    ///
+    /// Pass in the [`WidgetVisuals`] the widget should have now,
+    /// e.g. from [`Style::interact`], and get back the ones to paint it with.
+    /// If `transition` has zero duration, `target` is returned as is.
+    ///
+    /// The built-in widgets do this with [`Style::widget_transition`].
+    ///
+    /// [`WidgetVisuals`]: crate::style::WidgetVisuals
+    #[track_caller] // To track repaint cause
+    pub fn animate_widget_visuals(
+        &self,
+        id: Id,
+        target: crate::style::WidgetVisuals,
+        transition: crate::style::WidgetTransition,
+    ) -> crate::style::WidgetVisuals {
+        if transition.duration <= 0.0 {
+            return target;
+        }
+
+        let animated = self.write(|ctx| {
+            ctx.animation_manager.animate_visuals(
+                &ctx.viewports.entry(ctx.viewport_id()).or_default().input,
+                transition,
+                id,
+                target,
+            )
+        });
+        let animation_in_progress = animated != target;
+        if animation_in_progress {
+            self.request_repaint();
+        }
+
+        animated
+    }
+
+    /// Play a [`crate::animation::Track`] of keyframes, returning its current value.
+    ///
+    /// The track starts playing the first time this is called with the given `id`,
+    /// and can be restarted with [`Self::reset_animation`].
+    ///
+    /// This only requests repaints while the value is changing,
+    /// e.g. not during the delay or a hold, nor after the track has finished.
+    #[track_caller] // To track repaint cause
+    pub fn animate_track<T: crate::animation::Animatable>(
+        &self,
+        id: Id,
+        track: &crate::animation::Track<T>,
+    ) -> T {
+        let (now, start_time) = self.write(|ctx| {
+            let input = &ctx.viewports.entry(ctx.viewport_id()).or_default().input;
+            let start_time = ctx.animation_manager.track_start_time(input, id);
+            (input.time, start_time)
+        });
+
+        let (value, next_change) = track.sample_and_next_change((now - start_time) as f32);
+        match next_change {
+            Some(0.0) => self.request_repaint(),
+            Some(seconds) => self.request_repaint_after_secs(seconds),
+            None => {}
+        }
+        value
+    }
+
+    /// Pull a value towards `target` with a [`crate::animation::Spring`].
+    ///
+    /// At the first call the value is set to `target`.
+    /// After that, it springs towards the latest `target`, keeping its velocity when the target changes.
+    #[track_caller] // To track repaint cause
+    pub fn animate_spring<T: crate::animation::Animatable>(
+        &self,
+        id: Id,
+        target: T,
+        spring: crate::animation::Spring,
+    ) -> T {
+        let (value, at_rest) = self.write(|ctx| {
+            ctx.animation_manager.animate_spring(
+                &ctx.viewports.entry(ctx.viewport_id()).or_default().input,
+                &spring,
+                id,
+                target.to_components(),
+            )
+        });
+        if !at_rest {
+            self.request_repaint();
+        }
+        T::from_components(value)
+    }
+
+    /// Forget the state of the animations with this id,
+    /// so that e.g. [`Self::animate_track`] plays from the start again.
+    pub fn reset_animation(&self, id: Id) {
+        self.write(|ctx| ctx.animation_manager.reset(id));
+    }
+
     /// Clear memory of any animations.
     pub fn clear_animations(&self) {
         self.write(|ctx| ctx.animation_manager = Default::default());
@@ -4151,4 +4260,43 @@ mod test {
             );
         }
     }
+
+    #[cfg(all(feature = "system_fonts", feature = "default_fonts"))]
+    #[test]
+    fn test_repaint_when_system_fonts_are_scanned() {
+        use crate::{FontDefinitions, FontId};
+
+        // Contains the font files, next to some license files:
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/../epaint_default_fonts/fonts");
+
+        let mut definitions = FontDefinitions::default();
+        for fonts in definitions.families.values_mut() {
+            fonts.retain(|name| name != "NotoEmoji-Regular" && name != "emoji-icon-font");
+        }
+        definitions.system_fallback = Some(epaint::text::SystemFontFallback::from_directories([
+            directory,
+        ]));
+
+        let ctx = Context::default();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        ctx.set_request_repaint_callback(move |_| {
+            if std::thread::current().name() == Some("epaint_system_fonts") {
+                sender.send(()).ok();
+            }
+        });
+        ctx.set_fonts(definitions);
+
+        let has_cat =
+            |ctx: &Context| ctx.fonts(|fonts| fonts.has_glyph(&FontId::proportional(14.0), '🐱'));
+        let _ = ctx.run(Default::default(), |ctx| {
+            has_cat(ctx); // Probably missing, since the scan is still running
+        });
+
+        receiver
+            .recv_timeout(std::time::Duration::from_secs(60))
+            .expect("A repaint should be requested when the scan finishes");
+
+        // The next pass finds the character, without any new input:
+        let _ = ctx.run(Default::default(), |ctx| assert!(has_cat(ctx)));
+    }
 }
@@ -0,0 +1,223 @@
+//! Named colors and sizes that are part of the [`crate::Visuals`], see [`DesignTokens`].
+
+use std::collections::BTreeMap;
+
+use crate::Color32;
+
+/// A key into [`DesignTokens`].
+///
+/// This is implemented for strings, and for [`SemanticColor`].
+/// You can implement it for your own types to get typed, autocompleted tokens:
+///
+/// ```
+/// # use egui::{Color32, DesignToken};
+/// enum AppColor {
+///     AccentSecondary,
+///     DangerBackground,
+/// }
+///
+/// impl DesignToken for AppColor {
+///     fn name(&self) -> &str {
+///         match self {
+///             Self::AccentSecondary => "accent_secondary",
+///             Self::DangerBackground => "danger_background",
+///         }
+///     }
+/// }
+///
+/// # egui::__run_test_ctx(|ctx| {
+/// ctx.style_mut_of(egui::Theme::Dark, |style| {
+///     let tokens = &mut style.visuals.tokens;
+///     tokens.set_color(AppColor::DangerBackground, Color32::from_rgb(80, 20, 20));
+/// });
+/// ctx.style_mut_of(egui::Theme::Light, |style| {
+///     let tokens = &mut style.visuals.tokens;
+///     tokens.set_color(AppColor::DangerBackground, Color32::from_rgb(255, 220, 220));
+/// });
+/// # });
+///
+/// # egui::__run_test_ui(|ui| {
+/// // Picks the color of the current theme:
+/// let fill = ui.visuals().token_color(AppColor::DangerBackground);
+/// ui.label(egui::RichText::new("Saved").color_token(egui::SemanticColor::Success));
+/// # });
+/// ```
+pub trait DesignToken {
+    /// The name the token is stored under, e.g. in theme files.
+    fn name(&self) -> &str;
+}
+
+impl DesignToken for str {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl DesignToken for String {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+impl<T: DesignToken + ?Sized> DesignToken for &T {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// Colors with a meaning, that all themes should define.
+///
+/// Look them up with [`crate::Visuals::token_color`].
+/// Most are part of [`DesignTokens::dark`] and [`DesignTokens::light`],
+/// but [`Self::Warning`] and [`Self::Danger`] follow the [`crate::Visuals`] fields they correspond to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SemanticColor {
+    /// The main brand or highlight color.
+    Accent,
+
+    /// Something went well, e.g. a passed check.
+    Success,
+
+    /// Neutral information.
+    Info,
+
+    /// Something needs attention.
+    ///
+    /// Unless set in [`DesignTokens`], this is [`crate::Visuals::warn_fg_color`].
+    Warning,
+
+    /// Something went wrong, or is destructive.
+    ///
+    /// Unless set in [`DesignTokens`], this is [`crate::Visuals::error_fg_color`].
+    Danger,
+}
+
+impl SemanticColor {
+    pub const ALL: [Self; 5] = [
+        Self::Accent,
+        Self::Success,
+        Self::Info,
+        Self::Warning,
+        Self::Danger,
+    ];
+}
+
+impl DesignToken for SemanticColor {
+    fn name(&self) -> &str {
+        match self {
+            Self::Accent => "accent",
+            Self::Success => "success",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Danger => "danger",
+        }
+    }
+}
+
+/// An extensible table of named colors and sizes,
+/// stored in [`crate::Visuals::tokens`].
+///
+/// Since the dark and light [`crate::Style`] each have their own [`crate::Visuals`],
+/// a token can have a different value in each theme,
+/// and looking it up gives the value for the current theme (see [`crate::ThemePreference`]).
+///
+/// Look up colors with [`crate::Visuals::token_color`], or use them for text with [`crate::RichText::color_token`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DesignTokens {
+    pub colors: BTreeMap<String, Color32>,
+
+    /// Sizes, spacings, etc.
+    pub numbers: BTreeMap<String, f32>,
+}
+
+impl DesignTokens {
+    /// The default tokens of the dark theme.
+    pub fn dark() -> Self {
+        Self::default()
+            .with_color(SemanticColor::Accent, Color32::from_rgb(0, 140, 190))
+            .with_color(SemanticColor::Success, Color32::from_rgb(90, 200, 90))
+            .with_color(SemanticColor::Info, Color32::from_rgb(90, 170, 255))
+    }
+
+    /// The default tokens of the light theme.
+    pub fn light() -> Self {
+        Self::default()
+            .with_color(SemanticColor::Accent, Color32::from_rgb(0, 110, 170))
+            .with_color(SemanticColor::Success, Color32::from_rgb(0, 140, 0))
+            .with_color(SemanticColor::Info, Color32::from_rgb(0, 100, 200))
+    }
+
+    #[inline]
+    pub fn with_color(mut self, token: impl DesignToken, color: Color32) -> Self {
+        self.set_color(token, color);
+        self
+    }
+
+    #[inline]
+    pub fn with_number(mut self, token: impl DesignToken, number: f32) -> Self {
+        self.set_number(token, number);
+        self
+    }
+
+    pub fn color(&self, token: impl DesignToken) -> Option<Color32> {
+        self.colors.get(token.name()).copied()
+    }
+
+    pub fn set_color(&mut self, token: impl DesignToken, color: Color32) {
+        self.colors.insert(token.name().to_owned(), color);
+    }
+
+    pub fn number(&self, token: impl DesignToken) -> Option<f32> {
+        self.numbers.get(token.name()).copied()
+    }
+
+    pub fn set_number(&mut self, token: impl DesignToken, number: f32) {
+        self.numbers.insert(token.name().to_owned(), number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semantic_colors_in_all_themes() {
+        for visuals in [crate::Visuals::dark(), crate::Visuals::light()] {
+            for color in SemanticColor::ALL {
+                assert!(
+                    visuals.try_token_color(color).is_some(),
+                    "{color:?} is missing"
+                );
+            }
+        }
+        assert_ne!(
+            DesignTokens::dark().color(SemanticColor::Success),
+            DesignTokens::light().color(SemanticColor::Success)
+        );
+    }
+
+    #[test]
+    fn test_string_and_typed_keys() {
+        let tokens = DesignTokens::default().with_color("success", Color32::GREEN);
+        assert_eq!(tokens.color(SemanticColor::Success), Some(Color32::GREEN));
+        assert_eq!(tokens.color("success".to_owned()), Some(Color32::GREEN));
+        assert_eq!(tokens.number("gutter"), None);
+    }
+
+    #[test]
+    fn test_warning_and_danger_follow_visuals() {
+        let mut visuals = crate::Visuals::light();
+        visuals.warn_fg_color = Color32::YELLOW;
+        visuals.error_fg_color = Color32::BROWN;
+        assert_eq!(visuals.token_color(SemanticColor::Warning), Color32::YELLOW);
+        assert_eq!(visuals.token_color(SemanticColor::Danger), Color32::BROWN);
+
+        // An explicit token wins:
+        visuals
+            .tokens
+            .set_color(SemanticColor::Danger, Color32::RED);
+        assert_eq!(visuals.token_color(SemanticColor::Danger), Color32::RED);
+    }
+}
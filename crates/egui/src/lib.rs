@@ -407,12 +407,14 @@
 #![allow(clippy::float_cmp)]
 #![allow(clippy::manual_range_contains)]
 
+pub mod animation;
 mod animation_manager;
 pub mod cache;
 pub mod containers;
 mod context;
 mod data;
 pub mod debug_text;
+mod design_tokens;
 mod drag_and_drop;
 pub(crate) mod grid;
 pub mod gui_zoom;
@@ -434,6 +436,7 @@ pub(crate) mod placer;
 pub mod response;
 mod sense;
 pub mod style;
+mod style_rules;
 pub mod text_selection;
 mod ui;
 mod ui_builder;
@@ -476,9 +479,12 @@ pub use epaint::{
 pub mod text {
     pub use crate::text_selection::CCursorRange;
     pub use epaint::text::{
-        FontData, FontDefinitions, FontFamily, Fonts, Galley, LayoutJob, LayoutSection, TAB_SIZE,
-        TextFormat, TextWrapping, cursor::CCursor,
+        FontData, FontDefinitions, FontFamily, Fonts, Galley, InlineBox, LayoutJob, LayoutSection,
+        TAB_SIZE, TextFormat, TextWrapping, cursor::CCursor,
     };
+
+    #[cfg(feature = "system_fonts")]
+    pub use epaint::text::SystemFontFallback;
 }
 
 pub use self::{
@@ -493,6 +499,7 @@ pub use self::{
             UserAttentionType, WidgetInfo,
         },
     },
+    design_tokens::{DesignToken, DesignTokens, SemanticColor},
     drag_and_drop::DragAndDrop,
     epaint::text::TextWrapMode,
     grid::Grid,
@@ -506,6 +513,7 @@ pub use self::{
     response::{InnerResponse, Response},
     sense::Sense,
     style::{FontSelection, Spacing, Style, TextStyle, Visuals},
+    style_rules::{StyleRule, WidgetVisualsOverride},
     text::{Galley, TextFormat},
     ui::Ui,
     ui_builder::UiBuilder,
@@ -745,6 +745,13 @@ impl Focus {
 }
 
 impl Memory {
+    /// The version of the serialized format of [`Memory`].
+    ///
+    /// This is bumped whenever a change to egui means that [`Memory`] serialized by an older egui
+    /// can no longer be deserialized, so that integrations (like `eframe`)
+    /// can back up and migrate the old data instead of silently dropping it.
+    pub const SERIALIZATION_VERSION: u32 = 1;
+
     pub(crate) fn begin_pass(&mut self, new_raw_input: &RawInput, viewports: &ViewportIdSet) {
         profiling::function_scope!();
 
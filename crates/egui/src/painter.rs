@@ -383,6 +383,8 @@ impl Painter {
             radius,
             fill: fill_color.into(),
             stroke: stroke.into(),
+            stroke_dash: None,
+            brush: None,
         })
     }
 
@@ -397,6 +399,8 @@ impl Painter {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_dash: None,
+            brush: None,
         })
     }
 
@@ -406,6 +410,8 @@ impl Painter {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_dash: None,
+            brush: None,
         })
     }
 
@@ -7,8 +7,8 @@ use epaint::{CornerRadius, Shadow, Stroke, text::FontTweak};
 use std::{collections::BTreeMap, ops::RangeInclusive, sync::Arc};
 
 use crate::{
-    ComboBox, CursorIcon, FontFamily, FontId, Grid, Margin, Response, RichText, TextWrapMode,
-    WidgetText,
+    ComboBox, CursorIcon, DesignToken, DesignTokens, FontFamily, FontId, Grid, Margin, Response,
+    RichText, StyleRule, TextWrapMode, WidgetText,
     ecolor::Color32,
     emath::{Rangef, Rect, Vec2, pos2, vec2},
     reset_button_with,
@@ -314,6 +314,11 @@ pub struct Style {
     /// How many seconds a typical animation should last.
     pub animation_time: f32,
 
+    /// How widgets like [`crate::Button`] animate when they are hovered, clicked, etc.
+    ///
+    /// Off by default.
+    pub widget_transition: WidgetTransition,
+
     /// Options to help debug why egui behaves strangely.
     ///
     /// Only available in debug builds.
@@ -336,6 +341,11 @@ pub struct Style {
 
     /// Use a more compact style for menus.
     pub compact_menu_style: bool,
+
+    /// Changes to [`Visuals::widgets`] for widgets with certain classes.
+    ///
+    /// See [`StyleRule`] and [`crate::UiBuilder::class`].
+    pub class_rules: Vec<StyleRule>,
 }
 
 #[test]
@@ -356,14 +366,37 @@ impl Style {
     pub fn interact_selectable(&self, response: &Response, selected: bool) -> WidgetVisuals {
         let mut visuals = *self.visuals.widgets.style(response);
         if selected {
-            visuals.weak_bg_fill = self.visuals.selection.bg_fill;
-            visuals.bg_fill = self.visuals.selection.bg_fill;
-            // visuals.bg_stroke = self.visuals.selection.stroke;
-            visuals.fg_stroke = self.visuals.selection.stroke;
+            self.select(&mut visuals);
         }
         visuals
     }
 
+    /// Change the visuals of a widget to show that it is selected.
+    pub(crate) fn select(&self, visuals: &mut WidgetVisuals) {
+        visuals.weak_bg_fill = self.visuals.selection.bg_fill;
+        visuals.bg_fill = self.visuals.selection.bg_fill;
+        // visuals.bg_stroke = self.visuals.selection.stroke;
+        visuals.fg_stroke = self.visuals.selection.stroke;
+    }
+
+    /// The [`Visuals::widgets`] for a widget with the given classes,
+    /// after applying the matching [`Self::class_rules`].
+    ///
+    /// Usually you want [`crate::Ui::widgets_with_classes`] instead,
+    /// which also includes the classes of the [`crate::Ui`].
+    pub fn widgets_with_classes(
+        &self,
+        has_class: impl Fn(&str) -> bool,
+    ) -> std::borrow::Cow<'_, Widgets> {
+        let mut widgets = std::borrow::Cow::Borrowed(&self.visuals.widgets);
+        for rule in &self.class_rules {
+            if rule.matches(&has_class) {
+                rule.apply(widgets.to_mut());
+            }
+        }
+        widgets
+    }
+
     /// Style to use for non-interactive widgets.
     pub fn noninteractive(&self) -> &WidgetVisuals {
         &self.visuals.widgets.noninteractive
@@ -831,6 +864,75 @@ impl ScrollAnimation {
 
 // ----------------------------------------------------------------------------
 
+/// How widgets animate between the states in [`Widgets`], e.g. when they become hovered.
+///
+/// The fill, strokes, corner radius and expansion of the [`WidgetVisuals`] are interpolated.
+/// Used by [`crate::Context::animate_widget_visuals`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WidgetTransition {
+    /// How many seconds a transition takes.
+    ///
+    /// The default is zero, which means widgets change their look instantly.
+    pub duration: f32,
+
+    /// How to move from the old to the new look over the duration.
+    pub easing: emath::easing::Easing,
+}
+
+impl Default for WidgetTransition {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl WidgetTransition {
+    /// No animation, change instantly.
+    pub fn none() -> Self {
+        Self {
+            duration: 0.0,
+            easing: emath::easing::Easing::CubicOut,
+        }
+    }
+
+    /// Animate with the given duration, in seconds.
+    pub fn duration(duration: f32) -> Self {
+        Self {
+            duration,
+            ..Self::none()
+        }
+    }
+
+    #[inline]
+    pub fn easing(mut self, easing: emath::easing::Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn ui(&mut self, ui: &mut crate::Ui) {
+        let Self { duration, easing } = self;
+
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(duration)
+                    .range(0.0..=1.0)
+                    .speed(0.01)
+                    .suffix(" s"),
+            );
+            ComboBox::from_id_salt("widget_transition_easing")
+                .selected_text(format!("{easing:?}"))
+                .show_ui(ui, |ui| {
+                    for option in emath::easing::Easing::ALL {
+                        ui.selectable_value(easing, option, format!("{option:?}"));
+                    }
+                });
+        });
+    }
+}
+
+// ----------------------------------------------------------------------------
+
 /// How and when interaction happens.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -1028,6 +1130,11 @@ pub struct Visuals {
 
     /// How much to modify the alpha of a disabled widget.
     pub disabled_alpha: f32,
+
+    /// Named colors and sizes, like [`crate::SemanticColor::Success`].
+    ///
+    /// Add your own to have them follow the dark/light theme.
+    pub tokens: DesignTokens,
 }
 
 impl Visuals {
@@ -1095,6 +1202,31 @@ impl Visuals {
     pub fn gray_out(&self, color: Color32) -> Color32 {
         crate::ecolor::tint_color_towards(color, self.widgets.noninteractive.weak_bg_fill)
     }
+
+    /// Look up a color in [`Self::tokens`].
+    ///
+    /// Falls back to [`Self::text_color`] if the token isn't defined.
+    pub fn token_color(&self, token: impl DesignToken) -> Color32 {
+        self.try_token_color(token)
+            .unwrap_or_else(|| self.text_color())
+    }
+
+    /// Look up a color in [`Self::tokens`], or `None` if the token isn't defined.
+    ///
+    /// Unless they are set in the tokens, [`crate::SemanticColor::Warning`]
+    /// and [`crate::SemanticColor::Danger`] are [`Self::warn_fg_color`] and [`Self::error_fg_color`].
+    pub fn try_token_color(&self, token: impl DesignToken) -> Option<Color32> {
+        let name = token.name();
+        self.tokens.color(name).or_else(|| {
+            if name == crate::SemanticColor::Warning.name() {
+                Some(self.warn_fg_color)
+            } else if name == crate::SemanticColor::Danger.name() {
+                Some(self.error_fg_color)
+            } else {
+                None
+            }
+        })
+    }
 }
 
 /// Selected text, selected elements etc
@@ -1197,6 +1329,27 @@ impl WidgetVisuals {
         self.fg_stroke.color
     }
 
+    /// Interpolate between `self` (at `t = 0`) and `other` (at `t = 1`).
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp_stroke = |a: Stroke, b: Stroke| Stroke {
+            width: emath::lerp(a.width..=b.width, t),
+            color: a.color.lerp_to_gamma(b.color, t),
+        };
+        let corner_radius = {
+            let a = epaint::CornerRadiusF32::from(self.corner_radius);
+            let b = epaint::CornerRadiusF32::from(other.corner_radius);
+            (a + (b - a) * t).into()
+        };
+        Self {
+            bg_fill: self.bg_fill.lerp_to_gamma(other.bg_fill, t),
+            weak_bg_fill: self.weak_bg_fill.lerp_to_gamma(other.weak_bg_fill, t),
+            bg_stroke: lerp_stroke(self.bg_stroke, other.bg_stroke),
+            corner_radius,
+            fg_stroke: lerp_stroke(self.fg_stroke, other.fg_stroke),
+            expansion: emath::lerp(self.expansion..=other.expansion, t),
+        }
+    }
+
     #[deprecated = "Renamed to corner_radius"]
     pub fn rounding(&self) -> CornerRadius {
         self.corner_radius
@@ -1304,6 +1457,7 @@ impl Default for Style {
             interaction: Interaction::default(),
             visuals: Visuals::default(),
             animation_time: 1.0 / 12.0,
+            widget_transition: WidgetTransition::default(),
             #[cfg(debug_assertions)]
             debug: Default::default(),
             explanation_tooltips: false,
@@ -1311,6 +1465,7 @@ impl Default for Style {
             always_scroll_the_only_direction: false,
             scroll_animation: ScrollAnimation::default(),
             compact_menu_style: true,
+            class_rules: Vec::new(),
         }
     }
 }
@@ -1415,6 +1570,8 @@ impl Visuals {
 
             numeric_color_space: NumericColorSpace::GammaByte,
             disabled_alpha: 0.5,
+
+            tokens: DesignTokens::dark(),
         }
     }
 
@@ -1454,6 +1611,8 @@ impl Visuals {
                 ..Default::default()
             },
 
+            tokens: DesignTokens::light(),
+
             ..Self::dark()
         }
     }
@@ -1608,6 +1767,7 @@ impl Style {
             interaction,
             visuals,
             animation_time,
+            widget_transition,
             #[cfg(debug_assertions)]
             debug,
             explanation_tooltips,
@@ -1615,6 +1775,7 @@ impl Style {
             always_scroll_the_only_direction,
             scroll_animation,
             compact_menu_style,
+            class_rules,
         } = self;
 
         crate::Grid::new("_options").show(ui, |ui| {
@@ -1709,6 +1870,10 @@ impl Style {
                     .suffix(" s"),
             );
             ui.end_row();
+
+            ui.label("Widget transition");
+            widget_transition.ui(ui);
+            ui.end_row();
         });
 
         ui.collapsing("🔠 Text styles", |ui| text_styles_ui(ui, text_styles));
@@ -1716,6 +1881,7 @@ impl Style {
         ui.collapsing("☝ Interaction", |ui| interaction.ui(ui));
         ui.collapsing("🎨 Visuals", |ui| visuals.ui(ui));
         ui.collapsing("🔄 Scroll animation", |ui| scroll_animation.ui(ui));
+        ui.collapsing("🏷 Class rules", |ui| class_rules_ui(ui, class_rules));
 
         #[cfg(debug_assertions)]
         ui.collapsing("🐛 Debug", |ui| debug.ui(ui));
@@ -2096,6 +2262,7 @@ impl Visuals {
 
             numeric_color_space,
             disabled_alpha,
+            tokens,
         } = self;
 
         ui.collapsing("Background colors", |ui| {
@@ -2191,6 +2358,7 @@ impl Visuals {
 
         ui.collapsing("Widgets", |ui| widgets.ui(ui));
         ui.collapsing("Selection", |ui| selection.ui(ui));
+        ui.collapsing("Design tokens", |ui| tokens.ui(ui));
 
         ui.collapsing("Misc", |ui| {
             ui.add(Slider::new(resize_corner_size, 0.0..=20.0).text("resize_corner_size"));
@@ -2380,6 +2548,41 @@ fn ui_text_color(ui: &mut Ui, color: &mut Color32, label: impl Into<RichText>) -
     .response
 }
 
+fn class_rules_ui(ui: &mut Ui, class_rules: &mut Vec<StyleRule>) {
+    if class_rules.is_empty() {
+        ui.weak("No rules");
+    }
+    let mut remove = None;
+    for (i, rule) in class_rules.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rule.selector)
+                .on_hover_text("Space-separated class names");
+            if ui.small_button("🗑").on_hover_text("Remove rule").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        class_rules.remove(i);
+    }
+}
+
+impl DesignTokens {
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let Self { colors, numbers } = self;
+
+        for (name, color) in colors {
+            ui_text_color(ui, color, name.as_str());
+        }
+        for (name, number) in numbers {
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(number).speed(0.1));
+                ui.label(name.as_str());
+            });
+        }
+    }
+}
+
 impl HandleShape {
     pub fn ui(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
@@ -2628,6 +2831,7 @@ impl Widget for &mut crate::Frame {
             shadow,
             fill,
             stroke,
+            stroke_dash: _,
         } = self;
 
         crate::Grid::new("frame")
@@ -0,0 +1,231 @@
+//! Style classes for widgets, see [`StyleRule`].
+
+use crate::{
+    Color32, CornerRadius, Stroke,
+    style::{WidgetVisuals, Widgets},
+};
+
+/// Changes to some of the fields of a [`WidgetVisuals`].
+///
+/// Fields that are `None` are left as they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WidgetVisualsOverride {
+    pub bg_fill: Option<Color32>,
+    pub weak_bg_fill: Option<Color32>,
+    pub bg_stroke: Option<Stroke>,
+    pub corner_radius: Option<CornerRadius>,
+    pub fg_stroke: Option<Stroke>,
+    pub expansion: Option<f32>,
+}
+
+impl WidgetVisualsOverride {
+    #[inline]
+    pub fn bg_fill(mut self, bg_fill: impl Into<Color32>) -> Self {
+        self.bg_fill = Some(bg_fill.into());
+        self
+    }
+
+    #[inline]
+    pub fn weak_bg_fill(mut self, weak_bg_fill: impl Into<Color32>) -> Self {
+        self.weak_bg_fill = Some(weak_bg_fill.into());
+        self
+    }
+
+    #[inline]
+    pub fn bg_stroke(mut self, bg_stroke: impl Into<Stroke>) -> Self {
+        self.bg_stroke = Some(bg_stroke.into());
+        self
+    }
+
+    #[inline]
+    pub fn corner_radius(mut self, corner_radius: impl Into<CornerRadius>) -> Self {
+        self.corner_radius = Some(corner_radius.into());
+        self
+    }
+
+    #[inline]
+    pub fn fg_stroke(mut self, fg_stroke: impl Into<Stroke>) -> Self {
+        self.fg_stroke = Some(fg_stroke.into());
+        self
+    }
+
+    #[inline]
+    pub fn expansion(mut self, expansion: f32) -> Self {
+        self.expansion = Some(expansion);
+        self
+    }
+
+    pub fn apply(&self, visuals: &mut WidgetVisuals) {
+        let Self {
+            bg_fill,
+            weak_bg_fill,
+            bg_stroke,
+            corner_radius,
+            fg_stroke,
+            expansion,
+        } = *self;
+
+        if let Some(bg_fill) = bg_fill {
+            visuals.bg_fill = bg_fill;
+        }
+        if let Some(weak_bg_fill) = weak_bg_fill {
+            visuals.weak_bg_fill = weak_bg_fill;
+        }
+        if let Some(bg_stroke) = bg_stroke {
+            visuals.bg_stroke = bg_stroke;
+        }
+        if let Some(corner_radius) = corner_radius {
+            visuals.corner_radius = corner_radius;
+        }
+        if let Some(fg_stroke) = fg_stroke {
+            visuals.fg_stroke = fg_stroke;
+        }
+        if let Some(expansion) = expansion {
+            visuals.expansion = expansion;
+        }
+    }
+}
+
+/// Changes the look of widgets with certain classes, like a CSS rule.
+///
+/// Rules are stored in [`crate::Style::class_rules`].
+/// A widget has the classes set on it directly (e.g. with [`crate::Button::class`]),
+/// the classes of the [`crate::Ui`]:s it is in (see [`crate::UiBuilder::class`]),
+/// and a class for its type, like `"button"`, `"checkbox"` or `"text_edit"`.
+///
+/// The rule applies if the widget has all the classes in the [`Self::selector`].
+/// If several rules apply, they are applied in order, so later rules win.
+///
+/// ```
+/// # use egui::{Color32, Stroke, StyleRule, WidgetVisualsOverride};
+/// # egui::__run_test_ui(|ui| {
+/// ui.style_mut().class_rules.extend([
+///     StyleRule::new("danger button")
+///         .all(WidgetVisualsOverride::default().weak_bg_fill(Color32::from_rgb(160, 30, 30)))
+///         .hovered(WidgetVisualsOverride::default().weak_bg_fill(Color32::from_rgb(200, 40, 40))),
+///     StyleRule::new("toolbar").all(WidgetVisualsOverride::default().corner_radius(0)),
+/// ]);
+///
+/// ui.scope_builder(egui::UiBuilder::new().class("toolbar"), |ui| {
+///     ui.horizontal(|ui| {
+///         ui.button("Save");
+///         ui.add(egui::Button::new("Delete").class("danger"));
+///     });
+/// });
+/// # });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StyleRule {
+    /// Space-separated class names, e.g. `"primary button"`.
+    pub selector: String,
+
+    /// Applied to the widget in all states of interaction.
+    pub all: WidgetVisualsOverride,
+
+    /// Applied on top of [`Self::all`] when the widget is at rest.
+    pub inactive: WidgetVisualsOverride,
+
+    /// Applied on top of [`Self::all`] when the widget is hovered.
+    pub hovered: WidgetVisualsOverride,
+
+    /// Applied on top of [`Self::all`] when the widget is clicked, dragged or focused.
+    pub active: WidgetVisualsOverride,
+}
+
+impl StyleRule {
+    pub fn new(selector: impl Into<String>) -> Self {
+        Self {
+            selector: selector.into(),
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn all(mut self, all: WidgetVisualsOverride) -> Self {
+        self.all = all;
+        self
+    }
+
+    #[inline]
+    pub fn inactive(mut self, inactive: WidgetVisualsOverride) -> Self {
+        self.inactive = inactive;
+        self
+    }
+
+    #[inline]
+    pub fn hovered(mut self, hovered: WidgetVisualsOverride) -> Self {
+        self.hovered = hovered;
+        self
+    }
+
+    #[inline]
+    pub fn active(mut self, active: WidgetVisualsOverride) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// Does a widget with these classes match the [`Self::selector`]?
+    pub fn matches(&self, has_class: impl Fn(&str) -> bool) -> bool {
+        self.selector.split_whitespace().all(has_class)
+    }
+
+    pub(crate) fn apply(&self, widgets: &mut Widgets) {
+        let Widgets {
+            noninteractive,
+            inactive,
+            hovered,
+            active,
+            open,
+        } = widgets;
+
+        for visuals in [noninteractive, inactive, hovered, active, open] {
+            self.all.apply(visuals);
+        }
+        self.inactive.apply(&mut widgets.inactive);
+        self.hovered.apply(&mut widgets.hovered);
+        self.active.apply(&mut widgets.active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Style;
+
+    #[test]
+    fn test_rules_in_order() {
+        let red = Color32::RED;
+        let green = Color32::GREEN;
+        let style = Style {
+            class_rules: vec![
+                StyleRule::new("button").all(WidgetVisualsOverride::default().weak_bg_fill(red)),
+                StyleRule::new("primary button")
+                    .all(WidgetVisualsOverride::default().weak_bg_fill(green))
+                    .hovered(WidgetVisualsOverride::default().expansion(2.0)),
+            ],
+            ..Default::default()
+        };
+        let default_widgets = &style.visuals.widgets;
+
+        let checkbox = style.widgets_with_classes(|class| class == "checkbox");
+        assert_eq!(&*checkbox, default_widgets);
+
+        let button = style.widgets_with_classes(|class| class == "button");
+        assert_eq!(button.inactive.weak_bg_fill, red);
+        assert_eq!(button.hovered.expansion, default_widgets.hovered.expansion);
+
+        let primary = style.widgets_with_classes(|class| ["primary", "button"].contains(&class));
+        assert_eq!(primary.inactive.weak_bg_fill, green);
+        assert_eq!(primary.hovered.weak_bg_fill, green);
+        assert_eq!(primary.hovered.expansion, 2.0);
+        assert_eq!(
+            primary.inactive.expansion,
+            default_widgets.inactive.expansion
+        );
+        assert_eq!(primary.inactive.bg_fill, default_widgets.inactive.bg_fill);
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{Galley, Painter, Rect, Ui, Visuals, pos2, vec2};
+use crate::{Color32, Galley, Painter, Rangef, Rect, Ui, Visuals, vec2};
 
 use super::CCursorRange;
 
@@ -15,6 +15,22 @@ pub fn paint_text_selection(
     galley: &mut Arc<Galley>,
     visuals: &Visuals,
     cursor_range: &CCursorRange,
+    new_vertex_indices: Option<&mut Vec<RowVertexIndices>>,
+) {
+    paint_text_highlight(
+        galley,
+        visuals.selection.bg_fill,
+        cursor_range,
+        new_vertex_indices,
+    );
+}
+
+/// Adds rectangles of the given color behind the glyphs in the given range,
+/// e.g. to highlight search results.
+pub fn paint_text_highlight(
+    galley: &mut Arc<Galley>,
+    color: Color32,
+    cursor_range: &CCursorRange,
     mut new_vertex_indices: Option<&mut Vec<RowVertexIndices>>,
 ) {
     if cursor_range.is_empty() {
@@ -25,7 +41,6 @@ pub fn paint_text_selection(
     // and so we need to clone it if it is shared:
     let galley: &mut Galley = Arc::make_mut(galley);
 
-    let color = visuals.selection.bg_fill;
     let [min, max] = cursor_range.sorted_cursors();
     let min = galley.layout_from_cursor(min);
     let max = galley.layout_from_cursor(max);
@@ -33,64 +48,83 @@ pub fn paint_text_selection(
     for ri in min.row..=max.row {
         let row = Arc::make_mut(&mut galley.rows[ri].row);
 
-        let left = if ri == min.row {
-            row.x_offset(min.column)
+        let newline_size = if row.ends_with_newline {
+            row.height() / 2.0 // visualize that we select the newline
         } else {
             0.0
         };
-        let right = if ri == max.row {
-            row.x_offset(max.column)
+
+        let x_ranges = if row.has_rtl() {
+            // Bidirectional text: the selected characters may not be next to each other.
+            let start_column = if ri == min.row { min.column } else { 0 };
+            let end_column = if ri == max.row {
+                max.column
+            } else {
+                row.char_count_excluding_newline()
+            };
+            let mut x_ranges = row.x_ranges(start_column..end_column);
+            if ri != max.row && 0.0 < newline_size {
+                x_ranges.push(Rangef::new(row.size.x, row.size.x + newline_size));
+            }
+            x_ranges
         } else {
-            let newline_size = if row.ends_with_newline {
-                row.height() / 2.0 // visualize that we select the newline
+            let left = if ri == min.row {
+                row.x_offset(min.column)
             } else {
                 0.0
             };
-            row.size.x + newline_size
+            let right = if ri == max.row {
+                row.x_offset(max.column)
+            } else {
+                row.size.x + newline_size
+            };
+            vec![Rangef::new(left, right)]
         };
 
-        let rect = Rect::from_min_max(pos2(left, 0.0), pos2(right, row.size.y));
-        let mesh = &mut row.visuals.mesh;
-
-        // Time to insert the selection rectangle into the row mesh.
-        // It should be on top (after) of any background in the galley,
-        // but behind (before) any glyphs. The row visuals has this information:
-        let glyph_index_start = row.visuals.glyph_index_start;
-
-        // Start by appending the selection rectangle to end of the mesh, as two triangles (= 6 indices):
-        let num_indices_before = mesh.indices.len();
-        mesh.add_colored_rect(rect, color);
-        assert_eq!(
-            num_indices_before + 6,
-            mesh.indices.len(),
-            "We expect exactly 6 new indices"
-        );
-
-        // Copy out the new triangles:
-        let selection_triangles = [
-            mesh.indices[num_indices_before],
-            mesh.indices[num_indices_before + 1],
-            mesh.indices[num_indices_before + 2],
-            mesh.indices[num_indices_before + 3],
-            mesh.indices[num_indices_before + 4],
-            mesh.indices[num_indices_before + 5],
-        ];
-
-        // Move every old triangle forwards by 6 indices to make room for the new triangle:
-        for i in (glyph_index_start..num_indices_before).rev() {
-            mesh.indices.swap(i, i + 6);
-        }
-        // Put the new triangle in place:
-        mesh.indices[glyph_index_start..glyph_index_start + 6]
-            .clone_from_slice(&selection_triangles);
-
-        row.visuals.mesh_bounds = mesh.calc_bounds();
-
-        if let Some(new_vertex_indices) = &mut new_vertex_indices {
-            new_vertex_indices.push(RowVertexIndices {
-                row: ri,
-                vertex_indices: selection_triangles,
-            });
+        for x_range in x_ranges {
+            let rect = Rect::from_x_y_ranges(x_range, 0.0..=row.size.y);
+            let mesh = &mut row.visuals.mesh;
+
+            // Time to insert the selection rectangle into the row mesh.
+            // It should be on top (after) of any background in the galley,
+            // but behind (before) any glyphs. The row visuals has this information:
+            let glyph_index_start = row.visuals.glyph_index_start;
+
+            // Start by appending the selection rectangle to end of the mesh, as two triangles (= 6 indices):
+            let num_indices_before = mesh.indices.len();
+            mesh.add_colored_rect(rect, color);
+            assert_eq!(
+                num_indices_before + 6,
+                mesh.indices.len(),
+                "We expect exactly 6 new indices"
+            );
+
+            // Copy out the new triangles:
+            let selection_triangles = [
+                mesh.indices[num_indices_before],
+                mesh.indices[num_indices_before + 1],
+                mesh.indices[num_indices_before + 2],
+                mesh.indices[num_indices_before + 3],
+                mesh.indices[num_indices_before + 4],
+                mesh.indices[num_indices_before + 5],
+            ];
+
+            // Move every old triangle forwards by 6 indices to make room for the new triangle:
+            for i in (glyph_index_start..num_indices_before).rev() {
+                mesh.indices.swap(i, i + 6);
+            }
+            // Put the new triangle in place:
+            mesh.indices[glyph_index_start..glyph_index_start + 6]
+                .clone_from_slice(&selection_triangles);
+
+            row.visuals.mesh_bounds = mesh.calc_bounds();
+
+            if let Some(new_vertex_indices) = &mut new_vertex_indices {
+                new_vertex_indices.push(RowVertexIndices {
+                    row: ri,
+                    vertex_indices: selection_triangles,
+                });
+            }
         }
     }
 }
@@ -465,6 +465,24 @@ impl Ui {
         &mut self.style_mut().visuals
     }
 
+    /// The [`crate::Visuals::widgets`] to use for a widget in this [`Ui`],
+    /// after applying the [`Style::class_rules`] that match it.
+    ///
+    /// `widget_class` is the class of the type of widget, e.g. `"button"`,
+    /// and `classes` are the classes set on that specific widget.
+    /// The classes of this [`Ui`] and its parents are also taken into account (see [`UiBuilder::class`]).
+    pub fn widgets_with_classes(
+        &self,
+        widget_class: &str,
+        classes: &[String],
+    ) -> std::borrow::Cow<'_, style::Widgets> {
+        self.style.widgets_with_classes(|class| {
+            class == widget_class
+                || classes.iter().any(|c| c == class)
+                || self.stack.has_class(class)
+        })
+    }
+
     /// Get a reference to this [`Ui`]'s [`UiStack`].
     #[inline]
     pub fn stack(&self) -> &Arc<UiStack> {
@@ -124,6 +124,16 @@ impl UiBuilder {
         self
     }
 
+    /// Add a style class to the [`Ui`] and all widgets in it.
+    ///
+    /// The class selects which [`Style::class_rules`] apply, e.g. `"toolbar"`.
+    /// Can be called multiple times to add several classes.
+    #[inline]
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.ui_stack_info.classes.push(class.into());
+        self
+    }
+
     /// Set if you want sense clicks and/or drags. Default is [`Sense::hover`].
     ///
     /// The sense will be registered below the Senses of any widgets contained in this [`Ui`], so
@@ -106,6 +106,9 @@ pub struct UiStackInfo {
     pub kind: Option<UiKind>,
     pub frame: Frame,
     pub tags: UiTags,
+
+    /// Style classes of this [`crate::Ui`], see [`crate::StyleRule`].
+    pub classes: Vec<String>,
 }
 
 impl UiStackInfo {
@@ -141,6 +144,13 @@ impl UiStackInfo {
         self.tags.insert(key, Some(Arc::new(value)));
         self
     }
+
+    /// Add a style class, see [`crate::StyleRule`].
+    #[inline]
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.classes.push(class.into());
+        self
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -267,6 +277,12 @@ impl UiStack {
     pub fn contained_in(&self, kind: UiKind) -> bool {
         self.iter().any(|frame| frame.kind() == Some(kind))
     }
+
+    /// Check if this node or any of its parents has the given style class.
+    pub fn has_class(&self, class: &str) -> bool {
+        self.iter()
+            .any(|frame| frame.info.classes.iter().any(|c| c == class))
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -34,6 +34,7 @@ pub struct RichText {
     background_color: Color32,
     expand_bg: f32,
     text_color: Option<Color32>,
+    text_color_token: Option<String>,
     code: bool,
     strong: bool,
     weak: bool,
@@ -55,6 +56,7 @@ impl Default for RichText {
             background_color: Default::default(),
             expand_bg: 1.0,
             text_color: Default::default(),
+            text_color_token: Default::default(),
             code: Default::default(),
             strong: Default::default(),
             weak: Default::default(),
@@ -304,6 +306,17 @@ impl RichText {
         self
     }
 
+    /// Color the text with a color from [`crate::Visuals::tokens`],
+    /// e.g. [`crate::SemanticColor::Success`].
+    ///
+    /// The color is looked up when the text is laid out, so it follows the current theme.
+    /// Ignored if the token isn't defined, or if [`Self::color`] is also set.
+    #[inline]
+    pub fn color_token(mut self, token: impl crate::DesignToken) -> Self {
+        self.text_color_token = Some(token.name().to_owned());
+        self
+    }
+
     /// Read the font height of the selected text style.
     ///
     /// Returns a value rounded to [`emath::GUI_ROUNDING`].
@@ -390,7 +403,8 @@ impl RichText {
             text_style,
             background_color,
             expand_bg,
-            text_color: _, // already used by `get_text_color`
+            text_color: _,       // already used by `get_text_color`
+            text_color_token: _, // already used by `get_text_color`
             code,
             strong: _, // already used by `get_text_color`
             weak: _,   // already used by `get_text_color`
@@ -463,6 +477,12 @@ impl RichText {
     fn get_text_color(&self, visuals: &Visuals) -> Option<Color32> {
         if let Some(text_color) = self.text_color {
             Some(text_color)
+        } else if let Some(color) = self
+            .text_color_token
+            .as_ref()
+            .and_then(|token| visuals.try_token_color(token))
+        {
+            Some(color)
         } else if self.strong {
             Some(visuals.strong_text_color())
         } else if self.weak {
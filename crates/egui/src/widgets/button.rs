@@ -34,6 +34,7 @@ pub struct Button<'a> {
     selected: bool,
     image_tint_follows_text_color: bool,
     limit_image_size: bool,
+    classes: Vec<String>,
 }
 
 impl<'a> Button<'a> {
@@ -49,6 +50,7 @@ impl<'a> Button<'a> {
             selected: false,
             image_tint_follows_text_color: false,
             limit_image_size: false,
+            classes: Vec::new(),
         }
     }
 
@@ -212,6 +214,16 @@ impl<'a> Button<'a> {
         self
     }
 
+    /// Add a style class, e.g. `"primary"`.
+    ///
+    /// The button will look up its visuals using the [`crate::Style::class_rules`]
+    /// that match its classes, the classes of the [`Ui`], and the `"button"` class.
+    #[inline]
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.classes.push(class.into());
+        self
+    }
+
     /// Show the button and return a [`AtomLayoutResponse`] for painting custom contents.
     pub fn atom_ui(self, ui: &mut Ui) -> AtomLayoutResponse {
         let Button {
@@ -225,6 +237,7 @@ impl<'a> Button<'a> {
             selected,
             image_tint_follows_text_color,
             limit_image_size,
+            classes,
         } = self;
 
         if !small {
@@ -260,7 +273,17 @@ impl<'a> Button<'a> {
             .allocate(ui);
 
         let response = if ui.is_rect_visible(prepared.response.rect) {
-            let visuals = ui.style().interact_selectable(&prepared.response, selected);
+            let mut visuals = *ui
+                .widgets_with_classes("button", &classes)
+                .style(&prepared.response);
+            if selected {
+                ui.style().select(&mut visuals);
+            }
+            let visuals = ui.ctx().animate_widget_visuals(
+                prepared.response.id,
+                visuals,
+                ui.style().widget_transition,
+            );
 
             if image_tint_follows_text_color {
                 prepared.map_images(|image| image.tint(visuals.text_color()));
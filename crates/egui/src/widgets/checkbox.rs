@@ -21,6 +21,7 @@ pub struct Checkbox<'a> {
     checked: &'a mut bool,
     atoms: Atoms<'a>,
     indeterminate: bool,
+    classes: Vec<String>,
 }
 
 impl<'a> Checkbox<'a> {
@@ -29,6 +30,7 @@ impl<'a> Checkbox<'a> {
             checked,
             atoms: atoms.into_atoms(),
             indeterminate: false,
+            classes: Vec::new(),
         }
     }
 
@@ -45,6 +47,16 @@ impl<'a> Checkbox<'a> {
         self.indeterminate = indeterminate;
         self
     }
+
+    /// Add a style class.
+    ///
+    /// The checkbox will look up its visuals using the [`crate::Style::class_rules`]
+    /// that match its classes, the classes of the [`Ui`], and the `"checkbox"` class.
+    #[inline]
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.classes.push(class.into());
+        self
+    }
 }
 
 impl Widget for Checkbox<'_> {
@@ -53,6 +65,7 @@ impl Widget for Checkbox<'_> {
             checked,
             mut atoms,
             indeterminate,
+            classes,
         } = self;
 
         let spacing = &ui.spacing();
@@ -97,7 +110,14 @@ impl Widget for Checkbox<'_> {
 
         if ui.is_rect_visible(prepared.response.rect) {
             // let visuals = ui.style().interact_selectable(&response, *checked); // too colorful
-            let visuals = *ui.style().interact(&prepared.response);
+            let visuals = *ui
+                .widgets_with_classes("checkbox", &classes)
+                .style(&prepared.response);
+            let visuals = ui.ctx().animate_widget_visuals(
+                prepared.response.id,
+                visuals,
+                ui.style().widget_transition,
+            );
             prepared.fallback_text_color = visuals.text_color();
             let response = prepared.paint(ui);
 
@@ -227,6 +227,8 @@ fn color_slider_2d(
             radius: rect.width() / 12.0,
             fill: picked_color,
             stroke: Stroke::new(visuals.fg_stroke.width, contrast_color(picked_color)),
+            stroke_dash: None,
+            brush: None,
         });
     }
 
@@ -4,7 +4,10 @@ use std::{cmp::Ordering, ops::RangeInclusive};
 
 use crate::{
     Button, CursorIcon, Id, Key, MINUS_CHAR_STR, Modifiers, NumExt as _, Response, RichText, Sense,
-    TextEdit, TextWrapMode, Ui, Widget, WidgetInfo, emath, text,
+    Stroke, TextEdit, TextWrapMode, Ui, Widget, WidgetInfo,
+    emath::{self, expression::ExpressionError},
+    epaint::StrokeKind,
+    text,
 };
 
 // ----------------------------------------------------------------------------
@@ -26,6 +29,82 @@ fn set(get_set_value: &mut GetSetValue<'_>, value: f64) {
     (get_set_value)(Some(value));
 }
 
+// ----------------------------------------------------------------------------
+
+/// Evaluates the text typed into a [`DragValue`] or [`crate::Slider`] as an arithmetic expression,
+/// with support for units and constants.
+///
+/// For instance `2*pi`, `1.5e3 + 20` or `10mm + 2cm`.
+/// See [`emath::expression::evaluate`] for the full syntax.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut length_in_meters = 1.0;
+/// let units = egui::ExpressionParser::default()
+///     .name("m", 1.0)
+///     .name("cm", 0.01)
+///     .name("mm", 0.001)
+///     .name("in", 0.0254);
+/// ui.add(egui::DragValue::new(&mut length_in_meters).expression_parser(units).suffix(" m"));
+/// # });
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpressionParser {
+    /// The units and constants that can be used in expressions, and what they are worth.
+    ///
+    /// A unit is worth its size in the unit of the edited value,
+    /// so if the value is in meters, `mm` should be `0.001`.
+    ///
+    /// Later entries take precedence over earlier ones.
+    /// Default: `pi`, `tau` and `e`.
+    pub names: Vec<(String, f64)>,
+}
+
+impl Default for ExpressionParser {
+    fn default() -> Self {
+        Self {
+            names: vec![
+                ("pi".to_owned(), std::f64::consts::PI),
+                ("π".to_owned(), std::f64::consts::PI),
+                ("tau".to_owned(), std::f64::consts::TAU),
+                ("e".to_owned(), std::f64::consts::E),
+            ],
+        }
+    }
+}
+
+impl ExpressionParser {
+    /// Add a unit or constant.
+    #[inline]
+    pub fn name(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.names.push((name.into(), value));
+        self
+    }
+
+    /// Evaluate the given text.
+    ///
+    /// # Errors
+    /// If the text is not a valid expression, or uses an unknown name.
+    pub fn parse(&self, text: &str) -> Result<f64, ExpressionError> {
+        emath::expression::evaluate(text, |name| {
+            self.names
+                .iter()
+                .rev()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| *value)
+        })
+    }
+}
+
+/// Text the user typed into a [`DragValue`] with an [`ExpressionParser`] that could not be parsed.
+///
+/// Kept after the edit ends, so the user can fix it.
+#[derive(Clone, Debug)]
+struct InvalidInput {
+    text: String,
+    error: String,
+}
+
 /// A numeric value that you can change by dragging the number. More compact than a [`crate::Slider`].
 ///
 /// ```
@@ -46,6 +125,7 @@ pub struct DragValue<'a> {
     max_decimals: Option<usize>,
     custom_formatter: Option<NumFormatter<'a>>,
     custom_parser: Option<NumParser<'a>>,
+    expression_parser: Option<ExpressionParser>,
     update_while_editing: bool,
 }
 
@@ -77,6 +157,7 @@ impl<'a> DragValue<'a> {
             max_decimals: None,
             custom_formatter: None,
             custom_parser: None,
+            expression_parser: None,
             update_while_editing: true,
         }
     }
@@ -415,6 +496,18 @@ impl<'a> DragValue<'a> {
         .custom_parser(|s| i64::from_str_radix(s, 16).map(|n| n as f64).ok())
     }
 
+    /// Evaluate the typed text as an expression, with units, using the given [`ExpressionParser`].
+    ///
+    /// This is used instead of any [`Self::custom_parser`].
+    ///
+    /// When the text is invalid the widget shows an error,
+    /// and the text is kept after editing ends, instead of reverting to the old value.
+    #[inline]
+    pub fn expression_parser(mut self, expression_parser: ExpressionParser) -> Self {
+        self.expression_parser = Some(expression_parser);
+        self
+    }
+
     /// Update the value on each key press when text-editing the value.
     ///
     /// Default: `true`.
@@ -439,6 +532,7 @@ impl Widget for DragValue<'_> {
             max_decimals,
             custom_formatter,
             custom_parser,
+            expression_parser,
             update_while_editing,
         } = self;
 
@@ -461,6 +555,14 @@ impl Widget for DragValue<'_> {
             ui.data_mut(|data| data.remove::<String>(id));
         }
 
+        // The error is only shown when there is an expression parser.
+        let parse_text = |value_text: &str| match &expression_parser {
+            Some(expression_parser) => expression_parser
+                .parse(value_text)
+                .map_err(|err| err.to_string()),
+            None => parse(&custom_parser, value_text).ok_or_else(String::new),
+        };
+
         let old_value = get(&mut get_set_value);
         let mut value = old_value;
         let aim_rad = ui.input(|i| i.aim_radius() as f64);
@@ -522,7 +624,10 @@ impl Widget for DragValue<'_> {
 
         if old_value != value {
             set(&mut get_set_value, value);
-            ui.data_mut(|data| data.remove::<String>(id));
+            ui.data_mut(|data| {
+                data.remove::<String>(id);
+                data.remove::<InvalidInput>(id);
+            });
         }
 
         let value_text = match custom_formatter {
@@ -535,27 +640,45 @@ impl Widget for DragValue<'_> {
 
         let text_style = ui.style().drag_value_text_style.clone();
 
-        if ui.memory(|mem| mem.lost_focus(id)) && !ui.input(|i| i.key_pressed(Key::Escape)) {
-            let value_text = ui.data_mut(|data| data.remove_temp::<String>(id));
-            if let Some(value_text) = value_text {
-                // We were editing the value as text last frame, but lost focus.
-                // Make sure we applied the last text value:
-                let parsed_value = parse(&custom_parser, &value_text);
-                if let Some(mut parsed_value) = parsed_value {
-                    // User edits always clamps:
-                    parsed_value = clamp_value_to_range(parsed_value, range.clone());
-                    set(&mut get_set_value, parsed_value);
+        if ui.memory(|mem| mem.lost_focus(id)) {
+            if ui.input(|i| i.key_pressed(Key::Escape)) {
+                ui.data_mut(|data| data.remove::<InvalidInput>(id));
+            } else {
+                let value_text = ui.data_mut(|data| data.remove_temp::<String>(id));
+                if let Some(value_text) = value_text {
+                    // We were editing the value as text last frame, but lost focus.
+                    // Make sure we applied the last text value:
+                    match parse_text(&value_text) {
+                        Ok(parsed_value) => {
+                            // User edits always clamps:
+                            let parsed_value = clamp_value_to_range(parsed_value, range.clone());
+                            set(&mut get_set_value, parsed_value);
+                            ui.data_mut(|data| data.remove::<InvalidInput>(id));
+                        }
+                        Err(error) => {
+                            if expression_parser.is_some() {
+                                let invalid_input = InvalidInput {
+                                    text: value_text,
+                                    error,
+                                };
+                                ui.data_mut(|data| data.insert_temp(id, invalid_input));
+                            }
+                        }
+                    }
                 }
             }
         }
 
+        let invalid_input = ui.data(|data| data.get_temp::<InvalidInput>(id));
+
         // some clones below are redundant if AccessKit is disabled
         #[expect(clippy::redundant_clone)]
         let mut response = if is_kb_editing {
             let mut value_text = ui
                 .data_mut(|data| data.remove_temp::<String>(id))
+                .or_else(|| invalid_input.map(|invalid_input| invalid_input.text))
                 .unwrap_or_else(|| value_text.clone());
-            let response = ui.add(
+            let mut response = ui.add(
                 TextEdit::singleline(&mut value_text)
                     .clip_text(false)
                     .horizontal_align(ui.layout().horizontal_align())
@@ -581,24 +704,45 @@ impl Widget for DragValue<'_> {
                 // Update only when the edit has lost focus.
                 response.lost_focus() && !ui.input(|i| i.key_pressed(Key::Escape))
             };
+            let parsed_value = parse_text(&value_text);
             if update {
-                let parsed_value = parse(&custom_parser, &value_text);
-                if let Some(mut parsed_value) = parsed_value {
+                if let Ok(parsed_value) = parsed_value {
                     // User edits always clamps:
-                    parsed_value = clamp_value_to_range(parsed_value, range.clone());
+                    let parsed_value = clamp_value_to_range(parsed_value, range.clone());
                     set(&mut get_set_value, parsed_value);
+                    ui.data_mut(|data| data.remove::<InvalidInput>(id));
+                }
+            }
+            if let (Some(_), Err(error)) = (&expression_parser, parsed_value) {
+                ui.painter().rect_stroke(
+                    response.rect,
+                    ui.visuals().widgets.active.corner_radius,
+                    Stroke::new(1.0, ui.visuals().error_fg_color),
+                    StrokeKind::Inside,
+                );
+                response = response.on_hover_text(&error);
+                if response.lost_focus() && !ui.input(|i| i.key_pressed(Key::Escape)) {
+                    let invalid_input = InvalidInput {
+                        text: value_text.clone(),
+                        error,
+                    };
+                    ui.data_mut(|data| data.insert_temp(id, invalid_input));
                 }
             }
             ui.data_mut(|data| data.insert_temp(id, value_text));
             response
         } else {
-            let button = Button::new(
-                RichText::new(format!("{}{}{}", prefix, value_text.clone(), suffix))
-                    .text_style(text_style),
-            )
-            .wrap_mode(TextWrapMode::Extend)
-            .sense(Sense::click_and_drag())
-            .min_size(ui.spacing().interact_size); // TODO(emilk): find some more generic solution to `min_size`
+            let button_text = match &invalid_input {
+                Some(invalid_input) => {
+                    RichText::new(format!("{}{}{}", prefix, invalid_input.text, suffix))
+                        .color(ui.visuals().error_fg_color)
+                }
+                None => RichText::new(format!("{}{}{}", prefix, value_text.clone(), suffix)),
+            };
+            let button = Button::new(button_text.text_style(text_style))
+                .wrap_mode(TextWrapMode::Extend)
+                .sense(Sense::click_and_drag())
+                .min_size(ui.spacing().interact_size); // TODO(emilk): find some more generic solution to `min_size`
 
             let cursor_icon = if value <= *range.start() {
                 CursorIcon::ResizeEast
@@ -611,7 +755,9 @@ impl Widget for DragValue<'_> {
             let response = ui.add(button);
             let mut response = response.on_hover_cursor(cursor_icon);
 
-            if ui.style().explanation_tooltips {
+            if let Some(invalid_input) = &invalid_input {
+                response = response.on_hover_text(&invalid_input.error);
+            } else if ui.style().explanation_tooltips {
                 response = response.on_hover_text(format!(
                     "{}{}{}\nDrag to edit or click to enter a value.\nPress 'Shift' while dragging for better control.",
                     prefix,
@@ -628,7 +774,10 @@ impl Widget for DragValue<'_> {
             if response.clicked() {
                 ui.data_mut(|data| data.remove::<String>(id));
                 ui.memory_mut(|mem| mem.request_focus(id));
-                select_all_text(ui, id, response.id, &value_text);
+                let edit_text = invalid_input
+                    .as_ref()
+                    .map_or(&value_text, |invalid_input| &invalid_input.text);
+                select_all_text(ui, id, response.id, edit_text);
             } else if response.dragged() {
                 ui.ctx().set_cursor_icon(cursor_icon);
 
@@ -656,7 +805,10 @@ impl Widget for DragValue<'_> {
                     let rounded_new_value = clamp_value_to_range(rounded_new_value, range.clone());
                     set(&mut get_set_value, rounded_new_value);
 
-                    ui.data_mut(|data| data.insert_temp::<f64>(id, precise_value));
+                    ui.data_mut(|data| {
+                        data.insert_temp::<f64>(id, precise_value);
+                        data.remove::<InvalidInput>(id);
+                    });
                 }
             }
 
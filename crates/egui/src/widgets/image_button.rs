@@ -116,7 +116,11 @@ impl Widget for ImageButton<'_> {
                     selection.stroke,
                 )
             } else if self.frame {
-                let visuals = ui.style().interact(&response);
+                let visuals = ui.ctx().animate_widget_visuals(
+                    response.id,
+                    *ui.style().interact(&response),
+                    ui.style().widget_transition,
+                );
                 let expansion = Vec2::splat(visuals.expansion);
                 (
                     expansion,
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use crate::{
-    Align, Direction, FontSelection, Galley, Pos2, Response, Sense, Stroke, TextWrapMode, Ui,
-    Widget, WidgetInfo, WidgetText, WidgetType, epaint, pos2, text_selection::LabelSelectionState,
+    Align, Direction, FontSelection, Galley, Image, Pos2, Response, Sense, Stroke, TextWrapMode,
+    Ui, Widget, WidgetInfo, WidgetText, WidgetType, epaint, pos2,
+    text_selection::LabelSelectionState,
 };
 
 /// Static text.
@@ -29,6 +30,7 @@ pub struct Label {
     selectable: Option<bool>,
     halign: Option<Align>,
     show_tooltip_when_elided: bool,
+    inline_images: Vec<Image<'static>>,
 }
 
 impl Label {
@@ -40,6 +42,7 @@ impl Label {
             selectable: None,
             halign: None,
             show_tooltip_when_elided: true,
+            inline_images: Vec::new(),
         }
     }
 
@@ -133,6 +136,28 @@ impl Label {
         self.show_tooltip_when_elided = show;
         self
     }
+
+    /// Paint an image in the next inline box of the text.
+    ///
+    /// The first call fills the first box added with
+    /// [`crate::text::LayoutJob::append_inline_box`], the second call the second box, etc.
+    /// The image is scaled to fit the box.
+    ///
+    /// ```
+    /// # use egui::{Label, text::{InlineBox, LayoutJob, TextFormat}, vec2};
+    /// # egui::__run_test_ui(|ui| {
+    /// let mut job = LayoutJob::default();
+    /// job.append("Press ", 0.0, TextFormat::default());
+    /// job.append_inline_box(InlineBox::new(vec2(16.0, 16.0)).with_descent(2.0), TextFormat::default());
+    /// job.append(" to continue", 0.0, TextFormat::default());
+    /// ui.add(Label::new(job).inline_image(egui::include_image!("../../assets/ferris.png")));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn inline_image(mut self, image: impl Into<Image<'static>>) -> Self {
+        self.inline_images.push(image.into());
+        self
+    }
 }
 
 impl Label {
@@ -264,7 +289,7 @@ impl Label {
 }
 
 impl Widget for Label {
-    fn ui(self, ui: &mut Ui) -> Response {
+    fn ui(mut self, ui: &mut Ui) -> Response {
         // Interactive = the uses asked to sense interaction.
         // We DON'T want to have the color respond just because the text is selectable;
         // the cursor is enough to communicate that.
@@ -272,6 +297,7 @@ impl Widget for Label {
 
         let selectable = self.selectable;
         let show_tooltip_when_elided = self.show_tooltip_when_elided;
+        let inline_images = std::mem::take(&mut self.inline_images);
 
         let (galley_pos, galley, mut response) = self.layout_in_ui(ui);
         response
@@ -295,6 +321,12 @@ impl Widget for Label {
                 Stroke::NONE
             };
 
+            let inline_boxes = if inline_images.is_empty() {
+                vec![]
+            } else {
+                galley.inline_boxes()
+            };
+
             let selectable = selectable.unwrap_or_else(|| ui.style().interaction.selectable_labels);
             if selectable {
                 LabelSelectionState::label_text_selection(
@@ -311,6 +343,10 @@ impl Widget for Label {
                         .with_underline(underline),
                 );
             }
+
+            for (image, (_, rect)) in inline_images.iter().zip(inline_boxes) {
+                image.paint_at(ui, rect.translate(galley_pos.to_vec2()));
+            }
         }
 
         response
@@ -16,6 +16,7 @@ mod image_button;
 mod label;
 mod progress_bar;
 mod radio_button;
+mod range_slider;
 mod selected_label;
 mod separator;
 mod slider;
@@ -25,7 +26,7 @@ pub mod text_edit;
 pub use self::{
     button::Button,
     checkbox::Checkbox,
-    drag_value::DragValue,
+    drag_value::{DragValue, ExpressionParser},
     hyperlink::{Hyperlink, Link},
     image::{
         FrameDurations, Image, ImageFit, ImageOptions, ImageSize, ImageSource,
@@ -35,6 +36,7 @@ pub use self::{
     label::Label,
     progress_bar::ProgressBar,
     radio_button::RadioButton,
+    range_slider::RangeSlider,
     selected_label::SelectableLabel,
     separator::Separator,
     slider::{Slider, SliderClamping, SliderOrientation},
@@ -72,6 +72,11 @@ impl Widget for RadioButton<'_> {
         if ui.is_rect_visible(prepared.response.rect) {
             // let visuals = ui.style().interact_selectable(&response, checked); // too colorful
             let visuals = *ui.style().interact(&prepared.response);
+            let visuals = ui.ctx().animate_widget_visuals(
+                prepared.response.id,
+                visuals,
+                ui.style().widget_transition,
+            );
 
             prepared.fallback_text_color = visuals.text_color();
             let response = prepared.paint(ui);
@@ -86,6 +91,8 @@ impl Widget for RadioButton<'_> {
                     radius: big_icon_rect.width() / 2.0 + visuals.expansion,
                     fill: visuals.bg_fill,
                     stroke: visuals.bg_stroke,
+                    stroke_dash: None,
+                    brush: None,
                 });
 
                 if checked {
@@ -95,6 +102,8 @@ impl Widget for RadioButton<'_> {
                         fill: visuals.fg_stroke.color, // Intentional to use stroke and not fill
                         // fill: ui.visuals().selection.stroke.color, // too much color
                         stroke: Default::default(),
+                        stroke_dash: None,
+                        brush: None,
                     });
                 }
             }
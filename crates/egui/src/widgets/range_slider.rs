@@ -0,0 +1,728 @@
+#![allow(clippy::needless_pass_by_value)] // False positives with `impl ToString`
+
+use std::ops::RangeInclusive;
+
+use crate::{
+    DragValue, EventFilter, ExpressionParser, Key, Label, NumExt as _, Pos2, Rangef, Rect,
+    Response, Sense, TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText, WidgetType,
+    emath, epaint, lerp, pos2, remap_clamp, style, style::HandleShape, vec2,
+};
+
+use super::{
+    SliderOrientation,
+    drag_value::clamp_value_to_range,
+    slider::{SliderSpec, normalized_from_value, value_from_normalized},
+};
+
+// ----------------------------------------------------------------------------
+
+/// Combined into one function (rather than two) to make it easier
+/// for the borrow checker.
+type GetSetRange<'a> = Box<dyn 'a + FnMut(Option<(f64, f64)>) -> (f64, f64)>;
+
+/// One of the two handles of a [`RangeSlider`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Handle {
+    Min,
+    Max,
+}
+
+impl Handle {
+    const BOTH: [Self; 2] = [Self::Min, Self::Max];
+}
+
+/// Select an interval of numbers with a slider that has two handles.
+///
+/// Drag a handle, or the rail next to it, to move it.
+/// Each handle can also be focused and moved with the arrow keys.
+///
+/// The `range` defines the values you get when pulling a handle to the far edges,
+/// and should go from low to high.
+/// The selected interval is always clamped to this range,
+/// and its start is kept smaller than or equal to its end.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// let mut price = 20.0..=80.0;
+/// ui.add(egui::RangeSlider::new(&mut price, 0.0..=100.0).text("Price"));
+///
+/// let mut year: std::ops::RangeInclusive<i32> = 1990..=2010;
+/// ui.add(egui::RangeSlider::new(&mut year, 1900..=2025));
+/// # });
+/// ```
+///
+/// The default size is set by [`crate::style::Spacing::slider_width`].
+#[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
+pub struct RangeSlider<'a> {
+    get_set_value: GetSetRange<'a>,
+    range: RangeInclusive<f64>,
+    spec: SliderSpec,
+    smart_aim: bool,
+    show_value: bool,
+    orientation: SliderOrientation,
+    text: WidgetText,
+
+    /// Sets the minimal step of the widget values
+    step: Option<f64>,
+
+    min_decimals: usize,
+    max_decimals: Option<usize>,
+    expression_parser: Option<ExpressionParser>,
+    handle_shape: Option<HandleShape>,
+}
+
+impl<'a> RangeSlider<'a> {
+    /// Creates a new horizontal range slider.
+    pub fn new<Num: emath::Numeric>(
+        value: &'a mut RangeInclusive<Num>,
+        range: RangeInclusive<Num>,
+    ) -> Self {
+        let range_f64 = range.start().to_f64()..=range.end().to_f64();
+        let slf = Self::from_get_set(range_f64, move |v: Option<(f64, f64)>| {
+            if let Some((start, end)) = v {
+                *value = Num::from_f64(start)..=Num::from_f64(end);
+            }
+            (value.start().to_f64(), value.end().to_f64())
+        });
+
+        if Num::INTEGRAL { slf.integer() } else { slf }
+    }
+
+    pub fn from_get_set(
+        range: RangeInclusive<f64>,
+        get_set_value: impl 'a + FnMut(Option<(f64, f64)>) -> (f64, f64),
+    ) -> Self {
+        let range = if range.start() <= range.end() {
+            range
+        } else {
+            *range.end()..=*range.start()
+        };
+        Self {
+            get_set_value: Box::new(get_set_value),
+            range,
+            spec: SliderSpec {
+                logarithmic: false,
+                smallest_positive: 1e-6,
+                largest_finite: f64::INFINITY,
+            },
+            smart_aim: true,
+            show_value: true,
+            orientation: SliderOrientation::Horizontal,
+            text: Default::default(),
+            step: None,
+            min_decimals: 0,
+            max_decimals: None,
+            expression_parser: None,
+            handle_shape: None,
+        }
+    }
+
+    /// Control whether or not the slider shows the current values.
+    /// Default: `true`.
+    #[inline]
+    pub fn show_value(mut self, show_value: bool) -> Self {
+        self.show_value = show_value;
+        self
+    }
+
+    /// Show a text next to the slider (e.g. explaining what the slider controls).
+    #[inline]
+    pub fn text(mut self, text: impl Into<WidgetText>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// Vertical or horizontal slider? The default is horizontal.
+    #[inline]
+    pub fn orientation(mut self, orientation: SliderOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Make this a vertical slider.
+    #[inline]
+    pub fn vertical(mut self) -> Self {
+        self.orientation = SliderOrientation::Vertical;
+        self
+    }
+
+    /// Make this a logarithmic slider.
+    /// This is great for when the slider spans a huge range,
+    /// e.g. from one to a million.
+    /// The default is OFF.
+    #[inline]
+    pub fn logarithmic(mut self, logarithmic: bool) -> Self {
+        self.spec.logarithmic = logarithmic;
+        self
+    }
+
+    /// For logarithmic sliders that includes zero:
+    /// what is the smallest positive value you want to be able to select?
+    /// The default is `1` for integer sliders and `1e-6` for real sliders.
+    #[inline]
+    pub fn smallest_positive(mut self, smallest_positive: f64) -> Self {
+        self.spec.smallest_positive = smallest_positive;
+        self
+    }
+
+    /// For logarithmic sliders, the largest positive value we are interested in
+    /// before the slider switches to `INFINITY`, if that is the higher end.
+    /// Default: INFINITY.
+    #[inline]
+    pub fn largest_finite(mut self, largest_finite: f64) -> Self {
+        self.spec.largest_finite = largest_finite;
+        self
+    }
+
+    /// Turn smart aim on/off. Default is ON.
+    /// There is almost no point in turning this off.
+    #[inline]
+    pub fn smart_aim(mut self, smart_aim: bool) -> Self {
+        self.smart_aim = smart_aim;
+        self
+    }
+
+    /// Sets the minimal change of the values.
+    ///
+    /// Value `0.0` effectively disables the feature.
+    ///
+    /// Default: `0.0` (disabled).
+    #[inline]
+    pub fn step_by(mut self, step: f64) -> Self {
+        self.step = if step != 0.0 { Some(step) } else { None };
+        self
+    }
+
+    /// Set a minimum number of decimals to display.
+    ///
+    /// Normally you don't need to pick a precision, as the slider will intelligently pick a precision for you.
+    #[inline]
+    pub fn min_decimals(mut self, min_decimals: usize) -> Self {
+        self.min_decimals = min_decimals;
+        self
+    }
+
+    /// Set a maximum number of decimals to display.
+    ///
+    /// Values will also be rounded to this number of decimals.
+    #[inline]
+    pub fn max_decimals(mut self, max_decimals: usize) -> Self {
+        self.max_decimals = Some(max_decimals);
+        self
+    }
+
+    /// Set an exact number of decimals to display.
+    ///
+    /// Values will also be rounded to this number of decimals.
+    #[inline]
+    pub fn fixed_decimals(mut self, num_decimals: usize) -> Self {
+        self.min_decimals = num_decimals;
+        self.max_decimals = Some(num_decimals);
+        self
+    }
+
+    /// Evaluate text typed into the value fields as an expression, with units, using the given [`ExpressionParser`].
+    ///
+    /// See [`DragValue::expression_parser`].
+    #[inline]
+    pub fn expression_parser(mut self, expression_parser: ExpressionParser) -> Self {
+        self.expression_parser = Some(expression_parser);
+        self
+    }
+
+    /// Change the shape of the slider handles.
+    ///
+    /// This setting can be enabled globally for all sliders with [`crate::Visuals::handle_shape`].
+    #[inline]
+    pub fn handle_shape(mut self, handle_shape: HandleShape) -> Self {
+        self.handle_shape = Some(handle_shape);
+        self
+    }
+
+    /// Helper: equivalent to `self.fixed_decimals(0).smallest_positive(1.0).step_by(1.0)`.
+    /// If you use one of the integer constructors (e.g. `RangeSlider::new(&mut 1..=5, 0..=10)`) this is called for you.
+    #[inline]
+    pub fn integer(self) -> Self {
+        self.fixed_decimals(0).smallest_positive(1.0).step_by(1.0)
+    }
+
+    /// The current interval, clamped to the range, with `start <= end`.
+    fn get_values(&mut self) -> (f64, f64) {
+        let (start, end) = (self.get_set_value)(None);
+        let start = clamp_value_to_range(start, self.range.clone());
+        let end = clamp_value_to_range(end, self.range.clone());
+        if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        }
+    }
+
+    fn get_value(&mut self, handle: Handle) -> f64 {
+        let (start, end) = self.get_values();
+        match handle {
+            Handle::Min => start,
+            Handle::Max => end,
+        }
+    }
+
+    fn round_value(&self, mut value: f64) -> f64 {
+        value = clamp_value_to_range(value, self.range.clone());
+        if let Some(step) = self.step {
+            let start = *self.range.start();
+            value = start + ((value - start) / step).round() * step;
+        }
+        if let Some(max_decimals) = self.max_decimals {
+            value = emath::round_to_decimals(value, max_decimals);
+        }
+        value
+    }
+
+    /// Move one handle, without passing the other one.
+    fn set_value(&mut self, handle: Handle, value: f64) {
+        let value = self.round_value(value);
+        let (start, end) = self.get_values();
+        let new_values = match handle {
+            Handle::Min => (value.at_most(end), end),
+            Handle::Max => (start, value.at_least(start)),
+        };
+        (self.get_set_value)(Some(new_values));
+    }
+
+    /// For instance, `position` is the mouse position and `position_range` is the physical location of the slider on the screen.
+    fn value_from_position(&self, position: f32, position_range: Rangef) -> f64 {
+        let normalized = remap_clamp(position, position_range, 0.0..=1.0) as f64;
+        value_from_normalized(normalized, self.range.clone(), &self.spec)
+    }
+
+    fn position_from_value(&self, value: f64, position_range: Rangef) -> f32 {
+        let normalized = normalized_from_value(value, self.range.clone(), &self.spec);
+        lerp(position_range, normalized as f32)
+    }
+
+    fn aimed_value_at(&self, ui: &Ui, position: f32, position_range: Rangef) -> f64 {
+        if self.smart_aim {
+            let aim_radius = ui.input(|i| i.aim_radius());
+            emath::smart_aim::best_in_range_f64(
+                self.value_from_position(position - aim_radius, position_range),
+                self.value_from_position(position + aim_radius, position_range),
+            )
+        } else {
+            self.value_from_position(position, position_range)
+        }
+    }
+}
+
+impl RangeSlider<'_> {
+    /// The handle closest to `position`.
+    ///
+    /// If the handles are on top of each other, pick the one on the side of `position`.
+    fn closest_handle(&mut self, position: f32, position_range: Rangef) -> Handle {
+        let (start, end) = self.get_values();
+        let start_position = self.position_from_value(start, position_range);
+        let end_position = self.position_from_value(end, position_range);
+        let start_distance = (position - start_position).abs();
+        let end_distance = (position - end_position).abs();
+        if start_distance < end_distance {
+            Handle::Min
+        } else if end_distance < start_distance {
+            Handle::Max
+        } else if self.value_from_position(position, position_range) < start {
+            Handle::Min
+        } else {
+            Handle::Max
+        }
+    }
+
+    /// Just the slider, no text.
+    fn slider_ui(&mut self, ui: &Ui, response: &Response) -> [Response; 2] {
+        let rect = response.rect;
+        let handle_shape = self
+            .handle_shape
+            .unwrap_or_else(|| ui.style().visuals.handle_shape);
+        let position_range = self.position_range(&rect, &handle_shape);
+        let handle_ids = Handle::BOTH.map(|handle| response.id.with(handle));
+
+        // Which handle is being dragged:
+        let dragged_id = response.id.with("dragged");
+        let mut dragged = None;
+        if let Some(pointer_position_2d) = response.interact_pointer_pos() {
+            let position = self.pointer_position(pointer_position_2d);
+            let new_value = self.aimed_value_at(ui, position, position_range);
+            let mut handle = ui
+                .data(|data| data.get_temp(dragged_id))
+                .unwrap_or_else(|| self.closest_handle(position, position_range));
+
+            // Dragging a handle past the other one switches to moving the other one:
+            let (start, end) = self.get_values();
+            if handle == Handle::Max && new_value < start {
+                handle = Handle::Min;
+            } else if handle == Handle::Min && end < new_value {
+                handle = Handle::Max;
+            }
+
+            self.set_value(handle, new_value);
+            ui.data_mut(|data| data.insert_temp(dragged_id, handle));
+            let handle_id = handle_ids[handle as usize];
+            if !ui.memory(|mem| mem.has_focus(handle_id)) {
+                ui.memory_mut(|mem| mem.request_focus(handle_id));
+            }
+            dragged = Some(handle);
+        } else {
+            ui.data_mut(|data| data.remove::<Handle>(dragged_id));
+        }
+
+        // The handles only take focus. All pointer input goes through the rail.
+        let radius = self.handle_radius(&rect);
+        let handle_responses = Handle::BOTH.map(|handle| {
+            let value = self.get_value(handle);
+            let center = self.marker_center(self.position_from_value(value, position_range), &rect);
+            let handle_rect = Rect::from_center_size(center, Vec2::splat(2.0 * radius));
+            let handle_response = ui.interact(
+                handle_rect,
+                handle_ids[handle as usize],
+                Sense::focusable_noninteractive(),
+            );
+            self.keyboard_input(ui, &handle_response, handle, position_range);
+            handle_response
+        });
+
+        for (handle, handle_response) in Handle::BOTH.into_iter().zip(&handle_responses) {
+            let value = self.get_value(handle);
+            let label = match handle {
+                Handle::Min => "Minimum",
+                Handle::Max => "Maximum",
+            };
+            let label = if self.text.is_empty() {
+                label.to_owned()
+            } else {
+                format!("{} {}", self.text.text(), label.to_lowercase())
+            };
+            handle_response.widget_info(|| WidgetInfo::slider(ui.is_enabled(), value, &label));
+        }
+
+        if ui.is_rect_visible(rect) {
+            let hovered = if dragged.is_none() {
+                response.hover_pos().map(|pointer_position_2d| {
+                    let position = self.pointer_position(pointer_position_2d);
+                    self.closest_handle(position, position_range)
+                })
+            } else {
+                None
+            };
+            let handle_visuals = Handle::BOTH.map(|handle| {
+                let widgets = &ui.visuals().widgets;
+                if !response.sense.interactive() {
+                    widgets.noninteractive
+                } else if dragged == Some(handle) || handle_responses[handle as usize].has_focus() {
+                    widgets.active
+                } else if hovered == Some(handle) {
+                    widgets.hovered
+                } else {
+                    widgets.inactive
+                }
+            });
+            self.paint(ui, &rect, position_range, &handle_shape, handle_visuals);
+        }
+
+        handle_responses
+    }
+
+    fn keyboard_input(
+        &mut self,
+        ui: &Ui,
+        response: &Response,
+        handle: Handle,
+        position_range: Rangef,
+    ) {
+        if !response.has_focus() {
+            return;
+        }
+
+        ui.ctx().memory_mut(|m| {
+            m.set_focus_lock_filter(
+                response.id,
+                EventFilter {
+                    // pressing arrows in the orientation of the
+                    // slider should not move focus to next widget
+                    horizontal_arrows: matches!(self.orientation, SliderOrientation::Horizontal),
+                    vertical_arrows: matches!(self.orientation, SliderOrientation::Vertical),
+                    ..Default::default()
+                },
+            );
+        });
+
+        let (dec_key, inc_key) = match self.orientation {
+            SliderOrientation::Horizontal => (Key::ArrowLeft, Key::ArrowRight),
+            // Note that this is for moving the slider position,
+            // so up = decrement y coordinate:
+            SliderOrientation::Vertical => (Key::ArrowUp, Key::ArrowDown),
+        };
+        let kb_step =
+            ui.input(|input| input.num_presses(inc_key) as f32 - input.num_presses(dec_key) as f32);
+        if kb_step == 0.0 {
+            return;
+        }
+
+        let ui_point_per_step = 1.0; // move this many ui points for each kb_step
+        let prev_value = self.get_value(handle);
+        let prev_position = self.position_from_value(prev_value, position_range);
+        let new_position = prev_position + ui_point_per_step * kb_step;
+        let new_value = match self.step {
+            Some(step) => prev_value + (kb_step as f64 * step),
+            None if self.smart_aim => {
+                let aim_radius = 0.49 * ui_point_per_step; // Chosen so we don't include `prev_value` in the search.
+                emath::smart_aim::best_in_range_f64(
+                    self.value_from_position(new_position - aim_radius, position_range),
+                    self.value_from_position(new_position + aim_radius, position_range),
+                )
+            }
+            _ => self.value_from_position(new_position, position_range),
+        };
+        self.set_value(handle, new_value);
+    }
+
+    fn paint(
+        &mut self,
+        ui: &Ui,
+        rect: &Rect,
+        position_range: Rangef,
+        handle_shape: &HandleShape,
+        handle_visuals: [style::WidgetVisuals; 2],
+    ) {
+        let widget_visuals = &ui.visuals().widgets;
+        let spacing = &ui.style().spacing;
+
+        let rail_radius = (spacing.slider_rail_height / 2.0).at_least(0.0);
+        let rail_rect = self.rail_rect(rect, rail_radius);
+        let corner_radius = widget_visuals.inactive.corner_radius;
+
+        ui.painter()
+            .rect_filled(rail_rect, corner_radius, widget_visuals.inactive.bg_fill);
+
+        let (start, end) = self.get_values();
+        let start_center =
+            self.marker_center(self.position_from_value(start, position_range), &rail_rect);
+        let end_center =
+            self.marker_center(self.position_from_value(end, position_range), &rail_rect);
+
+        // The selected interval:
+        let mut fill_rect = rail_rect;
+        match self.orientation {
+            SliderOrientation::Horizontal => {
+                fill_rect.min.x = start_center.x;
+                fill_rect.max.x = end_center.x;
+            }
+            SliderOrientation::Vertical => {
+                fill_rect.min.y = end_center.y;
+                fill_rect.max.y = start_center.y;
+            }
+        }
+        ui.painter()
+            .rect_filled(fill_rect, corner_radius, ui.visuals().selection.bg_fill);
+
+        let radius = self.handle_radius(rect);
+        for (center, visuals) in [start_center, end_center].into_iter().zip(handle_visuals) {
+            match *handle_shape {
+                HandleShape::Circle => {
+                    ui.painter().add(epaint::CircleShape {
+                        center,
+                        radius: radius + visuals.expansion,
+                        fill: visuals.bg_fill,
+                        stroke: visuals.fg_stroke,
+                        stroke_dash: None,
+                        brush: None,
+                    });
+                }
+                HandleShape::Rect { aspect_ratio } => {
+                    let v = match self.orientation {
+                        SliderOrientation::Horizontal => Vec2::new(radius * aspect_ratio, radius),
+                        SliderOrientation::Vertical => Vec2::new(radius, radius * aspect_ratio),
+                    };
+                    let v = v + Vec2::splat(visuals.expansion);
+                    let rect = Rect::from_center_size(center, 2.0 * v);
+                    ui.painter().rect(
+                        rect,
+                        visuals.corner_radius,
+                        visuals.bg_fill,
+                        visuals.fg_stroke,
+                        epaint::StrokeKind::Inside,
+                    );
+                }
+            }
+        }
+    }
+
+    fn marker_center(&self, position_1d: f32, rail_rect: &Rect) -> Pos2 {
+        match self.orientation {
+            SliderOrientation::Horizontal => pos2(position_1d, rail_rect.center().y),
+            SliderOrientation::Vertical => pos2(rail_rect.center().x, position_1d),
+        }
+    }
+
+    fn pointer_position(&self, pointer_position_2d: Pos2) -> f32 {
+        match self.orientation {
+            SliderOrientation::Horizontal => pointer_position_2d.x,
+            SliderOrientation::Vertical => pointer_position_2d.y,
+        }
+    }
+
+    fn position_range(&self, rect: &Rect, handle_shape: &HandleShape) -> Rangef {
+        let handle_radius = self.handle_radius(rect);
+        let handle_radius = match handle_shape {
+            HandleShape::Circle => handle_radius,
+            HandleShape::Rect { aspect_ratio } => handle_radius * aspect_ratio,
+        };
+        match self.orientation {
+            SliderOrientation::Horizontal => rect.x_range().shrink(handle_radius),
+            // The vertical case has to be flipped because the largest slider value maps to the
+            // lowest y value (which is at the top)
+            SliderOrientation::Vertical => rect.y_range().shrink(handle_radius).flip(),
+        }
+    }
+
+    fn rail_rect(&self, rect: &Rect, radius: f32) -> Rect {
+        match self.orientation {
+            SliderOrientation::Horizontal => Rect::from_min_max(
+                pos2(rect.left(), rect.center().y - radius),
+                pos2(rect.right(), rect.center().y + radius),
+            ),
+            SliderOrientation::Vertical => Rect::from_min_max(
+                pos2(rect.center().x - radius, rect.top()),
+                pos2(rect.center().x + radius, rect.bottom()),
+            ),
+        }
+    }
+
+    fn handle_radius(&self, rect: &Rect) -> f32 {
+        let limit = match self.orientation {
+            SliderOrientation::Horizontal => rect.height(),
+            SliderOrientation::Vertical => rect.width(),
+        };
+        limit / 2.5
+    }
+
+    /// A [`DragValue`] for one of the handles.
+    fn value_ui(&mut self, ui: &mut Ui, handle: Handle, position_range: Rangef) -> Response {
+        let (start, end) = self.get_values();
+        let mut value = self.get_value(handle);
+        let range = match handle {
+            Handle::Min => *self.range.start()..=end,
+            Handle::Max => start..=*self.range.end(),
+        };
+        let speed = self.step.unwrap_or_else(|| {
+            // delta(value) / delta(points)
+            let position = self.position_from_value(value, position_range);
+            self.value_from_position(position + 0.5, position_range)
+                - self.value_from_position(position - 0.5, position_range)
+        });
+
+        let response = ui.add({
+            let mut dv = DragValue::new(&mut value)
+                .speed(speed)
+                .range(range)
+                .min_decimals(self.min_decimals)
+                .max_decimals_opt(self.max_decimals);
+            if let Some(expression_parser) = &self.expression_parser {
+                dv = dv.expression_parser(expression_parser.clone());
+            }
+            dv
+        });
+        if value != self.get_value(handle) {
+            self.set_value(handle, value);
+        }
+        response
+    }
+
+    fn add_contents(&mut self, ui: &mut Ui) -> Response {
+        let old_values = self.get_values();
+        if (self.get_set_value)(None) != old_values {
+            (self.get_set_value)(Some(old_values));
+        }
+
+        let thickness = ui
+            .text_style_height(&TextStyle::Body)
+            .at_least(ui.spacing().interact_size.y);
+        let desired_size = match self.orientation {
+            SliderOrientation::Horizontal => vec2(ui.spacing().slider_width, thickness),
+            SliderOrientation::Vertical => vec2(thickness, ui.spacing().slider_width),
+        };
+        let mut response = ui.allocate_response(desired_size, Sense::drag());
+        let handle_responses = self.slider_ui(ui, &response);
+        response.widget_info(|| {
+            WidgetInfo::labeled(WidgetType::Slider, ui.is_enabled(), self.text.text())
+        });
+        let slider_response = response.clone();
+        for handle_response in handle_responses {
+            response = response.union(handle_response);
+        }
+
+        if self.show_value {
+            let handle_shape = self
+                .handle_shape
+                .unwrap_or_else(|| ui.style().visuals.handle_shape);
+            let position_range = self.position_range(&slider_response.rect, &handle_shape);
+            let start_response = self.value_ui(ui, Handle::Min, position_range);
+            ui.label("–");
+            let end_response = self.value_ui(ui, Handle::Max, position_range);
+            response = response.union(start_response).union(end_response);
+        }
+
+        if !self.text.is_empty() {
+            let label_response =
+                ui.add(Label::new(self.text.clone()).wrap_mode(TextWrapMode::Extend));
+            slider_response.labelled_by(label_response.id);
+        }
+
+        if self.get_values() != old_values {
+            response.mark_changed();
+        }
+        response
+    }
+}
+
+impl Widget for RangeSlider<'_> {
+    fn ui(mut self, ui: &mut Ui) -> Response {
+        let inner_response = match self.orientation {
+            SliderOrientation::Horizontal => ui.horizontal(|ui| self.add_contents(ui)),
+            SliderOrientation::Vertical => ui.vertical(|ui| self.add_contents(ui)),
+        };
+
+        inner_response.inner | inner_response.response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_do_not_pass() {
+        let mut value = 2.0..=8.0;
+        let mut slider = RangeSlider::new(&mut value, 10.0..=0.0).step_by(0.5);
+        assert_eq!(slider.range, 0.0..=10.0);
+
+        slider.set_value(Handle::Min, 9.0);
+        assert_eq!(slider.get_values(), (8.0, 8.0));
+
+        slider.set_value(Handle::Max, 3.3);
+        assert_eq!(slider.get_values(), (8.0, 8.0));
+
+        slider.set_value(Handle::Min, -4.0);
+        slider.set_value(Handle::Max, 7.3);
+        assert_eq!(slider.get_values(), (0.0, 7.5));
+    }
+
+    #[test]
+    fn test_closest_handle() {
+        let mut value = 5..=5;
+        let mut slider = RangeSlider::new(&mut value, 0..=10);
+        let position_range = Rangef::new(0.0, 100.0);
+        assert_eq!(slider.closest_handle(20.0, position_range), Handle::Min);
+        assert_eq!(slider.closest_handle(80.0, position_range), Handle::Max);
+
+        slider.set_value(Handle::Min, 2.0);
+        assert_eq!(slider.closest_handle(30.0, position_range), Handle::Min);
+        assert_eq!(slider.closest_handle(40.0, position_range), Handle::Max);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    NumExt as _, Response, Sense, TextStyle, Ui, Widget, WidgetInfo, WidgetText, WidgetType,
+    Color32, NumExt as _, Response, Sense, Stroke, TextStyle, Ui, Widget, WidgetInfo, WidgetText,
+    WidgetType,
 };
 
 /// One out of several alternatives, either selected or not.
@@ -66,9 +67,19 @@ impl Widget for SelectableLabel {
                 .align_size_within_rect(galley.size(), rect.shrink2(button_padding))
                 .min;
 
-            let visuals = ui.style().interact_selectable(&response, selected);
+            let mut visuals = ui.style().interact_selectable(&response, selected);
+            let has_background =
+                selected || response.hovered() || response.highlighted() || response.has_focus();
+            if !has_background {
+                // Fade the background in and out:
+                visuals.weak_bg_fill = Color32::TRANSPARENT;
+                visuals.bg_stroke = Stroke::NONE;
+            }
+            let visuals =
+                ui.ctx()
+                    .animate_widget_visuals(response.id, visuals, ui.style().widget_transition);
 
-            if selected || response.hovered() || response.highlighted() || response.has_focus() {
+            if visuals.weak_bg_fill != Color32::TRANSPARENT || !visuals.bg_stroke.is_empty() {
                 let rect = rect.expand(visuals.expansion);
 
                 ui.painter().rect(
@@ -3,9 +3,9 @@
 use std::ops::RangeInclusive;
 
 use crate::{
-    Color32, DragValue, EventFilter, Key, Label, MINUS_CHAR_STR, NumExt as _, Pos2, Rangef, Rect,
-    Response, Sense, TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText, emath,
-    epaint, lerp, pos2, remap, remap_clamp, style, style::HandleShape, vec2,
+    Color32, DragValue, EventFilter, ExpressionParser, Key, Label, MINUS_CHAR_STR, NumExt as _,
+    Pos2, Rangef, Rect, Response, Sense, TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo,
+    WidgetText, emath, epaint, lerp, pos2, remap, remap_clamp, style, style::HandleShape, vec2,
 };
 
 use super::drag_value::clamp_value_to_range;
@@ -32,17 +32,17 @@ fn set(get_set_value: &mut GetSetValue<'_>, value: f64) {
 // ----------------------------------------------------------------------------
 
 #[derive(Clone)]
-struct SliderSpec {
-    logarithmic: bool,
+pub(super) struct SliderSpec {
+    pub(super) logarithmic: bool,
 
     /// For logarithmic sliders, the smallest positive value we are interested in.
     /// 1 for integer sliders, maybe 1e-6 for others.
-    smallest_positive: f64,
+    pub(super) smallest_positive: f64,
 
     /// For logarithmic sliders, the largest positive value we are interested in
     /// before the slider switches to `INFINITY`, if that is the higher end.
     /// Default: INFINITY.
-    largest_finite: f64,
+    pub(super) largest_finite: f64,
 }
 
 /// Specifies the orientation of a [`Slider`].
@@ -115,6 +115,7 @@ pub struct Slider<'a> {
     max_decimals: Option<usize>,
     custom_formatter: Option<NumFormatter<'a>>,
     custom_parser: Option<NumParser<'a>>,
+    expression_parser: Option<ExpressionParser>,
     trailing_fill: Option<bool>,
     handle_shape: Option<HandleShape>,
     update_while_editing: bool,
@@ -162,6 +163,7 @@ impl<'a> Slider<'a> {
             max_decimals: None,
             custom_formatter: None,
             custom_parser: None,
+            expression_parser: None,
             trailing_fill: None,
             handle_shape: None,
             update_while_editing: true,
@@ -481,6 +483,15 @@ impl<'a> Slider<'a> {
         self
     }
 
+    /// Evaluate text typed into the value field as an expression, with units, using the given [`ExpressionParser`].
+    ///
+    /// See [`DragValue::expression_parser`].
+    #[inline]
+    pub fn expression_parser(mut self, expression_parser: ExpressionParser) -> Self {
+        self.expression_parser = Some(expression_parser);
+        self
+    }
+
     /// Set `custom_formatter` and `custom_parser` to display and parse numbers as binary integers. Floating point
     /// numbers are *not* supported.
     ///
@@ -827,6 +838,8 @@ impl Slider<'_> {
                         radius: radius + visuals.expansion,
                         fill: visuals.bg_fill,
                         stroke: visuals.fg_stroke,
+                        stroke_dash: None,
+                        brush: None,
                     });
                 }
                 style::HandleShape::Rect { aspect_ratio } => {
@@ -940,6 +953,9 @@ impl Slider<'_> {
             if let Some(parser) = &self.custom_parser {
                 dv = dv.custom_parser(parser);
             }
+            if let Some(expression_parser) = &self.expression_parser {
+                dv = dv.expression_parser(expression_parser.clone());
+            }
             dv
         });
         if value != self.get_value() {
@@ -1065,7 +1081,11 @@ const INFINITY: f64 = f64::INFINITY;
 /// give a scale that this many orders of magnitude in size.
 const INF_RANGE_MAGNITUDE: f64 = 10.0;
 
-fn value_from_normalized(normalized: f64, range: RangeInclusive<f64>, spec: &SliderSpec) -> f64 {
+pub(super) fn value_from_normalized(
+    normalized: f64,
+    range: RangeInclusive<f64>,
+    spec: &SliderSpec,
+) -> f64 {
     let (min, max) = (*range.start(), *range.end());
 
     if min.is_nan() || max.is_nan() {
@@ -1117,7 +1137,11 @@ fn value_from_normalized(normalized: f64, range: RangeInclusive<f64>, spec: &Sli
     }
 }
 
-fn normalized_from_value(value: f64, range: RangeInclusive<f64>, spec: &SliderSpec) -> f64 {
+pub(super) fn normalized_from_value(
+    value: f64,
+    range: RangeInclusive<f64>,
+    spec: &SliderSpec,
+) -> f64 {
     let (min, max) = (*range.start(), *range.end());
 
     if min.is_nan() || max.is_nan() {
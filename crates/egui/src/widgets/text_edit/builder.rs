@@ -7,17 +7,27 @@ use epaint::{
 };
 
 use crate::{
-    Align, Align2, Color32, Context, CursorIcon, Event, EventFilter, FontSelection, Id, ImeEvent,
-    Key, KeyboardShortcut, Margin, Modifiers, NumExt as _, Response, Sense, Shape, TextBuffer,
-    TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText, WidgetWithState, epaint,
+    Align, Align2, Color32, Context, CursorIcon, Event, EventFilter, FontId, FontSelection, Id,
+    ImeEvent, Key, KeyboardShortcut, Margin, Modifiers, NumExt as _, Painter, Pos2, Response,
+    Sense, Shape, TextBuffer, TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText,
+    WidgetWithState, epaint,
     os::OperatingSystem,
     output::OutputEvent,
-    response, text_selection,
-    text_selection::{CCursorRange, text_cursor_state::cursor_rect, visuals::paint_text_selection},
+    pos2, response, text_selection,
+    text_selection::{
+        CCursorRange,
+        text_cursor_state::cursor_rect,
+        visuals::{paint_text_highlight, paint_text_selection},
+    },
     vec2,
 };
 
-use super::{TextEditOutput, TextEditState};
+use super::{
+    TextEditOutput, TextEditState,
+    find_replace::{FindReplaceState, Search, find_replace_ui},
+    multi_cursor::{add_next_occurrence, edit_at_cursors, merge_cursors},
+    undoer::RecordingTextBuffer,
+};
 
 type LayouterFn<'t> = &'t mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>;
 
@@ -89,6 +99,10 @@ pub struct TextEdit<'t> {
     char_limit: usize,
     return_key: Option<KeyboardShortcut>,
     background_color: Option<Color32>,
+    classes: Vec<String>,
+    multi_cursor: bool,
+    find_replace: bool,
+    line_numbers: bool,
 }
 
 impl WidgetWithState for TextEdit<'_> {
@@ -148,6 +162,10 @@ impl<'t> TextEdit<'t> {
             char_limit: usize::MAX,
             return_key: Some(KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
             background_color: None,
+            classes: Vec::new(),
+            multi_cursor: false,
+            find_replace: false,
+            line_numbers: false,
         }
     }
 
@@ -155,6 +173,9 @@ impl<'t> TextEdit<'t> {
     /// By default it comes with:
     /// - monospaced font
     /// - focus lock (tab will insert a tab character instead of moving focus)
+    ///
+    /// [Multiple cursors](Self::multi_cursor), a [find/replace bar](Self::find_replace)
+    /// and [line numbers](Self::line_numbers) can be turned on separately.
     pub fn code_editor(self) -> Self {
         self.font(TextStyle::Monospace).lock_focus(true)
     }
@@ -396,6 +417,55 @@ impl<'t> TextEdit<'t> {
         self.return_key = return_key.into();
         self
     }
+
+    /// Allow editing the text in several places at once.
+    ///
+    /// Alt-click adds a cursor, and Cmd/Ctrl+D selects the next occurrence of the selected text
+    /// (or the word at the cursor, if nothing is selected).
+    /// Typing, deleting, pasting etc then happens at every cursor, and is undone in one step.
+    /// Escape goes back to a single cursor.
+    ///
+    /// The extra cursors are stored in [`TextEditState::extra_cursors`].
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn multi_cursor(mut self, multi_cursor: bool) -> Self {
+        self.multi_cursor = multi_cursor;
+        self
+    }
+
+    /// Show a find/replace bar when pressing Cmd/Ctrl+F.
+    ///
+    /// Replace is opened with Cmd+Alt+F on Mac, and Ctrl+H elsewhere.
+    /// With the `regex` feature, the query can also be a regular expression.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn find_replace(mut self, find_replace: bool) -> Self {
+        self.find_replace = find_replace;
+        self
+    }
+
+    /// Show line numbers to the left of the text.
+    ///
+    /// A wrapped line only gets a number on its first row.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Add a style class.
+    ///
+    /// The [`TextEdit`] will look up its visuals using the [`crate::Style::class_rules`]
+    /// that match its classes, the classes of the [`Ui`], and the `"text_edit"` class.
+    #[inline]
+    pub fn class(mut self, class: impl Into<String>) -> Self {
+        self.classes.push(class.into());
+        self
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -422,17 +492,23 @@ impl TextEdit<'_> {
     /// }
     /// # });
     /// ```
-    pub fn show(self, ui: &mut Ui) -> TextEditOutput {
+    pub fn show(mut self, ui: &mut Ui) -> TextEditOutput {
+        let classes = std::mem::take(&mut self.classes);
         let is_mutable = self.text.is_mutable();
         let frame = self.frame;
         let where_to_put_background = ui.painter().add(Shape::Noop);
         let background_color = self
             .background_color
             .unwrap_or_else(|| ui.visuals().text_edit_bg_color());
-        let output = self.show_content(ui);
+        let output = self.show_content(ui, &classes);
 
         if frame {
-            let visuals = ui.style().interact(&output.response);
+            let widgets = ui.widgets_with_classes("text_edit", &classes);
+            let visuals = &ui.ctx().animate_widget_visuals(
+                output.response.id,
+                *widgets.style(&output.response),
+                ui.style().widget_transition,
+            );
             let frame_rect = output.response.rect.expand(visuals.expansion);
             let shape = if is_mutable {
                 if output.response.has_focus() {
@@ -453,7 +529,7 @@ impl TextEdit<'_> {
                     )
                 }
             } else {
-                let visuals = &ui.style().visuals.widgets.inactive;
+                let visuals = &widgets.inactive;
                 epaint::RectShape::stroke(
                     frame_rect,
                     visuals.corner_radius,
@@ -468,7 +544,7 @@ impl TextEdit<'_> {
         output
     }
 
-    fn show_content(self, ui: &mut Ui) -> TextEditOutput {
+    fn show_content(self, ui: &mut Ui, classes: &[String]) -> TextEditOutput {
         let TextEdit {
             text,
             hint_text,
@@ -493,26 +569,51 @@ impl TextEdit<'_> {
             char_limit,
             return_key,
             background_color: _,
+            classes: _,
+            multi_cursor,
+            find_replace,
+            line_numbers,
         } = self;
 
         let text_color = text_color
             .or(ui.visuals().override_text_color)
             // .unwrap_or_else(|| ui.style().interact(&response).text_color()); // too bright
-            .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+            .unwrap_or_else(|| {
+                ui.widgets_with_classes("text_edit", classes)
+                    .inactive
+                    .text_color()
+            });
 
         let prev_text = text.as_str().to_owned();
         let hint_text_str = hint_text.text().to_owned();
 
         let font_id = font_selection.resolve(ui.style());
         let row_height = ui.fonts(|f| f.row_height(&font_id));
+
+        let gutter_width_for = |galley: Option<&Galley>| {
+            if line_numbers {
+                let num_lines = galley.map_or(1, num_lines);
+                let num_digits = num_lines.to_string().len().at_least(2);
+                let digit_width = ui.fonts(|f| f.glyph_width(&font_id, '0'));
+                num_digits as f32 * digit_width + ui.spacing().item_spacing.x
+            } else {
+                0.0
+            }
+        };
+        let mut gutter_width = gutter_width_for(None);
+
         const MIN_WIDTH: f32 = 24.0; // Never make a [`TextEdit`] more narrow than this.
-        let available_width = (ui.available_width() - margin.sum().x).at_least(MIN_WIDTH);
         let desired_width = desired_width.unwrap_or_else(|| ui.spacing().text_edit_width);
-        let wrap_width = if ui.layout().horizontal_justify() {
-            available_width
-        } else {
-            desired_width.min(available_width)
+        let wrap_width_for = |gutter_width: f32| {
+            let available_width =
+                (ui.available_width() - margin.sum().x - gutter_width).at_least(MIN_WIDTH);
+            if ui.layout().horizontal_justify() {
+                available_width
+            } else {
+                desired_width.min(available_width)
+            }
         };
+        let mut wrap_width = wrap_width_for(gutter_width);
 
         let font_id_clone = font_id.clone();
         let mut default_layouter = move |ui: &Ui, text: &dyn TextBuffer, wrap_width: f32| {
@@ -529,6 +630,16 @@ impl TextEdit<'_> {
 
         let mut galley = layouter(ui, text, wrap_width);
 
+        if line_numbers {
+            // We guessed a two-digit gutter. Longer texts need a wider one,
+            // which only matters if it makes the text wrap earlier:
+            gutter_width = gutter_width_for(Some(&galley));
+            if wrap_width_for(gutter_width) != wrap_width {
+                wrap_width = wrap_width_for(gutter_width);
+                galley = layouter(ui, text, wrap_width);
+            }
+        }
+
         let desired_inner_width = if clip_text {
             wrap_width // visual clipping with scroll in singleline input.
         } else {
@@ -536,9 +647,12 @@ impl TextEdit<'_> {
         };
         let desired_height = (desired_height_rows.at_least(1) as f32) * row_height;
         let desired_inner_size = vec2(desired_inner_width, galley.size().y.max(desired_height));
-        let desired_outer_size = (desired_inner_size + margin.sum()).at_least(min_size);
+        let desired_outer_size =
+            (desired_inner_size + margin.sum() + vec2(gutter_width, 0.0)).at_least(min_size);
         let (auto_id, outer_rect) = ui.allocate_space(desired_outer_size);
-        let rect = outer_rect - margin; // inner rect (excluding frame/margin).
+        let mut rect = outer_rect - margin; // inner rect (excluding frame/margin).
+        let gutter_rect = Rect::from_min_max(rect.min, pos2(rect.min.x + gutter_width, rect.max.y));
+        rect.min.x = gutter_rect.max.x;
 
         let id = id.unwrap_or_else(|| {
             if let Some(id_salt) = id_salt {
@@ -596,6 +710,20 @@ impl TextEdit<'_> {
                 }
 
                 let is_being_dragged = ui.ctx().is_being_dragged(response.id);
+
+                if response.hovered() && ui.input(|i| i.pointer.any_pressed()) {
+                    let previous_range = state.cursor.char_range();
+                    match previous_range {
+                        Some(previous_range) if multi_cursor && ui.input(|i| i.modifiers.alt) => {
+                            // Alt-click: keep the old cursor, and add a new one at the pointer.
+                            state.extra_cursors.push(previous_range);
+                        }
+                        _ => {
+                            state.extra_cursors.clear();
+                        }
+                    }
+                }
+
                 let did_interact = state.cursor.pointer_interaction(
                     ui,
                     &response,
@@ -607,6 +735,11 @@ impl TextEdit<'_> {
                 if did_interact || response.clicked() {
                     ui.memory_mut(|mem| mem.request_focus(response.id));
 
+                    if let Some(mut primary) = state.cursor.char_range() {
+                        merge_cursors(&mut primary, &mut state.extra_cursors);
+                        state.cursor.set_char_range(Some(primary));
+                    }
+
                     state.last_interaction_time = ui.ctx().input(|i| i.time);
                 }
             }
@@ -619,6 +752,13 @@ impl TextEdit<'_> {
         let mut cursor_range = None;
         let prev_cursor_range = state.cursor.range(&galley);
         if interactive && ui.memory(|mem| mem.has_focus(id)) {
+            let event_filter = EventFilter {
+                // Escape goes back to a single cursor, or closes the find/replace bar:
+                escape: event_filter.escape
+                    || (multi_cursor && !state.extra_cursors.is_empty())
+                    || (find_replace && state.find_replace.is_some()),
+                ..event_filter
+            };
             ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
 
             let default_cursor_range = if cursor_at_end {
@@ -641,6 +781,8 @@ impl TextEdit<'_> {
                 char_limit,
                 event_filter,
                 return_key,
+                multi_cursor,
+                find_replace,
             );
 
             if changed {
@@ -694,7 +836,7 @@ impl TextEdit<'_> {
         if ui.is_rect_visible(rect) {
             if text.as_str().is_empty() && !hint_text.is_empty() {
                 let hint_text_color = ui.visuals().weak_text_color();
-                let hint_text_font_id = hint_text_font.unwrap_or(font_id.into());
+                let hint_text_font_id = hint_text_font.unwrap_or_else(|| font_id.clone().into());
                 let galley = if multiline {
                     hint_text.into_galley(
                         ui,
@@ -718,13 +860,33 @@ impl TextEdit<'_> {
             }
 
             let has_focus = ui.memory(|mem| mem.has_focus(id));
+            let find_replace = find_replace
+                .then_some(state.find_replace.as_ref())
+                .flatten();
+
+            if let Some(Ok(Some(search))) = find_replace.map(Search::new) {
+                let color = ui.visuals().selection.bg_fill.gamma_multiply(0.4);
+                for char_range in search.find_all(text.as_str()) {
+                    let range = CCursorRange::two(
+                        CCursor::new(char_range.start),
+                        CCursor::new(char_range.end),
+                    );
+                    paint_text_highlight(&mut galley, color, &range, None);
+                }
+            }
 
-            if has_focus {
+            // Keep showing the current match while the find/replace bar has keyboard focus:
+            if has_focus || find_replace.is_some() {
                 if let Some(cursor_range) = state.cursor.range(&galley) {
                     // Add text selection rectangles to the galley:
                     paint_text_selection(&mut galley, ui.visuals(), &cursor_range, None);
                 }
             }
+            if has_focus {
+                for range in &state.extra_cursors {
+                    paint_text_selection(&mut galley, ui.visuals(), range, None);
+                }
+            }
 
             if !clip_text {
                 // Allocate additional space if edits were made this frame that changed the size. This is important so that,
@@ -741,6 +903,33 @@ impl TextEdit<'_> {
 
             painter.galley(galley_pos, galley.clone(), text_color);
 
+            if line_numbers {
+                paint_line_numbers(
+                    ui,
+                    &ui.painter_at(gutter_rect),
+                    &galley,
+                    pos2(
+                        gutter_rect.right() - ui.spacing().item_spacing.x,
+                        galley_pos.y,
+                    ),
+                    &font_id,
+                    state.cursor.range(&galley).filter(|_| has_focus),
+                );
+            }
+
+            if state
+                .find_replace
+                .as_mut()
+                .is_some_and(|find| std::mem::take(&mut find.scroll_to_selection))
+            {
+                if let Some(cursor_range) = state.cursor.range(&galley) {
+                    let primary_cursor_rect =
+                        cursor_rect(&galley, &cursor_range.primary, row_height)
+                            .translate(galley_pos.to_vec2());
+                    ui.scroll_to_rect(primary_cursor_rect + margin, None);
+                }
+            }
+
             if has_focus {
                 if let Some(cursor_range) = state.cursor.range(&galley) {
                     let primary_cursor_rect =
@@ -770,6 +959,16 @@ impl TextEdit<'_> {
                                 primary_cursor_rect,
                                 now - state.last_interaction_time,
                             );
+                            for range in &state.extra_cursors {
+                                let cursor_rect = cursor_rect(&galley, &range.primary, row_height)
+                                    .translate(galley_pos.to_vec2());
+                                text_selection::visuals::paint_text_cursor(
+                                    ui,
+                                    &painter,
+                                    cursor_rect,
+                                    now - state.last_interaction_time,
+                                );
+                            }
                         }
 
                         // Set IME output (in screen coords) when text is editable and visible
@@ -789,6 +988,14 @@ impl TextEdit<'_> {
             }
         }
 
+        if find_replace {
+            let bar_rect = outer_rect.intersect(ui.clip_rect());
+            if find_replace_ui(ui, id, bar_rect, &mut state, text) {
+                galley = layouter(ui, text, wrap_width);
+                response.mark_changed();
+            }
+        }
+
         // Ensures correct IME behavior when the text input area gains or loses focus.
         if state.ime_enabled && (response.gained_focus() || response.lost_focus()) {
             state.ime_enabled = false;
@@ -861,6 +1068,61 @@ impl TextEdit<'_> {
     }
 }
 
+/// The number of lines (paragraphs) in the galley, which is at least one.
+fn num_lines(galley: &Galley) -> usize {
+    1 + galley
+        .rows
+        .iter()
+        .filter(|row| row.ends_with_newline)
+        .count()
+}
+
+/// Paint the number of each line (paragraph) at its first row, right-aligned to `right_top.x`.
+///
+/// The line with the primary cursor is painted in a stronger color.
+fn paint_line_numbers(
+    ui: &Ui,
+    painter: &Painter,
+    galley: &Galley,
+    right_top: Pos2,
+    font_id: &FontId,
+    cursor_range: Option<CCursorRange>,
+) {
+    let current_line = cursor_range.map(|range| {
+        let row = galley.layout_from_cursor(range.primary).row;
+        1 + galley.rows[..row]
+            .iter()
+            .filter(|row| row.ends_with_newline)
+            .count()
+    });
+
+    let mut line_number = 1;
+    for (row_index, row) in galley.rows.iter().enumerate() {
+        let is_first_row = row_index == 0 || galley.rows[row_index - 1].ends_with_newline;
+        let row_rect = Rect::from_x_y_ranges(
+            painter.clip_rect().x_range(),
+            right_top.y + row.min_y()..=right_top.y + row.max_y(),
+        );
+        if is_first_row && ui.is_rect_visible(row_rect) {
+            let color = if current_line == Some(line_number) {
+                ui.visuals().strong_text_color()
+            } else {
+                ui.visuals().weak_text_color()
+            };
+            painter.text(
+                pos2(right_top.x, row_rect.top()),
+                Align2::RIGHT_TOP,
+                line_number.to_string(),
+                font_id.clone(),
+                color,
+            );
+        }
+        if row.ends_with_newline {
+            line_number += 1;
+        }
+    }
+}
+
 fn mask_if_password(is_password: bool, text: &str) -> String {
     fn mask_password(text: &str) -> String {
         std::iter::repeat_n(
@@ -880,7 +1142,7 @@ fn mask_if_password(is_password: bool, text: &str) -> String {
 // ----------------------------------------------------------------------------
 
 /// Check for (keyboard) events to edit the cursor and/or text.
-#[expect(clippy::too_many_arguments)]
+#[expect(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
 fn events(
     ui: &crate::Ui,
     state: &mut TextEditState,
@@ -895,17 +1157,26 @@ fn events(
     char_limit: usize,
     event_filter: EventFilter,
     return_key: Option<KeyboardShortcut>,
+    multi_cursor: bool,
+    find_replace: bool,
 ) -> (bool, CCursorRange) {
     let os = ui.ctx().os();
 
     let mut cursor_range = state.cursor.range(galley).unwrap_or(default_cursor_range);
 
-    // We feed state to the undoer both before and after handling input
-    // so that the undoer creates automatic saves even when there are no events for a while.
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range, text.as_str().to_owned()),
-    );
+    let mut extra_cursors = if multi_cursor {
+        std::mem::take(&mut state.extra_cursors)
+    } else {
+        vec![]
+    };
+    for range in &mut extra_cursors {
+        range.primary = galley.clamp_cursor(&range.primary);
+        range.secondary = galley.clamp_cursor(&range.secondary);
+    }
+    merge_cursors(&mut cursor_range, &mut extra_cursors);
+
+    // If the text was changed by someone else, the undo history no longer applies:
+    state.undoer.lock().check_text(text.len_bytes());
 
     let copy_if_not_password = |ui: &Ui, text: String| {
         if !password {
@@ -924,179 +1195,258 @@ fn events(
     }
 
     for event in &events {
-        let did_mutate_text = match event {
-            // First handle events that only changes the selection cursor, not the text:
-            event if cursor_range.on_event(os, event, galley, id) => None,
-
-            Event::Copy => {
-                if cursor_range.is_empty() {
-                    None
-                } else {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
-                    None
-                }
-            }
-            Event::Cut => {
-                if cursor_range.is_empty() {
+        let cursor_range_before = cursor_range;
+        let mut is_undo_or_redo = false;
+
+        // Record what the event changes, for the undo history:
+        let mut recorder = RecordingTextBuffer::new(text);
+
+        let did_mutate_text = {
+            let text: &mut dyn TextBuffer = &mut recorder;
+            match event {
+                Event::Key {
+                    key: Key::Escape,
+                    pressed: true,
+                    ..
+                } if !extra_cursors.is_empty() => {
+                    extra_cursors.clear();
                     None
-                } else {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
-                    Some(CCursorRange::one(text.delete_selected(&cursor_range)))
                 }
-            }
-            Event::Paste(text_to_insert) => {
-                if !text_to_insert.is_empty() {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-
-                    text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
-
-                    Some(CCursorRange::one(ccursor))
-                } else {
+                Event::Key {
+                    key: Key::Escape,
+                    pressed: true,
+                    ..
+                } if find_replace && state.find_replace.is_some() => {
+                    state.find_replace = None;
                     None
                 }
-            }
-            Event::Text(text_to_insert) => {
-                // Newlines are handled by `Key::Enter`.
-                if !text_to_insert.is_empty() && text_to_insert != "\n" && text_to_insert != "\r" {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-
-                    text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
 
-                    Some(CCursorRange::one(ccursor))
-                } else {
+                Event::Key {
+                    key: Key::D,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if multi_cursor && modifiers.matches_logically(Modifiers::COMMAND) => {
+                    add_next_occurrence(text.as_str(), &mut cursor_range, &mut extra_cursors);
                     None
                 }
-            }
-            Event::Key {
-                key: Key::Tab,
-                pressed: true,
-                modifiers,
-                ..
-            } if multiline => {
-                let mut ccursor = text.delete_selected(&cursor_range);
-                if modifiers.shift {
-                    // TODO(emilk): support removing indentation over a selection?
-                    text.decrease_indentation(&mut ccursor);
-                } else {
-                    text.insert_text_at(&mut ccursor, "\t", char_limit);
-                }
-                Some(CCursorRange::one(ccursor))
-            }
-            Event::Key {
-                key,
-                pressed: true,
-                modifiers,
-                ..
-            } if return_key.is_some_and(|return_key| {
-                *key == return_key.logical_key && modifiers.matches_logically(return_key.modifiers)
-            }) =>
-            {
-                if multiline {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-                    text.insert_text_at(&mut ccursor, "\n", char_limit);
-                    // TODO(emilk): if code editor, auto-indent by same leading tabs, + one if the lines end on an opening bracket
-                    Some(CCursorRange::one(ccursor))
-                } else {
-                    ui.memory_mut(|mem| mem.surrender_focus(id)); // End input with enter
-                    break;
-                }
-            }
 
-            Event::Key {
-                key,
-                pressed: true,
-                modifiers,
-                ..
-            } if (modifiers.matches_logically(Modifiers::COMMAND) && *key == Key::Y)
-                || (modifiers.matches_logically(Modifiers::SHIFT | Modifiers::COMMAND)
-                    && *key == Key::Z) =>
-            {
-                if let Some((redo_ccursor_range, redo_txt)) = state
-                    .undoer
-                    .lock()
-                    .redo(&(cursor_range, text.as_str().to_owned()))
-                {
-                    text.replace_with(redo_txt);
-                    Some(*redo_ccursor_range)
-                } else {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if find_replace && is_find_shortcut(os, *key, modifiers).is_some() => {
+                    let show_replace = is_find_shortcut(os, *key, modifiers) == Some(true);
+                    let selected_text = cursor_range.slice_str(text.as_str());
+                    state.find_replace = Some(FindReplaceState::open(
+                        state.find_replace.take(),
+                        selected_text,
+                        show_replace,
+                    ));
                     None
                 }
-            }
 
-            Event::Key {
-                key: Key::Z,
-                pressed: true,
-                modifiers,
-                ..
-            } if modifiers.matches_logically(Modifiers::COMMAND) => {
-                if let Some((undo_ccursor_range, undo_txt)) = state
-                    .undoer
-                    .lock()
-                    .undo(&(cursor_range, text.as_str().to_owned()))
-                {
-                    text.replace_with(undo_txt);
-                    Some(*undo_ccursor_range)
-                } else {
+                // First handle events that only changes the selection cursor, not the text:
+                event if cursor_range.on_event(os, event, galley, id) => {
+                    if matches!(event, Event::Key { .. }) {
+                        // Move all cursors the same way:
+                        for range in &mut extra_cursors {
+                            range.on_event(os, event, galley, id);
+                        }
+                        merge_cursors(&mut cursor_range, &mut extra_cursors);
+                    } else {
+                        extra_cursors.clear();
+                    }
                     None
                 }
-            }
 
-            Event::Key {
-                modifiers,
-                key,
-                pressed: true,
-                ..
-            } => check_for_mutating_key_press(os, &cursor_range, text, galley, modifiers, *key),
-
-            Event::Ime(ime_event) => match ime_event {
-                ImeEvent::Enabled => {
-                    state.ime_enabled = true;
-                    state.ime_cursor_range = cursor_range;
+                Event::Copy => {
+                    let selected_text = selected_text(text.as_str(), &cursor_range, &extra_cursors);
+                    if !selected_text.is_empty() {
+                        copy_if_not_password(ui, selected_text);
+                    }
                     None
                 }
-                ImeEvent::Preedit(text_mark) => {
-                    if text_mark == "\n" || text_mark == "\r" {
+                Event::Cut => {
+                    let selected_text = selected_text(text.as_str(), &cursor_range, &extra_cursors);
+                    if selected_text.is_empty() {
                         None
                     } else {
-                        // Empty prediction can be produced when user press backspace
-                        // or escape during IME, so we clear current text.
-                        let mut ccursor = text.delete_selected(&cursor_range);
-                        let start_cursor = ccursor;
-                        if !text_mark.is_empty() {
-                            text.insert_text_at(&mut ccursor, text_mark, char_limit);
-                        }
-                        state.ime_cursor_range = cursor_range;
-                        Some(CCursorRange::two(start_cursor, ccursor))
+                        copy_if_not_password(ui, selected_text);
+                        edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                            (!range.is_empty())
+                                .then(|| CCursorRange::one(text.delete_selected(range)))
+                        })
                     }
                 }
-                ImeEvent::Commit(prediction) => {
-                    if prediction == "\n" || prediction == "\r" {
-                        None
+                Event::Paste(text_to_insert) => {
+                    if !text_to_insert.is_empty() {
+                        edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                            let mut ccursor = text.delete_selected(range);
+
+                            text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
+
+                            Some(CCursorRange::one(ccursor))
+                        })
                     } else {
-                        state.ime_enabled = false;
+                        None
+                    }
+                }
+                Event::Text(text_to_insert) => {
+                    // Newlines are handled by `Key::Enter`.
+                    if !text_to_insert.is_empty()
+                        && text_to_insert != "\n"
+                        && text_to_insert != "\r"
+                    {
+                        edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                            let mut ccursor = text.delete_selected(range);
+
+                            text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
 
-                        if !prediction.is_empty()
-                            && cursor_range.secondary.index
-                                == state.ime_cursor_range.secondary.index
-                        {
-                            let mut ccursor = text.delete_selected(&cursor_range);
-                            text.insert_text_at(&mut ccursor, prediction, char_limit);
                             Some(CCursorRange::one(ccursor))
+                        })
+                    } else {
+                        None
+                    }
+                }
+                Event::Key {
+                    key: Key::Tab,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if multiline => {
+                    edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                        let mut ccursor = text.delete_selected(range);
+                        if modifiers.shift {
+                            // TODO(emilk): support removing indentation over a selection?
+                            text.decrease_indentation(&mut ccursor);
                         } else {
-                            let ccursor = cursor_range.primary;
-                            Some(CCursorRange::one(ccursor))
+                            text.insert_text_at(&mut ccursor, "\t", char_limit);
                         }
+                        Some(CCursorRange::one(ccursor))
+                    })
+                }
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if return_key.is_some_and(|return_key| {
+                    *key == return_key.logical_key
+                        && modifiers.matches_logically(return_key.modifiers)
+                }) =>
+                {
+                    if multiline {
+                        edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                            let mut ccursor = text.delete_selected(range);
+                            text.insert_text_at(&mut ccursor, "\n", char_limit);
+                            // TODO(emilk): if code editor, auto-indent by same leading tabs, + one if the lines end on an opening bracket
+                            Some(CCursorRange::one(ccursor))
+                        })
+                    } else {
+                        ui.memory_mut(|mem| mem.surrender_focus(id)); // End input with enter
+                        break;
                     }
                 }
-                ImeEvent::Disabled => {
-                    state.ime_enabled = false;
-                    None
+
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if (modifiers.matches_logically(Modifiers::COMMAND) && *key == Key::Y)
+                    || (modifiers.matches_logically(Modifiers::SHIFT | Modifiers::COMMAND)
+                        && *key == Key::Z) =>
+                {
+                    extra_cursors.clear();
+                    is_undo_or_redo = true;
+                    state.undoer.lock().redo(text)
                 }
-            },
 
-            _ => None,
+                Event::Key {
+                    key: Key::Z,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if modifiers.matches_logically(Modifiers::COMMAND) => {
+                    extra_cursors.clear();
+                    is_undo_or_redo = true;
+                    state.undoer.lock().undo(text)
+                }
+
+                Event::Key {
+                    modifiers,
+                    key,
+                    pressed: true,
+                    ..
+                } => edit_at_cursors(text, &cursor_range, &mut extra_cursors, |text, range| {
+                    // The cursors are edited from last to first, so the galley is still correct up to `range`.
+                    check_for_mutating_key_press(os, range, text, galley, modifiers, *key)
+                }),
+
+                Event::Ime(ime_event) => match ime_event {
+                    ImeEvent::Enabled => {
+                        extra_cursors.clear();
+                        state.ime_enabled = true;
+                        state.ime_cursor_range = cursor_range;
+                        None
+                    }
+                    ImeEvent::Preedit(text_mark) => {
+                        if text_mark == "\n" || text_mark == "\r" {
+                            None
+                        } else {
+                            // Empty prediction can be produced when user press backspace
+                            // or escape during IME, so we clear current text.
+                            let mut ccursor = text.delete_selected(&cursor_range);
+                            let start_cursor = ccursor;
+                            if !text_mark.is_empty() {
+                                text.insert_text_at(&mut ccursor, text_mark, char_limit);
+                            }
+                            state.ime_cursor_range = cursor_range;
+                            Some(CCursorRange::two(start_cursor, ccursor))
+                        }
+                    }
+                    ImeEvent::Commit(prediction) => {
+                        if prediction == "\n" || prediction == "\r" {
+                            None
+                        } else {
+                            state.ime_enabled = false;
+
+                            if !prediction.is_empty()
+                                && cursor_range.secondary.index
+                                    == state.ime_cursor_range.secondary.index
+                            {
+                                let mut ccursor = text.delete_selected(&cursor_range);
+                                text.insert_text_at(&mut ccursor, prediction, char_limit);
+                                Some(CCursorRange::one(ccursor))
+                            } else {
+                                let ccursor = cursor_range.primary;
+                                Some(CCursorRange::one(ccursor))
+                            }
+                        }
+                    }
+                    ImeEvent::Disabled => {
+                        state.ime_enabled = false;
+                        None
+                    }
+                },
+
+                _ => None,
+            }
         };
 
+        let operations = recorder.into_operations();
+        if !is_undo_or_redo {
+            state.undoer.lock().add_edit(
+                ui.input(|i| i.time),
+                text.len_bytes(),
+                cursor_range_before,
+                operations,
+                did_mutate_text.unwrap_or(cursor_range),
+            );
+        }
+
         if let Some(new_ccursor_range) = did_mutate_text {
             any_change = true;
 
@@ -1109,15 +1459,40 @@ fn events(
     }
 
     state.cursor.set_char_range(Some(cursor_range));
-
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range, text.as_str().to_owned()),
-    );
+    state.extra_cursors = extra_cursors;
 
     (any_change, cursor_range)
 }
 
+/// The text selected by all cursors, in order, separated by newlines.
+fn selected_text(text: &str, primary: &CCursorRange, extra_cursors: &[CCursorRange]) -> String {
+    let mut ranges: Vec<&CCursorRange> = std::iter::once(primary)
+        .chain(extra_cursors)
+        .filter(|range| !range.is_empty())
+        .collect();
+    ranges.sort_by_key(|range| range.as_sorted_char_range().start);
+    ranges
+        .iter()
+        .map(|range| range.slice_str(text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Is this the shortcut for opening the find bar?
+///
+/// Returns `Some(true)` for find-and-replace, `Some(false)` for just find.
+fn is_find_shortcut(os: OperatingSystem, key: Key, modifiers: &Modifiers) -> Option<bool> {
+    if !modifiers.command {
+        None
+    } else if key == Key::F {
+        Some(os == OperatingSystem::Mac && modifiers.alt)
+    } else if key == Key::H && os != OperatingSystem::Mac {
+        Some(true)
+    } else {
+        None
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 fn remove_ime_incompatible_events(events: &mut Vec<Event>) {
@@ -0,0 +1,469 @@
+//! The find/replace bar of [`crate::TextEdit::find_replace`].
+
+use std::ops::Range;
+
+use epaint::text::cursor::CCursor;
+
+use crate::{
+    Align, Frame, Id, Key, Layout, NumExt as _, Rect, RichText, Sense, TextBuffer, TextEdit, Ui,
+    UiBuilder, pos2, text_selection::CCursorRange, vec2,
+};
+
+use super::{TextEditState, undoer::RecordingTextBuffer};
+
+/// The state of an open find/replace bar.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FindReplaceState {
+    pub query: String,
+    pub replacement: String,
+    pub match_case: bool,
+
+    /// Interpret [`Self::query`] as a regular expression.
+    #[cfg(feature = "regex")]
+    pub regex: bool,
+
+    pub show_replace: bool,
+
+    /// Move keyboard focus to the query field.
+    pub focus_query: bool,
+
+    /// Scroll the [`crate::TextEdit`] to show the primary cursor.
+    pub scroll_to_selection: bool,
+}
+
+impl FindReplaceState {
+    /// Open the bar (or move focus to it, if it is already open),
+    /// searching for the selected text, if any.
+    pub fn open(previous: Option<Self>, selected_text: &str, show_replace: bool) -> Self {
+        let mut state = previous.unwrap_or_default();
+        if !selected_text.is_empty() && !selected_text.contains('\n') {
+            state.query = selected_text.to_owned();
+        }
+        state.show_replace |= show_replace;
+        state.focus_query = true;
+        state
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A compiled search query.
+pub(crate) enum Search {
+    Plain {
+        needle: Vec<char>,
+        match_case: bool,
+    },
+
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl Search {
+    /// Returns `Ok(None)` if the query is empty.
+    ///
+    /// # Errors
+    /// If the query is an invalid regular expression.
+    #[cfg_attr(not(feature = "regex"), expect(clippy::unnecessary_wraps))]
+    pub fn new(state: &FindReplaceState) -> Result<Option<Self>, String> {
+        if state.query.is_empty() {
+            return Ok(None);
+        }
+
+        #[cfg(feature = "regex")]
+        if state.regex {
+            return regex::RegexBuilder::new(&state.query)
+                .case_insensitive(!state.match_case)
+                .multi_line(true)
+                .build()
+                .map(|regex| Some(Self::Regex(regex)))
+                .map_err(|err| err.to_string());
+        }
+
+        Ok(Some(Self::Plain {
+            needle: state.query.chars().collect(),
+            match_case: state.match_case,
+        }))
+    }
+
+    /// All non-overlapping, non-empty matches, as character ranges.
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Self::Plain { needle, match_case } => {
+                let chars_match = |a: &char, b: &char| {
+                    a == b || (!match_case && a.to_lowercase().eq(b.to_lowercase()))
+                };
+
+                let haystack: Vec<char> = text.chars().collect();
+                let mut matches = vec![];
+                let mut start = 0;
+                while start + needle.len() <= haystack.len() {
+                    let end = start + needle.len();
+                    if haystack[start..end]
+                        .iter()
+                        .zip(needle)
+                        .all(|(a, b)| chars_match(a, b))
+                    {
+                        matches.push(start..end);
+                        start = end;
+                    } else {
+                        start += 1;
+                    }
+                }
+                matches
+            }
+
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => {
+                // Convert byte offsets to char offsets as we go:
+                let mut byte_index = 0;
+                let mut char_index = 0;
+                let mut to_char_index = |new_byte_index: usize| {
+                    char_index += text[byte_index..new_byte_index].chars().count();
+                    byte_index = new_byte_index;
+                    char_index
+                };
+
+                regex
+                    .find_iter(text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| to_char_index(m.start())..to_char_index(m.end()))
+                    .collect()
+            }
+        }
+    }
+
+    /// What to replace the match at `char_range` with.
+    ///
+    /// For regular expressions, `$1`, `$name` etc are replaced with the captured groups.
+    #[cfg_attr(not(feature = "regex"), expect(clippy::unused_self, unused_variables))]
+    pub fn replacement(&self, text: &str, char_range: Range<usize>, replacement: &str) -> String {
+        #[cfg(feature = "regex")]
+        if let Self::Regex(regex) = self {
+            let byte_start = text.byte_index_from_char_index(char_range.start);
+            if let Some(captures) = regex.captures_at(text, byte_start) {
+                if captures.get(0).is_some_and(|m| m.start() == byte_start) {
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+                    return expanded;
+                }
+            }
+        }
+
+        replacement.to_owned()
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Show the find/replace bar over the top right corner of `rect`, if it is open.
+///
+/// Returns `true` if the text was changed.
+pub(crate) fn find_replace_ui(
+    ui: &mut Ui,
+    editor_id: Id,
+    rect: Rect,
+    state: &mut TextEditState,
+    text: &mut dyn TextBuffer,
+) -> bool {
+    let Some(mut find) = state.find_replace.take() else {
+        return false;
+    };
+
+    let query_id = editor_id.with("find_query");
+    let replacement_id = editor_id.with("find_replacement");
+    let show_replace = find.show_replace && text.is_mutable();
+
+    let search = Search::new(&find);
+    let matches = match &search {
+        Ok(Some(search)) => search.find_all(text.as_str()),
+        _ => vec![],
+    };
+    let selection = state
+        .cursor
+        .char_range()
+        .map_or(0..0, |range| range.as_sorted_char_range());
+    let current_match = matches.iter().position(|m| *m == selection);
+
+    // Where the bar goes. We need to know its size up front, to block clicks from reaching the text below.
+    let frame = Frame::popup(ui.style());
+    let num_rows = if show_replace { 2.0 } else { 1.0 };
+    let height = num_rows * ui.spacing().interact_size.y
+        + (num_rows - 1.0) * ui.spacing().item_spacing.y
+        + frame.total_margin().sum().y;
+    let width = 360.0_f32.at_most(rect.width());
+    let bar_rect = Rect::from_min_size(pos2(rect.right() - width, rect.top()), vec2(width, height));
+    ui.interact(bar_rect, editor_id.with("find_bar"), Sense::click());
+
+    let mut bar_ui = ui.new_child(
+        UiBuilder::new()
+            .id_salt(editor_id.with("find_bar"))
+            .max_rect(bar_rect)
+            .layout(Layout::top_down_justified(Align::Min)),
+    );
+
+    let mut open = true;
+    let mut query_changed = false;
+    let mut go_to: Option<bool> = None; // Some(true) = next, Some(false) = previous
+    let mut replace_one = false;
+    let mut replace_all = false;
+
+    frame.show(&mut bar_ui, |ui| {
+        let enter_pressed = |ui: &Ui| ui.input(|i| i.key_pressed(Key::Enter));
+        let escape_pressed = |ui: &Ui| ui.input(|i| i.key_pressed(Key::Escape));
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if ui.small_button("🗙").on_hover_text("Close").clicked() {
+                open = false;
+            }
+            if ui.small_button("⏷").on_hover_text("Next match").clicked() {
+                go_to = Some(true);
+            }
+            if ui
+                .small_button("⏶")
+                .on_hover_text("Previous match")
+                .clicked()
+            {
+                go_to = Some(false);
+            }
+            #[cfg(feature = "regex")]
+            {
+                query_changed |= ui
+                    .toggle_value(&mut find.regex, RichText::new(".*").monospace())
+                    .on_hover_text("Use regular expression")
+                    .changed();
+            }
+            query_changed |= ui
+                .toggle_value(&mut find.match_case, "Aa")
+                .on_hover_text("Match case")
+                .changed();
+            if text.is_mutable() {
+                ui.toggle_value(&mut find.show_replace, "🔄")
+                    .on_hover_text("Replace");
+            }
+
+            match &search {
+                Err(err) => {
+                    ui.label(RichText::new("⚠").color(ui.visuals().error_fg_color))
+                        .on_hover_text(err);
+                }
+                Ok(None) => {}
+                Ok(Some(_)) => {
+                    let count = match current_match {
+                        _ if matches.is_empty() => "No results".to_owned(),
+                        Some(index) => format!("{} of {}", index + 1, matches.len()),
+                        None => format!("{} found", matches.len()),
+                    };
+                    ui.weak(count);
+                }
+            }
+
+            let response = ui.add(
+                TextEdit::singleline(&mut find.query)
+                    .id(query_id)
+                    .hint_text("Find")
+                    .desired_width(ui.available_width()),
+            );
+            query_changed |= response.changed();
+            if response.lost_focus() {
+                if enter_pressed(ui) {
+                    go_to = Some(!ui.input(|i| i.modifiers.shift));
+                    ui.memory_mut(|mem| mem.request_focus(query_id));
+                } else if escape_pressed(ui) {
+                    open = false;
+                }
+            }
+        });
+
+        if show_replace {
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                replace_all |= ui
+                    .small_button("All")
+                    .on_hover_text("Replace all")
+                    .clicked();
+                replace_one |= ui
+                    .small_button("Replace")
+                    .on_hover_text("Replace this match, and go to the next one")
+                    .clicked();
+
+                let response = ui.add(
+                    TextEdit::singleline(&mut find.replacement)
+                        .id(replacement_id)
+                        .hint_text("Replace")
+                        .desired_width(ui.available_width()),
+                );
+                if response.lost_focus() {
+                    if enter_pressed(ui) {
+                        replace_one = true;
+                        ui.memory_mut(|mem| mem.request_focus(replacement_id));
+                    } else if escape_pressed(ui) {
+                        open = false;
+                    }
+                }
+            });
+        }
+    });
+
+    if std::mem::take(&mut find.focus_query) {
+        ui.memory_mut(|mem| mem.request_focus(query_id));
+        let mut query_state = TextEditState::load(ui.ctx(), query_id).unwrap_or_default();
+        query_state.cursor.set_char_range(Some(CCursorRange::two(
+            CCursor::default(),
+            CCursor::new(find.query.chars().count()),
+        )));
+        query_state.store(ui.ctx(), query_id);
+    }
+
+    let mut text_changed = false;
+
+    if let (Ok(Some(search)), true) = (Search::new(&find), query_changed || go_to.is_some()) {
+        let matches = search.find_all(text.as_str());
+        let next = match go_to {
+            // Incremental search: stay on the current match while it still matches.
+            None => matches.iter().find(|m| selection.start <= m.start),
+            Some(true) => matches.iter().find(|m| selection.start < m.start),
+            Some(false) => matches.iter().rev().find(|m| m.start < selection.start),
+        };
+        let wrapped = match go_to {
+            Some(false) => matches.last(),
+            _ => matches.first(),
+        };
+        if let Some(next) = next.or(wrapped) {
+            select(state, &mut find, next.clone());
+        }
+    }
+
+    if let (Ok(Some(search)), true) = (&search, replace_one || replace_all) {
+        let cursor_before = state.cursor.char_range().unwrap_or_default();
+        let mut recorder = RecordingTextBuffer::new(text);
+        let text: &mut dyn TextBuffer = &mut recorder;
+
+        if replace_all {
+            for m in matches.iter().rev() {
+                let replacement = search.replacement(text.as_str(), m.clone(), &find.replacement);
+                replace(text, m.clone(), &replacement);
+            }
+            text_changed = !matches.is_empty();
+        } else if let Some(index) = current_match {
+            let m = matches[index].clone();
+            let replacement = search.replacement(text.as_str(), m.clone(), &find.replacement);
+            replace(text, m.clone(), &replacement);
+            text_changed = true;
+
+            // Go to the next match:
+            let matches = search.find_all(text.as_str());
+            let after = m.start + replacement.chars().count();
+            if let Some(next) = matches
+                .iter()
+                .find(|next| after <= next.start)
+                .or(matches.first())
+            {
+                select(state, &mut find, next.clone());
+            } else {
+                select(state, &mut find, m.start..after);
+            }
+        } else if let Some(next) = matches
+            .iter()
+            .find(|m| selection.start <= m.start)
+            .or(matches.first())
+        {
+            select(state, &mut find, next.clone());
+        }
+
+        let text_len = text.len_bytes();
+        let operations = recorder.into_operations();
+        if text_changed {
+            // Make the replacement a single undo step:
+            let mut undoer = state.undoer.lock();
+            undoer.add_undo_point();
+            undoer.add_edit(
+                ui.input(|i| i.time),
+                text_len,
+                cursor_before,
+                operations,
+                state.cursor.char_range().unwrap_or_default(),
+            );
+            undoer.add_undo_point();
+        }
+    }
+
+    if open {
+        state.find_replace = Some(find);
+    } else {
+        ui.memory_mut(|mem| mem.request_focus(editor_id));
+    }
+
+    if text_changed || query_changed || go_to.is_some() {
+        ui.ctx().request_repaint();
+    }
+
+    text_changed
+}
+
+fn select(state: &mut TextEditState, find: &mut FindReplaceState, char_range: Range<usize>) {
+    state.cursor.set_char_range(Some(CCursorRange::two(
+        CCursor::new(char_range.start),
+        CCursor::new(char_range.end),
+    )));
+    state.extra_cursors.clear();
+    find.scroll_to_selection = true;
+}
+
+fn replace(text: &mut dyn TextBuffer, char_range: Range<usize>, replacement: &str) {
+    text.delete_char_range(char_range.clone());
+    text.insert_text(replacement, char_range.start);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str, match_case: bool) -> Search {
+        let state = FindReplaceState {
+            query: query.to_owned(),
+            match_case,
+            ..Default::default()
+        };
+        Search::new(&state).unwrap().unwrap()
+    }
+
+    #[cfg(feature = "regex")]
+    fn search_regex(query: &str, match_case: bool) -> Result<Search, String> {
+        let state = FindReplaceState {
+            query: query.to_owned(),
+            match_case,
+            regex: true,
+            ..Default::default()
+        };
+        Search::new(&state).map(Option::unwrap)
+    }
+
+    #[test]
+    fn test_find_plain() {
+        assert_eq!(search("ab", true).find_all("abAbab"), vec![0..2, 4..6]);
+        assert_eq!(
+            search("ab", false).find_all("abAbab"),
+            vec![0..2, 2..4, 4..6]
+        );
+        assert_eq!(search("aa", true).find_all("aaaaa"), vec![0..2, 2..4]);
+        assert_eq!(search("ö", false).find_all("åÖö"), vec![1..2, 2..3]);
+        assert!(search("abc", true).find_all("ab").is_empty());
+        assert!(Search::new(&FindReplaceState::default()).unwrap().is_none());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_find_regex() {
+        let digits = search_regex(r"\d+", true).unwrap();
+        assert_eq!(digits.find_all("å1 22 x333"), vec![1..2, 3..5, 7..10]);
+
+        let line_start = search_regex("^x", false).unwrap();
+        assert_eq!(line_start.find_all("x\nX\nyx"), vec![0..1, 2..3]);
+
+        let assignment = search_regex(r"(\w+)=(\w+)", true).unwrap();
+        let text = "a=1, bb=2";
+        assert_eq!(assignment.find_all(text), vec![0..3, 5..9]);
+        assert_eq!(assignment.replacement(text, 5..9, "$2=$1"), "2=bb");
+
+        assert!(search_regex("(", true).is_err());
+    }
+}
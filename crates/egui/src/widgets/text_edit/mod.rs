@@ -1,9 +1,20 @@
 mod builder;
+mod find_replace;
+mod multi_cursor;
 mod output;
+mod rope;
 mod state;
 mod text_buffer;
+mod undoer;
+mod virtual_text_edit;
 
 pub use {
-    crate::text_selection::TextCursorState, builder::TextEdit, output::TextEditOutput,
-    state::TextEditState, text_buffer::TextBuffer,
+    crate::text_selection::TextCursorState,
+    builder::TextEdit,
+    output::TextEditOutput,
+    rope::Rope,
+    state::TextEditState,
+    text_buffer::TextBuffer,
+    undoer::TextEditUndoer,
+    virtual_text_edit::{VirtualTextEdit, VirtualTextEditOutput},
 };
@@ -0,0 +1,367 @@
+//! Editing text at several cursors at once.
+
+use std::{borrow::Cow, ops::Range};
+
+use epaint::text::cursor::CCursor;
+
+use crate::{
+    TextBuffer,
+    text_selection::{
+        CCursorRange,
+        text_cursor_state::{
+            byte_index_from_char_index, char_index_from_byte_index, is_word_char, select_word_at,
+        },
+    },
+};
+
+/// Apply the same edit at the primary cursor and all the extra cursors.
+///
+/// The edits are applied from the last cursor in the text to the first,
+/// so each edit sees the text before it as it was originally.
+/// `edit` returns the new cursor range, or `None` if it did not change the text.
+///
+/// Returns the new primary cursor range, or `None` if no edit changed the text.
+pub(crate) fn edit_at_cursors(
+    text: &mut dyn TextBuffer,
+    primary: &CCursorRange,
+    extra_cursors: &mut Vec<CCursorRange>,
+    mut edit: impl FnMut(&mut dyn TextBuffer, &CCursorRange) -> Option<CCursorRange>,
+) -> Option<CCursorRange> {
+    if extra_cursors.is_empty() {
+        return edit(text, primary);
+    }
+
+    let mut cursors: Vec<(CCursorRange, bool)> = std::iter::once((*primary, true))
+        .chain(extra_cursors.iter().map(|range| (*range, false)))
+        .collect();
+    cursors.sort_by_key(|(range, _)| std::cmp::Reverse(range.as_sorted_char_range().start));
+
+    let mut text = CharCountingTextBuffer {
+        num_chars: text.len_chars(),
+        buffer: text,
+    };
+
+    let mut any_edit = false;
+    let mut edited: Vec<(CCursorRange, bool)> = Vec::with_capacity(cursors.len());
+    for (range, is_primary) in cursors {
+        let num_chars_before = text.num_chars;
+        let new_range = if let Some(new_range) = edit(&mut text, &range) {
+            any_edit = true;
+            new_range
+        } else {
+            range
+        };
+        let num_chars_after = text.num_chars;
+
+        // The cursors after this one have moved:
+        if num_chars_after != num_chars_before {
+            for (later_range, _) in &mut edited {
+                shift_range(
+                    later_range,
+                    num_chars_after as isize - num_chars_before as isize,
+                );
+            }
+        }
+        edited.push((new_range, is_primary));
+    }
+
+    if !any_edit {
+        return None;
+    }
+
+    let mut new_primary = *primary;
+    extra_cursors.clear();
+    for (range, is_primary) in edited.into_iter().rev() {
+        if is_primary {
+            new_primary = range;
+        } else {
+            extra_cursors.push(range);
+        }
+    }
+    merge_cursors(&mut new_primary, extra_cursors);
+    Some(new_primary)
+}
+
+/// Keeps track of the number of chars in the text as it is edited,
+/// so that we don't need to count them after every edit.
+struct CharCountingTextBuffer<'a> {
+    buffer: &'a mut dyn TextBuffer,
+    num_chars: usize,
+}
+
+impl TextBuffer for CharCountingTextBuffer<'_> {
+    fn is_mutable(&self) -> bool {
+        self.buffer.is_mutable()
+    }
+
+    fn as_str(&self) -> &str {
+        self.buffer.as_str()
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        let num_inserted = self.buffer.insert_text(text, char_index);
+        self.num_chars += num_inserted;
+        num_inserted
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        let char_range = char_range.start.min(self.num_chars)..char_range.end.min(self.num_chars);
+        self.num_chars -= char_range.len();
+        self.buffer.delete_char_range(char_range);
+    }
+
+    fn char_range(&self, char_range: Range<usize>) -> &str {
+        self.buffer.char_range(char_range)
+    }
+
+    fn len_bytes(&self) -> usize {
+        self.buffer.len_bytes()
+    }
+
+    fn len_chars(&self) -> usize {
+        self.num_chars
+    }
+
+    fn slice(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        self.buffer.slice(char_range)
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<CharCountingTextBuffer<'static>>()
+    }
+}
+
+fn shift_range(range: &mut CCursorRange, delta: isize) {
+    for cursor in [&mut range.primary, &mut range.secondary] {
+        cursor.index = cursor.index.saturating_add_signed(delta);
+    }
+}
+
+/// Merge cursors that overlap, and sort the extra cursors.
+///
+/// If the primary cursor overlaps an extra cursor, the primary cursor grows to cover both.
+pub(crate) fn merge_cursors(primary: &mut CCursorRange, extra_cursors: &mut Vec<CCursorRange>) {
+    if extra_cursors.is_empty() {
+        return;
+    }
+
+    extra_cursors.sort_by_key(|range| range.as_sorted_char_range().start);
+
+    let mut merged: Vec<CCursorRange> = Vec::with_capacity(extra_cursors.len());
+    for range in extra_cursors.drain(..) {
+        if overlaps(primary, &range) {
+            *primary = union(primary, &range);
+        } else if let Some(last) = merged.last_mut().filter(|last| overlaps(last, &range)) {
+            *last = union(last, &range);
+        } else {
+            merged.push(range);
+        }
+    }
+
+    // Growing the primary cursor may have made it overlap more of them:
+    merged.retain(|range| {
+        if overlaps(primary, range) {
+            *primary = union(primary, range);
+            false
+        } else {
+            true
+        }
+    });
+
+    *extra_cursors = merged;
+}
+
+/// Two ranges overlap if they share a character, or are the same empty range.
+fn overlaps(a: &CCursorRange, b: &CCursorRange) -> bool {
+    let a = a.as_sorted_char_range();
+    let b = b.as_sorted_char_range();
+    a == b || (a.start < b.end && b.start < a.end)
+}
+
+/// The smallest range covering both, keeping the direction of `a`.
+fn union(a: &CCursorRange, b: &CCursorRange) -> CCursorRange {
+    let a_range = a.as_sorted_char_range();
+    let b_range = b.as_sorted_char_range();
+    let min = CCursor::new(a_range.start.min(b_range.start));
+    let max = CCursor::new(a_range.end.max(b_range.end));
+    if a.is_sorted() {
+        CCursorRange {
+            primary: min,
+            secondary: max,
+            h_pos: None,
+        }
+    } else {
+        CCursorRange::two(min, max)
+    }
+}
+
+/// Add a cursor at the next occurrence of the text selected by the primary cursor,
+/// and make it the new primary cursor.
+///
+/// If nothing is selected, select the word at the primary cursor instead.
+///
+/// Returns `true` if the cursors changed.
+pub(crate) fn add_next_occurrence(
+    text: &str,
+    primary: &mut CCursorRange,
+    extra_cursors: &mut Vec<CCursorRange>,
+) -> bool {
+    if primary.is_empty() {
+        let word = select_word_at(text, primary.primary);
+        if word.is_empty() || !word.slice_str(text).chars().all(is_word_char) {
+            return false;
+        }
+        *primary = word;
+        return true;
+    }
+
+    let needle = primary.slice_str(text);
+    let num_needle_chars = needle.chars().count();
+    let start = byte_index_from_char_index(text, primary.as_sorted_char_range().end);
+
+    // Byte offsets of all occurrences (including overlapping ones) that start in `from..until`:
+    let occurrences = |mut from: usize, until: usize| {
+        std::iter::from_fn(move || {
+            let index = from + text[from..].find(needle)?;
+            if until <= index {
+                return None;
+            }
+            from = index + text[index..].chars().next().map_or(1, char::len_utf8);
+            Some(index)
+        })
+    };
+    let is_selected = |index: usize| {
+        std::iter::once(&*primary)
+            .chain(extra_cursors.iter())
+            .any(|range| range.as_sorted_char_range().start == index)
+    };
+    let next = occurrences(start, text.len())
+        .chain(occurrences(0, start))
+        .map(|byte_index| char_index_from_byte_index(text, byte_index))
+        .find(|&index| !is_selected(index));
+
+    if let Some(index) = next {
+        extra_cursors.push(*primary);
+        *primary = CCursorRange::two(CCursor::new(index), CCursor::new(index + num_needle_chars));
+        merge_cursors(primary, extra_cursors);
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(index: usize) -> CCursorRange {
+        CCursorRange::one(CCursor::new(index))
+    }
+
+    #[test]
+    fn test_insert_at_cursors() {
+        let mut text = String::from("a\nbb\nccc");
+        let mut extra_cursors = vec![cursor(4), cursor(8)];
+        let primary = edit_at_cursors(&mut text, &cursor(1), &mut extra_cursors, |text, range| {
+            let mut ccursor = text.delete_selected(range);
+            text.insert_text_at(&mut ccursor, ";", usize::MAX);
+            Some(CCursorRange::one(ccursor))
+        });
+        assert_eq!(text, "a;\nbb;\nccc;");
+        assert_eq!(primary.map(|range| range.primary.index), Some(2));
+        let extra_indices: Vec<usize> = extra_cursors.iter().map(|r| r.primary.index).collect();
+        assert_eq!(extra_indices, vec![6, 11]);
+    }
+
+    #[test]
+    fn test_delete_merges_cursors() {
+        let mut text = String::from("abc");
+        let mut extra_cursors = vec![cursor(2), cursor(3)];
+        let primary = edit_at_cursors(&mut text, &cursor(1), &mut extra_cursors, |text, range| {
+            Some(CCursorRange::one(text.delete_previous_char(range.primary)))
+        });
+        assert_eq!(text, "");
+        assert_eq!(primary.map(|range| range.primary.index), Some(0));
+        assert!(extra_cursors.is_empty());
+    }
+
+    #[test]
+    fn test_add_next_occurrence() {
+        let text = "foo bar foo baz foo";
+        let mut primary = cursor(1);
+        let mut extra_cursors = vec![];
+
+        assert!(add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        assert_eq!(primary.as_sorted_char_range(), 0..3);
+
+        assert!(add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        assert_eq!(primary.as_sorted_char_range(), 8..11);
+
+        assert!(add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        assert_eq!(primary.as_sorted_char_range(), 16..19);
+
+        // All occurrences are selected:
+        assert!(!add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        let mut starts: Vec<usize> = extra_cursors
+            .iter()
+            .map(|range| range.as_sorted_char_range().start)
+            .collect();
+        starts.sort_unstable();
+        assert_eq!(starts, vec![0, 8]);
+    }
+
+    #[test]
+    fn test_add_next_occurrence_with_multibyte_chars() {
+        let text = "åäö, åäö och åäö";
+        let mut primary = CCursorRange::two(CCursor::new(5), CCursor::new(8));
+        let mut extra_cursors = vec![];
+
+        assert!(add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        assert_eq!(primary.as_sorted_char_range(), 13..16);
+
+        // Wraps around to the start:
+        assert!(add_next_occurrence(text, &mut primary, &mut extra_cursors));
+        assert_eq!(primary.as_sorted_char_range(), 0..3);
+    }
+
+    #[test]
+    fn test_multi_cursor_edit_is_one_undo_step() {
+        use crate::{CentralPanel, Context, Event, Id, Key, Modifiers, RawInput, TextEdit};
+
+        let ctx = Context::default();
+        let id = Id::new("editor");
+        let mut text = String::from("a\nb\nc");
+
+        let mut state = crate::text_edit::TextEditState::default();
+        state.cursor.set_char_range(Some(cursor(1)));
+        state.extra_cursors = vec![cursor(3), cursor(5)];
+        state.store(&ctx, id);
+        ctx.memory_mut(|mem| mem.request_focus(id));
+
+        let run = |events: Vec<Event>, text: &mut String| {
+            let input = RawInput {
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    TextEdit::multiline(text).id(id).multi_cursor(true).show(ui);
+                });
+            });
+        };
+
+        run(vec![], &mut text);
+        run(vec![Event::Text(";".to_owned())], &mut text);
+        assert_eq!(text, "a;\nb;\nc;");
+
+        let undo = Event::Key {
+            key: Key::Z,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::COMMAND,
+        };
+        run(vec![undo], &mut text);
+        assert_eq!(text, "a\nb\nc");
+    }
+}
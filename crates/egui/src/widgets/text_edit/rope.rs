@@ -0,0 +1,562 @@
+use std::{borrow::Cow, ops::Range, sync::OnceLock};
+
+use super::TextBuffer;
+use crate::text_selection::text_cursor_state::{byte_index_from_char_index, slice_char_range};
+
+/// Chunks are split when they grow larger than this many bytes.
+const MAX_CHUNK_LEN: usize = 4096;
+
+#[derive(Clone, Debug, Default)]
+struct Chunk {
+    text: String,
+    num_chars: usize,
+    num_newlines: usize,
+
+    /// Number of chars in all the chunks before this one.
+    chars_before: usize,
+
+    /// Number of newlines in all the chunks before this one.
+    newlines_before: usize,
+}
+
+impl Chunk {
+    fn new(text: String) -> Self {
+        let mut chunk = Self {
+            text,
+            num_chars: 0,
+            num_newlines: 0,
+            chars_before: 0,
+            newlines_before: 0,
+        };
+        chunk.recount();
+        chunk
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        if self.num_chars == self.text.len() {
+            // Only ASCII
+            char_index
+        } else if self.num_chars <= char_index {
+            self.text.len()
+        } else {
+            byte_index_from_char_index(&self.text, char_index)
+        }
+    }
+
+    fn recount(&mut self) {
+        self.num_chars = self.text.chars().count();
+        self.num_newlines = self.text.bytes().filter(|&b| b == b'\n').count();
+    }
+}
+
+/// Split `text` into chunks of at most [`MAX_CHUNK_LEN`] bytes (unless a single char is larger).
+fn split_into_chunks(text: &str) -> impl Iterator<Item = Chunk> + '_ {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = rest.len().min(MAX_CHUNK_LEN);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(Chunk::new(chunk.to_owned()))
+    })
+}
+
+/// A text buffer for very large texts, stored as a list of small chunks.
+///
+/// Inserting and deleting only touches the text of the chunks that are affected,
+/// and counting chars and lines is cheap, so a [`Rope`] can hold
+/// hundreds of thousands of lines without every edit copying all of them.
+/// Each chunk knows how many chars and lines come before it,
+/// so finding a char or line is a binary search over the chunks.
+///
+/// Use it with [`super::VirtualTextEdit`], which only lays out the lines that are visible.
+///
+/// [`Rope`] also implements [`TextBuffer`], so it can be used with [`super::TextEdit`] too,
+/// but [`TextBuffer::as_str`] needs to copy the whole text into one [`String`]
+/// the first time it is called after an edit.
+/// Undo and redo only use [`TextBuffer::len_bytes`] and [`TextBuffer::slice`], which don't.
+///
+/// ```
+/// # use egui::text_edit::Rope;
+/// let mut rope = Rope::from("first line\nsecond line");
+/// rope.push_str("\nthird line");
+/// assert_eq!(rope.len_lines(), 3);
+/// assert_eq!(rope.line(1), "second line");
+/// assert_eq!(rope.line_to_char(2), 23);
+/// assert_eq!(rope.char_to_line(12), 1);
+/// ```
+#[derive(Clone, Default)]
+pub struct Rope {
+    chunks: Vec<Chunk>,
+
+    /// Length in bytes.
+    len: usize,
+    num_chars: usize,
+    num_newlines: usize,
+
+    /// The whole text, for [`TextBuffer::as_str`]. Cleared on every edit.
+    flat: OnceLock<String>,
+}
+
+impl std::fmt::Debug for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rope")
+            .field("len", &self.len)
+            .field("lines", &self.len_lines())
+            .field("chunks", &self.chunks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut rope = Self::default();
+        rope.push_str(text);
+        rope
+    }
+}
+
+impl From<String> for Rope {
+    #[inline]
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl Rope {
+    /// An empty rope.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Length of the text in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of chars in the text.
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.num_chars
+    }
+
+    /// Number of lines in the text.
+    ///
+    /// This is one more than the number of newlines, so an empty text has one (empty) line.
+    #[inline]
+    pub fn len_lines(&self) -> usize {
+        self.num_newlines + 1
+    }
+
+    /// The chunks of text the rope is made of, in order.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(|chunk| chunk.text.as_str())
+    }
+
+    /// Append text at the end, e.g. new lines of a log.
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.num_chars, text);
+    }
+
+    /// The char index of the start of the given line.
+    ///
+    /// Lines past the end return [`Self::len_chars`].
+    pub fn line_to_char(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        if line > self.num_newlines {
+            return self.num_chars;
+        }
+
+        // Find the chunk containing newline number `line`:
+        let chunk_index = self
+            .chunks
+            .partition_point(|chunk| chunk.newlines_before + chunk.num_newlines < line);
+        let Some(chunk) = self.chunks.get(chunk_index) else {
+            return self.num_chars;
+        };
+        let newline_index = line - chunk.newlines_before;
+        let mut newlines_seen = 0;
+        for (i, c) in chunk.text.chars().enumerate() {
+            if c == '\n' {
+                newlines_seen += 1;
+                if newlines_seen == newline_index {
+                    return chunk.chars_before + i + 1;
+                }
+            }
+        }
+        self.num_chars
+    }
+
+    /// The line containing the given char index.
+    ///
+    /// Indices past the end return the last line.
+    pub fn char_to_line(&self, char_index: usize) -> usize {
+        let chunk_index = self
+            .chunks
+            .partition_point(|chunk| chunk.chars_before + chunk.num_chars <= char_index);
+        let Some(chunk) = self.chunks.get(chunk_index) else {
+            return self.num_newlines;
+        };
+        let newlines_in_chunk = chunk
+            .text
+            .chars()
+            .take(char_index - chunk.chars_before)
+            .filter(|&c| c == '\n')
+            .count();
+        chunk.newlines_before + newlines_in_chunk
+    }
+
+    /// The text of the given line, without the trailing newline.
+    ///
+    /// Lines past the end are empty.
+    pub fn line(&self, line: usize) -> Cow<'_, str> {
+        if line > self.num_newlines {
+            return Cow::Borrowed("");
+        }
+        let start = self.line_to_char(line);
+        let end = if line == self.num_newlines {
+            self.num_chars
+        } else {
+            self.line_to_char(line + 1) - 1
+        };
+        self.slice(start..end)
+    }
+
+    /// The text in the given range of chars.
+    ///
+    /// This only allocates if the range spans more than one chunk.
+    pub fn slice(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        let start = char_range.start.min(self.num_chars);
+        let end = char_range.end.clamp(start, self.num_chars);
+        if start == end {
+            return Cow::Borrowed("");
+        }
+
+        let (first_chunk, start_in_chunk) = self.chunk_at_char(start);
+        let (last_chunk, end_in_chunk) = self.chunk_at_char(end);
+        let first = &self.chunks[first_chunk];
+
+        if first_chunk == last_chunk {
+            let start = first.byte_index(start_in_chunk);
+            let end = first.byte_index(end_in_chunk);
+            return Cow::Borrowed(&first.text[start..end]);
+        }
+
+        let mut text = String::with_capacity(end - start);
+        text.push_str(&first.text[first.byte_index(start_in_chunk)..]);
+        for chunk in &self.chunks[first_chunk + 1..last_chunk] {
+            text.push_str(&chunk.text);
+        }
+        let last = &self.chunks[last_chunk];
+        text.push_str(&last.text[..last.byte_index(end_in_chunk)]);
+        Cow::Owned(text)
+    }
+
+    /// Insert text at the given char index.
+    ///
+    /// Returns the number of chars inserted.
+    pub fn insert(&mut self, char_index: usize, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        self.flat = OnceLock::new();
+
+        let inserted = Chunk::new(text.to_owned());
+        self.len += inserted.text.len();
+        self.num_chars += inserted.num_chars;
+        self.num_newlines += inserted.num_newlines;
+
+        if self.chunks.is_empty() {
+            self.chunks.extend(split_into_chunks(text));
+            self.update_offsets(0);
+            return inserted.num_chars;
+        }
+
+        let (chunk_index, char_in_chunk) = self.chunk_at_char(char_index);
+        let chunk = &mut self.chunks[chunk_index];
+        let byte_index = chunk.byte_index(char_in_chunk);
+        chunk.text.insert_str(byte_index, text);
+        chunk.num_chars += inserted.num_chars;
+        chunk.num_newlines += inserted.num_newlines;
+
+        if MAX_CHUNK_LEN < chunk.text.len() {
+            let chunk = std::mem::take(chunk);
+            self.chunks.splice(
+                chunk_index..=chunk_index,
+                split_into_chunks(&chunk.text).collect::<Vec<_>>(),
+            );
+        }
+        self.update_offsets(chunk_index);
+
+        inserted.num_chars
+    }
+
+    /// Remove the chars in the given range.
+    pub fn remove(&mut self, char_range: Range<usize>) {
+        let start = char_range.start.min(self.num_chars);
+        let end = char_range.end.clamp(start, self.num_chars);
+        if start == end {
+            return;
+        }
+        self.flat = OnceLock::new();
+
+        let (first_chunk, start_in_chunk) = self.chunk_at_char(start);
+        let (last_chunk, end_in_chunk) = self.chunk_at_char(end);
+
+        for chunk_index in first_chunk..=last_chunk {
+            let chunk = &mut self.chunks[chunk_index];
+            let start = if chunk_index == first_chunk {
+                chunk.byte_index(start_in_chunk)
+            } else {
+                0
+            };
+            let end = if chunk_index == last_chunk {
+                chunk.byte_index(end_in_chunk)
+            } else {
+                chunk.text.len()
+            };
+
+            let removed = Chunk::new(chunk.text.drain(start..end).collect());
+            chunk.num_chars -= removed.num_chars;
+            chunk.num_newlines -= removed.num_newlines;
+            self.len -= removed.text.len();
+            self.num_chars -= removed.num_chars;
+            self.num_newlines -= removed.num_newlines;
+        }
+
+        self.chunks.retain(|chunk| !chunk.text.is_empty());
+
+        // Merge small neighbors, so that repeated edits don't leave lots of tiny chunks:
+        let merge_index = first_chunk.min(self.chunks.len().saturating_sub(1));
+        if merge_index + 1 < self.chunks.len()
+            && self.chunks[merge_index].text.len() + self.chunks[merge_index + 1].text.len()
+                <= MAX_CHUNK_LEN
+        {
+            let next = self.chunks.remove(merge_index + 1);
+            let chunk = &mut self.chunks[merge_index];
+            chunk.text.push_str(&next.text);
+            chunk.num_chars += next.num_chars;
+            chunk.num_newlines += next.num_newlines;
+        }
+        self.update_offsets(merge_index);
+    }
+
+    /// Recompute [`Chunk::chars_before`] and [`Chunk::newlines_before`]
+    /// from `first_chunk` onwards, after an edit of that chunk.
+    ///
+    /// This is O(chunks), but only adds up numbers.
+    fn update_offsets(&mut self, first_chunk: usize) {
+        let (mut chars_before, mut newlines_before) = first_chunk
+            .checked_sub(1)
+            .and_then(|previous| self.chunks.get(previous))
+            .map_or((0, 0), |previous| {
+                (
+                    previous.chars_before + previous.num_chars,
+                    previous.newlines_before + previous.num_newlines,
+                )
+            });
+        for chunk in self.chunks.iter_mut().skip(first_chunk) {
+            chunk.chars_before = chars_before;
+            chunk.newlines_before = newlines_before;
+            chars_before += chunk.num_chars;
+            newlines_before += chunk.num_newlines;
+        }
+    }
+
+    /// Find the chunk containing the given char index,
+    /// and the char index within that chunk.
+    ///
+    /// An index at the boundary between two chunks returns the end of the first one.
+    fn chunk_at_char(&self, char_index: usize) -> (usize, usize) {
+        let chunk_index = self
+            .chunks
+            .partition_point(|chunk| chunk.chars_before + chunk.num_chars < char_index);
+        if let Some(chunk) = self.chunks.get(chunk_index) {
+            (chunk_index, char_index - chunk.chars_before)
+        } else {
+            let last = self.chunks.len().saturating_sub(1);
+            (last, self.chunks.last().map_or(0, |chunk| chunk.num_chars))
+        }
+    }
+}
+
+impl TextBuffer for Rope {
+    fn is_mutable(&self) -> bool {
+        true
+    }
+
+    fn as_str(&self) -> &str {
+        self.flat.get_or_init(|| self.to_string())
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        self.insert(char_index, text)
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        assert!(
+            char_range.start <= char_range.end,
+            "start must be <= end, but got {char_range:?}"
+        );
+        self.remove(char_range);
+    }
+
+    fn len_bytes(&self) -> usize {
+        self.len
+    }
+
+    fn len_chars(&self) -> usize {
+        self.num_chars
+    }
+
+    fn slice(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        Self::slice(self, char_range)
+    }
+
+    fn char_range(&self, char_range: Range<usize>) -> &str {
+        // Avoid flattening the whole rope if the range is within one chunk:
+        match self.slice(char_range.clone()) {
+            Cow::Borrowed(text) => text,
+            Cow::Owned(_) => slice_char_range(self.as_str(), char_range),
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn replace_with(&mut self, text: &str) {
+        *self = Self::from(text);
+    }
+
+    fn take(&mut self) -> String {
+        std::mem::take(self).to_string()
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<Self>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A text that spans many chunks, with some multi-byte chars.
+    fn long_text() -> String {
+        use std::fmt::Write as _;
+
+        let mut text = String::new();
+        for i in 0..2000 {
+            writeln!(text, "line {i} ✓").unwrap();
+        }
+        text
+    }
+
+    #[test]
+    fn test_lines() {
+        let text = long_text();
+        let rope = Rope::from(text.as_str());
+        assert!(1 < rope.chunks.len());
+        assert_eq!(rope.to_string(), text);
+        assert_eq!(rope.len(), text.len());
+        assert_eq!(rope.len_chars(), text.chars().count());
+        assert_eq!(rope.len_lines(), 2001);
+
+        for (line_index, line) in text.split('\n').enumerate() {
+            assert_eq!(rope.line(line_index), line);
+
+            let start = rope.line_to_char(line_index);
+            assert_eq!(rope.char_to_line(start), line_index);
+            assert_eq!(
+                rope.slice(start..start + line.chars().count()),
+                line,
+                "line {line_index}"
+            );
+        }
+        assert_eq!(rope.line(2001), "");
+        assert_eq!(rope.line_to_char(2001), rope.len_chars());
+        assert_eq!(rope.char_to_line(usize::MAX), 2000);
+    }
+
+    #[test]
+    fn test_edits_match_string() {
+        let mut string = long_text();
+        let mut rope = Rope::from(string.as_str());
+
+        let edits: &[(Range<usize>, &str)] = &[
+            (10..10, "hello"),
+            (4000..4100, ""),
+            (100..9000, "a\nb\nc"),
+            (0..0, &"x".repeat(10_000)),
+            (9_990..10_010, "✓✓"),
+            (0..usize::MAX, ""),
+            (0..0, "again\n"),
+        ];
+        for (range, insert) in edits {
+            let char_count = string.chars().count();
+            let range = range.start.min(char_count)..range.end.min(char_count);
+            string.delete_char_range(range.clone());
+            string.insert_text(insert, range.start);
+
+            rope.delete_char_range(range.clone());
+            rope.insert_text(insert, range.start);
+
+            assert_eq!(rope.as_str(), string);
+            assert_eq!(rope.len(), string.len());
+            assert_eq!(rope.len_chars(), string.chars().count());
+            assert_eq!(rope.len_lines(), string.split('\n').count());
+            assert!(rope.chunks.iter().all(|chunk| !chunk.text.is_empty()));
+            for (line_index, line) in string.split('\n').enumerate() {
+                assert_eq!(rope.line(line_index), line, "line {line_index}");
+                assert_eq!(rope.char_to_line(rope.line_to_char(line_index)), line_index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_undo_without_flattening() {
+        use super::super::{TextEditUndoer, undoer::RecordingTextBuffer};
+        use crate::text_selection::CCursorRange;
+
+        let text = long_text();
+        let mut rope = Rope::from(text.as_str());
+        let mut undoer = TextEditUndoer::default();
+        let cursor = CCursorRange::default();
+
+        let mut recorder = RecordingTextBuffer::new(&mut rope);
+        recorder.delete_char_range(100..9000);
+        recorder.insert_text("hello", 100);
+        let operations = recorder.into_operations();
+        undoer.add_edit(0.0, rope.len(), cursor, operations, cursor);
+
+        assert!(undoer.undo(&mut rope).is_some());
+        assert_eq!(rope.to_string(), text);
+        assert!(
+            rope.flat.get().is_none(),
+            "Undo should not flatten the rope"
+        );
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     text_selection::{CCursorRange, TextCursorState},
 };
 
-pub type TextEditUndoer = crate::util::undoer::Undoer<(CCursorRange, String)>;
+use super::{TextEditUndoer, find_replace::FindReplaceState};
 
 /// The text edit state stored between frames.
 ///
@@ -35,8 +35,15 @@ pub type TextEditUndoer = crate::util::undoer::Undoer<(CCursorRange, String)>;
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct TextEditState {
     /// Controls the text selection.
+    ///
+    /// This is the primary cursor, which is the one that moves when you click, and that keeps the view scrolled.
     pub cursor: TextCursorState,
 
+    /// Additional cursors, for editing in many places at once.
+    ///
+    /// Only used by a [`crate::TextEdit`] with [`crate::TextEdit::multi_cursor`] enabled.
+    pub extra_cursors: Vec<CCursorRange>,
+
     /// Wrapped in Arc for cheaper clones.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) undoer: Arc<Mutex<TextEditUndoer>>,
@@ -53,6 +60,10 @@ pub struct TextEditState {
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) singleline_offset: f32,
 
+    /// The find/replace bar, if open.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) find_replace: Option<FindReplaceState>,
+
     /// When did the user last press a key or click on the `TextEdit`.
     /// Used to pause the cursor animation when typing.
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -44,6 +44,28 @@ pub trait TextBuffer {
         slice_char_range(self.as_str(), char_range)
     }
 
+    /// Length of the text in bytes.
+    ///
+    /// Override this if [`Self::as_str`] is expensive, like for [`super::Rope`].
+    fn len_bytes(&self) -> usize {
+        self.as_str().len()
+    }
+
+    /// Number of chars in the text.
+    ///
+    /// Override this if [`Self::as_str`] is expensive, like for [`super::Rope`].
+    fn len_chars(&self) -> usize {
+        self.as_str().chars().count()
+    }
+
+    /// Reads the given character range, like [`Self::char_range`],
+    /// but without needing the whole text as one `str`.
+    ///
+    /// Override this if [`Self::as_str`] is expensive, like for [`super::Rope`].
+    fn slice(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        Cow::Borrowed(self.char_range(char_range))
+    }
+
     fn byte_index_from_char_index(&self, char_index: usize) -> usize {
         byte_index_from_char_index(self.as_str(), char_index)
     }
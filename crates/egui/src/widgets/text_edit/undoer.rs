@@ -0,0 +1,606 @@
+use std::{borrow::Cow, collections::VecDeque, ops::Range};
+
+use crate::{TextBuffer, text_selection::CCursorRange, util::undoer::Settings};
+
+/// How many chars before and after an edit we remember,
+/// to check that the edit still fits the text when undoing or redoing it.
+const SURROUNDING_CHARS: usize = 8;
+
+/// The text right before and after an edit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct Surroundings {
+    before: String,
+    after: String,
+}
+
+impl Surroundings {
+    /// The text around `char_range`, which is either the text to delete,
+    /// or an empty range where text is to be inserted.
+    fn around(buffer: &dyn TextBuffer, char_range: Range<usize>) -> Self {
+        let start = char_range.start.saturating_sub(SURROUNDING_CHARS);
+        Self {
+            before: buffer.slice(start..char_range.start).into_owned(),
+            after: buffer
+                .slice(char_range.end..char_range.end + SURROUNDING_CHARS)
+                .into_owned(),
+        }
+    }
+}
+
+/// A single change to the text of a [`crate::TextEdit`].
+///
+/// The `surroundings` is the text around the change, which is the same before and after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum TextOperation {
+    Insert {
+        char_index: usize,
+        text: String,
+        surroundings: Surroundings,
+    },
+    Delete {
+        char_index: usize,
+        text: String,
+        surroundings: Surroundings,
+    },
+}
+
+impl TextOperation {
+    /// Inserting `text` at `char_index`. Call this before inserting it.
+    pub fn insert(buffer: &dyn TextBuffer, char_index: usize, text: String) -> Self {
+        Self::Insert {
+            char_index,
+            text,
+            surroundings: Surroundings::around(buffer, char_index..char_index),
+        }
+    }
+
+    /// Deleting `char_range`. Call this before deleting it.
+    pub fn delete(buffer: &dyn TextBuffer, char_range: Range<usize>) -> Self {
+        Self::Delete {
+            char_index: char_range.start,
+            text: buffer.slice(char_range.clone()).into_owned(),
+            surroundings: Surroundings::around(buffer, char_range),
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        match self {
+            Self::Insert {
+                char_index,
+                text,
+                surroundings,
+            } => Self::Delete {
+                char_index: *char_index,
+                text: text.clone(),
+                surroundings: surroundings.clone(),
+            },
+            Self::Delete {
+                char_index,
+                text,
+                surroundings,
+            } => Self::Insert {
+                char_index: *char_index,
+                text: text.clone(),
+                surroundings: surroundings.clone(),
+            },
+        }
+    }
+
+    /// Apply the operation, if the text looks like we expect it to.
+    ///
+    /// Returns `false` if the text was changed by someone else, so the operation doesn't fit.
+    fn apply(&self, buffer: &mut dyn TextBuffer) -> bool {
+        match self {
+            Self::Insert {
+                char_index,
+                text,
+                surroundings,
+            } => {
+                if Surroundings::around(buffer, *char_index..*char_index) != *surroundings {
+                    return false;
+                }
+                buffer.insert_text(text, *char_index);
+                true
+            }
+            Self::Delete {
+                char_index,
+                text,
+                surroundings,
+            } => {
+                let char_range = *char_index..*char_index + text.chars().count();
+                if buffer.slice(char_range.clone()) != text.as_str()
+                    || Surroundings::around(buffer, char_range.clone()) != *surroundings
+                {
+                    return false;
+                }
+                buffer.delete_char_range(char_range);
+                true
+            }
+        }
+    }
+
+    /// Try to merge `next` into `self`, e.g. when typing one character at a time.
+    fn merge(&mut self, next: &Self) -> bool {
+        match (self, next) {
+            (
+                Self::Insert {
+                    char_index, text, ..
+                },
+                Self::Insert {
+                    char_index: next_index,
+                    text: next_text,
+                    ..
+                },
+            ) if *next_index == *char_index + text.chars().count() => {
+                // The text around the insertion point stays the same:
+                text.push_str(next_text);
+                true
+            }
+            (
+                Self::Delete {
+                    char_index,
+                    text,
+                    surroundings,
+                },
+                Self::Delete {
+                    char_index: next_index,
+                    text: next_text,
+                    surroundings: next_surroundings,
+                },
+            ) => {
+                if *next_index + next_text.chars().count() == *char_index {
+                    // Backspace
+                    text.insert_str(0, next_text);
+                    *char_index = *next_index;
+                } else if next_index == char_index {
+                    // Delete
+                    text.push_str(next_text);
+                } else {
+                    return false;
+                }
+                // Either way, the latest deletion has the text around both of them:
+                surroundings.clone_from(next_surroundings);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Edits that are undone and redone together.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct UndoStep {
+    cursor_before: CCursorRange,
+    cursor_after: CCursorRange,
+    operations: Vec<TextOperation>,
+}
+
+impl UndoStep {
+    fn push(&mut self, operation: TextOperation) {
+        if let Some(last) = self.operations.last_mut() {
+            if last.merge(&operation) {
+                return;
+            }
+        }
+        self.operations.push(operation);
+    }
+
+    /// Undo the step, or return `false` and leave the text as it was
+    /// if it was changed by someone else.
+    fn undo(&self, buffer: &mut dyn TextBuffer) -> bool {
+        let inverse = self.operations.iter().rev().map(TextOperation::inverse);
+        apply_all(buffer, inverse)
+    }
+
+    fn redo(&self, buffer: &mut dyn TextBuffer) -> bool {
+        apply_all(buffer, self.operations.iter().cloned())
+    }
+}
+
+/// Apply all operations, or none of them.
+fn apply_all(buffer: &mut dyn TextBuffer, operations: impl Iterator<Item = TextOperation>) -> bool {
+    let mut applied = vec![];
+    for operation in operations {
+        if operation.apply(buffer) {
+            applied.push(operation);
+        } else {
+            for operation in applied.iter().rev() {
+                operation.inverse().apply(buffer);
+            }
+            return false;
+        }
+    }
+    true
+}
+
+/// Edits that are still coming in quick succession.
+#[derive(Clone, Debug)]
+struct Flux {
+    start_time: f64,
+    latest_change_time: f64,
+    step: UndoStep,
+}
+
+/// Undo history of a [`crate::TextEdit`].
+///
+/// Unlike the general [`crate::util::undoer::Undoer`], this does not store a copy of the text
+/// for each undo point, but only the characters that were inserted and deleted.
+/// This keeps memory use and per-frame cost low for large texts.
+///
+/// Edits are grouped into undo steps by time, following the same [`Settings`] as
+/// [`crate::util::undoer::Undoer`]: a new step starts when the text has been stable for
+/// [`Settings::stable_time`] seconds, or when the current step is older than
+/// [`Settings::auto_save_interval`].
+///
+/// If the text is changed by something other than the [`crate::TextEdit`], the history is forgotten.
+/// Such changes are detected by the length of the text changing,
+/// or by the text around an edit no longer being what it was when undoing or redoing it.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TextEditUndoer {
+    settings: Settings,
+
+    /// New undo steps are added to the back.
+    undos: VecDeque<UndoStep>,
+
+    /// Steps that were undone, and can be redone.
+    /// Cleared when there is a new edit.
+    redos: Vec<UndoStep>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    flux: Option<Flux>,
+
+    /// The length of the text (in bytes) after the latest edit we know of.
+    ///
+    /// Used to detect changes made by someone else.
+    text_len: Option<usize>,
+}
+
+impl TextEditUndoer {
+    /// Create a new [`TextEditUndoer`] with the given [`Settings`].
+    pub fn with_settings(settings: Settings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Is there anything to undo?
+    pub fn has_undo(&self) -> bool {
+        self.flux.is_some() || !self.undos.is_empty()
+    }
+
+    /// Is there anything to redo?
+    pub fn has_redo(&self) -> bool {
+        !self.redos.is_empty()
+    }
+
+    /// Is the text currently being edited, i.e. are we waiting to finish the current undo step?
+    pub fn is_in_flux(&self) -> bool {
+        self.flux.is_some()
+    }
+
+    /// Forget the history if the text was changed by someone else since the latest edit.
+    ///
+    /// `text_len` is the current length of the text (in bytes).
+    pub(crate) fn check_text(&mut self, text_len: usize) {
+        if self.text_len.is_some_and(|len| len != text_len) {
+            *self = Self::with_settings(self.settings.clone());
+        }
+    }
+
+    /// Record the operations of one edit.
+    ///
+    /// `text_len` is the length of the text (in bytes) after the edit.
+    ///
+    /// They are added to the current undo step if it is recent enough,
+    /// otherwise a new undo step is started.
+    pub(crate) fn add_edit(
+        &mut self,
+        current_time: f64,
+        text_len: usize,
+        cursor_before: CCursorRange,
+        operations: Vec<TextOperation>,
+        cursor_after: CCursorRange,
+    ) {
+        self.text_len = Some(text_len);
+        if operations.is_empty() {
+            return;
+        }
+
+        self.redos.clear();
+
+        if let Some(flux) = &mut self.flux {
+            let time_since_latest_change = (current_time - flux.latest_change_time) as f32;
+            let time_since_flux_start = (current_time - flux.start_time) as f32;
+            if time_since_latest_change < self.settings.stable_time
+                && time_since_flux_start < self.settings.auto_save_interval
+            {
+                flux.latest_change_time = current_time;
+                flux.step.cursor_after = cursor_after;
+                for operation in operations {
+                    flux.step.push(operation);
+                }
+                return;
+            }
+        }
+
+        self.add_undo_point();
+
+        let mut step = UndoStep {
+            cursor_before,
+            cursor_after,
+            operations: Vec::with_capacity(operations.len()),
+        };
+        for operation in operations {
+            step.push(operation);
+        }
+        self.flux = Some(Flux {
+            start_time: current_time,
+            latest_change_time: current_time,
+            step,
+        });
+    }
+
+    /// Finish the current undo step, so that the next edit starts a new one.
+    pub fn add_undo_point(&mut self) {
+        if let Some(flux) = self.flux.take() {
+            self.undos.push_back(flux.step);
+        }
+        while self.undos.len() > self.settings.max_undos {
+            self.undos.pop_front();
+        }
+    }
+
+    /// Undo the latest step, returning the cursor range from before it.
+    ///
+    /// Returns `None` if there is nothing to undo,
+    /// or if the text was changed by someone else so that the history no longer fits.
+    pub fn undo(&mut self, text: &mut dyn TextBuffer) -> Option<CCursorRange> {
+        self.check_text(text.len_bytes());
+        self.add_undo_point();
+        let step = self.undos.pop_back()?;
+        if step.undo(text) {
+            let cursor = step.cursor_before;
+            self.redos.push(step);
+            self.text_len = Some(text.len_bytes());
+            Some(cursor)
+        } else {
+            *self = Self::with_settings(self.settings.clone());
+            None
+        }
+    }
+
+    /// Redo the latest undone step, returning the cursor range from after it.
+    ///
+    /// Returns `None` if there is nothing to redo,
+    /// or if the text was changed by someone else so that the history no longer fits.
+    pub fn redo(&mut self, text: &mut dyn TextBuffer) -> Option<CCursorRange> {
+        self.check_text(text.len_bytes());
+        let step = self.redos.pop()?;
+        if step.redo(text) {
+            let cursor = step.cursor_after;
+            self.undos.push_back(step);
+            self.text_len = Some(text.len_bytes());
+            Some(cursor)
+        } else {
+            *self = Self::with_settings(self.settings.clone());
+            None
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Wraps a [`TextBuffer`] and records the changes made to it.
+pub(crate) struct RecordingTextBuffer<'a> {
+    buffer: &'a mut dyn TextBuffer,
+    operations: Vec<TextOperation>,
+}
+
+impl<'a> RecordingTextBuffer<'a> {
+    pub fn new(buffer: &'a mut dyn TextBuffer) -> Self {
+        Self {
+            buffer,
+            operations: vec![],
+        }
+    }
+
+    pub fn into_operations(self) -> Vec<TextOperation> {
+        self.operations
+    }
+
+    /// Clamp `char_range` to the text.
+    fn clamp(&self, char_range: Range<usize>) -> Range<usize> {
+        let num_chars = self.buffer.len_chars();
+        char_range.start.min(num_chars)..char_range.end.min(num_chars)
+    }
+}
+
+impl TextBuffer for RecordingTextBuffer<'_> {
+    fn is_mutable(&self) -> bool {
+        self.buffer.is_mutable()
+    }
+
+    fn as_str(&self) -> &str {
+        self.buffer.as_str()
+    }
+
+    fn char_range(&self, char_range: Range<usize>) -> &str {
+        self.buffer.char_range(char_range)
+    }
+
+    fn len_bytes(&self) -> usize {
+        self.buffer.len_bytes()
+    }
+
+    fn len_chars(&self) -> usize {
+        self.buffer.len_chars()
+    }
+
+    fn slice(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        self.buffer.slice(char_range)
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        let char_index = self.clamp(char_index..char_index).start;
+        let surroundings = Surroundings::around(self.buffer, char_index..char_index);
+        let num_inserted = self.buffer.insert_text(text, char_index);
+        if 0 < num_inserted {
+            self.operations.push(TextOperation::Insert {
+                char_index,
+                text: text.chars().take(num_inserted).collect(),
+                surroundings,
+            });
+        }
+        num_inserted
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        let char_range = self.clamp(char_range);
+        if char_range.is_empty() {
+            return;
+        }
+        self.operations
+            .push(TextOperation::delete(self.buffer, char_range.clone()));
+        self.buffer.delete_char_range(char_range);
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<RecordingTextBuffer<'static>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epaint::text::cursor::CCursor;
+
+    use super::*;
+
+    fn cursor(index: usize) -> CCursorRange {
+        CCursorRange::one(CCursor::new(index))
+    }
+
+    /// Apply an edit to `text` and record it in `undoer`.
+    fn edit(
+        undoer: &mut TextEditUndoer,
+        time: f64,
+        text: &mut String,
+        edit: impl FnOnce(&mut dyn TextBuffer),
+    ) {
+        let mut recorder = RecordingTextBuffer::new(text);
+        edit(&mut recorder);
+        let operations = recorder.into_operations();
+        undoer.add_edit(time, text.len(), cursor(0), operations, cursor(0));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut undoer = TextEditUndoer::default();
+        let mut text = String::from("hello world");
+
+        // Typing quickly is one step:
+        edit(&mut undoer, 0.0, &mut text, |t| {
+            t.insert_text("!", 11);
+        });
+        edit(&mut undoer, 0.1, &mut text, |t| {
+            t.insert_text("?", 12);
+        });
+        assert_eq!(text, "hello world!?");
+
+        // After a pause, a new step starts:
+        edit(&mut undoer, 2.0, &mut text, |t| {
+            t.delete_char_range(0..6);
+            t.insert_text("å", 0);
+        });
+        assert_eq!(text, "åworld!?");
+
+        assert!(undoer.undo(&mut text).is_some());
+        assert_eq!(text, "hello world!?");
+        assert!(undoer.undo(&mut text).is_some());
+        assert_eq!(text, "hello world");
+        assert!(undoer.undo(&mut text).is_none());
+
+        assert!(undoer.redo(&mut text).is_some());
+        assert_eq!(text, "hello world!?");
+        assert!(undoer.redo(&mut text).is_some());
+        assert_eq!(text, "åworld!?");
+        assert!(!undoer.has_redo());
+    }
+
+    #[test]
+    fn test_merge_operations() {
+        let mut undoer = TextEditUndoer::default();
+        let mut text = String::from("abc");
+        for (i, c) in ["x", "y", "z"].into_iter().enumerate() {
+            edit(&mut undoer, 0.0, &mut text, |t| {
+                t.insert_text(c, 3 + i);
+            });
+        }
+        for i in 0..2 {
+            edit(&mut undoer, 0.0, &mut text, |t| {
+                t.delete_char_range(5 - i..6 - i);
+            });
+        }
+        assert_eq!(text, "abcx");
+
+        let operations = &undoer.flux.as_ref().unwrap().step.operations;
+        assert_eq!(
+            operations,
+            &vec![
+                TextOperation::Insert {
+                    char_index: 3,
+                    text: "xyz".to_owned(),
+                    surroundings: Surroundings {
+                        before: "abc".to_owned(),
+                        after: String::new(),
+                    },
+                },
+                TextOperation::Delete {
+                    char_index: 4,
+                    text: "yz".to_owned(),
+                    surroundings: Surroundings {
+                        before: "abcx".to_owned(),
+                        after: String::new(),
+                    },
+                },
+            ]
+        );
+
+        assert!(undoer.undo(&mut text).is_some());
+        assert_eq!(text, "abc");
+    }
+
+    #[test]
+    fn test_external_change_forgets_history() {
+        let mut undoer = TextEditUndoer::default();
+        let mut text = String::from("abc");
+        edit(&mut undoer, 0.0, &mut text, |t| {
+            t.insert_text("d", 3);
+        });
+
+        text = "something else".to_owned();
+        assert!(undoer.undo(&mut text).is_none());
+        assert_eq!(text, "something else");
+        assert!(!undoer.has_undo());
+    }
+
+    #[test]
+    fn test_same_length_change_forgets_history() {
+        let mut undoer = TextEditUndoer::default();
+        let mut text = String::from("hello world");
+        edit(&mut undoer, 0.0, &mut text, |t| {
+            t.delete_char_range(6..11);
+        });
+        assert_eq!(text, "hello ");
+
+        // The length is the same, but the text around the edit is not:
+        text = "HELLO ".to_owned();
+        assert!(undoer.undo(&mut text).is_none());
+        assert_eq!(text, "HELLO ");
+        assert!(!undoer.has_undo());
+    }
+}
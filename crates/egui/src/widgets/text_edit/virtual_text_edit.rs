@@ -0,0 +1,786 @@
+use std::{ops::Range, sync::Arc};
+
+use emath::GuiRounding as _;
+use epaint::text::{Galley, LayoutJob, cursor::CCursor};
+
+use crate::{
+    Align2, Color32, Context, CursorIcon, Event, EventFilter, FontId, FontSelection, Id, Key,
+    Modifiers, NumExt as _, Rect, Response, ScrollArea, Sense, TextStyle, Ui, Vec2, Widget,
+    WidgetInfo, WidgetType,
+    mutex::Mutex,
+    pos2,
+    text_selection::{
+        CCursorRange,
+        text_cursor_state::{ccursor_next_word, ccursor_previous_word, select_word_at},
+        visuals::paint_text_cursor,
+    },
+    vec2,
+};
+
+use super::{Rope, TextEditUndoer, undoer::TextOperation};
+
+/// A text editor for very large texts, e.g. a log viewer with hundreds of thousands of lines.
+///
+/// A [`super::TextEdit`] lays out all of its text every frame, which gets slow for large texts.
+/// A [`VirtualTextEdit`] stores its text in a [`Rope`], and only lays out the lines
+/// that are visible in its [`ScrollArea`], one [`Galley`] per line.
+/// These are cached by egui, so lines that don't change are not laid out again.
+///
+/// Lines are never wrapped, and the editor always fills the available space.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::text_edit::{Rope, VirtualTextEdit};
+///
+/// let mut log = Rope::from("first line\nsecond line");
+/// log.push_str("\nthird line");
+/// ui.add(VirtualTextEdit::new(&mut log).read_only(true).stick_to_bottom(true));
+/// # });
+/// ```
+#[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
+pub struct VirtualTextEdit<'t> {
+    text: &'t mut Rope,
+    id_salt: Option<Id>,
+    font: FontSelection,
+    text_color: Option<Color32>,
+    read_only: bool,
+    line_numbers: bool,
+    stick_to_bottom: bool,
+}
+
+impl<'t> VirtualTextEdit<'t> {
+    /// Monospace font, no line numbers.
+    pub fn new(text: &'t mut Rope) -> Self {
+        Self {
+            text,
+            id_salt: None,
+            font: TextStyle::Monospace.into(),
+            text_color: None,
+            read_only: false,
+            line_numbers: false,
+            stick_to_bottom: false,
+        }
+    }
+
+    /// A source for the unique [`Id`], e.g. `.id_salt("log")` or `.id_salt(loop_index)`.
+    #[inline]
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = Some(Id::new(id_salt));
+        self
+    }
+
+    /// Pick a [`crate::FontId`] or [`TextStyle`].
+    #[inline]
+    pub fn font(mut self, font_selection: impl Into<FontSelection>) -> Self {
+        self.font = font_selection.into();
+        self
+    }
+
+    #[inline]
+    pub fn text_color(mut self, text_color: Color32) -> Self {
+        self.text_color = Some(text_color);
+        self
+    }
+
+    /// If `true`, the text can't be edited, but can still be selected and copied.
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Show line numbers to the left of the text.
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Keep scrolling to the bottom when text is added there, e.g. for a log.
+    ///
+    /// See [`ScrollArea::stick_to_bottom`]. Default is `false`.
+    #[inline]
+    pub fn stick_to_bottom(mut self, stick_to_bottom: bool) -> Self {
+        self.stick_to_bottom = stick_to_bottom;
+        self
+    }
+}
+
+impl Widget for VirtualTextEdit<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        self.show(ui).response
+    }
+}
+
+/// The output from a [`VirtualTextEdit`].
+pub struct VirtualTextEditOutput {
+    /// The interaction response.
+    pub response: Response,
+
+    /// Where the text cursor is.
+    pub cursor_range: Option<CCursorRange>,
+}
+
+/// What a [`VirtualTextEdit`] remembers between frames.
+#[derive(Clone, Default)]
+struct VirtualTextEditState {
+    cursor: Option<CCursorRange>,
+    undoer: Arc<Mutex<TextEditUndoer>>,
+
+    /// The widest line we have laid out so far, which decides how far we can scroll sideways.
+    max_line_width: f32,
+
+    /// When did the user last press a key or click on the editor.
+    /// Used to pause the cursor animation when typing.
+    last_interaction_time: f64,
+}
+
+impl VirtualTextEdit<'_> {
+    /// Show the editor, and return the response and where the cursor is.
+    pub fn show(self, ui: &mut Ui) -> VirtualTextEditOutput {
+        let Self {
+            text,
+            id_salt,
+            font,
+            text_color,
+            read_only,
+            line_numbers,
+            stick_to_bottom,
+        } = self;
+
+        let id = id_salt.map_or_else(
+            || ui.next_auto_id(),
+            |id_salt| ui.make_persistent_id(id_salt),
+        );
+        ui.skip_ahead_auto_ids(1);
+
+        let lines = Lines {
+            ctx: ui.ctx().clone(),
+            font_id: font.resolve(ui.style()),
+        };
+        let row_height = lines.row_height();
+        let text_color = text_color.unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+
+        let mut state: VirtualTextEditState = ui.data_mut(|d| d.get_temp(id)).unwrap_or_default();
+
+        // The text may have been changed by someone else:
+        state.cursor = state.cursor.map(|mut range| {
+            range.primary.index = range.primary.index.min(text.len_chars());
+            range.secondary.index = range.secondary.index.min(text.len_chars());
+            range
+        });
+
+        let gutter_width = if line_numbers {
+            let num_digits = text.len_lines().to_string().len().at_least(2);
+            let digit_width = ui.fonts(|f| f.glyph_width(&lines.font_id, '0'));
+            num_digits as f32 * digit_width + ui.spacing().item_spacing.x
+        } else {
+            0.0
+        };
+
+        let scroll_output = ScrollArea::both()
+            .id_salt(id.with("scroll_area"))
+            .auto_shrink(false)
+            .stick_to_bottom(stick_to_bottom)
+            // Jumping through a long text is too far to animate:
+            .animated(false)
+            .show_viewport(ui, |ui, viewport| {
+                let content_size = vec2(
+                    gutter_width + state.max_line_width + row_height,
+                    text.len_lines() as f32 * row_height,
+                )
+                .max(ui.available_size());
+                let rect = ui.allocate_space(content_size).1;
+                let mut response = ui.interact(rect, id, Sense::click_and_drag());
+                let text_origin = rect.min + vec2(gutter_width, 0.0);
+
+                if response.hovered() {
+                    ui.ctx().set_cursor_icon(CursorIcon::Text);
+                    if !read_only {
+                        ui.output_mut(|o| o.mutable_text_under_cursor = true);
+                    }
+                }
+
+                let cursor_before = state.cursor;
+
+                if let Some(pointer_pos) = response.interact_pointer_pos() {
+                    let cursor_at_pointer = lines.index_at_pos(text, pointer_pos - text_origin);
+                    if pointer_interaction(ui, &response, text, &mut state, cursor_at_pointer) {
+                        ui.memory_mut(|mem| mem.request_focus(id));
+                    }
+                }
+
+                let has_focus = ui.memory(|mem| mem.has_focus(id));
+                if has_focus {
+                    let event_filter = EventFilter {
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        ..Default::default()
+                    };
+                    ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+
+                    let page_lines =
+                        (viewport.height() / row_height).floor().at_least(1.0) as usize;
+                    let events = ui.input(|i| i.filtered_events(&event_filter));
+                    let changed =
+                        on_events(ui, &lines, text, &mut state, &events, read_only, page_lines);
+                    if changed {
+                        response.mark_changed();
+                    }
+                }
+
+                let cursor_rect = state.cursor.map(|range| {
+                    let (line, column) = line_column(text, range.primary.index);
+                    let x = lines.x_of(text, line, column);
+                    Rect::from_min_size(
+                        text_origin + vec2(x, line as f32 * row_height),
+                        vec2(0.0, row_height),
+                    )
+                    .expand(1.5)
+                });
+
+                if state.cursor != cursor_before {
+                    state.last_interaction_time = ui.input(|i| i.time);
+                    if let Some(cursor_rect) = cursor_rect {
+                        ui.scroll_to_rect(cursor_rect, None);
+                    }
+                }
+
+                // Only lay out and paint the visible lines:
+                let painter = ui.painter();
+                let first_line = (viewport.min.y / row_height).floor().at_least(0.0) as usize;
+                let end_line = ((viewport.max.y / row_height).ceil().at_least(0.0) as usize)
+                    .min(text.len_lines());
+                let selection = state
+                    .cursor
+                    .filter(|range| has_focus && !range.is_empty())
+                    .map(|range| range.as_sorted_char_range());
+                let current_line = state
+                    .cursor
+                    .filter(|_| has_focus)
+                    .map(|range| text.char_to_line(range.primary.index));
+
+                let mut line_start = text.line_to_char(first_line);
+                for line in first_line..end_line {
+                    let galley = lines.galley(text, line);
+                    let line_len = galley.end().index;
+                    state.max_line_width = state.max_line_width.max(galley.size().x);
+
+                    let pos = text_origin + vec2(0.0, line as f32 * row_height);
+
+                    if let Some(selection) = &selection {
+                        let line_range = line_start..line_start + line_len;
+                        if selection.start <= line_range.end && line_range.start < selection.end {
+                            let start = selection.start.max(line_range.start) - line_start;
+                            let end = selection.end.min(line_range.end) - line_start;
+                            let mut x_range = galley.pos_from_cursor(CCursor::new(start)).min.x
+                                ..galley.pos_from_cursor(CCursor::new(end)).min.x;
+                            if line_range.end < selection.end {
+                                // Show that the newline is selected too:
+                                x_range.end += row_height / 2.0;
+                            }
+                            painter.rect_filled(
+                                // Rounded, so that there are no seams between the lines:
+                                Rect::from_x_y_ranges(
+                                    pos.x + x_range.start..=pos.x + x_range.end,
+                                    pos.y..=pos.y + row_height,
+                                )
+                                .round_to_pixels(ui.pixels_per_point()),
+                                0.0,
+                                ui.visuals().selection.bg_fill,
+                            );
+                        }
+                    }
+
+                    painter.galley(pos, galley, text_color);
+
+                    if line_numbers {
+                        let color = if current_line == Some(line) {
+                            ui.visuals().strong_text_color()
+                        } else {
+                            ui.visuals().weak_text_color()
+                        };
+                        painter.text(
+                            pos2(text_origin.x - ui.spacing().item_spacing.x, pos.y),
+                            Align2::RIGHT_TOP,
+                            (line + 1).to_string(),
+                            lines.font_id.clone(),
+                            color,
+                        );
+                    }
+
+                    line_start += line_len + 1;
+                }
+
+                if has_focus && !read_only && ui.input(|i| i.focused) {
+                    if let Some(cursor_rect) = cursor_rect {
+                        let now = ui.input(|i| i.time);
+                        paint_text_cursor(
+                            ui,
+                            painter,
+                            cursor_rect,
+                            now - state.last_interaction_time,
+                        );
+                    }
+                }
+
+                response
+            });
+
+        let response = scroll_output.inner;
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::TextEdit, ui.is_enabled(), ""));
+
+        let cursor_range = state.cursor;
+        ui.data_mut(|d| d.insert_temp(id, state));
+
+        VirtualTextEditOutput {
+            response,
+            cursor_range,
+        }
+    }
+}
+
+/// Handle clicking and dragging.
+///
+/// Returns `true` if there was interaction.
+fn pointer_interaction(
+    ui: &Ui,
+    response: &Response,
+    text: &Rope,
+    state: &mut VirtualTextEditState,
+    cursor_at_pointer: usize,
+) -> bool {
+    let line = text.char_to_line(cursor_at_pointer);
+    let line_start = text.line_to_char(line);
+
+    if response.double_clicked() {
+        // Select word:
+        let word = select_word_at(
+            &text.line(line),
+            CCursor::new(cursor_at_pointer - line_start),
+        );
+        state.cursor = Some(CCursorRange::two(
+            CCursor::new(line_start + word.secondary.index),
+            CCursor::new(line_start + word.primary.index),
+        ));
+        true
+    } else if response.triple_clicked() {
+        // Select line, including the newline:
+        let line_end = text.line_to_char(line + 1);
+        state.cursor = Some(CCursorRange::two(
+            CCursor::new(line_start),
+            CCursor::new(line_end),
+        ));
+        true
+    } else if response.hovered() && ui.input(|i| i.pointer.any_pressed()) {
+        // The start of a drag (or a click).
+        let cursor_at_pointer = CCursor::new(cursor_at_pointer);
+        state.cursor = Some(match state.cursor {
+            Some(mut range) if ui.input(|i| i.modifiers.shift) => {
+                range.primary = cursor_at_pointer;
+                range.h_pos = None;
+                range
+            }
+            _ => CCursorRange::one(cursor_at_pointer),
+        });
+        true
+    } else if response.dragged() {
+        // Drag to select text:
+        if let Some(range) = &mut state.cursor {
+            range.primary = CCursor::new(cursor_at_pointer);
+            range.h_pos = None;
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Handle keyboard and clipboard events.
+///
+/// Returns `true` if the text changed.
+fn on_events(
+    ui: &Ui,
+    lines: &Lines,
+    text: &mut Rope,
+    state: &mut VirtualTextEditState,
+    events: &[Event],
+    read_only: bool,
+    page_lines: usize,
+) -> bool {
+    let mut any_change = false;
+    let mut cursor = state.cursor.unwrap_or_default();
+
+    for event in events {
+        let cursor_before = cursor;
+        let mut operations = vec![];
+        let mut is_undo_or_redo = false;
+
+        match event {
+            Event::Copy | Event::Cut => {
+                let selected = text.slice(cursor.as_sorted_char_range());
+                if !selected.is_empty() {
+                    ui.ctx().copy_text(selected.into_owned());
+                    if matches!(event, Event::Cut) && !read_only {
+                        replace_selection(text, &mut cursor, "", &mut operations);
+                    }
+                }
+            }
+
+            Event::Paste(text_to_insert) if !read_only => {
+                replace_selection(text, &mut cursor, text_to_insert, &mut operations);
+            }
+
+            // Newlines are handled by `Key::Enter`.
+            Event::Text(text_to_insert)
+                if !read_only && text_to_insert != "\n" && text_to_insert != "\r" =>
+            {
+                replace_selection(text, &mut cursor, text_to_insert, &mut operations);
+            }
+
+            Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } => {
+                let is_redo = (modifiers.matches_logically(Modifiers::COMMAND) && *key == Key::Y)
+                    || (modifiers.matches_logically(Modifiers::SHIFT | Modifiers::COMMAND)
+                        && *key == Key::Z);
+                let is_undo = modifiers.matches_logically(Modifiers::COMMAND) && *key == Key::Z;
+
+                if is_redo || is_undo {
+                    if !read_only {
+                        is_undo_or_redo = true;
+                        let mut undoer = state.undoer.lock();
+                        let new_cursor = if is_redo {
+                            undoer.redo(text)
+                        } else {
+                            undoer.undo(text)
+                        };
+                        if let Some(new_cursor) = new_cursor {
+                            cursor = new_cursor;
+                            any_change = true;
+                        }
+                    }
+                } else if *key == Key::A && modifiers.command {
+                    cursor = CCursorRange::two(CCursor::new(0), CCursor::new(text.len_chars()));
+                } else if *key == Key::Enter {
+                    if !read_only {
+                        replace_selection(text, &mut cursor, "\n", &mut operations);
+                    }
+                } else if matches!(key, Key::Backspace | Key::Delete) {
+                    if !read_only {
+                        let range = if cursor.is_empty() {
+                            deletion_range(text, cursor.primary.index, *key, modifiers)
+                        } else {
+                            cursor.as_sorted_char_range()
+                        };
+                        cursor = CCursorRange::one(CCursor::new(range.start));
+                        delete(text, range, &mut operations);
+                    }
+                } else {
+                    lines.move_cursor(text, &mut cursor, *key, modifiers, page_lines);
+                }
+            }
+
+            _ => {}
+        }
+
+        any_change |= !operations.is_empty();
+        if !is_undo_or_redo {
+            state.undoer.lock().add_edit(
+                ui.input(|i| i.time),
+                text.len(),
+                cursor_before,
+                operations,
+                cursor,
+            );
+        }
+    }
+
+    if state.cursor.is_some() || cursor != CCursorRange::default() {
+        state.cursor = Some(cursor);
+    }
+    any_change
+}
+
+fn delete(text: &mut Rope, char_range: Range<usize>, operations: &mut Vec<TextOperation>) {
+    if !char_range.is_empty() {
+        operations.push(TextOperation::delete(text, char_range.clone()));
+        text.remove(char_range);
+    }
+}
+
+fn replace_selection(
+    text: &mut Rope,
+    cursor: &mut CCursorRange,
+    text_to_insert: &str,
+    operations: &mut Vec<TextOperation>,
+) {
+    let range = cursor.as_sorted_char_range();
+    delete(text, range.clone(), operations);
+    let operation = TextOperation::insert(text, range.start, text_to_insert.to_owned());
+    let num_chars = text.insert(range.start, text_to_insert);
+    if 0 < num_chars {
+        operations.push(operation);
+    }
+    *cursor = CCursorRange::one(CCursor::new(range.start + num_chars));
+}
+
+/// The line and column (in chars) of the given char index.
+fn line_column(text: &Rope, char_index: usize) -> (usize, usize) {
+    let line = text.char_to_line(char_index);
+    (line, char_index - text.line_to_char(line))
+}
+
+/// What backspace or delete removes when nothing is selected.
+fn deletion_range(text: &Rope, char_index: usize, key: Key, modifiers: &Modifiers) -> Range<usize> {
+    let (line, column) = line_column(text, char_index);
+    let line_start = char_index - column;
+    let line_text = text.line(line);
+    let line_len = line_text.chars().count();
+
+    if key == Key::Backspace {
+        let start = if column == 0 {
+            char_index.saturating_sub(1)
+        } else if modifiers.alt || modifiers.ctrl {
+            line_start + ccursor_previous_word(&line_text, CCursor::new(column)).index
+        } else if modifiers.mac_cmd {
+            line_start
+        } else {
+            char_index - 1
+        };
+        start..char_index
+    } else {
+        let end = if column == line_len {
+            (char_index + 1).min(text.len_chars())
+        } else if modifiers.alt || modifiers.ctrl {
+            line_start + ccursor_next_word(&line_text, CCursor::new(column)).index
+        } else if modifiers.mac_cmd {
+            line_start + line_len
+        } else {
+            char_index + 1
+        };
+        char_index..end
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Lays out single lines of a [`Rope`], and maps between char indices and positions.
+///
+/// Only the lines that are asked for are laid out,
+/// so the cursor can move through lines that have never been shown.
+struct Lines {
+    ctx: Context,
+    font_id: FontId,
+}
+
+impl Lines {
+    fn row_height(&self) -> f32 {
+        self.ctx.fonts(|f| f.row_height(&self.font_id))
+    }
+
+    fn galley(&self, text: &Rope, line: usize) -> Arc<Galley> {
+        let job = LayoutJob::simple_singleline(
+            text.line(line).into_owned(),
+            self.font_id.clone(),
+            Color32::PLACEHOLDER,
+        );
+        self.ctx.fonts(|f| f.layout_job(job))
+    }
+
+    /// The horizontal position of the given column in a line.
+    fn x_of(&self, text: &Rope, line: usize, column: usize) -> f32 {
+        self.galley(text, line)
+            .pos_from_cursor(CCursor::new(column))
+            .min
+            .x
+    }
+
+    /// The char index closest to the horizontal position `x` in a line.
+    fn index_at(&self, text: &Rope, line: usize, x: f32) -> usize {
+        text.line_to_char(line) + self.galley(text, line).cursor_from_pos(vec2(x, 0.0)).index
+    }
+
+    /// The char index closest to a position relative to the top left of the text.
+    fn index_at_pos(&self, text: &Rope, pos: Vec2) -> usize {
+        let line = (pos.y / self.row_height()).floor().at_least(0.0) as usize;
+        let line = line.min(text.len_lines() - 1);
+        self.index_at(text, line, pos.x)
+    }
+
+    /// Move the cursor with the arrow keys, home/end, or page up/down.
+    fn move_cursor(
+        &self,
+        text: &Rope,
+        cursor: &mut CCursorRange,
+        key: Key,
+        modifiers: &Modifiers,
+        page_lines: usize,
+    ) {
+        let index = cursor.primary.index;
+        let (line, column) = line_column(text, index);
+        let line_start = index - column;
+        let line_end = || line_start + text.line(line).chars().count();
+        let last_line = text.len_lines() - 1;
+
+        // Keep the horizontal position when moving up and down through lines of different lengths:
+        let mut h_pos = None;
+        let mut vertical = |target_line: usize| {
+            let x = cursor
+                .h_pos
+                .unwrap_or_else(|| self.x_of(text, line, column));
+            h_pos = Some(x);
+            self.index_at(text, target_line, x)
+        };
+
+        let new_index = match key {
+            Key::ArrowLeft | Key::ArrowRight if modifiers.is_none() && !cursor.is_empty() => {
+                // Collapse the selection:
+                let range = cursor.as_sorted_char_range();
+                let index = if key == Key::ArrowLeft {
+                    range.start
+                } else {
+                    range.end
+                };
+                *cursor = CCursorRange::one(CCursor::new(index));
+                return;
+            }
+            Key::ArrowLeft => {
+                if column == 0 {
+                    index.saturating_sub(1)
+                } else if modifiers.alt || modifiers.ctrl {
+                    // alt on mac, ctrl on windows
+                    line_start + ccursor_previous_word(&text.line(line), CCursor::new(column)).index
+                } else if modifiers.mac_cmd {
+                    line_start
+                } else {
+                    index - 1
+                }
+            }
+            Key::ArrowRight => {
+                if index == line_end() {
+                    (index + 1).min(text.len_chars())
+                } else if modifiers.alt || modifiers.ctrl {
+                    // alt on mac, ctrl on windows
+                    line_start + ccursor_next_word(&text.line(line), CCursor::new(column)).index
+                } else if modifiers.mac_cmd {
+                    line_end()
+                } else {
+                    index + 1
+                }
+            }
+            Key::ArrowUp if modifiers.command => 0,
+            Key::ArrowDown if modifiers.command => text.len_chars(),
+            Key::ArrowUp => {
+                if line == 0 {
+                    0
+                } else {
+                    vertical(line - 1)
+                }
+            }
+            Key::ArrowDown => {
+                if line == last_line {
+                    text.len_chars()
+                } else {
+                    vertical(line + 1)
+                }
+            }
+            Key::PageUp => vertical(line.saturating_sub(page_lines)),
+            Key::PageDown => vertical((line + page_lines).min(last_line)),
+            Key::Home if modifiers.ctrl => 0,
+            Key::End if modifiers.ctrl => text.len_chars(),
+            Key::Home => line_start,
+            Key::End => line_end(),
+            _ => return,
+        };
+
+        cursor.primary = CCursor::new(new_index);
+        cursor.h_pos = h_pos;
+        if !modifiers.shift {
+            cursor.secondary = cursor.primary;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CentralPanel, RawInput};
+
+    fn key(key: Key, modifiers: Modifiers) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn test_edit_lines_that_were_never_shown() {
+        let ctx = Context::default();
+        let mut text = Rope::new();
+        for i in 0..100_000 {
+            text.push_str(&format!("line {i}\n"));
+        }
+        let editor_id = std::cell::Cell::new(Id::NULL);
+        let mut time = 0.0;
+
+        let mut run = |events: Vec<Event>, text: &mut Rope| {
+            time += 1.0;
+            let input = RawInput {
+                events,
+                time: Some(time),
+                screen_rect: Some(Rect::from_min_size(pos2(0.0, 0.0), vec2(800.0, 600.0))),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = VirtualTextEdit::new(text).id_salt("log").show(ui).response;
+                    editor_id.set(response.id);
+                });
+            });
+        };
+
+        run(vec![], &mut text);
+        ctx.memory_mut(|mem| mem.request_focus(editor_id.get()));
+
+        let down = key(Key::ArrowDown, Modifiers::NONE);
+        run(
+            vec![
+                down.clone(),
+                down.clone(),
+                down,
+                Event::Text("x".to_owned()),
+            ],
+            &mut text,
+        );
+        assert_eq!(text.line(3), "xline 3");
+
+        // Jump to the end, far outside of the visible lines:
+        run(
+            vec![
+                key(Key::ArrowDown, Modifiers::COMMAND),
+                key(Key::ArrowUp, Modifiers::NONE),
+                key(Key::End, Modifiers::NONE),
+                Event::Text("!".to_owned()),
+            ],
+            &mut text,
+        );
+        assert_eq!(text.line(99_999), "line 99999!");
+
+        run(vec![key(Key::Z, Modifiers::COMMAND)], &mut text);
+        assert_eq!(text.line(99_999), "line 99999");
+        run(vec![key(Key::Z, Modifiers::COMMAND)], &mut text);
+        assert_eq!(text.line(3), "line 3");
+        assert_eq!(text.len_lines(), 100_001);
+    }
+}
@@ -93,6 +93,8 @@ impl crate::View for CodeEditor {
                 egui::TextEdit::multiline(code)
                     .font(egui::TextStyle::Monospace) // for cursor height
                     .code_editor()
+                    .multi_cursor(true)
+                    .find_replace(true)
                     .desired_rows(10)
                     .lock_focus(true)
                     .desired_width(f32::INFINITY)
@@ -58,7 +58,7 @@ impl crate::View for FrameDemo {
                     .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
                     .corner_radius(ui.visuals().widgets.noninteractive.corner_radius)
                     .show(ui, |ui| {
-                        self.frame.show(ui, |ui| {
+                        self.frame.clone().show(ui, |ui| {
                             ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
                             ui.label(egui::RichText::new("Content").color(egui::Color32::WHITE));
                         });
@@ -291,6 +291,7 @@ fn rect_shape_ui(ui: &mut egui::Ui, shape: &mut RectShape) {
         fill,
         stroke,
         stroke_kind,
+        stroke_dash: _,
         blur_width,
         round_to_pixels,
         brush: _,
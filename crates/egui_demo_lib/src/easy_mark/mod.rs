@@ -1,4 +1,6 @@
 //! Experimental markup language
+//!
+//! To show real Markdown (`CommonMark`), use [`egui_extras::markdown::Markdown`] instead.
 
 mod easy_mark_editor;
 mod easy_mark_highlighter;
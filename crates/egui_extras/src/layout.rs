@@ -35,6 +35,9 @@ pub(crate) struct StripLayoutFlags {
     pub(crate) selected: bool,
     pub(crate) overline: bool,
 
+    /// Sense clicks on the whole cell, e.g. for sortable headers.
+    pub(crate) clickable: bool,
+
     /// Used when we want to accruately measure the size of this cell.
     pub(crate) sizing_pass: bool,
 }
@@ -209,7 +212,11 @@ impl<'l> StripLayout<'l> {
             .ui_stack_info(egui::UiStackInfo::new(egui::UiKind::TableCell))
             .max_rect(max_rect)
             .layout(self.cell_layout)
-            .sense(self.sense);
+            .sense(if flags.clickable {
+                self.sense.union(Sense::click())
+            } else {
+                self.sense
+            });
         if flags.sizing_pass {
             ui_builder = ui_builder.sizing_pass();
         }
@@ -228,6 +235,11 @@ impl<'l> StripLayout<'l> {
             }
         }
 
+        if flags.clickable {
+            // Selectable labels would swallow the clicks:
+            child_ui.style_mut().interaction.selectable_labels = false;
+        }
+
         if flags.selected {
             let stroke_color = child_ui.style().visuals.selection.stroke.color;
             child_ui.style_mut().visuals.override_text_color = Some(stroke_color);
@@ -18,9 +18,13 @@ pub mod syntax_highlighting;
 pub mod image;
 mod layout;
 mod loaders;
+pub mod markdown;
 mod sizing;
 mod strip;
 mod table;
+#[cfg(feature = "theme")]
+mod theme;
+mod tree_view;
 
 #[cfg(feature = "chrono")]
 pub use crate::datepicker::DatePickerButton;
@@ -29,6 +33,11 @@ pub(crate) use crate::layout::StripLayout;
 pub use crate::sizing::Size;
 pub use crate::strip::*;
 pub use crate::table::*;
+#[cfg(feature = "theme")]
+pub use crate::theme::{ThemeError, ThemeFile, ThemeFormat, parse_theme};
+pub use crate::tree_view::{
+    DropPosition, TreeView, TreeViewDelegate, TreeViewDrop, TreeViewResponse, TreeViewState,
+};
 
 pub use loaders::install_image_loaders;
 
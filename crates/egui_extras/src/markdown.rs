@@ -0,0 +1,512 @@
+//! Show [CommonMark](https://commonmark.org/) Markdown, with the
+//! [GitHub Flavored Markdown](https://github.github.com/gfm/) tables and task lists.
+//!
+//! Images are loaded with [`egui::load`], so remember to install some image loaders
+//! (e.g. with [`crate::install_image_loaders`]).
+//!
+//! Code blocks are highlighted with [`crate::syntax_highlighting`].
+
+pub mod parser;
+
+use std::sync::Arc;
+
+use egui::{
+    Align, Align2, CursorIcon, Id, Label, Layout, Rect, Response, RichText, Sense, Stroke,
+    TextStyle, Ui, vec2,
+};
+
+pub use parser::{Alignment, Block, Inline, ListItem, Style, parse};
+
+use crate::syntax_highlighting::{self, CodeTheme};
+
+/// How much bigger than the body text each heading level is.
+const HEADING_SCALE: [f32; 6] = [2.0, 1.6, 1.3, 1.15, 1.0, 0.9];
+
+/// Shows a Markdown document.
+///
+/// Links to headings, like `[Usage](#usage)`, scroll to the heading when clicked,
+/// if the [`Markdown`] is in a [`egui::ScrollArea`].
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// egui::ScrollArea::vertical().show(ui, |ui| {
+///     let output = egui_extras::markdown::Markdown::new(
+///         "# Shopping\n\n- [x] Milk\n- [ ] *Fresh* bread\n\n| Item | Price |\n|------|------:|\n| Milk | 1.20 |",
+///     )
+///     .show(ui);
+///     assert_eq!(output.headings[0].anchor, "shopping");
+/// });
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct Markdown<'a> {
+    text: &'a str,
+    id_salt: Option<Id>,
+    scroll_to_heading: Option<String>,
+}
+
+impl<'a> Markdown<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            id_salt: None,
+            scroll_to_heading: None,
+        }
+    }
+
+    /// Needed if the same document is shown more than once in the same [`Ui`].
+    #[inline]
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = Some(Id::new(id_salt));
+        self
+    }
+
+    /// Scroll to the heading with this anchor, e.g. `getting-started` for `## Getting started`.
+    ///
+    /// Only set this on the frame you want to scroll, or the user won't be able to scroll away.
+    /// See [`HeadingOutput::anchor`].
+    #[inline]
+    pub fn scroll_to_heading(mut self, anchor: impl Into<String>) -> Self {
+        self.scroll_to_heading = Some(anchor.into());
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) -> MarkdownOutput {
+        let Self {
+            text,
+            id_salt,
+            scroll_to_heading,
+        } = self;
+
+        let id = id_salt.map_or_else(
+            || ui.next_auto_id(),
+            |id_salt| ui.make_persistent_id(id_salt),
+        );
+        ui.skip_ahead_auto_ids(1);
+
+        let blocks = ui.memory_mut(|mem| mem.caches.cache::<ParseCache>().get(text));
+
+        // A link to a heading may have been clicked last frame:
+        let clicked_anchor = ui.data_mut(|data| data.remove_temp::<ClickedAnchor>(id));
+
+        let mut viewer = Viewer {
+            id,
+            scroll_to: scroll_to_heading.or(clicked_anchor.map(|anchor| anchor.0)),
+            headings: Vec::new(),
+            tight: false,
+            list_depth: 0,
+            counter: 0,
+        };
+        let response = ui.vertical(|ui| viewer.blocks(ui, &blocks)).response;
+
+        MarkdownOutput {
+            response,
+            headings: viewer.headings,
+        }
+    }
+}
+
+/// What [`Markdown::show`] returns.
+pub struct MarkdownOutput {
+    /// Covers the whole document.
+    pub response: Response,
+
+    /// All the headings of the document, in order.
+    ///
+    /// Useful for showing a table of contents.
+    pub headings: Vec<HeadingOutput>,
+}
+
+/// A heading shown by [`Markdown::show`].
+#[derive(Clone, Debug)]
+pub struct HeadingOutput {
+    /// 1-6
+    pub level: u8,
+
+    /// The heading as plain text.
+    pub text: String,
+
+    /// The unique anchor of the heading, for [`Markdown::scroll_to_heading`].
+    pub anchor: String,
+
+    /// Where the heading was shown.
+    pub rect: Rect,
+}
+
+// ----------------------------------------------------------------------------
+
+#[derive(Default)]
+struct MarkdownParser;
+
+impl egui::cache::ComputerMut<&str, Arc<Vec<Block>>> for MarkdownParser {
+    fn compute(&mut self, text: &str) -> Arc<Vec<Block>> {
+        Arc::new(parse(text))
+    }
+}
+
+type ParseCache = egui::cache::FrameCache<Arc<Vec<Block>>, MarkdownParser>;
+
+/// Private type, so that users can't interfere with it in the `IdTypeMap`.
+#[derive(Clone, Default)]
+struct ClickedAnchor(String);
+
+struct Viewer {
+    id: Id,
+
+    /// The anchor of the heading to scroll to.
+    scroll_to: Option<String>,
+
+    headings: Vec<HeadingOutput>,
+
+    /// Are we in a tight list, i.e. without space between paragraphs?
+    tight: bool,
+
+    list_depth: usize,
+
+    /// Gives each code block and table its own id.
+    counter: usize,
+}
+
+impl Viewer {
+    fn next_id(&mut self) -> Id {
+        self.counter += 1;
+        self.id.with(self.counter)
+    }
+
+    fn blocks(&mut self, ui: &mut Ui, blocks: &[Block]) {
+        let blocks = blocks
+            .iter()
+            .filter(|block| !matches!(block, Block::Html(html) if html.starts_with("<!--")));
+        for (i, block) in blocks.enumerate() {
+            if 0 < i && !self.tight {
+                ui.add_space(0.5 * ui.text_style_height(&TextStyle::Body));
+            }
+            self.block(ui, block);
+        }
+    }
+
+    fn block(&mut self, ui: &mut Ui, block: &Block) {
+        match block {
+            Block::Paragraph(inlines) => {
+                self.inlines(ui, inlines, None, false, true);
+            }
+            Block::Heading {
+                level,
+                anchor,
+                content,
+            } => {
+                let body_size = TextStyle::Body.resolve(ui.style()).size;
+                let size = body_size * HEADING_SCALE[usize::from(*level).clamp(1, 6) - 1];
+                let response = self.inlines(ui, content, Some(size), true, true);
+                if *level <= 2 {
+                    ui.separator();
+                }
+
+                if self.scroll_to.as_deref() == Some(anchor.as_str()) {
+                    response.scroll_to_me(Some(Align::TOP));
+                    self.scroll_to = None;
+                }
+                self.headings.push(HeadingOutput {
+                    level: *level,
+                    text: parser::plain_text(content),
+                    anchor: anchor.clone(),
+                    rect: response.rect,
+                });
+            }
+            Block::ThematicBreak => {
+                ui.separator();
+            }
+            Block::Code { language, code } => self.code_block(ui, language, code),
+            Block::Html(html) => {
+                // We can't render HTML, so show it as it is:
+                ui.label(RichText::new(html).monospace().weak());
+            }
+            Block::Quote(blocks) => {
+                let response = egui::Frame::new()
+                    .inner_margin(egui::Margin {
+                        left: 12,
+                        ..Default::default()
+                    })
+                    .show(ui, |ui| self.blocks(ui, blocks))
+                    .response;
+                let rect = response.rect;
+                ui.painter().vline(
+                    rect.left() + 3.0,
+                    rect.y_range(),
+                    Stroke::new(3.0, ui.visuals().weak_text_color()),
+                );
+            }
+            Block::List {
+                start,
+                tight,
+                items,
+            } => {
+                let was_tight = std::mem::replace(&mut self.tight, *tight);
+                self.list_depth += 1;
+                self.list(ui, *start, items);
+                self.list_depth -= 1;
+                self.tight = was_tight;
+            }
+            Block::Table {
+                alignments,
+                header,
+                rows,
+            } => self.table(ui, alignments, header, rows),
+        }
+    }
+
+    /// Show some inline content, in a wrapping layout unless `wrap` is false.
+    fn inlines(
+        &self,
+        ui: &mut Ui,
+        inlines: &[Inline],
+        size: Option<f32>,
+        strong: bool,
+        wrap: bool,
+    ) -> Response {
+        let row_height = size.unwrap_or_else(|| ui.text_style_height(&TextStyle::Body));
+        let add_contents = |ui: &mut Ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            ui.set_row_height(row_height);
+
+            for inline in inlines {
+                match inline {
+                    Inline::Text { text, style, link } => {
+                        for (i, line) in text.split('\n').enumerate() {
+                            if 0 < i {
+                                ui.end_row();
+                            }
+                            if line.is_empty() {
+                                continue;
+                            }
+                            let text = rich_text(line, *style, size, strong);
+                            if let Some(url) = link {
+                                let response = ui.add(egui::Link::new(text));
+                                self.link(ui, &response, url);
+                            } else {
+                                ui.add(Label::new(text));
+                            }
+                        }
+                    }
+                    Inline::Image { url, alt, link } => {
+                        let mut image = egui::Image::new(url.as_str())
+                            .fit_to_original_size(1.0)
+                            .max_width(ui.max_rect().width())
+                            .alt_text(alt.as_str());
+                        if link.is_some() {
+                            image = image.sense(Sense::click());
+                        }
+                        let response = ui.add(image);
+                        if let Some(url) = link {
+                            self.link(ui, &response.on_hover_cursor(CursorIcon::PointingHand), url);
+                        }
+                    }
+                }
+            }
+        };
+
+        if wrap {
+            ui.horizontal_wrapped(add_contents).response
+        } else {
+            ui.horizontal(add_contents).response
+        }
+    }
+
+    fn link(&self, ui: &Ui, response: &Response, url: &str) {
+        if let Some(anchor) = url.strip_prefix('#') {
+            if response.clicked() {
+                ui.data_mut(|data| data.insert_temp(self.id, ClickedAnchor(anchor.to_owned())));
+                ui.ctx().request_repaint();
+            }
+            return;
+        }
+
+        if response.clicked_with_open_in_background() {
+            ui.ctx().open_url(egui::OpenUrl {
+                url: url.to_owned(),
+                new_tab: true,
+            });
+        } else if response.clicked() {
+            ui.ctx().open_url(egui::OpenUrl {
+                url: url.to_owned(),
+                new_tab: false,
+            });
+        }
+
+        if ui.style().url_in_tooltip {
+            response.clone().on_hover_text(url);
+        }
+    }
+
+    fn code_block(&mut self, ui: &mut Ui, language: &str, code: &str) {
+        let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
+        let code = code.strip_suffix('\n').unwrap_or(code);
+        let layout_job =
+            syntax_highlighting::highlight(ui.ctx(), ui.style(), &theme, code, language);
+        let id = self.next_id();
+
+        egui::Frame::new()
+            .fill(ui.visuals().code_bg_color)
+            .corner_radius(ui.visuals().widgets.noninteractive.corner_radius)
+            .inner_margin(6)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                egui::ScrollArea::horizontal().id_salt(id).show(ui, |ui| {
+                    ui.add(Label::new(layout_job).extend().selectable(true));
+                });
+            });
+    }
+
+    fn list(&mut self, ui: &mut Ui, start: Option<u64>, items: &[ListItem]) {
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let text_color = ui.visuals().text_color();
+
+        let numbers = start.map(|start| {
+            (start..)
+                .take(items.len())
+                .map(|number| {
+                    ui.painter()
+                        .layout_no_wrap(format!("{number}."), font_id.clone(), text_color)
+                })
+                .collect::<Vec<_>>()
+        });
+        let marker_width = numbers
+            .iter()
+            .flatten()
+            .map(|galley| galley.size().x)
+            .fold(row_height, f32::max);
+
+        for (i, item) in items.iter().enumerate() {
+            if 0 < i && !self.tight {
+                ui.add_space(0.5 * row_height);
+            }
+            ui.horizontal_top(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(vec2(marker_width, row_height), Sense::hover());
+                if let Some(numbers) = &numbers {
+                    let galley = numbers[i].clone();
+                    let pos = Align2::RIGHT_CENTER
+                        .align_size_within_rect(galley.size(), rect)
+                        .min;
+                    ui.painter().galley(pos, galley, text_color);
+                } else if item.checked.is_none() {
+                    self.bullet(ui, rect);
+                }
+
+                if let Some(mut checked) = item.checked {
+                    ui.add_enabled(false, egui::Checkbox::without_text(&mut checked));
+                }
+
+                ui.vertical(|ui| self.blocks(ui, &item.blocks));
+            });
+        }
+    }
+
+    /// Paint the bullet of a bullet list item, depending on how deep in nested lists we are.
+    fn bullet(&self, ui: &Ui, rect: Rect) {
+        let painter = ui.painter();
+        let color = ui.visuals().strong_text_color();
+        let center = rect.center();
+        let radius = rect.height() / 8.0;
+        match self.list_depth {
+            0 | 1 => {
+                painter.circle_filled(center, radius, color);
+            }
+            2 => {
+                painter.circle_stroke(center, radius, Stroke::new(1.0, color));
+            }
+            _ => {
+                painter.rect_filled(
+                    Rect::from_center_size(center, vec2(2.0, 2.0) * radius),
+                    0.0,
+                    color,
+                );
+            }
+        }
+    }
+
+    fn table(
+        &mut self,
+        ui: &mut Ui,
+        alignments: &[Alignment],
+        header: &[Vec<Inline>],
+        rows: &[Vec<Vec<Inline>>],
+    ) {
+        let id = self.next_id();
+        egui::ScrollArea::horizontal().id_salt(id).show(ui, |ui| {
+            egui::Grid::new(id).striped(true).show(ui, |ui| {
+                for (alignment, cell) in alignments.iter().zip(header) {
+                    self.table_cell(ui, *alignment, cell, true);
+                }
+                ui.end_row();
+
+                for row in rows {
+                    for (alignment, cell) in alignments.iter().zip(row) {
+                        self.table_cell(ui, *alignment, cell, false);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn table_cell(&self, ui: &mut Ui, alignment: Alignment, cell: &[Inline], header: bool) {
+        let plain = cell
+            .iter()
+            .all(|inline| matches!(inline, Inline::Text { link: None, .. }));
+        if !plain {
+            // Links and images need their own widgets, so we can't align them as one piece.
+            self.inlines(ui, cell, None, header, false);
+            return;
+        }
+
+        let mut layout_job = egui::text::LayoutJob::default();
+        for inline in cell {
+            if let Inline::Text { text, style, .. } = inline {
+                rich_text(text, *style, None, header).append_to(
+                    &mut layout_job,
+                    ui.style(),
+                    egui::FontSelection::Default,
+                    Align::Center,
+                );
+            }
+        }
+
+        let align = match alignment {
+            Alignment::None | Alignment::Left => Align::Min,
+            Alignment::Center => Align::Center,
+            Alignment::Right => Align::Max,
+        };
+        ui.with_layout(Layout::top_down(align), |ui| {
+            ui.add(Label::new(layout_job).extend());
+        });
+    }
+}
+
+fn rich_text(text: &str, style: Style, size: Option<f32>, strong: bool) -> RichText {
+    let Style {
+        strong: is_strong,
+        italics,
+        strikethrough,
+        code,
+    } = style;
+
+    let mut text = RichText::new(text);
+    if code {
+        text = text.code();
+    }
+    if let Some(size) = size {
+        text = text.size(size);
+    }
+    if strong || is_strong {
+        text = text.strong();
+    }
+    if italics {
+        text = text.italics();
+    }
+    if strikethrough {
+        text = text.strikethrough();
+    }
+    text
+}
@@ -0,0 +1,2319 @@
+//! Parse [CommonMark](https://commonmark.org/) into a list of [`Block`]s.
+//!
+//! Also supports the [GitHub Flavored Markdown](https://github.github.com/gfm/) extensions for
+//! tables, task lists, strikethrough and autolinks.
+//!
+//! Raw HTML is kept as text (except for `<br>`), since we can't render it.
+//! Tabs are expanded to spaces before parsing.
+//!
+//! The block structure is parsed with the two-phase algorithm described in the `CommonMark` spec:
+//! first each line is matched against the open blocks, then the inline content of the
+//! paragraphs and headings is parsed, with the link reference definitions from the whole document.
+
+use std::borrow::Cow;
+
+type Definitions = ahash::HashMap<String, String>;
+
+/// The style of a piece of inline text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    /// `**strong**`
+    pub strong: bool,
+
+    /// `*emphasis*`
+    pub italics: bool,
+
+    /// `~~strikethrough~~`
+    pub strikethrough: bool,
+
+    /// `` `code` ``
+    pub code: bool,
+}
+
+/// Inline content of a paragraph, heading or table cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inline {
+    /// Some text, possibly part of a link.
+    ///
+    /// Hard line breaks are included as `\n`.
+    Text {
+        text: String,
+        style: Style,
+
+        /// The url of the link this text is part of, if any.
+        link: Option<String>,
+    },
+
+    /// `![alt](url)`
+    Image {
+        url: String,
+        alt: String,
+
+        /// The url of the link this image is part of, if any.
+        link: Option<String>,
+    },
+}
+
+/// The alignment of a table column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// `---`
+    #[default]
+    None,
+
+    /// `:--`
+    Left,
+
+    /// `:-:`
+    Center,
+
+    /// `--:`
+    Right,
+}
+
+/// A block of a Markdown document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+
+    /// `# Heading`
+    Heading {
+        /// 1-6
+        level: u8,
+
+        /// The GitHub-style anchor of the heading, e.g. `getting-started` for `## Getting started`.
+        ///
+        /// Unique within the document, so that links like `[start](#getting-started)` can scroll to it.
+        anchor: String,
+
+        content: Vec<Inline>,
+    },
+
+    /// `---`
+    ThematicBreak,
+
+    /// Fenced or indented code.
+    Code {
+        /// The first word of the info string of a fenced code block, e.g. `rust`.
+        language: String,
+
+        code: String,
+    },
+
+    /// Raw HTML.
+    Html(String),
+
+    /// `> quote`
+    Quote(Vec<Block>),
+
+    /// A bullet list or ordered list.
+    List {
+        /// The number of the first item of an ordered list, or `None` for a bullet list.
+        start: Option<u64>,
+
+        /// A tight list has no blank lines between its items.
+        tight: bool,
+
+        items: Vec<ListItem>,
+    },
+
+    /// A GitHub Flavored Markdown table.
+    Table {
+        alignments: Vec<Alignment>,
+
+        /// One [`Vec<Inline>`] per column.
+        header: Vec<Vec<Inline>>,
+
+        /// Each row has one [`Vec<Inline>`] per column.
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+}
+
+/// An item of a [`Block::List`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListItem {
+    /// Set for task list items: `- [ ]` and `- [x]`.
+    pub checked: Option<bool>,
+
+    pub blocks: Vec<Block>,
+}
+
+/// Parse a Markdown document.
+///
+/// ```
+/// use egui_extras::markdown::{Block, Inline, Style, parse};
+///
+/// let blocks = parse("# Hello\n\n*world*");
+/// assert_eq!(
+///     blocks[1],
+///     Block::Paragraph(vec![Inline::Text {
+///         text: "world".to_owned(),
+///         style: Style { italics: true, ..Default::default() },
+///         link: None,
+///     }])
+/// );
+/// ```
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut parser = BlockParser::default();
+    for line in text.lines() {
+        parser.add_line(&expand_tabs(line));
+        parser.line_number += 1;
+    }
+    parser.finish();
+
+    let mut converter = Converter {
+        nodes: &parser.nodes,
+        definitions: &parser.definitions,
+        anchors: Default::default(),
+    };
+    converter.blocks(&parser.nodes[0].children)
+}
+
+/// Turn the contents of headings into anchors, like GitHub does:
+/// `Getting Started!` becomes `getting-started`.
+fn anchor_from_text(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else if c == ' ' {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The plain text of some inline content, without any styling.
+pub(crate) fn plain_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text { text, .. } => text.as_str(),
+            Inline::Image { alt, .. } => alt.as_str(),
+        })
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// Block structure
+
+const CODE_INDENT: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ListMarker {
+    /// `None` for bullet lists.
+    start: Option<u64>,
+
+    /// The bullet character, or the delimiter after the number (`.` or `)`).
+    ch: u8,
+}
+
+enum Kind {
+    Document,
+    BlockQuote,
+    List {
+        marker: ListMarker,
+    },
+    Item {
+        /// Lines must be indented this much to continue the item.
+        content_offset: usize,
+    },
+    Paragraph,
+    Heading {
+        level: u8,
+    },
+    ThematicBreak,
+    FencedCode {
+        fence: u8,
+        fence_len: usize,
+        fence_indent: usize,
+        language: String,
+    },
+    IndentedCode,
+    Html {
+        /// The block ends with the line containing this, or at a blank line if `None`.
+        end: Option<&'static str>,
+    },
+    Table {
+        alignments: Vec<Alignment>,
+
+        /// The first row is the header.
+        rows: Vec<Vec<String>>,
+    },
+}
+
+struct Node {
+    kind: Kind,
+    parent: usize,
+    children: Vec<usize>,
+    open: bool,
+    start_line: usize,
+    end_line: usize,
+
+    /// The text of leaf blocks, one `\n`-terminated line at a time.
+    content: String,
+}
+
+enum Continuation {
+    Matched,
+    Unmatched,
+
+    /// The line closed the block (a closing code fence), so there is nothing more to do with it.
+    Finished,
+}
+
+enum Start {
+    None,
+    Container,
+    Leaf,
+}
+
+struct BlockParser {
+    /// The first node is the document.
+    nodes: Vec<Node>,
+
+    /// The deepest open block.
+    tip: usize,
+
+    line_number: usize,
+
+    /// The deepest block that the current line continued.
+    last_matched: usize,
+
+    /// Have the blocks that the current line didn't continue been closed?
+    all_closed: bool,
+
+    /// Link reference definitions, by normalized label.
+    definitions: Definitions,
+}
+
+impl Default for BlockParser {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node {
+                kind: Kind::Document,
+                parent: 0,
+                children: Vec::new(),
+                open: true,
+                start_line: 0,
+                end_line: 0,
+                content: String::new(),
+            }],
+            tip: 0,
+            line_number: 0,
+            last_matched: 0,
+            all_closed: true,
+            definitions: Default::default(),
+        }
+    }
+}
+
+impl BlockParser {
+    fn add_line(&mut self, line: &str) {
+        let mut pos = 0;
+
+        // Which of the open blocks does this line continue?
+        let mut container = 0;
+        while let Some(&last) = self.nodes[container].children.last() {
+            if !self.nodes[last].open {
+                break;
+            }
+            match self.continues(last, line, &mut pos) {
+                Continuation::Matched => container = last,
+                Continuation::Unmatched => break,
+                Continuation::Finished => return,
+            }
+        }
+
+        self.last_matched = container;
+        self.all_closed = container == self.tip;
+
+        // Does the line start any new blocks?
+        let mut matched_leaf =
+            self.accepts_lines(container) && !matches!(self.nodes[container].kind, Kind::Paragraph);
+        while !matched_leaf {
+            match self.start_block(line, &mut pos, container) {
+                Start::None => {
+                    pos += count_indent(line, pos);
+                    break;
+                }
+                Start::Container => container = self.tip,
+                Start::Leaf => {
+                    container = self.tip;
+                    matched_leaf = true;
+                }
+            }
+        }
+
+        // Add the rest of the line to the right block:
+        let blank = line[pos..].bytes().all(|b| b == b' ');
+        if !self.all_closed && !blank && matches!(self.nodes[self.tip].kind, Kind::Paragraph) {
+            // Lazy continuation of a paragraph, e.g. in a block quote without the `>`.
+            self.add_text(self.tip, &line[pos..]);
+        } else {
+            self.close_unmatched();
+            if self.accepts_lines(container) {
+                self.add_text(container, &line[pos..]);
+                if let Kind::Html { end: Some(end) } = self.nodes[container].kind {
+                    if line[pos..].to_lowercase().contains(end) {
+                        self.finalize(container, self.line_number);
+                    }
+                }
+            } else if !blank {
+                let paragraph = self.add_child(container, Kind::Paragraph);
+                self.add_text(paragraph, &line[pos..]);
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let last_line = self.line_number.saturating_sub(1);
+        while self.tip != 0 {
+            self.finalize(self.tip, last_line);
+        }
+        self.finalize(0, last_line);
+    }
+
+    fn accepts_lines(&self, index: usize) -> bool {
+        matches!(
+            self.nodes[index].kind,
+            Kind::Paragraph
+                | Kind::FencedCode { .. }
+                | Kind::IndentedCode
+                | Kind::Html { .. }
+                | Kind::Table { .. }
+        )
+    }
+
+    fn continues(&mut self, index: usize, line: &str, pos: &mut usize) -> Continuation {
+        let indent = count_indent(line, *pos);
+        let nonspace = *pos + indent;
+        let rest = &line[nonspace..];
+        let blank = rest.is_empty();
+
+        let matched = match &self.nodes[index].kind {
+            Kind::Document | Kind::List { .. } => true,
+            Kind::BlockQuote => {
+                let matched = indent < CODE_INDENT && rest.starts_with('>');
+                if matched {
+                    *pos = nonspace + 1;
+                    if line[*pos..].starts_with(' ') {
+                        *pos += 1;
+                    }
+                }
+                matched
+            }
+            Kind::Item { content_offset } => {
+                if blank {
+                    // A list item can begin with at most one blank line.
+                    *pos = nonspace;
+                    !self.nodes[index].children.is_empty()
+                } else if *content_offset <= indent {
+                    *pos += content_offset;
+                    true
+                } else {
+                    false
+                }
+            }
+            Kind::Paragraph => !blank,
+            Kind::Table { .. } => {
+                // Other blocks can interrupt a table:
+                !blank
+                    && !(indent < CODE_INDENT
+                        && (rest.starts_with('>')
+                            || atx_heading(rest).is_some()
+                            || opening_fence(rest).is_some()
+                            || is_thematic_break(rest)))
+            }
+            Kind::Heading { .. } | Kind::ThematicBreak => false,
+            Kind::FencedCode {
+                fence,
+                fence_len,
+                fence_indent,
+                ..
+            } => {
+                if indent < CODE_INDENT && is_closing_fence(rest, *fence, *fence_len) {
+                    self.finalize(index, self.line_number);
+                    return Continuation::Finished;
+                }
+                *pos += indent.min(*fence_indent);
+                true
+            }
+            Kind::IndentedCode => {
+                if CODE_INDENT <= indent {
+                    *pos += CODE_INDENT;
+                    true
+                } else if blank {
+                    *pos = nonspace;
+                    true
+                } else {
+                    false
+                }
+            }
+            Kind::Html { end } => !blank || end.is_some(),
+        };
+
+        if matched {
+            Continuation::Matched
+        } else {
+            Continuation::Unmatched
+        }
+    }
+
+    fn start_block(&mut self, line: &str, pos: &mut usize, container: usize) -> Start {
+        let indent = count_indent(line, *pos);
+        let nonspace = *pos + indent;
+        let rest = &line[nonspace..];
+        let in_paragraph = matches!(self.nodes[container].kind, Kind::Paragraph);
+
+        if CODE_INDENT <= indent {
+            // Indented code can't interrupt a paragraph.
+            if rest.is_empty() || matches!(self.nodes[self.tip].kind, Kind::Paragraph) {
+                return Start::None;
+            }
+            *pos += CODE_INDENT;
+            self.close_unmatched();
+            self.add_child(container, Kind::IndentedCode);
+            return Start::Leaf;
+        }
+
+        if rest.starts_with('>') {
+            *pos = nonspace + 1;
+            if line[*pos..].starts_with(' ') {
+                *pos += 1;
+            }
+            self.close_unmatched();
+            self.add_child(container, Kind::BlockQuote);
+            return Start::Container;
+        }
+
+        if let Some((level, content)) = atx_heading(rest) {
+            self.close_unmatched();
+            let heading = self.add_child(container, Kind::Heading { level });
+            self.nodes[heading].content = content.to_owned();
+            *pos = line.len();
+            return Start::Leaf;
+        }
+
+        if let Some((fence, fence_len, info)) = opening_fence(rest) {
+            self.close_unmatched();
+            let language = unescape(info.split_whitespace().next().unwrap_or_default());
+            self.add_child(
+                container,
+                Kind::FencedCode {
+                    fence,
+                    fence_len,
+                    fence_indent: indent,
+                    language: language.into_owned(),
+                },
+            );
+            *pos = line.len();
+            return Start::Leaf;
+        }
+
+        if let Some(html) = html_block_start(rest) {
+            self.close_unmatched();
+            self.add_child(container, html);
+            return Start::Leaf;
+        }
+
+        if in_paragraph {
+            if let Some(level) = setext_underline(rest) {
+                self.close_unmatched();
+                self.extract_definitions(container);
+                if !self.nodes[container].content.is_empty() {
+                    self.nodes[container].kind = Kind::Heading { level };
+                    self.nodes[container].content =
+                        self.nodes[container].content.trim_end().to_owned();
+                    self.finalize(container, self.line_number);
+                    *pos = line.len();
+                    return Start::Leaf;
+                }
+            }
+
+            if let Some(alignments) = table_delimiter_row(rest) {
+                if self.start_table(container, alignments) {
+                    *pos = line.len();
+                    return Start::Leaf;
+                }
+            }
+        }
+
+        if is_thematic_break(rest) {
+            self.close_unmatched();
+            self.add_child(container, Kind::ThematicBreak);
+            *pos = line.len();
+            return Start::Leaf;
+        }
+
+        if let Some((marker, marker_len)) = list_marker(rest, in_paragraph) {
+            let after_marker = nonspace + marker_len;
+            let spaces = count_indent(line, after_marker);
+            let blank_item = after_marker + spaces == line.len();
+            let padding = if blank_item || spaces == 0 || CODE_INDENT < spaces {
+                // The content starts one space after the marker
+                // (and may be indented code, if there are more spaces).
+                *pos = after_marker + spaces.min(1);
+                marker_len + 1
+            } else {
+                *pos = after_marker + spaces;
+                marker_len + spaces
+            };
+
+            self.close_unmatched();
+            let same_list = matches!(
+                self.nodes[self.tip].kind,
+                Kind::List { marker: list_marker }
+                    if list_marker.ch == marker.ch
+                        && list_marker.start.is_some() == marker.start.is_some()
+            );
+            let list = if same_list {
+                self.tip
+            } else {
+                self.add_child(container, Kind::List { marker })
+            };
+            self.add_child(
+                list,
+                Kind::Item {
+                    content_offset: indent + padding,
+                },
+            );
+            return Start::Container;
+        }
+
+        Start::None
+    }
+
+    /// Turn the last line of a paragraph into the header of a table, if it has the right number of cells.
+    fn start_table(&mut self, paragraph: usize, alignments: Vec<Alignment>) -> bool {
+        self.close_unmatched();
+        let content = self.nodes[paragraph].content.trim_end_matches('\n');
+        let (before, header) = match content.rfind('\n') {
+            Some(newline) => (&content[..=newline], &content[newline + 1..]),
+            None => ("", content),
+        };
+        let header = split_table_row(header);
+        if header.len() != alignments.len() {
+            return false;
+        }
+
+        let table = Kind::Table {
+            alignments,
+            rows: vec![header],
+        };
+        if before.is_empty() {
+            self.nodes[paragraph].kind = table;
+            self.nodes[paragraph].content.clear();
+        } else {
+            self.nodes[paragraph].content = before.to_owned();
+            let parent = self.nodes[paragraph].parent;
+            self.finalize(paragraph, self.line_number.saturating_sub(2));
+            let table = self.add_child(parent, table);
+            self.nodes[table].start_line = self.line_number.saturating_sub(1);
+        }
+        true
+    }
+
+    fn add_text(&mut self, index: usize, text: &str) {
+        let node = &mut self.nodes[index];
+        if let Kind::Table { alignments, rows } = &mut node.kind {
+            if !text.trim().is_empty() {
+                let mut row = split_table_row(text);
+                row.resize(alignments.len(), String::new());
+                rows.push(row);
+            }
+        } else if matches!(node.kind, Kind::Paragraph) {
+            node.content.push_str(text.trim_start());
+            node.content.push('\n');
+        } else {
+            node.content.push_str(text);
+            node.content.push('\n');
+        }
+    }
+
+    fn add_child(&mut self, mut parent: usize, kind: Kind) -> usize {
+        while !can_contain(&self.nodes[parent].kind, &kind) {
+            self.finalize(parent, self.line_number.saturating_sub(1));
+            parent = self.nodes[parent].parent;
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            kind,
+            parent,
+            children: Vec::new(),
+            open: true,
+            start_line: self.line_number,
+            end_line: self.line_number,
+            content: String::new(),
+        });
+        self.nodes[parent].children.push(index);
+        self.tip = index;
+        index
+    }
+
+    /// Close the blocks that the current line didn't continue.
+    fn close_unmatched(&mut self) {
+        if !self.all_closed {
+            while self.tip != self.last_matched {
+                self.finalize(self.tip, self.line_number.saturating_sub(1));
+            }
+            self.all_closed = true;
+        }
+    }
+
+    fn finalize(&mut self, index: usize, end_line: usize) {
+        let parent = self.nodes[index].parent;
+        self.nodes[index].open = false;
+        self.nodes[index].end_line = end_line;
+
+        match self.nodes[index].kind {
+            Kind::Paragraph => {
+                self.extract_definitions(index);
+                if self.nodes[index].content.is_empty() {
+                    self.nodes[parent].children.retain(|&child| child != index);
+                }
+            }
+            Kind::FencedCode { .. } => {
+                // The first line is the info string:
+                let content = &mut self.nodes[index].content;
+                let first_line_len = content.find('\n').map_or(content.len(), |i| i + 1);
+                content.replace_range(..first_line_len, "");
+            }
+            Kind::IndentedCode => {
+                let content = &mut self.nodes[index].content;
+                let trimmed_len = content.trim_end().len();
+                content.truncate(trimmed_len);
+                content.push('\n');
+            }
+            Kind::Item { .. } | Kind::List { .. } => {
+                // Don't count trailing blank lines as part of the item:
+                let node = &self.nodes[index];
+                let end_line = node
+                    .children
+                    .last()
+                    .map_or(node.start_line, |&child| self.nodes[child].end_line);
+                self.nodes[index].end_line = end_line;
+            }
+            _ => {}
+        }
+
+        self.tip = parent;
+    }
+
+    /// Remove link reference definitions from the start of a paragraph.
+    fn extract_definitions(&mut self, paragraph: usize) {
+        let content = &mut self.nodes[paragraph].content;
+        while let Some((label, url, len)) = parse_definition(content) {
+            self.definitions.entry(label).or_insert(url);
+            content.replace_range(..len, "");
+        }
+        if content.trim().is_empty() {
+            content.clear();
+        }
+    }
+}
+
+fn can_contain(parent: &Kind, child: &Kind) -> bool {
+    match parent {
+        Kind::Document | Kind::BlockQuote | Kind::Item { .. } => {
+            !matches!(child, Kind::Item { .. })
+        }
+        Kind::List { .. } => matches!(child, Kind::Item { .. }),
+        _ => false,
+    }
+}
+
+fn expand_tabs(line: &str) -> Cow<'_, str> {
+    if !line.contains('\t') {
+        return Cow::Borrowed(line);
+    }
+    let mut expanded = String::with_capacity(line.len() + 8);
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = 4 - column % 4;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    Cow::Owned(expanded)
+}
+
+fn count_indent(line: &str, pos: usize) -> usize {
+    line[pos..].bytes().take_while(|&b| b == b' ').count()
+}
+
+/// `## Heading ##` -> `(2, "Heading")`
+fn atx_heading(rest: &str) -> Option<(u8, &str)> {
+    let level = rest.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let content = &rest[level..];
+    if !content.is_empty() && !content.starts_with(' ') {
+        return None;
+    }
+
+    let content = content.trim();
+    // Remove the optional closing sequence:
+    let without_hashes = content.trim_end_matches('#');
+    let content = if without_hashes.is_empty() {
+        ""
+    } else if without_hashes.ends_with(' ') {
+        without_hashes.trim_end()
+    } else {
+        content
+    };
+    Some((level as u8, content))
+}
+
+/// `~~~ rust` -> `(b'~', 3, "rust")`
+fn opening_fence(rest: &str) -> Option<(u8, usize, &str)> {
+    let fence = *rest.as_bytes().first()?;
+    if fence != b'`' && fence != b'~' {
+        return None;
+    }
+    let fence_len = rest.bytes().take_while(|&b| b == fence).count();
+    let info = rest[fence_len..].trim();
+    if fence_len < 3 || (fence == b'`' && info.contains('`')) {
+        return None;
+    }
+    Some((fence, fence_len, info))
+}
+
+fn is_closing_fence(rest: &str, fence: u8, fence_len: usize) -> bool {
+    let len = rest.bytes().take_while(|&b| b == fence).count();
+    fence_len <= len && rest[len..].trim().is_empty()
+}
+
+fn setext_underline(rest: &str) -> Option<u8> {
+    let rest = rest.trim_end();
+    if !rest.is_empty() && rest.bytes().all(|b| b == b'=') {
+        Some(1)
+    } else if !rest.is_empty() && rest.bytes().all(|b| b == b'-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn is_thematic_break(rest: &str) -> bool {
+    let Some(ch) = rest.bytes().next() else {
+        return false;
+    };
+    matches!(ch, b'*' | b'-' | b'_')
+        && rest.bytes().all(|b| b == ch || b == b' ')
+        && 3 <= rest.bytes().filter(|&b| b == ch).count()
+}
+
+/// Returns the marker and its length in bytes.
+fn list_marker(rest: &str, interrupts_paragraph: bool) -> Option<(ListMarker, usize)> {
+    let bytes = rest.as_bytes();
+    let (marker, len) = match bytes.first()? {
+        ch @ (b'*' | b'+' | b'-') => (
+            ListMarker {
+                start: None,
+                ch: *ch,
+            },
+            1,
+        ),
+        b'0'..=b'9' => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            let delimiter = *bytes.get(digits)?;
+            if 9 < digits || (delimiter != b'.' && delimiter != b')') {
+                return None;
+            }
+            let start = rest[..digits].parse().ok()?;
+            if interrupts_paragraph && start != 1 {
+                return None;
+            }
+            (
+                ListMarker {
+                    start: Some(start),
+                    ch: delimiter,
+                },
+                digits + 1,
+            )
+        }
+        _ => return None,
+    };
+
+    let after = &rest[len..];
+    if !after.is_empty() && !after.starts_with(' ') {
+        return None;
+    }
+    if interrupts_paragraph && after.trim().is_empty() {
+        // An empty list item can't interrupt a paragraph.
+        return None;
+    }
+    Some((marker, len))
+}
+
+/// Does this line start an HTML block?
+fn html_block_start(rest: &str) -> Option<Kind> {
+    if !rest.starts_with('<') {
+        return None;
+    }
+    let lower = rest.to_lowercase();
+
+    for tag in ["script", "pre", "style", "textarea"] {
+        if let Some(after) = lower[1..].strip_prefix(tag) {
+            if after.is_empty() || after.starts_with([' ', '>']) {
+                let end = match tag {
+                    "script" => "</script>",
+                    "pre" => "</pre>",
+                    "style" => "</style>",
+                    _ => "</textarea>",
+                };
+                return Some(Kind::Html { end: Some(end) });
+            }
+        }
+    }
+    if lower.starts_with("<!--") {
+        return Some(Kind::Html { end: Some("-->") });
+    }
+    if lower.starts_with("<?") {
+        return Some(Kind::Html { end: Some("?>") });
+    }
+    if lower.starts_with("<![cdata[") {
+        return Some(Kind::Html { end: Some("]]>") });
+    }
+    if lower
+        .strip_prefix("<!")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+    {
+        return Some(Kind::Html { end: Some(">") });
+    }
+
+    const BLOCK_TAGS: &[&str] = &[
+        "address",
+        "article",
+        "aside",
+        "base",
+        "basefont",
+        "blockquote",
+        "body",
+        "caption",
+        "center",
+        "col",
+        "colgroup",
+        "dd",
+        "details",
+        "dialog",
+        "dir",
+        "div",
+        "dl",
+        "dt",
+        "fieldset",
+        "figcaption",
+        "figure",
+        "footer",
+        "form",
+        "frame",
+        "frameset",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "head",
+        "header",
+        "hr",
+        "html",
+        "iframe",
+        "legend",
+        "li",
+        "link",
+        "main",
+        "menu",
+        "menuitem",
+        "nav",
+        "noframes",
+        "ol",
+        "optgroup",
+        "option",
+        "p",
+        "param",
+        "search",
+        "section",
+        "summary",
+        "table",
+        "tbody",
+        "td",
+        "tfoot",
+        "th",
+        "thead",
+        "title",
+        "tr",
+        "track",
+        "ul",
+    ];
+    let name = lower[1..].strip_prefix('/').unwrap_or(&lower[1..]);
+    let name_len = name
+        .bytes()
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .count();
+    let after = &name[name_len..];
+    if BLOCK_TAGS.contains(&&name[..name_len])
+        && (after.is_empty() || after.starts_with([' ', '>']) || after.starts_with("/>"))
+    {
+        return Some(Kind::Html { end: None });
+    }
+
+    None
+}
+
+/// `| :-- | :-: |` -> `[Left, Center]`
+fn table_delimiter_row(rest: &str) -> Option<Vec<Alignment>> {
+    if !rest.contains('|') && !rest.contains(':') {
+        // Could be a setext heading or a thematic break.
+        return None;
+    }
+    split_table_row(rest)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.bytes().all(|b| b == b'-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            })
+        })
+        .collect()
+}
+
+fn split_table_row(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = if row.ends_with('|') && !row.ends_with("\\|") {
+        &row[..row.len() - 1]
+    } else {
+        row
+    };
+
+    let mut cells = vec![String::new()];
+    let mut chars = row.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                // `\|` is a pipe inside a cell. Other escapes are handled by the inline parser.
+                match chars.next() {
+                    Some('|') => cells.last_mut().unwrap().push('|'),
+                    Some(next) => {
+                        let cell = cells.last_mut().unwrap();
+                        cell.push('\\');
+                        cell.push(next);
+                    }
+                    None => cells.last_mut().unwrap().push('\\'),
+                }
+            }
+            '|' => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_owned()).collect()
+}
+
+/// `[label]: url "title"` -> `(normalized label, url, length in bytes)`
+fn parse_definition(text: &str) -> Option<(String, String, usize)> {
+    if !text.starts_with('[') {
+        return None;
+    }
+    let (label, mut pos) = link_label(text, 0)?;
+    if label.trim().is_empty() || !text[pos..].starts_with(':') {
+        return None;
+    }
+    pos += 1;
+    pos = skip_whitespace(text, pos);
+    let (url, after_url) = link_destination(text, pos)?;
+    if after_url == pos && !text[pos..].starts_with("<>") {
+        return None;
+    }
+
+    // The definition must end at the end of a line, with or without a title:
+    let line_end = |pos: usize| {
+        let rest = &text[pos..];
+        let spaces = rest.bytes().take_while(|&b| b == b' ').count();
+        match rest.as_bytes().get(spaces) {
+            None => Some(pos + spaces),
+            Some(b'\n') => Some(pos + spaces + 1),
+            Some(_) => None,
+        }
+    };
+
+    let title_start = skip_whitespace(text, after_url);
+    let end = (title_start > after_url)
+        .then(|| link_title(text, title_start))
+        .flatten()
+        .and_then(line_end)
+        .or_else(|| line_end(after_url))?;
+
+    Some((normalize_label(&label), url, end))
+}
+
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Parses `[label]` starting at `pos`, returning the label and the position after the `]`.
+fn link_label(text: &str, pos: usize) -> Option<(String, usize)> {
+    let mut chars = text[pos..].char_indices();
+    if chars.next()?.1 != '[' {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in chars {
+        if 1000 < i {
+            return None;
+        }
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => return None,
+            ']' => return Some((text[pos + 1..pos + i].to_owned(), pos + i + 1)),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses a link destination, returning the unescaped url and the position after it.
+fn link_destination(text: &str, pos: usize) -> Option<(String, usize)> {
+    let rest = &text[pos..];
+    if let Some(inner) = rest.strip_prefix('<') {
+        let mut escaped = false;
+        for (i, c) in inner.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '\n' | '<' => return None,
+                '>' => return Some((decode(&inner[..i]), pos + i + 2)),
+                _ => {}
+            }
+        }
+        return None;
+    }
+
+    let mut depth = 0;
+    let mut escaped = false;
+    let mut end = rest.len();
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                end = i;
+                break;
+            }
+            ')' => depth -= 1,
+            c if c.is_whitespace() || c.is_control() => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    (depth == 0).then(|| (decode(&rest[..end]), pos + end))
+}
+
+/// Parses a link title starting at `pos`, returning the position after it.
+fn link_title(text: &str, pos: usize) -> Option<usize> {
+    let mut chars = text[pos..].char_indices();
+    let close = match chars.next()?.1 {
+        '"' => '"',
+        '\'' => '\'',
+        '(' => ')',
+        _ => return None,
+    };
+    let mut escaped = false;
+    for (i, c) in chars {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == close => return Some(pos + i + 1),
+            '(' if close == ')' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn skip_whitespace(text: &str, pos: usize) -> usize {
+    pos + text[pos..].len() - text[pos..].trim_start().len()
+}
+
+/// Remove backslash escapes and decode entities.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['\\', '&']) {
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(escaped) = rest
+            .strip_prefix('\\')
+            .and_then(|after| after.chars().next())
+            .filter(char::is_ascii_punctuation)
+        {
+            decoded.push(escaped);
+            rest = &rest[2..];
+        } else if let Some((entity, len)) = entity(rest) {
+            decoded.push_str(&entity);
+            rest = &rest[len..];
+        } else {
+            decoded.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn unescape(text: &str) -> Cow<'_, str> {
+    if text.contains(['\\', '&']) {
+        Cow::Owned(decode(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Decode an HTML entity like `&amp;` or `&#x1F600;` at the start of the text.
+///
+/// Only the most common named entities are supported.
+fn entity(text: &str) -> Option<(String, usize)> {
+    let (end, _) = text.char_indices().take(34).find(|&(_, c)| c == ';')?;
+    let name = &text[1..end];
+    let c = if let Some(number) = name.strip_prefix('#') {
+        let code = if let Some(hex) = number.strip_prefix(['x', 'X']) {
+            u32::from_str_radix(hex, 16).ok()?
+        } else {
+            number.parse().ok()?
+        };
+        char::from_u32(code)
+            .filter(|&c| c != '\0')
+            .unwrap_or(char::REPLACEMENT_CHARACTER)
+    } else {
+        match name {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            "copy" => '©',
+            "reg" => '®',
+            "trade" => '™',
+            "hellip" => '…',
+            "mdash" => '—',
+            "ndash" => '–',
+            "lsquo" => '‘',
+            "rsquo" => '’',
+            "ldquo" => '“',
+            "rdquo" => '”',
+            "laquo" => '«',
+            "raquo" => '»',
+            "middot" => '·',
+            "bull" => '•',
+            "times" => '×',
+            "divide" => '÷',
+            "deg" => '°',
+            "plusmn" => '±',
+            "larr" => '←',
+            "rarr" => '→',
+            "uarr" => '↑',
+            "darr" => '↓',
+            "harr" => '↔',
+            "euro" => '€',
+            "pound" => '£',
+            "yen" => '¥',
+            "cent" => '¢',
+            "sect" => '§',
+            "para" => '¶',
+            _ => return None,
+        }
+    };
+    Some((c.to_string(), end + 1))
+}
+
+// ----------------------------------------------------------------------------
+// Turning the block structure into `Block`s
+
+struct Converter<'a> {
+    nodes: &'a [Node],
+    definitions: &'a Definitions,
+
+    /// How many times each heading anchor has been used so far.
+    anchors: ahash::HashMap<String, usize>,
+}
+
+impl Converter<'_> {
+    fn blocks(&mut self, children: &[usize]) -> Vec<Block> {
+        let nodes = self.nodes;
+        children
+            .iter()
+            .map(|&child| self.block(&nodes[child]))
+            .collect()
+    }
+
+    fn inlines(&self, text: &str) -> Vec<Inline> {
+        InlineParser::new(text, self.definitions).parse()
+    }
+
+    fn block(&mut self, node: &Node) -> Block {
+        match &node.kind {
+            Kind::Document | Kind::Item { .. } => unreachable!("handled by the parent"),
+            Kind::Paragraph => Block::Paragraph(self.inlines(node.content.trim_end())),
+            Kind::Heading { level } => {
+                let content = self.inlines(&node.content);
+
+                let mut anchor = anchor_from_text(&plain_text(&content));
+                let count = self.anchors.entry(anchor.clone()).or_default();
+                if 0 < *count {
+                    anchor = format!("{anchor}-{count}");
+                }
+                *count += 1;
+
+                Block::Heading {
+                    level: *level,
+                    anchor,
+                    content,
+                }
+            }
+            Kind::ThematicBreak => Block::ThematicBreak,
+            Kind::FencedCode { language, .. } => Block::Code {
+                language: language.clone(),
+                code: node.content.clone(),
+            },
+            Kind::IndentedCode => Block::Code {
+                language: String::new(),
+                code: node.content.clone(),
+            },
+            Kind::Html { .. } => Block::Html(node.content.trim_end().to_owned()),
+            Kind::BlockQuote => Block::Quote(self.blocks(&node.children)),
+            Kind::List { marker } => {
+                let nodes = self.nodes;
+                let items: Vec<&Node> = node.children.iter().map(|&i| &nodes[i]).collect();
+
+                // A list is loose if any of its items, or any blocks directly in them,
+                // are separated by blank lines.
+                let separated = |nodes: &[&Node]| {
+                    nodes
+                        .windows(2)
+                        .any(|pair| pair[0].end_line + 1 < pair[1].start_line)
+                };
+                let tight = !separated(&items)
+                    && items.iter().all(|item| {
+                        let children: Vec<&Node> =
+                            item.children.iter().map(|&i| &nodes[i]).collect();
+                        !separated(&children)
+                    });
+
+                Block::List {
+                    start: marker.start,
+                    tight,
+                    items: items.iter().map(|item| self.list_item(item)).collect(),
+                }
+            }
+            Kind::Table { alignments, rows } => {
+                let mut rows = rows
+                    .iter()
+                    .map(|row| row.iter().map(|cell| self.inlines(cell)).collect());
+                Block::Table {
+                    alignments: alignments.clone(),
+                    header: rows.next().unwrap_or_default(),
+                    rows: rows.collect(),
+                }
+            }
+        }
+    }
+
+    fn list_item(&mut self, item: &Node) -> ListItem {
+        let mut blocks = self.blocks(&item.children);
+
+        // Task list item?
+        let mut checked = None;
+        if let Some(Block::Paragraph(_)) = blocks.first() {
+            let paragraph = &self.nodes[item.children[0]];
+            let content = &paragraph.content;
+            for (prefix, is_checked) in [("[ ]", false), ("[x]", true), ("[X]", true)] {
+                if let Some(rest) = content.strip_prefix(prefix) {
+                    if rest.starts_with([' ', '\n']) {
+                        checked = Some(is_checked);
+                        if rest.trim().is_empty() {
+                            blocks.remove(0);
+                        } else {
+                            blocks[0] = Block::Paragraph(self.inlines(rest.trim()));
+                        }
+                    }
+                }
+            }
+        }
+
+        ListItem { checked, blocks }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Inline content
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Emphasis {
+    Italics,
+    Strong,
+    Strikethrough,
+}
+
+struct Delimiter {
+    ch: char,
+
+    /// How many of the delimiter characters are left, i.e. not yet used for emphasis.
+    count: usize,
+
+    original_count: usize,
+    can_open: bool,
+    can_close: bool,
+    opens: Vec<Emphasis>,
+    closes: Vec<Emphasis>,
+}
+
+enum Piece {
+    Text(String),
+    Code(String),
+
+    /// A run of `*`, `_` or `~`.
+    Delimiter(Delimiter),
+
+    /// A `[` or `![` that may turn out to start a link.
+    Bracket {
+        image: bool,
+    },
+
+    LinkStart {
+        url: String,
+        image: bool,
+    },
+    LinkEnd,
+}
+
+struct Bracket {
+    /// Index into [`InlineParser::pieces`].
+    piece: usize,
+
+    image: bool,
+
+    /// Links can't contain other links, so brackets before a link are deactivated.
+    active: bool,
+
+    /// Byte offset of the link text.
+    text_start: usize,
+}
+
+struct InlineParser<'a> {
+    text: &'a str,
+    pos: usize,
+    definitions: &'a Definitions,
+    pieces: Vec<Piece>,
+
+    /// Indices of the [`Piece::Delimiter`]s that can still be used for emphasis.
+    delimiters: Vec<usize>,
+
+    brackets: Vec<Bracket>,
+}
+
+impl<'a> InlineParser<'a> {
+    fn new(text: &'a str, definitions: &'a Definitions) -> Self {
+        Self {
+            text,
+            pos: 0,
+            definitions,
+            pieces: Vec::new(),
+            delimiters: Vec::new(),
+            brackets: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> Vec<Inline> {
+        while let Some(c) = self.text[self.pos..].chars().next() {
+            match c {
+                '\\' => self.backslash(),
+                '`' => self.code_span(),
+                '*' | '_' | '~' => self.delimiter_run(c),
+                '[' => self.open_bracket(false, 1),
+                '!' if self.text[self.pos..].starts_with("![") => self.open_bracket(true, 2),
+                ']' => self.close_bracket(),
+                '<' => self.angle_bracket(),
+                '&' => {
+                    if let Some((decoded, len)) = entity(&self.text[self.pos..]) {
+                        self.push_text(&decoded);
+                        self.pos += len;
+                    } else {
+                        self.push_text("&");
+                        self.pos += 1;
+                    }
+                }
+                '\n' => self.line_break(false),
+                'h' | 'w' => {
+                    if !self.bare_autolink() {
+                        self.push_text(&c.to_string());
+                        self.pos += 1;
+                    }
+                }
+                _ => {
+                    let rest = &self.text[self.pos..];
+                    let len = rest
+                        .find([
+                            '\\', '`', '*', '_', '~', '[', '!', ']', '<', '&', '\n', 'h', 'w',
+                        ])
+                        .unwrap_or(rest.len())
+                        .max(c.len_utf8());
+                    self.push_text(&rest[..len]);
+                    self.pos += len;
+                }
+            }
+        }
+
+        self.process_emphasis(0);
+        self.into_inlines()
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some(Piece::Text(last)) = self.pieces.last_mut() {
+            last.push_str(text);
+        } else {
+            self.pieces.push(Piece::Text(text.to_owned()));
+        }
+    }
+
+    fn previous_char(&self) -> Option<char> {
+        self.text[..self.pos].chars().next_back()
+    }
+
+    fn backslash(&mut self) {
+        let next = self.text[self.pos + 1..].chars().next();
+        match next {
+            Some('\n') => {
+                self.pos += 1;
+                self.line_break(true);
+            }
+            Some(c) if c.is_ascii_punctuation() => {
+                self.push_text(&c.to_string());
+                self.pos += 2;
+            }
+            _ => {
+                self.push_text("\\");
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn line_break(&mut self, hard: bool) {
+        let mut hard = hard;
+        let mut after_break = false;
+        if let Some(Piece::Text(last)) = self.pieces.last_mut() {
+            let trimmed_len = last.trim_end_matches(' ').len();
+            hard |= trimmed_len + 2 <= last.len();
+            last.truncate(trimmed_len);
+            after_break = last.ends_with('\n');
+        }
+        if hard {
+            self.push_text("\n");
+        } else if !after_break {
+            // A soft line break is just a space, unless it comes right after a `<br>`.
+            self.push_text(" ");
+        }
+
+        // Skip the newline and the indentation of the next line:
+        self.pos += 1;
+        self.pos += count_indent(self.text, self.pos);
+    }
+
+    fn code_span(&mut self) {
+        let rest = &self.text[self.pos..];
+        let run = rest.bytes().take_while(|&b| b == b'`').count();
+
+        // Find a closing run of the same length:
+        let mut search = run;
+        while let Some(offset) = rest[search..].find('`') {
+            let start = search + offset;
+            let len = rest[start..].bytes().take_while(|&b| b == b'`').count();
+            if len == run {
+                let mut code = rest[run..start].replace('\n', " ");
+                if 2 <= code.len()
+                    && code.starts_with(' ')
+                    && code.ends_with(' ')
+                    && !code.bytes().all(|b| b == b' ')
+                {
+                    code = code[1..code.len() - 1].to_owned();
+                }
+                self.pieces.push(Piece::Code(code));
+                self.pos += start + run;
+                return;
+            }
+            search = start + len;
+        }
+
+        self.push_text(&rest[..run]);
+        self.pos += run;
+    }
+
+    fn delimiter_run(&mut self, ch: char) {
+        let count = self.text[self.pos..]
+            .chars()
+            .take_while(|&c| c == ch)
+            .count();
+        let before = self.previous_char();
+        let after = self.text[self.pos + count..].chars().next();
+
+        if ch == '~' && 2 < count {
+            let text = self.text;
+            self.push_text(&text[self.pos..self.pos + count]);
+            self.pos += count;
+            return;
+        }
+
+        let is_whitespace = |c: Option<char>| c.is_none_or(char::is_whitespace);
+        let is_punctuation =
+            |c: Option<char>| c.is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace());
+        let left_flanking = !is_whitespace(after)
+            && (!is_punctuation(after) || is_whitespace(before) || is_punctuation(before));
+        let right_flanking = !is_whitespace(before)
+            && (!is_punctuation(before) || is_whitespace(after) || is_punctuation(after));
+
+        let (can_open, can_close) = if ch == '_' {
+            (
+                left_flanking && (!right_flanking || is_punctuation(before)),
+                right_flanking && (!left_flanking || is_punctuation(after)),
+            )
+        } else {
+            (left_flanking, right_flanking)
+        };
+
+        self.pos += count;
+        self.delimiters.push(self.pieces.len());
+        self.pieces.push(Piece::Delimiter(Delimiter {
+            ch,
+            count,
+            original_count: count,
+            can_open,
+            can_close,
+            opens: Vec::new(),
+            closes: Vec::new(),
+        }));
+    }
+
+    fn open_bracket(&mut self, image: bool, len: usize) {
+        self.pos += len;
+        self.brackets.push(Bracket {
+            piece: self.pieces.len(),
+            image,
+            active: true,
+            text_start: self.pos,
+        });
+        self.pieces.push(Piece::Bracket { image });
+    }
+
+    fn close_bracket(&mut self) {
+        let closer = self.pos;
+        self.pos += 1;
+
+        let Some(bracket) = self.brackets.pop() else {
+            self.push_text("]");
+            return;
+        };
+        if !bracket.active {
+            self.push_text("]");
+            return;
+        }
+
+        let Some((url, end)) = self.link_target(bracket.text_start, closer) else {
+            self.push_text("]");
+            return;
+        };
+        self.pos = end;
+
+        self.pieces[bracket.piece] = Piece::LinkStart {
+            url,
+            image: bracket.image,
+        };
+        self.process_emphasis(bracket.piece + 1);
+        self.pieces.push(Piece::LinkEnd);
+
+        if !bracket.image {
+            // No links in links:
+            for bracket in &mut self.brackets {
+                if !bracket.image {
+                    bracket.active = false;
+                }
+            }
+        }
+    }
+
+    /// After the `]` of a link text: `(url)`, `[label]`, `[]` or nothing.
+    fn link_target(&self, text_start: usize, closer: usize) -> Option<(String, usize)> {
+        let after = closer + 1;
+        let lookup = |label: &str| self.definitions.get(&normalize_label(label)).cloned();
+
+        if self.text[after..].starts_with('(') {
+            if let Some(inline) = self.inline_link(after) {
+                return Some(inline);
+            }
+        }
+
+        if let Some((label, end)) = link_label(self.text, after) {
+            if label.is_empty() {
+                // Collapsed reference: `[foo][]`
+                return lookup(&self.text[text_start..closer]).map(|url| (url, end));
+            }
+            // Full reference: `[text][label]`
+            return lookup(&label).map(|url| (url, end));
+        }
+
+        // Shortcut reference: `[foo]`
+        lookup(&self.text[text_start..closer]).map(|url| (url, after))
+    }
+
+    /// `(url "title")`
+    fn inline_link(&self, open_paren: usize) -> Option<(String, usize)> {
+        let text = self.text;
+        let mut pos = skip_whitespace(text, open_paren + 1);
+        if text[pos..].starts_with(')') {
+            return Some((String::new(), pos + 1));
+        }
+
+        let (url, after_url) = link_destination(text, pos)?;
+        pos = skip_whitespace(text, after_url);
+        if after_url < pos {
+            if let Some(after_title) = link_title(text, pos) {
+                pos = skip_whitespace(text, after_title);
+            }
+        }
+
+        text[pos..].starts_with(')').then(|| (url, pos + 1))
+    }
+
+    /// Autolinks like `<https://example.com>`, or inline HTML.
+    fn angle_bracket(&mut self) {
+        let rest = &self.text[self.pos..];
+        let Some(end) = rest.find('>') else {
+            self.push_text("<");
+            self.pos += 1;
+            return;
+        };
+        let inner = &rest[1..end];
+
+        let url = if is_uri(inner) {
+            Some(inner.to_owned())
+        } else if is_email(inner) {
+            Some(format!("mailto:{inner}"))
+        } else {
+            None
+        };
+
+        if let Some(url) = url {
+            self.pieces.push(Piece::LinkStart { url, image: false });
+            self.push_text(inner);
+            self.pieces.push(Piece::LinkEnd);
+            self.pos += end + 1;
+        } else if is_html_tag(inner) {
+            let tag = &rest[..=end];
+            let lower = tag.to_lowercase();
+            if lower == "<br>" || lower == "<br/>" || lower == "<br />" {
+                self.push_text("\n");
+            } else if !tag.starts_with("<!--") {
+                // We can't render HTML, so show it as it is:
+                self.push_text(tag);
+            }
+            self.pos += end + 1;
+        } else {
+            self.push_text("<");
+            self.pos += 1;
+        }
+    }
+
+    /// GitHub Flavored Markdown autolinks: `https://example.com` and `www.example.com`.
+    fn bare_autolink(&mut self) -> bool {
+        let at_word_start = self
+            .previous_char()
+            .is_none_or(|c| c.is_whitespace() || "(*_~".contains(c));
+        let in_link = self.brackets.iter().any(|bracket| !bracket.image);
+        if !at_word_start || in_link {
+            return false;
+        }
+
+        let rest = &self.text[self.pos..];
+        let prefix_len = ["https://", "http://", "www."]
+            .iter()
+            .find(|prefix| rest.starts_with(*prefix))
+            .map(|prefix| prefix.len());
+        let Some(prefix_len) = prefix_len else {
+            return false;
+        };
+
+        let mut len = rest
+            .find(|c: char| c.is_whitespace() || c == '<')
+            .unwrap_or(rest.len());
+        // Trailing punctuation is not part of the link:
+        loop {
+            let link = &rest[..len];
+            let unbalanced_paren =
+                link.ends_with(')') && link.matches('(').count() < link.matches(')').count();
+            if link.ends_with(['?', '!', '.', ',', ':', '*', '_', '~', '\'', '"'])
+                || unbalanced_paren
+            {
+                len -= 1;
+            } else {
+                break;
+            }
+        }
+        if len <= prefix_len {
+            return false;
+        }
+
+        let link = &rest[..len];
+        let url = if link.starts_with("www.") {
+            format!("http://{link}")
+        } else {
+            link.to_owned()
+        };
+        self.pieces.push(Piece::LinkStart { url, image: false });
+        self.pieces.push(Piece::Text(link.to_owned()));
+        self.pieces.push(Piece::LinkEnd);
+        self.pos += len;
+        true
+    }
+
+    /// Match up delimiter runs into emphasis, for all delimiters at or after the given piece.
+    fn process_emphasis(&mut self, first_piece: usize) {
+        let bottom = self
+            .delimiters
+            .iter()
+            .position(|&piece| first_piece <= piece)
+            .unwrap_or(self.delimiters.len());
+
+        // For each kind of closer, the first piece that can be an opener for it:
+        let mut openers_bottom: ahash::HashMap<(char, bool, usize), usize> = Default::default();
+
+        let mut closer_index = bottom;
+        while closer_index < self.delimiters.len() {
+            let closer_piece = self.delimiters[closer_index];
+            let Piece::Delimiter(closer) = &self.pieces[closer_piece] else {
+                unreachable!("delimiters only point to delimiter pieces");
+            };
+            let (ch, count, original_count, can_open) = (
+                closer.ch,
+                closer.count,
+                closer.original_count,
+                closer.can_open,
+            );
+            if !closer.can_close {
+                closer_index += 1;
+                continue;
+            }
+
+            let key = (ch, can_open, original_count % 3);
+            let floor = openers_bottom.get(&key).copied().unwrap_or(first_piece);
+
+            let opener_index = (bottom..closer_index)
+                .rev()
+                .take_while(|&i| floor <= self.delimiters[i])
+                .find(|&i| {
+                    let Piece::Delimiter(opener) = &self.pieces[self.delimiters[i]] else {
+                        return false;
+                    };
+                    if opener.ch != ch || !opener.can_open {
+                        return false;
+                    }
+                    if ch == '~' {
+                        return opener.count == count;
+                    }
+                    // The "rule of 3":
+                    let sum = opener.original_count + original_count;
+                    !((opener.can_close || can_open)
+                        && sum % 3 == 0
+                        && (opener.original_count % 3 != 0 || original_count % 3 != 0))
+                });
+
+            let Some(opener_index) = opener_index else {
+                openers_bottom.insert(key, closer_piece);
+                if can_open {
+                    closer_index += 1;
+                } else {
+                    self.delimiters.remove(closer_index);
+                }
+                continue;
+            };
+
+            let opener_piece = self.delimiters[opener_index];
+            let Piece::Delimiter(opener) = &mut self.pieces[opener_piece] else {
+                unreachable!("delimiters only point to delimiter pieces");
+            };
+            let (used, emphasis) = if ch == '~' {
+                (count, Emphasis::Strikethrough)
+            } else if 2 <= opener.count && 2 <= count {
+                (2, Emphasis::Strong)
+            } else {
+                (1, Emphasis::Italics)
+            };
+            opener.count -= used;
+            opener.opens.push(emphasis);
+            let opener_count = opener.count;
+
+            let Piece::Delimiter(closer) = &mut self.pieces[closer_piece] else {
+                unreachable!("delimiters only point to delimiter pieces");
+            };
+            closer.count -= used;
+            closer.closes.push(emphasis);
+            let closer_count = closer.count;
+
+            // Delimiters between the opener and closer can't be used anymore:
+            self.delimiters.drain(opener_index + 1..closer_index);
+            closer_index = opener_index + 1;
+
+            if opener_count == 0 {
+                self.delimiters.remove(opener_index);
+                closer_index -= 1;
+            }
+            if closer_count == 0 {
+                self.delimiters.remove(closer_index);
+            }
+        }
+
+        self.delimiters.truncate(bottom);
+    }
+
+    fn into_inlines(self) -> Vec<Inline> {
+        let mut inlines = Vec::new();
+        let mut open = Vec::new();
+        let mut italics = 0;
+        let mut strong = 0;
+        let mut strikethrough = 0;
+
+        for piece in self.pieces {
+            let style = Style {
+                strong: 0 < strong,
+                italics: 0 < italics,
+                strikethrough: 0 < strikethrough,
+                code: false,
+            };
+            match piece {
+                Piece::Text(text) => emit(&mut inlines, &mut open, &text, style),
+                Piece::Code(code) => {
+                    emit(
+                        &mut inlines,
+                        &mut open,
+                        &code,
+                        Style {
+                            code: true,
+                            ..style
+                        },
+                    );
+                }
+                Piece::Bracket { image } => {
+                    let bracket = if image { "![" } else { "[" };
+                    emit(&mut inlines, &mut open, bracket, style);
+                }
+                Piece::Delimiter(delimiter) => {
+                    for emphasis in &delimiter.closes {
+                        match emphasis {
+                            Emphasis::Italics => italics -= 1,
+                            Emphasis::Strong => strong -= 1,
+                            Emphasis::Strikethrough => strikethrough -= 1,
+                        }
+                    }
+
+                    // Whatever wasn't used for emphasis is just text:
+                    let style = Style {
+                        strong: 0 < strong,
+                        italics: 0 < italics,
+                        strikethrough: 0 < strikethrough,
+                        code: false,
+                    };
+                    let literal = delimiter.ch.to_string().repeat(delimiter.count);
+                    emit(&mut inlines, &mut open, &literal, style);
+
+                    for emphasis in delimiter.opens.iter().rev() {
+                        match emphasis {
+                            Emphasis::Italics => italics += 1,
+                            Emphasis::Strong => strong += 1,
+                            Emphasis::Strikethrough => strikethrough += 1,
+                        }
+                    }
+                }
+                Piece::LinkStart { url, image: false } => open.push(OpenLink::Link(url)),
+                Piece::LinkStart { url, image: true } => open.push(OpenLink::Image {
+                    url,
+                    alt: String::new(),
+                }),
+                Piece::LinkEnd => {
+                    if let Some(OpenLink::Image { url, alt }) = open.pop() {
+                        if let Some(OpenLink::Image { alt: outer_alt, .. }) = open.last_mut() {
+                            // An image in the description of an image.
+                            outer_alt.push_str(&alt);
+                        } else {
+                            let link = current_link(&open).cloned();
+                            inlines.push(Inline::Image { url, alt, link });
+                        }
+                    }
+                }
+            }
+        }
+
+        inlines
+    }
+}
+
+/// A link or image we are inside of, while turning [`Piece`]s into [`Inline`]s.
+enum OpenLink {
+    Link(String),
+    Image { url: String, alt: String },
+}
+
+fn current_link(open: &[OpenLink]) -> Option<&String> {
+    open.iter().rev().find_map(|open| match open {
+        OpenLink::Link(url) => Some(url),
+        OpenLink::Image { .. } => None,
+    })
+}
+
+fn emit(inlines: &mut Vec<Inline>, open: &mut [OpenLink], text: &str, style: Style) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(OpenLink::Image { alt, .. }) = open.last_mut() {
+        alt.push_str(text);
+        return;
+    }
+
+    let link = current_link(open);
+    if let Some(Inline::Text {
+        text: last_text,
+        style: last_style,
+        link: last_link,
+    }) = inlines.last_mut()
+    {
+        if *last_style == style && last_link.as_ref() == link {
+            last_text.push_str(text);
+            return;
+        }
+    }
+    inlines.push(Inline::Text {
+        text: text.to_owned(),
+        style,
+        link: link.cloned(),
+    });
+}
+
+fn is_uri(text: &str) -> bool {
+    let Some((scheme, rest)) = text.split_once(':') else {
+        return false;
+    };
+    (2..=32).contains(&scheme.len())
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+        && !rest.contains(|c: char| c.is_whitespace() || c.is_control() || c == '<')
+}
+
+fn is_email(text: &str) -> bool {
+    let Some((local, domain)) = text.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(c))
+        && !domain.is_empty()
+        && domain.split('.').all(|part| {
+            !part.is_empty()
+                && !part.starts_with('-')
+                && !part.ends_with('-')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// The inside of `<…>`: an opening or closing tag, or a comment.
+fn is_html_tag(inner: &str) -> bool {
+    if inner.starts_with("!--") {
+        return inner.ends_with("--");
+    }
+    let name = inner.strip_prefix('/').unwrap_or(inner);
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !name.contains('<')
+        && name
+            .chars()
+            .take_while(|c| !c.is_whitespace() && *c != '/')
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Inline {
+        styled(text, Style::default())
+    }
+
+    fn styled(text: &str, style: Style) -> Inline {
+        Inline::Text {
+            text: text.to_owned(),
+            style,
+            link: None,
+        }
+    }
+
+    fn link(text: &str, url: &str) -> Inline {
+        Inline::Text {
+            text: text.to_owned(),
+            style: Style::default(),
+            link: Some(url.to_owned()),
+        }
+    }
+
+    fn paragraph(inlines: Vec<Inline>) -> Block {
+        Block::Paragraph(inlines)
+    }
+
+    const STRONG: Style = Style {
+        strong: true,
+        italics: false,
+        strikethrough: false,
+        code: false,
+    };
+    const ITALICS: Style = Style {
+        strong: false,
+        italics: true,
+        strikethrough: false,
+        code: false,
+    };
+    const CODE: Style = Style {
+        strong: false,
+        italics: false,
+        strikethrough: false,
+        code: true,
+    };
+
+    #[test]
+    fn test_headings() {
+        assert_eq!(
+            parse("# Hello *world* #\nSetext\n===\n\n## Hello world"),
+            [
+                Block::Heading {
+                    level: 1,
+                    anchor: "hello-world".to_owned(),
+                    content: vec![text("Hello "), styled("world", ITALICS)],
+                },
+                Block::Heading {
+                    level: 1,
+                    anchor: "setext".to_owned(),
+                    content: vec![text("Setext")],
+                },
+                Block::Heading {
+                    level: 2,
+                    anchor: "hello-world-1".to_owned(),
+                    content: vec![text("Hello world")],
+                },
+            ]
+        );
+        assert_eq!(
+            parse("#5 bolt\n\n####### seven"),
+            [
+                paragraph(vec![text("#5 bolt")]),
+                paragraph(vec![text("####### seven")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emphasis() {
+        assert_eq!(
+            parse("*a* **b** ***c*** _d_ snake_case_word ~~e~~ **f*"),
+            [paragraph(vec![
+                styled("a", ITALICS),
+                text(" "),
+                styled("b", STRONG),
+                text(" "),
+                styled(
+                    "c",
+                    Style {
+                        strong: true,
+                        italics: true,
+                        ..Default::default()
+                    }
+                ),
+                text(" "),
+                styled("d", ITALICS),
+                text(" snake_case_word "),
+                styled(
+                    "e",
+                    Style {
+                        strikethrough: true,
+                        ..Default::default()
+                    }
+                ),
+                text(" *"),
+                styled("f", ITALICS),
+            ])]
+        );
+        assert_eq!(
+            parse("`*not emphasis*` \\*escaped\\* &amp; &#65;"),
+            [paragraph(vec![
+                styled("*not emphasis*", CODE),
+                text(" *escaped* & A"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_links_and_images() {
+        assert_eq!(
+            parse(
+                "[a](https://a.com \"title\") [b][ref] [ref] <https://c.com> https://d.com.\n\n[ref]: https://ref.com"
+            ),
+            [paragraph(vec![
+                link("a", "https://a.com"),
+                text(" "),
+                link("b", "https://ref.com"),
+                text(" "),
+                link("ref", "https://ref.com"),
+                text(" "),
+                link("https://c.com", "https://c.com"),
+                text(" "),
+                link("https://d.com", "https://d.com"),
+                text("."),
+            ])]
+        );
+        assert_eq!(
+            parse("[![badge](badge.svg)](https://ci.com) [not a link]"),
+            [paragraph(vec![
+                Inline::Image {
+                    url: "badge.svg".to_owned(),
+                    alt: "badge".to_owned(),
+                    link: Some("https://ci.com".to_owned()),
+                },
+                text(" [not a link]"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            parse("- a\n- b\n  - [x] c\n  - [ ] d\n\n3. e\n\n4. f"),
+            [
+                Block::List {
+                    start: None,
+                    tight: true,
+                    items: vec![
+                        ListItem {
+                            checked: None,
+                            blocks: vec![paragraph(vec![text("a")])],
+                        },
+                        ListItem {
+                            checked: None,
+                            blocks: vec![
+                                paragraph(vec![text("b")]),
+                                Block::List {
+                                    start: None,
+                                    tight: true,
+                                    items: vec![
+                                        ListItem {
+                                            checked: Some(true),
+                                            blocks: vec![paragraph(vec![text("c")])],
+                                        },
+                                        ListItem {
+                                            checked: Some(false),
+                                            blocks: vec![paragraph(vec![text("d")])],
+                                        },
+                                    ],
+                                },
+                            ],
+                        },
+                    ],
+                },
+                Block::List {
+                    start: Some(3),
+                    tight: false,
+                    items: vec![
+                        ListItem {
+                            checked: None,
+                            blocks: vec![paragraph(vec![text("e")])],
+                        },
+                        ListItem {
+                            checked: None,
+                            blocks: vec![paragraph(vec![text("f")])],
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_quotes_and_code() {
+        assert_eq!(
+            parse("> quote\nlazy\n> ```rust\n> let x = 1;\n> ```\n\n    indented\n\n---"),
+            [
+                Block::Quote(vec![
+                    paragraph(vec![text("quote lazy")]),
+                    Block::Code {
+                        language: "rust".to_owned(),
+                        code: "let x = 1;\n".to_owned(),
+                    },
+                ]),
+                Block::Code {
+                    language: String::new(),
+                    code: "indented\n".to_owned(),
+                },
+                Block::ThematicBreak,
+            ]
+        );
+        assert_eq!(
+            parse("<!-- comment -->\nText  \nbreak"),
+            [
+                Block::Html("<!-- comment -->".to_owned()),
+                paragraph(vec![text("Text\nbreak")]),
+            ]
+        );
+        assert_eq!(
+            parse("html<br>\nbreak"),
+            [paragraph(vec![text("html\nbreak")])]
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        assert_eq!(
+            parse("Intro\n| a | b |\n|:--|--:|\n| `1` | 2 \\| 3 |\n| 4 |\n\nafter"),
+            [
+                paragraph(vec![text("Intro")]),
+                Block::Table {
+                    alignments: vec![Alignment::Left, Alignment::Right],
+                    header: vec![vec![text("a")], vec![text("b")]],
+                    rows: vec![
+                        vec![vec![styled("1", CODE)], vec![text("2 | 3")]],
+                        vec![vec![text("4")], vec![]],
+                    ],
+                },
+                paragraph(vec![text("after")]),
+            ]
+        );
+    }
+}
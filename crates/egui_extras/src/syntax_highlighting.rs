@@ -1,10 +1,23 @@
 //! Syntax highlighting for code.
 //!
 //! Turn on the `syntect` feature for great syntax highlighting of any language.
+//!
+//! Turn on the `grammars` feature for lightweight highlighting of C, C++, GLSL, JavaScript, JSON,
+//! Python, Rust, shell, SQL, TOML, TypeScript, WGSL and YAML.
+//! It only re-highlights the lines that changed, so it is cheap to use for an editor.
+//! Grammars for more languages can be added with `GrammarBuilder` and `register_grammar`.
+//! Note that `syntect` takes priority, so the `grammars` feature does nothing if both are enabled.
+//!
 //! Otherwise, a very simple fallback will be used, that works okish for C, C++, Rust, and Python.
 
 #![allow(clippy::mem_forget)] // False positive from enum_map macro
 
+#[cfg(all(feature = "grammars", not(feature = "syntect")))]
+mod grammar;
+
+#[cfg(all(feature = "grammars", not(feature = "syntect")))]
+pub use grammar::{Grammar, GrammarBuilder, GrammarError, GrammarRule, register_grammar};
+
 use egui::TextStyle;
 use egui::text::LayoutJob;
 
@@ -86,6 +99,10 @@ fn highlight_inner(
                 HighlightSettings<'_>,
             ),
         ) -> LayoutJob {
+            #[cfg(all(feature = "grammars", not(feature = "syntect")))]
+            if let Some(job) = self.grammars.highlight(theme, code, lang) {
+                return job;
+            }
             Self::highlight(font_id.clone(), theme, code, lang, settings)
         }
     }
@@ -127,10 +144,11 @@ fn monospace_font_size(style: &egui::Style) -> f32 {
 
 // ----------------------------------------------------------------------------
 
+/// The kinds of text that a [`CodeTheme`] has a format for.
 #[cfg(not(feature = "syntect"))]
-#[derive(Clone, Copy, PartialEq, enum_map::Enum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, enum_map::Enum)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-enum TokenType {
+pub enum TokenType {
     Comment,
     Keyword,
     Literal,
@@ -492,7 +510,11 @@ impl std::hash::Hash for HighlightSettings<'_> {
 }
 
 #[derive(Default)]
-struct Highlighter;
+struct Highlighter {
+    /// Remembers the highlighting of each line, so that only edited lines need highlighting again.
+    #[cfg(all(feature = "grammars", not(feature = "syntect")))]
+    grammars: grammar::GrammarCache,
+}
 
 impl Highlighter {
     fn highlight(
@@ -566,6 +588,7 @@ impl Highlighter {
                         underline,
                         ..Default::default()
                     },
+                    inline_box: None,
                 });
             }
         }
@@ -0,0 +1,1369 @@
+//! A lightweight, incremental, grammar-based syntax highlighter.
+//!
+//! Each language is described by a [`Grammar`]: a set of states, each with an ordered list of
+//! regex rules, in the spirit of `TextMate` grammars. A rule can push a new state (e.g. when entering
+//! a block comment or a multi-line string) or pop back out of one.
+//!
+//! Text is tokenized one line at a time. The state stack at the start of each line is part of the
+//! cache key, so after an edit only the changed lines (and any lines whose start state changed)
+//! are tokenized again.
+//!
+//! Besides the built-in grammars, you can add your own with [`GrammarBuilder`] and [`register_grammar`].
+
+use std::sync::{
+    Arc, OnceLock,
+    atomic::{AtomicU64, Ordering},
+};
+
+use egui::{
+    mutex::RwLock,
+    text::{LayoutJob, LayoutSection},
+};
+use regex::Regex;
+
+use super::{CodeTheme, TokenType};
+
+/// Don't let runaway grammars grow the state stack forever.
+const MAX_STACK_DEPTH: usize = 32;
+
+/// Index into [`Grammar::states`].
+type StateIndex = u16;
+
+/// A syntax highlighting grammar for one language.
+///
+/// Create one with [`GrammarBuilder`].
+pub struct Grammar {
+    /// Unique for each grammar, so that lines tokenized by different grammars are cached separately.
+    id: u64,
+
+    /// Language names and file extensions this grammar is used for, in lowercase.
+    names: Vec<String>,
+
+    /// The first state is the root state.
+    states: Vec<State>,
+}
+
+struct State {
+    /// Used for any text not matched by a rule.
+    default: TokenType,
+
+    /// All rules of this state, combined into one alternation so that the leftmost match wins,
+    /// with ties broken by rule order.
+    regex: Regex,
+
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    /// The capture group of the combined regex that this rule corresponds to.
+    group: usize,
+
+    token: TokenType,
+
+    /// Token types of the rule's own capture groups, in order.
+    captures: Vec<TokenType>,
+
+    action: Action,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    None,
+    Push(StateIndex),
+    Pop,
+}
+
+fn registered_grammars() -> &'static RwLock<Vec<Arc<Grammar>>> {
+    static REGISTERED: OnceLock<RwLock<Vec<Arc<Grammar>>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+/// Use the given grammar to highlight its languages from now on.
+///
+/// Registered grammars take priority over the built-in ones, and later registrations over earlier ones,
+/// so this can also be used to replace a built-in grammar.
+///
+/// Highlighted code is memoized, so register your grammars before any code is shown.
+pub fn register_grammar(grammar: Grammar) {
+    registered_grammars().write().push(Arc::new(grammar));
+}
+
+impl Grammar {
+    /// Find a grammar by language name or file extension.
+    ///
+    /// Grammars added with [`register_grammar`] are searched before the built-in ones.
+    pub fn find(language: &str) -> Option<Arc<Self>> {
+        static BUILTIN: OnceLock<Vec<Arc<Grammar>>> = OnceLock::new();
+
+        let language = language.to_lowercase();
+        let matches = |grammar: &&Arc<Self>| grammar.names.contains(&language);
+
+        if let Some(grammar) = registered_grammars().read().iter().rev().find(matches) {
+            return Some(grammar.clone());
+        }
+
+        BUILTIN
+            .get_or_init(|| {
+                builtin::all()
+                    .into_iter()
+                    .map(|builder| Arc::new(builder.build().expect("built-in grammars are valid")))
+                    .collect()
+            })
+            .iter()
+            .find(matches)
+            .cloned()
+    }
+
+    /// The language names and file extensions this grammar is used for, in lowercase.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Tokenize one line (without the trailing newline).
+    ///
+    /// `stack` is the state stack at the start of the line, and is left as the state at the end of it.
+    /// Tokens are pushed as `(end_byte_offset, token_type)`, each starting where the previous one ended.
+    fn tokenize_line(
+        &self,
+        line: &str,
+        stack: &mut Vec<StateIndex>,
+        tokens: &mut Vec<(usize, TokenType)>,
+    ) {
+        let mut push_token = |end: usize, token: TokenType| {
+            let start = tokens.last().map_or(0, |&(end, _)| end);
+            if start < end {
+                tokens.push((end, token));
+            }
+        };
+
+        let mut state_index = *stack.last().unwrap_or(&0);
+        let mut locs = self.states[state_index as usize].regex.capture_locations();
+        let mut pos = 0;
+        let mut last_empty_match = None;
+
+        while pos < line.len() {
+            let current = *stack.last().unwrap_or(&0);
+            if current != state_index {
+                state_index = current;
+                locs = self.states[state_index as usize].regex.capture_locations();
+            }
+            let state = &self.states[state_index as usize];
+
+            let Some(m) = state.regex.captures_read_at(&mut locs, line, pos) else {
+                push_token(line.len(), state.default);
+                break;
+            };
+
+            if m.is_empty() {
+                if last_empty_match == Some((pos, stack.len())) {
+                    // The grammar is going around in circles - step over one character:
+                    let next = line[pos..]
+                        .chars()
+                        .next()
+                        .map_or(line.len(), |c| pos + c.len_utf8());
+                    push_token(next, state.default);
+                    pos = next;
+                    continue;
+                }
+                last_empty_match = Some((pos, stack.len()));
+            }
+
+            push_token(m.start(), state.default);
+
+            let Some(rule) = state
+                .rules
+                .iter()
+                .find(|rule| locs.get(rule.group).is_some())
+            else {
+                // Can't happen: one of the rules must have matched.
+                push_token(line.len(), state.default);
+                break;
+            };
+
+            for (i, &token) in rule.captures.iter().enumerate() {
+                if let Some((start, end)) = locs.get(rule.group + 1 + i) {
+                    push_token(start, rule.token);
+                    push_token(end, token);
+                }
+            }
+            push_token(m.end(), rule.token);
+
+            match rule.action {
+                Action::None => {}
+                Action::Push(state) => {
+                    if stack.len() < MAX_STACK_DEPTH {
+                        stack.push(state);
+                    }
+                }
+                Action::Pop => {
+                    if 1 < stack.len() {
+                        stack.pop();
+                    }
+                }
+            }
+
+            pos = m.end();
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A tokenized line, shared by all identical lines that start in the same state.
+struct CachedLine {
+    /// State stack at the end of the line.
+    end_stack: Vec<StateIndex>,
+
+    /// `(end_byte_offset, token_type)`.
+    tokens: Vec<(usize, TokenType)>,
+
+    /// When this line was last used, in calls to [`GrammarCache::highlight`].
+    generation: u64,
+}
+
+/// Remembers how each line was tokenized, so that editing a large text
+/// only re-tokenizes the lines that are affected by the edit.
+#[derive(Default)]
+pub struct GrammarCache {
+    generation: u64,
+
+    /// Keyed by the hash of grammar, start state stack and line contents.
+    lines: ahash::HashMap<u64, CachedLine>,
+}
+
+impl GrammarCache {
+    /// Highlight the given code, or return `None` if there is no grammar for the language.
+    pub fn highlight(
+        &mut self,
+        theme: &CodeTheme,
+        code: &str,
+        language: &str,
+    ) -> Option<LayoutJob> {
+        profiling::function_scope!();
+
+        let grammar = Grammar::find(language)?;
+        self.generation += 1;
+
+        let mut job = LayoutJob {
+            text: code.into(),
+            ..Default::default()
+        };
+        let mut last_token = None;
+        let mut append = |byte_range: std::ops::Range<usize>, token: TokenType| {
+            if last_token == Some(token) {
+                if let Some(last) = job.sections.last_mut() {
+                    last.byte_range.end = byte_range.end;
+                    return;
+                }
+            }
+            last_token = Some(token);
+            job.sections.push(LayoutSection {
+                leading_space: 0.0,
+                byte_range,
+                format: theme.formats[token].clone(),
+                inline_box: None,
+            });
+        };
+
+        let mut stack = vec![0];
+        let mut num_lines = 0;
+        let mut line_start = 0;
+
+        for line in code.split_inclusive('\n') {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let key = egui::util::hash((grammar.id, &stack, content));
+
+            let cached = self.lines.entry(key).or_insert_with(|| {
+                let mut end_stack = stack.clone();
+                let mut tokens = Vec::new();
+                grammar.tokenize_line(content, &mut end_stack, &mut tokens);
+                CachedLine {
+                    end_stack,
+                    tokens,
+                    generation: 0,
+                }
+            });
+            cached.generation = self.generation;
+
+            let mut token_start = line_start;
+            for &(end, token) in &cached.tokens {
+                append(token_start..line_start + end, token);
+                token_start = line_start + end;
+            }
+            if content.len() < line.len() {
+                append(
+                    line_start + content.len()..line_start + line.len(),
+                    TokenType::Whitespace,
+                );
+            }
+
+            stack.clone_from(&cached.end_stack);
+            line_start += line.len();
+            num_lines += 1;
+        }
+
+        // Forget lines that are no longer in use, but only once there are many of them,
+        // so that highlighting a few different texts in turn doesn't thrash the cache.
+        if 2 * num_lines + 1024 < self.lines.len() {
+            let generation = self.generation;
+            self.lines.retain(|_, line| line.generation == generation);
+        }
+
+        Some(job)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Why a [`GrammarBuilder`] could not build a [`Grammar`].
+#[derive(Debug)]
+pub enum GrammarError {
+    /// The grammar has no states.
+    NoStates,
+
+    /// The grammar has more states than fit in a [`StateIndex`].
+    TooManyStates,
+
+    /// A rule pushes a state that the grammar doesn't have.
+    UnknownState(String),
+
+    /// A rule pattern is not a valid regular expression.
+    BadPattern {
+        pattern: String,
+        error: regex::Error,
+    },
+
+    /// A rule has more capture token types than its pattern has capture groups.
+    TooManyCaptures { pattern: String },
+}
+
+impl std::fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoStates => write!(f, "The grammar has no states"),
+            Self::TooManyStates => write!(f, "The grammar has too many states"),
+            Self::UnknownState(name) => write!(f, "Unknown state {name:?}"),
+            Self::BadPattern { pattern, error } => write!(f, "Bad pattern {pattern:?}: {error}"),
+            Self::TooManyCaptures { pattern } => write!(
+                f,
+                "More capture token types than capture groups in {pattern:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+/// A rule of a [`GrammarBuilder`] state: a regex, and the [`TokenType`] of the text it matches.
+pub struct GrammarRule {
+    pattern: String,
+    token: TokenType,
+    captures: Vec<TokenType>,
+    push: Option<String>,
+    pop: bool,
+}
+
+impl GrammarRule {
+    /// Text matching the regex `pattern` gets the given token type.
+    pub fn new(pattern: impl Into<String>, token: TokenType) -> Self {
+        Self {
+            pattern: pattern.into(),
+            token,
+            captures: Vec::new(),
+            push: None,
+            pop: false,
+        }
+    }
+
+    /// Give the capture groups of the pattern their own token types.
+    #[inline]
+    pub fn captures(mut self, captures: &[TokenType]) -> Self {
+        self.captures = captures.to_vec();
+        self
+    }
+
+    /// Enter the named state after this rule matches.
+    #[inline]
+    pub fn push(mut self, state: impl Into<String>) -> Self {
+        self.push = Some(state.into());
+        self
+    }
+
+    /// Return to the previous state after this rule matches.
+    #[inline]
+    pub fn pop(mut self) -> Self {
+        self.pop = true;
+        self
+    }
+}
+
+/// Describes a [`Grammar`] as a set of named states, each with an ordered list of [`GrammarRule`]s.
+///
+/// In each state the leftmost match wins, with ties broken by rule order.
+///
+/// ```
+/// use egui_extras::syntax_highlighting::{GrammarBuilder, GrammarRule, TokenType};
+///
+/// let grammar = GrammarBuilder::new(&["ini"])
+///     .state(
+///         "root",
+///         TokenType::Punctuation,
+///         vec![
+///             GrammarRule::new(";.*", TokenType::Comment),
+///             GrammarRule::new(r"\[[^\]]*\]", TokenType::Keyword),
+///             GrammarRule::new("\"", TokenType::StringLiteral).push("string"),
+///             GrammarRule::new(r"\w+", TokenType::Literal),
+///         ],
+///     )
+///     .state(
+///         "string",
+///         TokenType::StringLiteral,
+///         vec![GrammarRule::new("\"", TokenType::StringLiteral).pop()],
+///     )
+///     .build()?;
+/// egui_extras::syntax_highlighting::register_grammar(grammar);
+/// # Ok::<(), egui_extras::syntax_highlighting::GrammarError>(())
+/// ```
+pub struct GrammarBuilder {
+    names: Vec<String>,
+    states: Vec<(String, TokenType, Vec<GrammarRule>)>,
+}
+
+impl GrammarBuilder {
+    /// The language names and file extensions the grammar is used for, e.g. `&["rs", "rust"]`.
+    ///
+    /// They are matched case-insensitively.
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_lowercase()).collect(),
+            states: Vec::new(),
+        }
+    }
+
+    /// Add a state, where any text not matched by one of the `rules` gets the `default` token type.
+    ///
+    /// The first state added is the root state.
+    #[inline]
+    pub fn state(
+        mut self,
+        name: impl Into<String>,
+        default: TokenType,
+        rules: Vec<GrammarRule>,
+    ) -> Self {
+        self.states.push((name.into(), default, rules));
+        self
+    }
+
+    /// Compile the rules of all states into a [`Grammar`].
+    ///
+    /// # Errors
+    /// If a pattern is not a valid regex, or a rule refers to a state that doesn't exist.
+    pub fn build(self) -> Result<Grammar, GrammarError> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let Self { names, states } = self;
+
+        if states.is_empty() {
+            return Err(GrammarError::NoStates);
+        }
+        if StateIndex::try_from(states.len()).is_err() {
+            return Err(GrammarError::TooManyStates);
+        }
+
+        let state_index = |name: &str| {
+            states
+                .iter()
+                .position(|(state_name, _, _)| state_name == name)
+                .map(|index| index as StateIndex)
+                .ok_or_else(|| GrammarError::UnknownState(name.to_owned()))
+        };
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|error| GrammarError::BadPattern {
+                pattern: pattern.to_owned(),
+                error,
+            })
+        };
+
+        let states = states
+            .iter()
+            .map(|(_, default, rule_defs)| {
+                let mut combined = String::new();
+                let mut rules = Vec::new();
+                let mut group = 1;
+                for def in rule_defs {
+                    let own_groups = compile(&def.pattern)?.captures_len() - 1;
+                    if own_groups < def.captures.len() {
+                        return Err(GrammarError::TooManyCaptures {
+                            pattern: def.pattern.clone(),
+                        });
+                    }
+
+                    if !combined.is_empty() {
+                        combined.push('|');
+                    }
+                    combined.push_str(&format!("({})", def.pattern));
+
+                    let action = if let Some(state) = &def.push {
+                        Action::Push(state_index(state)?)
+                    } else if def.pop {
+                        Action::Pop
+                    } else {
+                        Action::None
+                    };
+                    rules.push(Rule {
+                        group,
+                        token: def.token,
+                        captures: def.captures.clone(),
+                        action,
+                    });
+                    group += 1 + own_groups;
+                }
+                Ok(State {
+                    default: *default,
+                    regex: compile(&combined)?,
+                    rules,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Grammar {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            names,
+            states,
+        })
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+mod builtin {
+    //! The grammars that ship with `egui_extras`.
+
+    use super::{GrammarBuilder, GrammarRule, TokenType};
+    use TokenType::{Comment, Keyword, Literal, Punctuation, StringLiteral};
+
+    fn rule(pattern: impl Into<String>, token: TokenType) -> GrammarRule {
+        GrammarRule::new(pattern, token)
+    }
+
+    /// Matches any of the given words.
+    fn words(words: &[&str]) -> String {
+        format!(r"\b(?:{})\b", words.join("|"))
+    }
+
+    pub fn all() -> Vec<GrammarBuilder> {
+        vec![
+            cpp(),
+            glsl(),
+            javascript(),
+            json(),
+            python(),
+            rust(),
+            shell(),
+            sql(),
+            toml(),
+            typescript(),
+            wgsl(),
+            yaml(),
+        ]
+    }
+
+    // ------------------------------------------------------------------------
+    // Building blocks shared by several grammars:
+
+    const IDENTIFIER: &str = r"\w+";
+
+    fn double_quoted_string() -> GrammarRule {
+        rule(r#""(?:[^"\\]|\\.)*"?"#, StringLiteral)
+    }
+
+    fn single_quoted_string() -> GrammarRule {
+        rule(r"'(?:[^'\\]|\\.)*'?", StringLiteral)
+    }
+
+    /// Rules for the `block_comment` state.
+    fn block_comment(nested: bool) -> Vec<GrammarRule> {
+        let mut rules = vec![rule(r"\*/", Comment).pop()];
+        if nested {
+            rules.push(rule(r"/\*", Comment).push("block_comment"));
+        }
+        rules
+    }
+
+    /// Rules for a string with backslash escapes that may span several lines, ended by `end`.
+    fn escaped_string(end: &str) -> Vec<GrammarRule> {
+        vec![rule(r"\\.", StringLiteral), rule(end, StringLiteral).pop()]
+    }
+
+    // ------------------------------------------------------------------------
+
+    const CPP_KEYWORDS: &[&str] = &[
+        "alignas",
+        "alignof",
+        "and_eq",
+        "and",
+        "asm",
+        "auto",
+        "bitand",
+        "bitor",
+        "bool",
+        "break",
+        "case",
+        "catch",
+        "char",
+        "char16_t",
+        "char32_t",
+        "char8_t",
+        "class",
+        "co_await",
+        "co_return",
+        "co_yield",
+        "compl",
+        "concept",
+        "const_cast",
+        "const",
+        "consteval",
+        "constexpr",
+        "constinit",
+        "continue",
+        "decltype",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "dynamic_cast",
+        "else",
+        "enum",
+        "explicit",
+        "export",
+        "extern",
+        "false",
+        "float",
+        "for",
+        "friend",
+        "goto",
+        "if",
+        "inline",
+        "int",
+        "long",
+        "mutable",
+        "namespace",
+        "new",
+        "noexcept",
+        "not_eq",
+        "not",
+        "nullptr",
+        "operator",
+        "or_eq",
+        "or",
+        "private",
+        "protected",
+        "public",
+        "register",
+        "reinterpret_cast",
+        "requires",
+        "return",
+        "short",
+        "signed",
+        "sizeof",
+        "static_assert",
+        "static_cast",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "thread_local",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "typeid",
+        "typename",
+        "union",
+        "unsigned",
+        "using",
+        "virtual",
+        "void",
+        "volatile",
+        "wchar_t",
+        "while",
+        "xor_eq",
+        "xor",
+    ];
+
+    fn cpp() -> GrammarBuilder {
+        GrammarBuilder::new(&["c", "h", "cc", "cpp", "cxx", "hpp", "c++"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("//.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule(r"^\s*#\s*\w+", Keyword),
+                    rule(r#"(?:u8|[uUL])?""#, StringLiteral).push("string"),
+                    rule(r"'(?:[^'\\]|\\.)*'", StringLiteral),
+                    rule(words(CPP_KEYWORDS), Keyword),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(false))
+            .state("string", StringLiteral, escaped_string("\""))
+    }
+
+    fn glsl() -> GrammarBuilder {
+        GrammarBuilder::new(&["glsl", "vert", "frag", "geom", "comp", "tesc", "tese"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("//.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule(r"^\s*#\s*\w+", Keyword),
+                    rule(
+                        words(&[
+                            "attribute", "break", "buffer", "case", "centroid", "coherent",
+                            "const", "continue", "default", "discard", "do", "else", "false",
+                            "flat", "for", "highp", "if", "in", "inout", "invariant", "layout",
+                            "lowp", "mediump", "noperspective", "out", "patch", "precise",
+                            "precision", "readonly", "restrict", "return", "sample", "shared",
+                            "smooth", "struct", "subroutine", "switch", "true", "uniform",
+                            "varying", "volatile", "while", "writeonly",
+                        ]),
+                        Keyword,
+                    ),
+                    rule(
+                        r"\b(?:void|bool|int|uint|float|double|atomic_uint|[biud]?vec[234]|d?mat[234](?:x[234])?|[iu]?(?:sampler|image|texture)(?:1D|2D|3D|Cube|2DRect|Buffer|2DMS)(?:Array)?(?:Shadow)?|sampler(?:Shadow)?)\b",
+                        Keyword,
+                    ),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(false))
+    }
+
+    const JAVASCRIPT_KEYWORDS: &[&str] = &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "get",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "set",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+
+    const TYPESCRIPT_KEYWORDS: &[&str] = &[
+        "abstract",
+        "any",
+        "as",
+        "asserts",
+        "bigint",
+        "boolean",
+        "declare",
+        "enum",
+        "implements",
+        "infer",
+        "interface",
+        "is",
+        "keyof",
+        "namespace",
+        "never",
+        "number",
+        "object",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "satisfies",
+        "string",
+        "symbol",
+        "type",
+        "unique",
+        "unknown",
+    ];
+
+    fn javascript() -> GrammarBuilder {
+        ecmascript(
+            &["js", "javascript", "jsx", "mjs", "cjs"],
+            JAVASCRIPT_KEYWORDS,
+        )
+    }
+
+    fn typescript() -> GrammarBuilder {
+        ecmascript(
+            &["ts", "typescript", "tsx", "mts", "cts"],
+            &[JAVASCRIPT_KEYWORDS, TYPESCRIPT_KEYWORDS].concat(),
+        )
+    }
+
+    fn ecmascript(names: &[&str], keywords: &[&str]) -> GrammarBuilder {
+        let code_rules = || {
+            vec![
+                rule("//.*", Comment),
+                rule(r"/\*", Comment).push("block_comment"),
+                double_quoted_string(),
+                single_quoted_string(),
+                rule("`", StringLiteral).push("template"),
+                rule(words(keywords), Keyword),
+                rule(r"[\w$]+", Literal),
+            ]
+        };
+
+        let mut template_expression = vec![
+            rule(r"\{", Punctuation).push("template_expression"),
+            rule(r"\}", Punctuation).pop(),
+        ];
+        template_expression.extend(code_rules());
+
+        GrammarBuilder::new(names)
+            .state("root", Punctuation, code_rules())
+            .state("block_comment", Comment, block_comment(false))
+            .state(
+                "template",
+                StringLiteral,
+                vec![
+                    rule(r"\\.", StringLiteral),
+                    rule(r"\$\{", Punctuation).push("template_expression"),
+                    rule("`", StringLiteral).pop(),
+                ],
+            )
+            .state("template_expression", Punctuation, template_expression)
+    }
+
+    fn json() -> GrammarBuilder {
+        GrammarBuilder::new(&["json", "jsonc", "json5", "geojson"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    // JSON has no comments, but many JSON files do anyway:
+                    rule("//.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule(r#"("(?:[^"\\]|\\.)*")\s*:"#, Punctuation).captures(&[Keyword]),
+                    double_quoted_string(),
+                    rule(r"-?\b\d+(?:\.\d+)?(?:[eE][+-]?\d+)?\b", Literal),
+                    rule(words(&["true", "false", "null"]), Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(false))
+    }
+
+    fn python() -> GrammarBuilder {
+        GrammarBuilder::new(&["py", "pyi", "python"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("#.*", Comment),
+                    // A string prefix (`r`, `b`, `f`, …) can't be confused with the end of an
+                    // identifier, since the whole identifier is matched before we get here.
+                    rule(r#"[rRbBuUfF]{0,2}""""#, StringLiteral).push("triple_double"),
+                    rule(r"[rRbBuUfF]{0,2}'''", StringLiteral).push("triple_single"),
+                    rule(r#"[rRbBuUfF]{0,2}"(?:[^"\\]|\\.)*"?"#, StringLiteral),
+                    rule(r"[rRbBuUfF]{0,2}'(?:[^'\\]|\\.)*'?", StringLiteral),
+                    rule(r"@[\w.]+", Keyword),
+                    rule(
+                        words(&[
+                            "and", "as", "assert", "async", "await", "break", "case", "class",
+                            "continue", "def", "del", "elif", "else", "except", "False", "finally",
+                            "for", "from", "global", "if", "import", "in", "is", "lambda", "match",
+                            "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+                            "try", "while", "with", "yield",
+                        ]),
+                        Keyword,
+                    ),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("triple_double", StringLiteral, escaped_string(r#"""""#))
+            .state("triple_single", StringLiteral, escaped_string("'''"))
+    }
+
+    fn rust() -> GrammarBuilder {
+        GrammarBuilder::new(&["rs", "rust"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("//.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule(r##"b?r#*""##, StringLiteral).push("raw_string"),
+                    rule(r#"b?""#, StringLiteral).push("string"),
+                    rule(r"b?'(?:[^'\\]|\\[^']+)'", StringLiteral),
+                    rule(r"'\w+", Literal),
+                    rule(
+                        words(&[
+                            "as", "async", "await", "break", "const", "continue", "crate", "dyn",
+                            "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+                            "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+                            "self", "Self", "static", "struct", "super", "trait", "true", "type",
+                            "unsafe", "use", "where", "while",
+                        ]),
+                        Keyword,
+                    ),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(true))
+            .state("string", StringLiteral, escaped_string("\""))
+            .state(
+                "raw_string",
+                StringLiteral,
+                vec![rule("\"#*", StringLiteral).pop()],
+            )
+    }
+
+    fn shell() -> GrammarBuilder {
+        let variable = || rule(r"\$(?:\{[^}]*\}?|\w+|[#?@*!$-])", Literal);
+
+        GrammarBuilder::new(&["sh", "bash", "zsh", "shell", "shellscript"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    variable(),
+                    rule(r"(?:^|\s)#.*", Comment),
+                    rule("\"", StringLiteral).push("double_quoted"),
+                    rule("'", StringLiteral).push("single_quoted"),
+                    rule(
+                        words(&[
+                            "alias", "break", "case", "continue", "declare", "do", "done", "elif",
+                            "else", "esac", "eval", "exec", "exit", "export", "fi", "for",
+                            "function", "if", "in", "local", "readonly", "return", "select", "set",
+                            "shift", "source", "then", "time", "trap", "unset", "until", "while",
+                        ]),
+                        Keyword,
+                    ),
+                    rule(r"[\w./-]+", Literal),
+                ],
+            )
+            .state(
+                "double_quoted",
+                StringLiteral,
+                vec![
+                    rule(r"\\.", StringLiteral),
+                    variable(),
+                    rule("\"", StringLiteral).pop(),
+                ],
+            )
+            .state(
+                "single_quoted",
+                StringLiteral,
+                vec![rule("'", StringLiteral).pop()],
+            )
+    }
+
+    fn sql() -> GrammarBuilder {
+        let keywords = words(&[
+            "add",
+            "all",
+            "alter",
+            "and",
+            "any",
+            "as",
+            "asc",
+            "begin",
+            "between",
+            "by",
+            "case",
+            "cast",
+            "check",
+            "column",
+            "commit",
+            "constraint",
+            "create",
+            "cross",
+            "database",
+            "default",
+            "delete",
+            "desc",
+            "distinct",
+            "drop",
+            "else",
+            "end",
+            "exists",
+            "false",
+            "foreign",
+            "from",
+            "full",
+            "group",
+            "having",
+            "if",
+            "in",
+            "index",
+            "inner",
+            "insert",
+            "into",
+            "is",
+            "join",
+            "key",
+            "left",
+            "like",
+            "limit",
+            "not",
+            "null",
+            "offset",
+            "on",
+            "or",
+            "order",
+            "outer",
+            "primary",
+            "references",
+            "returning",
+            "right",
+            "rollback",
+            "select",
+            "set",
+            "table",
+            "then",
+            "transaction",
+            "true",
+            "union",
+            "unique",
+            "update",
+            "using",
+            "values",
+            "view",
+            "when",
+            "where",
+            "with",
+            // Types:
+            "bigint",
+            "blob",
+            "boolean",
+            "char",
+            "date",
+            "decimal",
+            "double",
+            "float",
+            "int",
+            "integer",
+            "numeric",
+            "real",
+            "smallint",
+            "text",
+            "timestamp",
+            "varchar",
+        ]);
+
+        GrammarBuilder::new(&["sql"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("--.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule("'", StringLiteral).push("string"),
+                    rule(r#""[^"]*"?"#, Literal),
+                    rule(format!("(?i){keywords}"), Keyword),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(false))
+            .state(
+                "string",
+                StringLiteral,
+                vec![rule("''", StringLiteral), rule("'", StringLiteral).pop()],
+            )
+    }
+
+    fn toml() -> GrammarBuilder {
+        GrammarBuilder::new(&["toml"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("#.*", Comment),
+                    rule(r"^\s*\[\[?[^\]]*\]\]?", Keyword),
+                    rule(r#"""""#, StringLiteral).push("multiline_basic"),
+                    rule("'''", StringLiteral).push("multiline_literal"),
+                    double_quoted_string(),
+                    rule("'[^']*'?", StringLiteral),
+                    rule(words(&["true", "false"]), Keyword),
+                    rule(r"[\w.:+-]+", Literal),
+                ],
+            )
+            .state("multiline_basic", StringLiteral, escaped_string(r#"""""#))
+            .state(
+                "multiline_literal",
+                StringLiteral,
+                vec![rule("'''", StringLiteral).pop()],
+            )
+    }
+
+    fn wgsl() -> GrammarBuilder {
+        GrammarBuilder::new(&["wgsl"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule("//.*", Comment),
+                    rule(r"/\*", Comment).push("block_comment"),
+                    rule(r"@\w+", Keyword),
+                    rule(
+                        words(&[
+                            "alias", "break", "case", "const", "const_assert", "continue",
+                            "continuing", "default", "diagnostic", "discard", "else", "enable",
+                            "false", "fn", "for", "if", "let", "loop", "override", "requires",
+                            "return", "struct", "switch", "true", "var", "while",
+                        ]),
+                        Keyword,
+                    ),
+                    rule(
+                        r"\b(?:bool|f16|f32|i32|u32|vec[234][fhiu]?|mat[234]x[234][fh]?|array|atomic|ptr|sampler(?:_comparison)?|texture_\w+)\b",
+                        Keyword,
+                    ),
+                    rule(IDENTIFIER, Literal),
+                ],
+            )
+            .state("block_comment", Comment, block_comment(true))
+    }
+
+    fn yaml() -> GrammarBuilder {
+        GrammarBuilder::new(&["yaml", "yml"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    rule(r"(?:^|\s)#.*", Comment),
+                    rule(
+                        r#"^(\s*(?:- +)*)([\w$./][^:#]*?|"[^"]*"|'[^']*')\s*:(?:\s|$)"#,
+                        Punctuation,
+                    )
+                    .captures(&[Punctuation, Keyword]),
+                    rule("\"", StringLiteral).push("double_quoted"),
+                    rule("'(?:[^']|'')*'?", StringLiteral),
+                    rule(r"[&*][\w-]+", Keyword),
+                    rule(r"![\w!/.-]*", Keyword),
+                    rule(r"[\w.+/@~-]+", Literal),
+                ],
+            )
+            .state("double_quoted", StringLiteral, escaped_string("\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(language: &str, code: &str) -> Vec<(String, TokenType)> {
+        let grammar = Grammar::find(language).unwrap();
+        let mut stack = vec![0];
+        let mut result: Vec<(String, TokenType)> = vec![];
+        for line in code.lines() {
+            let mut tokens = vec![];
+            grammar.tokenize_line(line, &mut stack, &mut tokens);
+            let mut start = 0;
+            for (end, token) in tokens {
+                match result.last_mut() {
+                    Some((text, last_token)) if *last_token == token && 0 < start => {
+                        text.push_str(&line[start..end]);
+                    }
+                    _ => result.push((line[start..end].to_owned(), token)),
+                }
+                start = end;
+            }
+        }
+
+        // Skip whitespace and punctuation:
+        result
+            .into_iter()
+            .map(|(text, token)| (text.trim().to_owned(), token))
+            .filter(|(text, token)| !text.is_empty() && *token != TokenType::Punctuation)
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_grammars() {
+        use TokenType::{Comment, Keyword, Literal, StringLiteral};
+
+        for language in [
+            "c", "cpp", "glsl", "js", "ts", "json", "py", "rs", "sh", "sql", "toml", "wgsl", "yaml",
+        ] {
+            assert!(Grammar::find(language).is_some(), "{language}");
+        }
+        assert!(Grammar::find("YAML").is_some());
+        assert!(Grammar::find("brainfuck").is_none());
+
+        assert_eq!(
+            tokens("json", r#"{"key": [1.5, true, "value"]}"#),
+            [
+                (r#""key""#.to_owned(), Keyword),
+                ("1.5".to_owned(), Literal),
+                ("true".to_owned(), Literal),
+                (r#""value""#.to_owned(), StringLiteral),
+            ]
+        );
+
+        assert_eq!(
+            tokens("rs", "let x = /* a\nb */ \"c\nd\";"),
+            [
+                ("let".to_owned(), Keyword),
+                ("x".to_owned(), Literal),
+                ("/* a".to_owned(), Comment),
+                ("b */".to_owned(), Comment),
+                ("\"c".to_owned(), StringLiteral),
+                ("d\"".to_owned(), StringLiteral),
+            ]
+        );
+
+        assert_eq!(
+            tokens("sql", "SELECT name FROM users -- all of them"),
+            [
+                ("SELECT".to_owned(), Keyword),
+                ("name".to_owned(), Literal),
+                ("FROM".to_owned(), Keyword),
+                ("users".to_owned(), Literal),
+                ("-- all of them".to_owned(), Comment),
+            ]
+        );
+
+        assert_eq!(
+            tokens("ts", "const s = `a${ {b: 1}.b }c`;"),
+            [
+                ("const".to_owned(), Keyword),
+                ("s".to_owned(), Literal),
+                ("`a".to_owned(), StringLiteral),
+                ("b".to_owned(), Literal),
+                ("1".to_owned(), Literal),
+                ("b".to_owned(), Literal),
+                ("c`".to_owned(), StringLiteral),
+            ]
+        );
+
+        assert_eq!(
+            tokens("yaml", "- name: \"x\" # comment"),
+            [
+                ("name".to_owned(), Keyword),
+                ("\"x\"".to_owned(), StringLiteral),
+                ("# comment".to_owned(), Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_register_grammar() {
+        use TokenType::{Comment, Keyword, Literal, Punctuation, StringLiteral};
+
+        let error = GrammarBuilder::new(&["x"])
+            .state("root", Punctuation, vec![GrammarRule::new("(", Literal)])
+            .build()
+            .err();
+        assert!(matches!(error, Some(GrammarError::BadPattern { .. })));
+
+        let error = GrammarBuilder::new(&["x"])
+            .state(
+                "root",
+                Punctuation,
+                vec![GrammarRule::new("\"", StringLiteral).push("string")],
+            )
+            .build()
+            .err();
+        assert!(matches!(error, Some(GrammarError::UnknownState(name)) if name == "string"));
+
+        let error = GrammarBuilder::new(&["x"])
+            .state(
+                "root",
+                Punctuation,
+                vec![GrammarRule::new("a", Literal).captures(&[Keyword])],
+            )
+            .build()
+            .err();
+        assert!(matches!(error, Some(GrammarError::TooManyCaptures { .. })));
+
+        assert!(Grammar::find("egui-test-ini").is_none());
+        let grammar = GrammarBuilder::new(&["Egui-Test-INI"])
+            .state(
+                "root",
+                Punctuation,
+                vec![
+                    GrammarRule::new(";.*", Comment),
+                    GrammarRule::new(r"\[[^\]]*\]", Keyword),
+                    GrammarRule::new(r"\w+", Literal),
+                ],
+            )
+            .build()
+            .unwrap();
+        register_grammar(grammar);
+
+        assert_eq!(
+            tokens("egui-test-ini", "[section]\nkey = value ; comment"),
+            [
+                ("[section]".to_owned(), Keyword),
+                ("key".to_owned(), Literal),
+                ("value".to_owned(), Literal),
+                ("; comment".to_owned(), Comment),
+            ]
+        );
+
+        let theme = CodeTheme::dark(12.0);
+        let job = GrammarCache::default()
+            .highlight(&theme, "[section]", "EGUI-TEST-INI")
+            .unwrap();
+        assert_eq!(job.sections[0].format, theme.formats[Keyword]);
+    }
+
+    #[test]
+    fn test_only_changed_lines_are_tokenized() {
+        let theme = CodeTheme::dark(12.0);
+        let mut cache = GrammarCache::default();
+
+        let mut lines: Vec<String> = (0..1000).map(|i| format!("let x{i} = {i};")).collect();
+        let original = lines.join("\n");
+        let job = cache.highlight(&theme, &original, "rust").unwrap();
+        assert_eq!(job.text, original);
+        assert_eq!(cache.lines.len(), 1000);
+
+        // Editing a line only tokenizes that line again:
+        lines[500] = "let y = 42;".to_owned();
+        cache.highlight(&theme, &lines.join("\n"), "rust").unwrap();
+        assert_eq!(cache.lines.len(), 1001);
+
+        // Opening a block comment changes the state of all the following lines:
+        lines[500] = "/* let y = 42;".to_owned();
+        let job = cache.highlight(&theme, &lines.join("\n"), "rust").unwrap();
+        assert_eq!(cache.lines.len(), 1001 + 500);
+        assert_eq!(
+            job.sections.last().unwrap().format,
+            theme.formats[TokenType::Comment]
+        );
+    }
+}
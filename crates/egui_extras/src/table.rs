@@ -3,6 +3,8 @@
 //! | fixed size | all available space/minimum | 30% of available width | fixed size |
 //! Takes all available height, so if you want something below the table, put it in a strip.
 
+use std::cmp::Ordering;
+
 use egui::{
     Align, Id, NumExt as _, Rangef, Rect, Response, ScrollArea, Ui, Vec2, Vec2b,
     scroll_area::{ScrollAreaOutput, ScrollBarVisibility, ScrollSource},
@@ -39,6 +41,9 @@ pub struct Column {
 
     resizable: Option<bool>,
 
+    /// Can the table be sorted by this column, by clicking its header?
+    sortable: bool,
+
     /// If set, we should accurately measure the size of this column this frame
     /// so that we can correctly auto-size it. This is done as a `sizing_pass`.
     auto_size_this_frame: bool,
@@ -90,6 +95,7 @@ impl Column {
             width_range: Rangef::new(0.0, f32::INFINITY),
             resizable: None,
             clip: false,
+            sortable: false,
             auto_size_this_frame: false,
         }
     }
@@ -104,6 +110,19 @@ impl Column {
         self
     }
 
+    /// Can the table be sorted by this column?
+    ///
+    /// If so, clicking the header of the column cycles between sorting
+    /// ascending, descending and not at all, and shows an arrow in the header.
+    /// The current sort is remembered by the table, and can be read with [`TableBody::sort`].
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
     /// If `true`: Allow the column to shrink enough to clip the contents.
     /// If `false`: The column will always be wide enough to contain all its content.
     ///
@@ -178,6 +197,75 @@ fn to_sizing(columns: &[Column]) -> crate::sizing::Sizing {
 
 // -----------------------------------------------------------------=----------
 
+/// In which direction a [`Table`] is sorted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SortDirection {
+    /// Smallest first.
+    Ascending,
+
+    /// Largest first.
+    Descending,
+}
+
+/// By which column, and in which direction, a [`Table`] is sorted.
+///
+/// See [`Column::sortable`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TableSort {
+    /// Index of the column.
+    pub column: usize,
+
+    pub direction: SortDirection,
+}
+
+impl TableSort {
+    pub fn ascending(column: usize) -> Self {
+        Self {
+            column,
+            direction: SortDirection::Ascending,
+        }
+    }
+
+    pub fn descending(column: usize) -> Self {
+        Self {
+            column,
+            direction: SortDirection::Descending,
+        }
+    }
+
+    /// Sort the given row indices.
+    ///
+    /// `compare(column, a, b)` should compare row `a` with row `b` by the given column.
+    /// The sort is stable, so rows that compare equal keep their order.
+    pub fn sort_rows(
+        &self,
+        rows: &mut [usize],
+        mut compare: impl FnMut(usize, usize, usize) -> Ordering,
+    ) {
+        let column = self.column;
+        match self.direction {
+            SortDirection::Ascending => rows.sort_by(|&a, &b| compare(column, a, b)),
+            SortDirection::Descending => rows.sort_by(|&a, &b| compare(column, b, a)),
+        }
+    }
+
+    /// What clicking the header of `column` does to the sort:
+    /// none → ascending → descending → none.
+    fn cycle(sort: Option<Self>, column: usize) -> Option<Self> {
+        match sort {
+            Some(sort) if sort.column == column => match sort.direction {
+                SortDirection::Ascending => Some(Self::descending(column)),
+                SortDirection::Descending => None,
+            },
+            _ => Some(Self::ascending(column)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------=----------
+
 struct TableScrollOptions {
     vscroll: bool,
     drag_to_scroll: bool,
@@ -253,6 +341,7 @@ pub struct TableBuilder<'a> {
     cell_layout: egui::Layout,
     scroll_options: TableScrollOptions,
     sense: egui::Sense,
+    default_sort: Option<TableSort>,
 }
 
 impl<'a> TableBuilder<'a> {
@@ -267,6 +356,7 @@ impl<'a> TableBuilder<'a> {
             cell_layout,
             scroll_options: Default::default(),
             sense: egui::Sense::hover(),
+            default_sort: None,
         }
     }
 
@@ -320,6 +410,17 @@ impl<'a> TableBuilder<'a> {
         self
     }
 
+    /// How to sort the table until the user clicks a sortable column header.
+    ///
+    /// See [`Column::sortable`].
+    ///
+    /// Default: `None`.
+    #[inline]
+    pub fn default_sort(mut self, default_sort: impl Into<Option<TableSort>>) -> Self {
+        self.default_sort = default_sort.into();
+        self
+    }
+
     /// Enable vertical scrolling in body (default: `true`)
     #[inline]
     pub fn vscroll(mut self, vscroll: bool) -> Self {
@@ -468,6 +569,7 @@ impl<'a> TableBuilder<'a> {
             cell_layout,
             scroll_options,
             sense,
+            default_sort,
         } = self;
 
         for (i, column) in columns.iter_mut().enumerate() {
@@ -483,8 +585,14 @@ impl<'a> TableBuilder<'a> {
 
         let state_id = ui.id().with(id_salt);
 
-        let (is_sizing_pass, state) =
-            TableState::load(ui, state_id, resizable, &columns, available_width);
+        let (is_sizing_pass, mut state) = TableState::load(
+            ui,
+            state_id,
+            resizable,
+            &columns,
+            available_width,
+            default_sort,
+        );
 
         let mut max_used_widths = vec![0.0; columns.len()];
         let table_top = ui.cursor().top();
@@ -509,6 +617,7 @@ impl<'a> TableBuilder<'a> {
                 selected: false,
                 overline: false,
                 response: &mut response,
+                sort: Some(&mut state.sort),
             });
             layout.allocate_rect();
         });
@@ -546,14 +655,21 @@ impl<'a> TableBuilder<'a> {
             cell_layout,
             scroll_options,
             sense,
+            default_sort,
         } = self;
 
         let striped = striped.unwrap_or(ui.visuals().striped);
 
         let state_id = ui.id().with(id_salt);
 
-        let (is_sizing_pass, state) =
-            TableState::load(ui, state_id, resizable, &columns, available_width);
+        let (is_sizing_pass, state) = TableState::load(
+            ui,
+            state_id,
+            resizable,
+            &columns,
+            available_width,
+            default_sort,
+        );
 
         let max_used_widths = vec![0.0; columns.len()];
         let table_top = ui.cursor().top();
@@ -587,6 +703,9 @@ struct TableState {
     /// If known from previous frame
     #[cfg_attr(feature = "serde", serde(skip))]
     max_used_widths: Vec<f32>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    sort: Option<TableSort>,
 }
 
 impl TableState {
@@ -597,6 +716,7 @@ impl TableState {
         resizable: bool,
         columns: &[Column],
         available_width: f32,
+        default_sort: Option<TableSort>,
     ) -> (bool, Self) {
         let rect = Rect::from_min_size(ui.available_rect_before_wrap().min, Vec2::ZERO);
         ui.ctx().check_for_id_clash(state_id, rect, "Table");
@@ -618,9 +738,20 @@ impl TableState {
             Self {
                 column_widths: initial_widths,
                 max_used_widths: Default::default(),
+                sort: default_sort,
             }
         });
 
+        // Forget the sort if the column is no longer sortable:
+        if let Some(sort) = state.sort {
+            if !columns
+                .get(sort.column)
+                .is_some_and(|column| column.sortable)
+            {
+                state.sort = None;
+            }
+        }
+
         if !is_sizing_pass && state.max_used_widths.len() == columns.len() {
             // Make sure any non-resizable `remainder` columns are updated
             // to take up the remainder of the current available width.
@@ -707,6 +838,13 @@ impl Table<'_> {
         self.ui
     }
 
+    /// How the table is sorted, after any click on a sortable header.
+    ///
+    /// See [`Column::sortable`].
+    pub fn sort(&self) -> Option<TableSort> {
+        self.state.sort
+    }
+
     /// Create table body after adding a header row
     pub fn body<F>(self, add_body_contents: F) -> ScrollAreaOutput<()>
     where
@@ -760,6 +898,7 @@ impl Table<'_> {
             scroll_area = scroll_area.vertical_scroll_offset(scroll_offset_y);
         }
 
+        let sort = state.sort;
         let columns_ref = &columns;
         let widths_ref = &state.column_widths;
         let max_used_widths_ref = &mut max_used_widths;
@@ -792,6 +931,7 @@ impl Table<'_> {
                     scroll_to_y_range: &mut scroll_to_y_range,
                     hovered_row_index,
                     hovered_row_index_id,
+                    sort,
                 });
 
                 if scroll_to_row.is_some() && scroll_to_y_range.is_none() {
@@ -946,6 +1086,8 @@ pub struct TableBody<'a> {
 
     /// Used to store the hovered row index between frames.
     hovered_row_index_id: egui::Id,
+
+    sort: Option<TableSort>,
 }
 
 impl<'a> TableBody<'a> {
@@ -976,6 +1118,68 @@ impl<'a> TableBody<'a> {
         self.widths
     }
 
+    /// How the table is sorted, if at all.
+    ///
+    /// See [`Column::sortable`] and [`Self::sorted_rows`].
+    pub fn sort(&self) -> Option<TableSort> {
+        self.sort
+    }
+
+    /// The given row indices, sorted according to [`Self::sort`].
+    ///
+    /// `compare(column, a, b)` should compare row `a` with row `b` by the given column.
+    ///
+    /// Pass in only the rows you want to show to also filter the table.
+    /// Use the result to look up which row to show in [`Self::rows`].
+    ///
+    /// If you have a lot of rows you may want to cache the result,
+    /// and only sort again when [`Self::sort`] changes.
+    ///
+    /// ### Example
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// use egui_extras::{TableBuilder, Column};
+    /// let fruits = [("banana", 3), ("apple", 7), ("cherry", 5)];
+    /// TableBuilder::new(ui)
+    ///     .column(Column::auto().sortable(true))
+    ///     .column(Column::remainder().sortable(true))
+    ///     .header(20.0, |mut header| {
+    ///         header.col(|ui| {
+    ///             ui.strong("Fruit");
+    ///         });
+    ///         header.col(|ui| {
+    ///             ui.strong("Count");
+    ///         });
+    ///     })
+    ///     .body(|body| {
+    ///         let order = body.sorted_rows(0..fruits.len(), |column, a, b| match column {
+    ///             0 => fruits[a].0.cmp(fruits[b].0),
+    ///             _ => fruits[a].1.cmp(&fruits[b].1),
+    ///         });
+    ///         body.rows(18.0, order.len(), |mut row| {
+    ///             let (name, count) = fruits[order[row.index()]];
+    ///             row.col(|ui| {
+    ///                 ui.label(name);
+    ///             });
+    ///             row.col(|ui| {
+    ///                 ui.label(count.to_string());
+    ///             });
+    ///         });
+    ///     });
+    /// # });
+    /// ```
+    pub fn sorted_rows(
+        &self,
+        rows: impl IntoIterator<Item = usize>,
+        compare: impl FnMut(usize, usize, usize) -> Ordering,
+    ) -> Vec<usize> {
+        let mut rows: Vec<usize> = rows.into_iter().collect();
+        if let Some(sort) = self.sort {
+            sort.sort_rows(&mut rows, compare);
+        }
+        rows
+    }
+
     /// Add a single row with the given height.
     ///
     /// ⚠️ It is much more performant to use [`Self::rows`] or [`Self::heterogeneous_rows`],
@@ -996,6 +1200,7 @@ impl<'a> TableBody<'a> {
             selected: false,
             overline: false,
             response: &mut response,
+            sort: None,
         });
         self.capture_hover_state(&response, self.row_index);
         let bottom_y = self.layout.cursor.y;
@@ -1078,6 +1283,7 @@ impl<'a> TableBody<'a> {
                 selected: false,
                 overline: false,
                 response: &mut response,
+                sort: None,
             });
             self.capture_hover_state(&response, row_index);
         }
@@ -1160,6 +1366,7 @@ impl<'a> TableBody<'a> {
                     selected: false,
                     overline: false,
                     response: &mut response,
+                    sort: None,
                 });
                 self.capture_hover_state(&response, row_index);
                 break;
@@ -1183,6 +1390,7 @@ impl<'a> TableBody<'a> {
                 overline: false,
                 selected: false,
                 response: &mut response,
+                sort: None,
             });
             self.capture_hover_state(&response, row_index);
             cursor_y += (row_height + spacing.y) as f64;
@@ -1271,6 +1479,9 @@ pub struct TableRow<'a, 'b> {
     overline: bool,
 
     response: &'b mut Option<Response>,
+
+    /// Only set for the header row.
+    sort: Option<&'b mut Option<TableSort>>,
 }
 
 impl TableRow<'_, '_> {
@@ -1301,16 +1512,20 @@ impl TableRow<'_, '_> {
         let width = CellSize::Absolute(width);
         let height = CellSize::Absolute(self.height);
 
+        let sortable =
+            self.sort.is_some() && self.columns.get(col_index).is_some_and(|c| c.sortable);
+
         let flags = StripLayoutFlags {
             clip,
             striped: self.striped,
             hovered: self.hovered,
             selected: self.selected,
             overline: self.overline,
+            clickable: sortable,
             sizing_pass: auto_size_this_frame || self.layout.ui.is_sizing_pass(),
         };
 
-        let (used_rect, response) = self.layout.add(
+        let (mut used_rect, response) = self.layout.add(
             flags,
             width,
             height,
@@ -1318,6 +1533,19 @@ impl TableRow<'_, '_> {
             add_cell_contents,
         );
 
+        if sortable {
+            if let Some(sort) = self.sort.as_deref_mut() {
+                if response.clicked() {
+                    *sort = TableSort::cycle(*sort, col_index);
+                }
+                let direction = sort
+                    .filter(|sort| sort.column == col_index)
+                    .map(|sort| sort.direction);
+                let indicator_width = paint_sort_indicator(self.layout.ui, &response, direction);
+                used_rect.max.x += indicator_width;
+            }
+        }
+
         if let Some(max_w) = self.max_used_widths.get_mut(col_index) {
             *max_w = max_w.max(used_rect.width());
         }
@@ -1378,3 +1606,137 @@ impl Drop for TableRow<'_, '_> {
         self.layout.end_line();
     }
 }
+
+/// Paint the arrow of a sortable column header, on the right side of the cell.
+///
+/// Returns the width used.
+fn paint_sort_indicator(ui: &Ui, response: &Response, direction: Option<SortDirection>) -> f32 {
+    let size = 0.5 * ui.spacing().icon_width;
+    let margin = 0.5 * ui.spacing().item_spacing.x;
+    let rect = Rect::from_center_size(
+        egui::pos2(
+            response.rect.right() - margin - 0.5 * size,
+            response.rect.center().y,
+        ),
+        Vec2::splat(size),
+    );
+
+    let (points, color) = match direction {
+        Some(SortDirection::Ascending) => (
+            vec![rect.left_bottom(), rect.right_bottom(), rect.center_top()],
+            ui.visuals().strong_text_color(),
+        ),
+        Some(SortDirection::Descending) => (
+            vec![rect.left_top(), rect.right_top(), rect.center_bottom()],
+            ui.visuals().strong_text_color(),
+        ),
+        None if response.hovered() => (
+            // Hint that clicking will sort ascending:
+            vec![rect.left_bottom(), rect.right_bottom(), rect.center_top()],
+            ui.visuals().weak_text_color(),
+        ),
+        None => return size + margin,
+    };
+    ui.painter().add(egui::Shape::convex_polygon(
+        points,
+        color,
+        egui::Stroke::NONE,
+    ));
+
+    size + margin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_cycle() {
+        let sort = TableSort::cycle(None, 1);
+        assert_eq!(sort, Some(TableSort::ascending(1)));
+        let sort = TableSort::cycle(sort, 1);
+        assert_eq!(sort, Some(TableSort::descending(1)));
+        assert_eq!(TableSort::cycle(sort, 1), None);
+        assert_eq!(TableSort::cycle(sort, 0), Some(TableSort::ascending(0)));
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let values = [3, 1, 2, 1];
+        let compare = |_column: usize, a: usize, b: usize| values[a].cmp(&values[b]);
+
+        let mut rows = vec![0, 1, 2, 3];
+        TableSort::ascending(0).sort_rows(&mut rows, compare);
+        assert_eq!(rows, vec![1, 3, 2, 0]);
+
+        let mut rows = vec![0, 1, 2, 3];
+        TableSort::descending(0).sort_rows(&mut rows, compare);
+        assert_eq!(rows, vec![0, 2, 1, 3], "Equal rows should keep their order");
+    }
+
+    #[test]
+    fn test_click_header_to_sort() {
+        let ctx = egui::Context::default();
+
+        // Returns the sort and the rect of the sortable header:
+        let run = |events: Vec<egui::Event>| {
+            let mut header_rect = Rect::NOTHING;
+            let mut sort = None;
+            let input = egui::RawInput {
+                screen_rect: Some(Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(400.0, 300.0),
+                )),
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let table = TableBuilder::new(ui)
+                        .column(Column::exact(100.0))
+                        .column(Column::exact(100.0).sortable(true))
+                        .header(20.0, |mut header| {
+                            header.col(|ui| {
+                                ui.label("Name");
+                            });
+                            header_rect = header
+                                .col(|ui| {
+                                    ui.label("Size");
+                                })
+                                .1
+                                .rect;
+                        });
+                    sort = table.sort();
+                    table.body(|_| {});
+                });
+            });
+            (sort, header_rect)
+        };
+
+        let click = |pos| {
+            vec![
+                egui::Event::PointerMoved(pos),
+                egui::Event::PointerButton {
+                    pos,
+                    button: egui::PointerButton::Primary,
+                    pressed: true,
+                    modifiers: Default::default(),
+                },
+                egui::Event::PointerButton {
+                    pos,
+                    button: egui::PointerButton::Primary,
+                    pressed: false,
+                    modifiers: Default::default(),
+                },
+            ]
+        };
+
+        run(vec![]);
+        let (sort, header_rect) = run(vec![]); // The cell response has a rect from the second frame
+        assert_eq!(sort, None);
+        let pos = header_rect.center();
+        assert_eq!(run(click(pos)).0, Some(TableSort::ascending(1)));
+        assert_eq!(run(click(pos)).0, Some(TableSort::descending(1)));
+        assert_eq!(run(click(pos)).0, None);
+    }
+}
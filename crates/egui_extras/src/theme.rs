@@ -0,0 +1,361 @@
+//! Load [`egui::Style`] from theme files, see [`ThemeFile`].
+
+use std::path::{Path, PathBuf};
+
+use egui::{Context, Style, Theme, Visuals};
+
+/// How often [`ThemeFile::watch`] checks if the file changed.
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The format of a theme file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeFormat {
+    Toml,
+    Ron,
+}
+
+impl ThemeFormat {
+    /// Guess the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "ron" => Some(Self::Ron),
+            _ => None,
+        }
+    }
+}
+
+/// Why a theme file could not be loaded.
+#[derive(Debug)]
+pub enum ThemeError {
+    /// The file could not be read.
+    Io(std::io::Error),
+
+    /// The file extension is neither `.toml` nor `.ron`.
+    UnknownFormat,
+
+    /// The file is not valid TOML or RON.
+    Parse(String),
+
+    /// The contents don't match the fields of [`egui::Style`].
+    Style(String),
+}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to read theme file: {err}"),
+            Self::UnknownFormat => f.write_str("Theme files must end with .toml or .ron"),
+            Self::Parse(err) => write!(f, "Failed to parse theme file: {err}"),
+            Self::Style(err) => write!(f, "Invalid theme: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// Parse a theme, returning which [`Theme`] it is for and the resulting [`Style`].
+///
+/// The theme only needs to contain the fields it changes.
+/// They are applied on top of the default [`Style`], with either [`Visuals::dark`] or [`Visuals::light`],
+/// as selected by the top-level `base` key (`"dark"` by default):
+///
+/// ```toml
+/// base = "light"
+///
+/// [spacing]
+/// item_spacing = { x = 10.0, y = 6.0 }
+///
+/// [visuals]
+/// window_fill = [250, 245, 235, 255]
+/// hyperlink_color = [0, 100, 200, 255]
+///
+/// [text_styles]
+/// Body = { size = 14.0, family = "Proportional" }
+/// ```
+///
+/// The same in RON looks like this.
+/// Note that enum variants, like `"Proportional"`, need to be written as strings:
+///
+/// ```ron
+/// (
+///     base: "light",
+///     spacing: (item_spacing: (x: 10.0, y: 6.0)),
+///     visuals: (
+///         window_fill: [250, 245, 235, 255],
+///         hyperlink_color: [0, 100, 200, 255],
+///     ),
+///     text_styles: { "Body": (size: 14.0, family: "Proportional") },
+/// )
+/// ```
+///
+/// # Errors
+/// On invalid TOML or RON, or fields that don't match [`Style`].
+pub fn parse_theme(text: &str, format: ThemeFormat) -> Result<(Theme, Style), ThemeError> {
+    profiling::function_scope!();
+
+    let mut overrides = match format {
+        ThemeFormat::Toml => {
+            toml::from_str::<toml::Table>(text).map_err(|err| ThemeError::Parse(err.to_string()))?
+        }
+        ThemeFormat::Ron => {
+            let value: ron::Value =
+                ron::from_str(text).map_err(|err| ThemeError::Parse(err.to_string()))?;
+            toml::Table::try_from(value).map_err(|err| ThemeError::Parse(err.to_string()))?
+        }
+    };
+
+    let theme = match overrides.remove("base") {
+        None => Theme::Dark,
+        Some(toml::Value::String(base)) if base == "dark" => Theme::Dark,
+        Some(toml::Value::String(base)) if base == "light" => Theme::Light,
+        Some(base) => {
+            return Err(ThemeError::Style(format!(
+                "`base` should be \"dark\" or \"light\", got {base:?}"
+            )));
+        }
+    };
+
+    let base_style = Style {
+        visuals: match theme {
+            Theme::Dark => Visuals::dark(),
+            Theme::Light => Visuals::light(),
+        },
+        ..Default::default()
+    };
+
+    let mut style =
+        toml::Table::try_from(&base_style).map_err(|err| ThemeError::Style(err.to_string()))?;
+    merge(&mut style, overrides);
+    let style = toml::Value::Table(style)
+        .try_into()
+        .map_err(|err: toml::de::Error| ThemeError::Style(err.to_string()))?;
+
+    Ok((theme, style))
+}
+
+/// Recursively replace the values in `base` with the ones in `overrides`.
+fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge(base, overrides);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A theme file on disk, that is reloaded whenever it changes.
+///
+/// This lets you tweak the look of your app without recompiling.
+/// See [`parse_theme`] for the file format.
+///
+/// ```no_run
+/// # egui::__run_test_ctx(|ctx| {
+/// let mut theme_file = egui_extras::ThemeFile::new("assets/theme.toml");
+///
+/// // Every frame:
+/// theme_file.watch(ctx);
+/// if let Some(error) = theme_file.error() {
+///     egui::TopBottomPanel::top("theme_error").show(ctx, |ui| {
+///         ui.colored_label(ui.visuals().error_fg_color, error);
+///     });
+/// }
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct ThemeFile {
+    path: PathBuf,
+
+    /// When we last checked the file, and its modification time back then.
+    #[cfg(not(target_arch = "wasm32"))]
+    last_check: Option<(f64, Option<std::time::SystemTime>)>,
+
+    error: Option<String>,
+}
+
+impl ThemeFile {
+    /// The file extension, `.toml` or `.ron`, selects the format.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            #[cfg(not(target_arch = "wasm32"))]
+            last_check: None,
+            error: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why the file failed to load the last time, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Load the file and set it as the style of its [`Theme`], using [`Context::set_style_of`].
+    ///
+    /// # Errors
+    /// If the file can't be read or parsed. The current style is then kept.
+    pub fn load(&mut self, ctx: &Context) -> Result<Theme, ThemeError> {
+        let result = self.read().and_then(|text| {
+            let format = ThemeFormat::from_path(&self.path).ok_or(ThemeError::UnknownFormat)?;
+            parse_theme(&text, format)
+        });
+        match result {
+            Ok((theme, style)) => {
+                log::debug!("Loaded {theme:?} theme from {:?}", self.path);
+                ctx.set_style_of(theme, style);
+                self.error = None;
+                Ok(theme)
+            }
+            Err(err) => {
+                log::warn!("{:?}: {err}", self.path);
+                self.error = Some(err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    /// Load the file if it changed since the last call.
+    ///
+    /// Call this every frame. It checks the modification time of the file a couple of times per second,
+    /// and requests a repaint so that changes show up even when the app is idle.
+    ///
+    /// Does nothing on the web, where there are no files to watch.
+    #[allow(
+        clippy::allow_attributes,
+        clippy::needless_pass_by_ref_mut,
+        clippy::unused_self
+    )] // on the web
+    pub fn watch(&mut self, ctx: &Context) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let now = ctx.input(|i| i.time);
+            if let Some((last_time, _)) = self.last_check {
+                let elapsed = now - last_time;
+                if elapsed < POLL_INTERVAL.as_secs_f64() {
+                    ctx.request_repaint_after_secs((POLL_INTERVAL.as_secs_f64() - elapsed) as f32);
+                    return;
+                }
+            }
+
+            let modified = std::fs::metadata(&self.path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let changed = self
+                .last_check
+                .is_none_or(|(_, last_modified)| last_modified != modified);
+            self.last_check = Some((now, modified));
+            if changed {
+                self.load(ctx).ok();
+            }
+            ctx.request_repaint_after(POLL_INTERVAL);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = ctx;
+    }
+
+    fn read(&self) -> Result<String, ThemeError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            std::fs::read_to_string(&self.path).map_err(ThemeError::Io)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err(ThemeError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("can't read {:?}: no file system on the web", self.path),
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_toml() {
+        let (theme, style) = parse_theme(
+            r#"
+            base = "light"
+
+            [spacing]
+            item_spacing = { x = 10.0, y = 6 }
+
+            [visuals]
+            window_fill = [250, 245, 235, 255]
+
+            [text_styles]
+            Body = { size = 14.0, family = "Monospace" }
+            "#,
+            ThemeFormat::Toml,
+        )
+        .unwrap();
+
+        assert_eq!(theme, Theme::Light);
+        assert_eq!(style.spacing.item_spacing, egui::vec2(10.0, 6.0));
+        assert_eq!(
+            style.visuals.window_fill,
+            egui::Color32::from_rgb(250, 245, 235)
+        );
+        assert_eq!(
+            style.text_styles[&egui::TextStyle::Body],
+            egui::FontId::monospace(14.0)
+        );
+
+        // Everything else is from the light defaults:
+        let light = Visuals::light();
+        assert_eq!(style.visuals.panel_fill, light.panel_fill);
+        assert_eq!(style.visuals.widgets, light.widgets);
+        assert_eq!(
+            style.spacing.button_padding,
+            Style::default().spacing.button_padding
+        );
+    }
+
+    #[test]
+    fn test_partial_ron() {
+        let (theme, style) = parse_theme(
+            r#"(
+                visuals: (hyperlink_color: [0, 100, 200, 255]),
+                text_styles: { "Heading": (size: 30.0, family: "Proportional") },
+            )"#,
+            ThemeFormat::Ron,
+        )
+        .unwrap();
+
+        assert_eq!(theme, Theme::Dark);
+        assert_eq!(
+            style.visuals.hyperlink_color,
+            egui::Color32::from_rgb(0, 100, 200)
+        );
+        assert_eq!(style.visuals.panel_fill, Visuals::dark().panel_fill);
+        assert_eq!(
+            style.text_styles[&egui::TextStyle::Heading],
+            egui::FontId::proportional(30.0)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse_theme("visuals = ", ThemeFormat::Toml),
+            Err(ThemeError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_theme(r#"base = "blue""#, ThemeFormat::Toml),
+            Err(ThemeError::Style(_))
+        ));
+        assert!(matches!(
+            parse_theme("[visuals]\nwindow_fill = \"red\"", ThemeFormat::Toml),
+            Err(ThemeError::Style(_))
+        ));
+    }
+}
@@ -0,0 +1,934 @@
+//! A tree of collapsible nodes, like the file explorer of an IDE.
+
+use std::hash::Hash;
+
+use egui::{
+    Align, Context, Event, EventFilter, Id, Key, Layout, Modifiers, Rect, Response, Sense, Stroke,
+    StrokeKind, Ui, UiBuilder, collapsing_header::CollapsingState, pos2, vec2,
+};
+
+/// Provides the nodes of a [`TreeView`], and how to show them.
+///
+/// The tree view only asks for what it needs:
+/// children are only requested for expanded nodes,
+/// and [`Self::node_ui`] is only called for rows that are visible.
+pub trait TreeViewDelegate {
+    /// Identifies a node. Must be unique within the tree, and stable between frames.
+    type NodeId: Clone + PartialEq + Hash + Send + Sync + 'static;
+
+    /// The top-level nodes, in order.
+    fn roots(&mut self) -> Vec<Self::NodeId>;
+
+    /// Does this node have any children?
+    ///
+    /// This is used to decide whether to show an expand arrow,
+    /// so it should be cheap, and not require loading the children.
+    fn has_children(&mut self, node: &Self::NodeId) -> bool;
+
+    /// The children of a node, in order.
+    ///
+    /// Only called for expanded nodes, so children can be loaded lazily.
+    fn children(&mut self, node: &Self::NodeId) -> Vec<Self::NodeId>;
+
+    /// Show the contents of a row, e.g. an icon and a label.
+    ///
+    /// The [`Ui`] has a left-to-right layout, vertically centered in the row.
+    fn node_ui(&mut self, ui: &mut Ui, node: &Self::NodeId);
+}
+
+/// Where dragged nodes were dropped, relative to the target node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropPosition {
+    /// As a sibling, just before the target.
+    Before,
+
+    /// As the last child of the target.
+    Inside,
+
+    /// As a sibling, just after the target.
+    After,
+}
+
+/// Nodes that were dragged and dropped in a [`TreeView`].
+///
+/// It is up to you to actually move the nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeViewDrop<NodeId> {
+    /// The dragged nodes, in the order they appear in the tree.
+    pub nodes: Vec<NodeId>,
+
+    /// The node they were dropped on.
+    pub target: NodeId,
+
+    /// Where, relative to [`Self::target`].
+    pub position: DropPosition,
+}
+
+/// The drag-and-drop payload of a [`TreeView`].
+struct DragPayload<NodeId> {
+    tree_id: Id,
+    nodes: Vec<NodeId>,
+}
+
+// ----------------------------------------------------------------------------
+
+/// The selection and keyboard cursor of a [`TreeView`].
+///
+/// Whether nodes are expanded is stored separately, as a [`CollapsingState`] per node.
+#[derive(Clone, Debug)]
+pub struct TreeViewState<NodeId> {
+    selected: Vec<NodeId>,
+
+    /// The node with keyboard focus.
+    cursor: Option<NodeId>,
+
+    /// Where range selections start.
+    anchor: Option<NodeId>,
+}
+
+impl<NodeId> Default for TreeViewState<NodeId> {
+    fn default() -> Self {
+        Self {
+            selected: Vec::new(),
+            cursor: None,
+            anchor: None,
+        }
+    }
+}
+
+impl<NodeId: Clone + PartialEq + Send + Sync + 'static> TreeViewState<NodeId> {
+    /// Load the state of the tree view with the given id, i.e. [`TreeViewResponse::id`].
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp::<Self>(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+
+    /// The selected nodes, in the order they were selected.
+    pub fn selected(&self) -> &[NodeId] {
+        &self.selected
+    }
+
+    pub fn is_selected(&self, node: &NodeId) -> bool {
+        self.selected.contains(node)
+    }
+
+    /// Replace the selection.
+    ///
+    /// This also moves the keyboard cursor to the last of the nodes.
+    pub fn set_selected(&mut self, selected: Vec<NodeId>) {
+        self.cursor = selected.last().cloned();
+        self.anchor = self.cursor.clone();
+        self.selected = selected;
+    }
+
+    /// The node with keyboard focus, if any.
+    pub fn cursor(&self) -> Option<&NodeId> {
+        self.cursor.as_ref()
+    }
+
+    fn select_only(&mut self, node: &NodeId) {
+        self.selected = vec![node.clone()];
+        self.cursor = Some(node.clone());
+        self.anchor = Some(node.clone());
+    }
+
+    fn toggle_selected(&mut self, node: &NodeId) {
+        if let Some(index) = self.selected.iter().position(|n| n == node) {
+            self.selected.remove(index);
+        } else {
+            self.selected.push(node.clone());
+        }
+        self.cursor = Some(node.clone());
+        self.anchor = Some(node.clone());
+    }
+
+    /// Select all rows between the anchor and `index`.
+    fn select_range(&mut self, rows: &[Row<NodeId>], index: usize) {
+        let anchor = self
+            .anchor
+            .as_ref()
+            .and_then(|anchor| rows.iter().position(|row| &row.node == anchor))
+            .unwrap_or(index);
+        let range = anchor.min(index)..=anchor.max(index);
+        self.selected = rows[range].iter().map(|row| row.node.clone()).collect();
+        self.cursor = Some(rows[index].node.clone());
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// What happened in a [`TreeView`] this frame.
+pub struct TreeViewResponse<NodeId> {
+    /// The id of the tree, used for [`TreeViewState::load`].
+    pub id: Id,
+
+    /// Covers the whole tree. Has focus when the tree has keyboard focus.
+    pub response: Response,
+
+    /// The selection, after any changes this frame.
+    pub state: TreeViewState<NodeId>,
+
+    /// Did the user change the selection this frame?
+    pub selection_changed: bool,
+
+    /// A node that was double-clicked, or had focus when enter was pressed.
+    pub activated: Option<NodeId>,
+
+    /// Nodes that were dragged and dropped this frame.
+    ///
+    /// Only if [`TreeView::drag_and_drop`] is enabled.
+    pub dropped: Option<TreeViewDrop<NodeId>>,
+}
+
+/// A flattened, visible node.
+struct Row<NodeId> {
+    node: NodeId,
+    depth: usize,
+
+    /// Index of the parent row.
+    parent: Option<usize>,
+
+    has_children: bool,
+    collapsing: Option<CollapsingState>,
+}
+
+impl<NodeId> Row<NodeId> {
+    fn is_open(&self) -> bool {
+        self.collapsing.as_ref().is_some_and(|c| c.is_open())
+    }
+}
+
+/// A tree of nodes that can be expanded and collapsed, like a file explorer.
+///
+/// The nodes are provided by a [`TreeViewDelegate`].
+///
+/// Features:
+/// * Click to select, ctrl/cmd-click to toggle, shift-click to select a range.
+/// * Keyboard navigation with the arrow keys, home/end and page up/down, once the tree has focus.
+///   Left/right collapses/expands, space selects and enter activates.
+/// * Only the visible rows are shown, so it can handle huge trees inside a [`egui::ScrollArea`].
+/// * Optional drag-and-drop of nodes, see [`Self::drag_and_drop`].
+///
+/// Each node stores whether it is open as a [`CollapsingState`],
+/// with the id [`TreeView::node_id`].
+///
+/// ```
+/// use egui_extras::{TreeView, TreeViewDelegate};
+///
+/// struct Numbers;
+///
+/// impl TreeViewDelegate for Numbers {
+///     type NodeId = u32;
+///
+///     fn roots(&mut self) -> Vec<u32> {
+///         vec![1, 2, 3]
+///     }
+///
+///     fn has_children(&mut self, node: &u32) -> bool {
+///         *node < 1000
+///     }
+///
+///     fn children(&mut self, node: &u32) -> Vec<u32> {
+///         (0..10).map(|i| 10 * node + i).collect()
+///     }
+///
+///     fn node_ui(&mut self, ui: &mut egui::Ui, node: &u32) {
+///         ui.label(node.to_string());
+///     }
+/// }
+///
+/// # egui::__run_test_ui(|ui| {
+/// egui::ScrollArea::vertical().show(ui, |ui| {
+///     let response = TreeView::new("numbers").show(ui, &mut Numbers);
+///     if response.selection_changed {
+///         println!("Selected: {:?}", response.state.selected());
+///     }
+/// });
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct TreeView {
+    id_salt: Id,
+    row_height: Option<f32>,
+    default_open: bool,
+    multi_select: bool,
+    drag_and_drop: bool,
+}
+
+impl TreeView {
+    pub fn new(id_salt: impl Hash) -> Self {
+        Self {
+            id_salt: Id::new(id_salt),
+            row_height: None,
+            default_open: false,
+            multi_select: true,
+            drag_and_drop: false,
+        }
+    }
+
+    /// Height of each row, without spacing.
+    ///
+    /// Default: [`egui::style::Spacing::interact_size`]`.y`.
+    #[inline]
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.row_height = Some(row_height);
+        self
+    }
+
+    /// Are nodes expanded the first time they are shown?
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn default_open(mut self, default_open: bool) -> Self {
+        self.default_open = default_open;
+        self
+    }
+
+    /// Can more than one node be selected, using the ctrl/cmd and shift keys?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn multi_select(mut self, multi_select: bool) -> Self {
+        self.multi_select = multi_select;
+        self
+    }
+
+    /// Can nodes be dragged, and dropped onto other nodes?
+    ///
+    /// Dragging a selected node drags the whole selection.
+    /// The drop is reported in [`TreeViewResponse::dropped`],
+    /// and it is up to you to move the nodes.
+    /// Nodes can't be dropped into themselves.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn drag_and_drop(mut self, drag_and_drop: bool) -> Self {
+        self.drag_and_drop = drag_and_drop;
+        self
+    }
+
+    /// The id of the [`CollapsingState`] of a node.
+    pub fn node_id(tree_id: Id, node: &impl Hash) -> Id {
+        tree_id.with(node)
+    }
+
+    pub fn show<D: TreeViewDelegate>(
+        self,
+        ui: &mut Ui,
+        delegate: &mut D,
+    ) -> TreeViewResponse<D::NodeId> {
+        profiling::function_scope!();
+
+        let id = ui.make_persistent_id(self.id_salt);
+        let mut state = TreeViewState::<D::NodeId>::load(ui.ctx(), id).unwrap_or_default();
+        let mut rows = self.flatten(ui.ctx(), id, delegate);
+
+        let row_height = self.row_height.unwrap_or(ui.spacing().interact_size.y);
+        let row_height_with_spacing = row_height + ui.spacing().item_spacing.y;
+
+        let mut selection_changed = false;
+        let mut activated = None;
+        let mut scroll_to_cursor = false;
+
+        if ui.memory(|mem| mem.has_focus(id)) {
+            let event_filter = EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            };
+            ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+
+            let page_rows = (ui.clip_rect().height() / row_height_with_spacing).floor() as usize;
+            for event in ui.input(|i| i.filtered_events(&event_filter)) {
+                let Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } = event
+                else {
+                    continue;
+                };
+                let action = KeyAction {
+                    key,
+                    modifiers,
+                    multi_select: self.multi_select,
+                    page_rows: page_rows.max(1),
+                };
+                match action.apply(ui, &mut state, &mut rows) {
+                    KeyResult::None => {}
+                    KeyResult::MovedCursor {
+                        selection_changed: changed,
+                    } => {
+                        selection_changed |= changed;
+                        scroll_to_cursor = true;
+                    }
+                    KeyResult::Toggled => {
+                        rows = self.flatten(ui.ctx(), id, delegate);
+                    }
+                    KeyResult::Activated(node) => {
+                        activated = Some(node);
+                    }
+                }
+            }
+        }
+
+        let width = ui.available_width();
+        let (_, rect) = ui.allocate_space(vec2(
+            width,
+            (rows.len() as f32 * row_height_with_spacing - ui.spacing().item_spacing.y).max(0.0),
+        ));
+        let response = ui.interact(rect, id, Sense::focusable_noninteractive());
+        let row_rect = |index: usize| {
+            Rect::from_min_size(
+                pos2(
+                    rect.left(),
+                    rect.top() + index as f32 * row_height_with_spacing,
+                ),
+                vec2(rect.width(), row_height),
+            )
+        };
+
+        if scroll_to_cursor {
+            let cursor_index = state
+                .cursor
+                .as_ref()
+                .and_then(|cursor| rows.iter().position(|row| &row.node == cursor));
+            if let Some(index) = cursor_index {
+                ui.scroll_to_rect(row_rect(index), None);
+            }
+        }
+
+        // Only show the rows we can see:
+        let clip_rect = ui.clip_rect();
+        let first_visible = ((clip_rect.top() - rect.top()) / row_height_with_spacing)
+            .floor()
+            .max(0.0) as usize;
+        let end_visible = (((clip_rect.bottom() - rect.top()) / row_height_with_spacing).ceil()
+            as usize)
+            .min(rows.len());
+
+        let has_focus = response.has_focus();
+        let mut dropped = None;
+
+        for index in first_visible..end_visible {
+            let row = &rows[index];
+            let rect = row_rect(index);
+            let row_id = Self::node_id(id, &row.node).with("row");
+            let sense = if self.drag_and_drop {
+                Sense::click_and_drag()
+            } else {
+                Sense::click()
+            };
+            let row_response = ui.interact(rect, row_id, sense);
+
+            if row_response.clicked() {
+                let modifiers = ui.input(|i| i.modifiers);
+                if self.multi_select && modifiers.shift {
+                    state.select_range(&rows, index);
+                } else if self.multi_select && modifiers.command {
+                    state.toggle_selected(&rows[index].node);
+                } else {
+                    state.select_only(&rows[index].node);
+                }
+                selection_changed = true;
+                ui.memory_mut(|mem| mem.request_focus(id));
+            }
+            if row_response.double_clicked() {
+                activated = Some(rows[index].node.clone());
+            }
+
+            if self.drag_and_drop {
+                if let Some(drop) = drag_and_drop_row(ui, id, &state, &rows, index, &row_response) {
+                    dropped = Some(drop);
+                }
+            }
+
+            let row = &mut rows[index];
+            let is_selected = state.is_selected(&row.node);
+
+            if ui.is_rect_visible(rect) {
+                let visuals = ui.visuals();
+                if is_selected {
+                    ui.painter()
+                        .rect_filled(rect, 0.0, visuals.selection.bg_fill);
+                } else if row_response.hovered() {
+                    ui.painter()
+                        .rect_filled(rect, 0.0, visuals.widgets.hovered.weak_bg_fill);
+                }
+                if has_focus && state.cursor.as_ref() == Some(&row.node) {
+                    ui.painter().rect_stroke(
+                        rect,
+                        0.0,
+                        visuals.selection.stroke,
+                        StrokeKind::Inside,
+                    );
+                }
+            }
+
+            let indent = ui.spacing().indent;
+            let left = rect.left() + row.depth as f32 * indent;
+
+            if let Some(collapsing) = &mut row.collapsing {
+                let button_rect =
+                    Rect::from_min_size(pos2(left, rect.top()), vec2(indent, rect.height()));
+                let button_response =
+                    ui.interact(button_rect, row_id.with("toggle"), Sense::click());
+                if button_response.clicked() {
+                    collapsing.toggle(ui);
+                    collapsing.store(ui.ctx());
+                }
+
+                let (mut icon_rect, _) = ui.spacing().icon_rectangles(button_rect);
+                icon_rect.set_center(button_rect.center());
+                let openness = collapsing.openness(ui.ctx());
+                egui::collapsing_header::paint_default_icon(
+                    ui,
+                    openness,
+                    &button_response.with_new_rect(icon_rect),
+                );
+            }
+
+            let content_rect = Rect::from_min_max(pos2(left + indent, rect.top()), rect.max);
+            let mut content_ui = ui.new_child(
+                UiBuilder::new()
+                    .max_rect(content_rect)
+                    .layout(Layout::left_to_right(Align::Center)),
+            );
+            content_ui.set_clip_rect(content_rect.intersect(clip_rect));
+            content_ui.style_mut().interaction.selectable_labels = false;
+            if is_selected {
+                content_ui.visuals_mut().override_text_color =
+                    Some(content_ui.visuals().selection.stroke.color);
+            }
+            delegate.node_ui(&mut content_ui, &row.node);
+        }
+
+        state.clone().store(ui.ctx(), id);
+
+        TreeViewResponse {
+            id,
+            response,
+            state,
+            selection_changed,
+            activated,
+            dropped,
+        }
+    }
+
+    /// The visible nodes, depth first.
+    fn flatten<D: TreeViewDelegate>(
+        &self,
+        ctx: &Context,
+        tree_id: Id,
+        delegate: &mut D,
+    ) -> Vec<Row<D::NodeId>> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(D::NodeId, usize, Option<usize>)> = delegate
+            .roots()
+            .into_iter()
+            .rev()
+            .map(|node| (node, 0, None))
+            .collect();
+
+        while let Some((node, depth, parent)) = stack.pop() {
+            let has_children = delegate.has_children(&node);
+            let collapsing = has_children.then(|| {
+                CollapsingState::load_with_default_open(
+                    ctx,
+                    Self::node_id(tree_id, &node),
+                    self.default_open,
+                )
+            });
+            let row = Row {
+                node,
+                depth,
+                parent,
+                has_children,
+                collapsing,
+            };
+            if row.is_open() {
+                let index = rows.len();
+                stack.extend(
+                    delegate
+                        .children(&row.node)
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, depth + 1, Some(index))),
+                );
+            }
+            rows.push(row);
+        }
+
+        rows
+    }
+}
+
+/// Handle dragging from, and dropping onto, a row.
+fn drag_and_drop_row<NodeId: Clone + PartialEq + Send + Sync + 'static>(
+    ui: &Ui,
+    tree_id: Id,
+    state: &TreeViewState<NodeId>,
+    rows: &[Row<NodeId>],
+    index: usize,
+    row_response: &Response,
+) -> Option<TreeViewDrop<NodeId>> {
+    let row = &rows[index];
+
+    if row_response.drag_started() {
+        let nodes = if state.is_selected(&row.node) {
+            // Drag the whole selection, in tree order:
+            rows.iter()
+                .filter(|row| state.is_selected(&row.node))
+                .map(|row| row.node.clone())
+                .collect()
+        } else {
+            vec![row.node.clone()]
+        };
+        row_response.dnd_set_drag_payload(DragPayload { tree_id, nodes });
+    }
+
+    let payload = row_response.dnd_hover_payload::<DragPayload<NodeId>>()?;
+    if payload.tree_id != tree_id {
+        return None;
+    }
+
+    let pointer = ui.ctx().pointer_interact_pos()?;
+    let rect = row_response.rect;
+    let t = (pointer.y - rect.top()) / rect.height();
+    let position = if t < 0.25 {
+        DropPosition::Before
+    } else if t > 0.75 && !row.is_open() {
+        DropPosition::After
+    } else {
+        DropPosition::Inside
+    };
+
+    // Can't drop a node into itself:
+    let mut ancestor = Some(index);
+    while let Some(i) = ancestor {
+        if payload.nodes.contains(&rows[i].node) {
+            return None;
+        }
+        ancestor = rows[i].parent;
+    }
+
+    let stroke = Stroke::new(2.0, ui.visuals().selection.stroke.color);
+    match position {
+        DropPosition::Before => {
+            ui.painter().hline(rect.x_range(), rect.top(), stroke);
+        }
+        DropPosition::Inside => {
+            ui.painter()
+                .rect_stroke(rect, 2.0, stroke, StrokeKind::Inside);
+        }
+        DropPosition::After => {
+            ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+        }
+    }
+
+    let payload = row_response.dnd_release_payload::<DragPayload<NodeId>>()?;
+    Some(TreeViewDrop {
+        nodes: payload.nodes.clone(),
+        target: row.node.clone(),
+        position,
+    })
+}
+
+// ----------------------------------------------------------------------------
+
+struct KeyAction {
+    key: Key,
+    modifiers: Modifiers,
+    multi_select: bool,
+    page_rows: usize,
+}
+
+enum KeyResult<NodeId> {
+    None,
+    MovedCursor {
+        selection_changed: bool,
+    },
+
+    /// A node was expanded or collapsed.
+    Toggled,
+
+    Activated(NodeId),
+}
+
+impl KeyAction {
+    fn apply<NodeId: Clone + PartialEq + Send + Sync + 'static>(
+        &self,
+        ui: &Ui,
+        state: &mut TreeViewState<NodeId>,
+        rows: &mut [Row<NodeId>],
+    ) -> KeyResult<NodeId> {
+        if rows.is_empty() {
+            return KeyResult::None;
+        }
+
+        let cursor = state
+            .cursor
+            .as_ref()
+            .and_then(|cursor| rows.iter().position(|row| &row.node == cursor));
+        let last = rows.len() - 1;
+
+        let target = match self.key {
+            Key::ArrowDown => cursor.map_or(0, |i| (i + 1).min(last)),
+            Key::ArrowUp => cursor.map_or(0, |i| i.saturating_sub(1)),
+            Key::PageDown => cursor.map_or(0, |i| (i + self.page_rows).min(last)),
+            Key::PageUp => cursor.map_or(0, |i| i.saturating_sub(self.page_rows)),
+            Key::Home => 0,
+            Key::End => last,
+            Key::ArrowRight | Key::ArrowLeft => {
+                let Some(i) = cursor else {
+                    return self.move_cursor(state, rows, 0);
+                };
+                let open = self.key == Key::ArrowRight;
+                let row = &mut rows[i];
+                if let Some(collapsing) = &mut row.collapsing {
+                    if collapsing.is_open() != open {
+                        collapsing.set_open(open);
+                        collapsing.store(ui.ctx());
+                        return KeyResult::Toggled;
+                    }
+                }
+                if open {
+                    if !row.has_children {
+                        return KeyResult::None;
+                    }
+                    (i + 1).min(last) // first child
+                } else if let Some(parent) = row.parent {
+                    parent
+                } else {
+                    return KeyResult::None;
+                }
+            }
+            Key::Space => {
+                let Some(i) = cursor else {
+                    return KeyResult::None;
+                };
+                if self.multi_select && self.modifiers.command {
+                    state.toggle_selected(&rows[i].node);
+                } else {
+                    state.select_only(&rows[i].node);
+                }
+                return KeyResult::MovedCursor {
+                    selection_changed: true,
+                };
+            }
+            Key::Enter => {
+                return cursor.map_or(KeyResult::None, |i| {
+                    KeyResult::Activated(rows[i].node.clone())
+                });
+            }
+            Key::A if self.multi_select && self.modifiers.command => {
+                state.selected = rows.iter().map(|row| row.node.clone()).collect();
+                return KeyResult::MovedCursor {
+                    selection_changed: true,
+                };
+            }
+            _ => return KeyResult::None,
+        };
+
+        self.move_cursor(state, rows, target)
+    }
+
+    fn move_cursor<NodeId: Clone + PartialEq + Send + Sync + 'static>(
+        &self,
+        state: &mut TreeViewState<NodeId>,
+        rows: &[Row<NodeId>],
+        index: usize,
+    ) -> KeyResult<NodeId> {
+        if self.multi_select && self.modifiers.shift {
+            state.select_range(rows, index);
+        } else if self.multi_select && self.modifiers.command {
+            // Move the cursor without changing the selection:
+            state.cursor = Some(rows[index].node.clone());
+            return KeyResult::MovedCursor {
+                selection_changed: false,
+            };
+        } else {
+            state.select_only(&rows[index].node);
+        }
+        KeyResult::MovedCursor {
+            selection_changed: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Node `n` has the children `10 * n + 1 ..= 10 * n + 3`, until a depth of three.
+    #[derive(Default)]
+    struct Tree {
+        shown: Vec<u32>,
+    }
+
+    impl TreeViewDelegate for Tree {
+        type NodeId = u32;
+
+        fn roots(&mut self) -> Vec<u32> {
+            vec![1, 2, 3]
+        }
+
+        fn has_children(&mut self, node: &u32) -> bool {
+            *node < 100
+        }
+
+        fn children(&mut self, node: &u32) -> Vec<u32> {
+            (1..=3).map(|i| 10 * node + i).collect()
+        }
+
+        fn node_ui(&mut self, ui: &mut Ui, node: &u32) {
+            self.shown.push(*node);
+            ui.label(node.to_string());
+        }
+    }
+
+    fn run(
+        ctx: &Context,
+        tree: &mut Tree,
+        tree_view: impl Fn() -> TreeView,
+        events: Vec<Event>,
+    ) -> TreeViewResponse<u32> {
+        tree.shown.clear();
+        let mut response = None;
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(pos2(0.0, 0.0), vec2(400.0, 300.0))),
+            events,
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    response = Some(tree_view().show(ui, tree));
+                });
+            });
+        });
+        response.unwrap()
+    }
+
+    fn key(key: Key, modifiers: Modifiers) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn test_keyboard_navigation() {
+        let ctx = Context::default();
+        let mut tree = Tree::default();
+        let tree_view = || TreeView::new("tree");
+
+        let response = run(&ctx, &mut tree, tree_view, vec![]);
+        assert_eq!(tree.shown, vec![1, 2, 3]);
+        ctx.memory_mut(|mem| mem.request_focus(response.id));
+        run(&ctx, &mut tree, tree_view, vec![]);
+
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::ArrowDown, Modifiers::NONE)],
+        );
+        assert_eq!(response.state.selected(), &[1]);
+        assert!(response.selection_changed);
+
+        // Expand, then move into the children:
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::ArrowRight, Modifiers::NONE)],
+        );
+        assert_eq!(tree.shown, vec![1, 11, 12, 13, 2, 3]);
+        assert!(!response.selection_changed);
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::ArrowRight, Modifiers::NONE)],
+        );
+        assert_eq!(response.state.selected(), &[11]);
+
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![
+                key(Key::ArrowDown, Modifiers::SHIFT),
+                key(Key::ArrowDown, Modifiers::SHIFT),
+            ],
+        );
+        assert_eq!(response.state.selected(), &[11, 12, 13]);
+        assert_eq!(response.state.cursor(), Some(&13));
+
+        // Back to the parent, and collapse it:
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::ArrowLeft, Modifiers::NONE)],
+        );
+        assert_eq!(response.state.selected(), &[1]);
+        run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::ArrowLeft, Modifiers::NONE)],
+        );
+        assert_eq!(tree.shown, vec![1, 2, 3]);
+
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::End, Modifiers::NONE)],
+        );
+        assert_eq!(response.state.selected(), &[3]);
+        let response = run(
+            &ctx,
+            &mut tree,
+            tree_view,
+            vec![key(Key::Enter, Modifiers::NONE)],
+        );
+        assert_eq!(response.activated, Some(3));
+
+        let response = run(
+            &ctx,
+            &mut tree,
+            || TreeView::new("tree").multi_select(false),
+            vec![key(Key::Home, Modifiers::SHIFT)],
+        );
+        assert_eq!(response.state.selected(), &[1]);
+    }
+
+    #[test]
+    fn test_only_visible_rows_are_shown() {
+        let ctx = Context::default();
+        let mut tree = Tree::default();
+        let response = run(
+            &ctx,
+            &mut tree,
+            || TreeView::new("tree").default_open(true),
+            vec![],
+        );
+
+        let num_rows = 3 + 9 + 27;
+        let row_height = ctx.style().spacing.interact_size.y + ctx.style().spacing.item_spacing.y;
+        assert!(response.response.rect.height() > num_rows as f32 * row_height - 10.0);
+        assert!(!tree.shown.is_empty());
+        assert!(tree.shown.len() < num_rows);
+        assert_eq!(&tree.shown[..5], &[1, 11, 111, 112, 113]);
+    }
+}
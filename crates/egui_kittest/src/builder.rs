@@ -87,6 +87,14 @@ impl<State> HarnessBuilder<State> {
         self
     }
 
+    /// Enable software rendering, which needs no GPU.
+    ///
+    /// This sets up a [`crate::software::SoftwareTestRenderer`].
+    #[cfg(feature = "software")]
+    pub fn software(self) -> Self {
+        self.renderer(crate::software::SoftwareTestRenderer::new())
+    }
+
     /// Enable wgpu rendering with a default setup suitable for testing.
     ///
     /// This sets up a [`crate::wgpu::WgpuTestRenderer`] with the default setup.
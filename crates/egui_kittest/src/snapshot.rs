@@ -363,7 +363,7 @@ pub fn image_snapshot(current: &image::RgbaImage, name: &str) {
     }
 }
 
-#[cfg(feature = "wgpu")]
+#[cfg(any(feature = "wgpu", feature = "software"))]
 impl<State> Harness<'_, State> {
     /// Render an image using the setup [`crate::TestRenderer`] and compare it to the snapshot
     /// with custom options.
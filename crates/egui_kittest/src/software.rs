@@ -0,0 +1,359 @@
+//! A pure-CPU [`crate::TestRenderer`], for machines without a GPU.
+//!
+//! The output mirrors what [`egui_wgpu`](https://docs.rs/egui-wgpu) renders into a
+//! gamma-space framebuffer: vertex colors are interpolated in gamma space,
+//! textures are filtered in linear space and everything is blended with premultiplied alpha.
+
+use std::collections::HashMap;
+
+use egui::epaint::{ClippedPrimitive, ImageData, Mesh, Primitive, TextureId, Vertex};
+use egui::{
+    Pos2, Rect, Rgba, TextureFilter, TextureOptions, TextureWrapMode, TexturesDelta, Vec2, ecolor,
+    vec2,
+};
+use image::RgbaImage;
+
+/// A texture uploaded through a [`TexturesDelta`].
+struct Texture {
+    size: [usize; 2],
+
+    /// Linear space, premultiplied alpha.
+    pixels: Vec<Rgba>,
+
+    options: TextureOptions,
+}
+
+impl Texture {
+    fn texel(&self, x: isize, y: isize) -> Rgba {
+        let x = wrap(x, self.size[0], self.options.wrap_mode);
+        let y = wrap(y, self.size[1], self.options.wrap_mode);
+        self.pixels[y * self.size[0] + x]
+    }
+
+    /// Sample the texture at the given normalized texture coordinate.
+    fn sample(&self, uv: Pos2, filter: TextureFilter) -> Rgba {
+        let [w, h] = self.size;
+        if w == 0 || h == 0 {
+            return Rgba::TRANSPARENT;
+        }
+        let x = uv.x * w as f32;
+        let y = uv.y * h as f32;
+        match filter {
+            TextureFilter::Nearest => self.texel(x.floor() as isize, y.floor() as isize),
+            TextureFilter::Linear => {
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let top = lerp_rgba(self.texel(x0, y0), self.texel(x0 + 1, y0), tx);
+                let bottom = lerp_rgba(self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1), tx);
+                lerp_rgba(top, bottom, ty)
+            }
+        }
+    }
+}
+
+/// Map a (possibly out of bounds) texel coordinate to a valid index according to the wrap mode.
+fn wrap(i: isize, size: usize, mode: TextureWrapMode) -> usize {
+    let size = size as isize;
+    let i = match mode {
+        TextureWrapMode::ClampToEdge => i.clamp(0, size - 1),
+        TextureWrapMode::Repeat => i.rem_euclid(size),
+        TextureWrapMode::MirroredRepeat => {
+            let i = i.rem_euclid(2 * size);
+            if i < size { i } else { 2 * size - 1 - i }
+        }
+    };
+    i as usize
+}
+
+fn lerp_rgba(a: Rgba, b: Rgba, t: f32) -> Rgba {
+    a * (1.0 - t) + b * t
+}
+
+/// Utility to render snapshots from a [`crate::Harness`] without a GPU.
+///
+/// This rasterizes the tessellated [`egui::epaint::Mesh`]es on the CPU, so it works on any
+/// machine and produces the exact same output on every run.
+///
+/// [`egui::PaintCallback`]s are not supported, and will result in a render error.
+#[derive(Default)]
+pub struct SoftwareTestRenderer {
+    textures: HashMap<TextureId, Texture>,
+}
+
+impl SoftwareTestRenderer {
+    /// Create a new [`SoftwareTestRenderer`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rasterize the given primitives into an image of the given size (in pixels).
+    ///
+    /// # Errors
+    /// Returns an error if a mesh references an unknown texture, or if there is a paint callback.
+    pub fn render_primitives(
+        &self,
+        primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        size_in_pixels: [usize; 2],
+    ) -> Result<RgbaImage, String> {
+        let mut target = Target::new(size_in_pixels);
+
+        for ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in primitives
+        {
+            match primitive {
+                Primitive::Mesh(mesh) => {
+                    let texture = self
+                        .textures
+                        .get(&mesh.texture_id)
+                        .ok_or_else(|| format!("Unknown texture: {:?}", mesh.texture_id))?;
+                    let scissor = target.scissor(*clip_rect, pixels_per_point);
+                    target.draw_mesh(mesh, texture, pixels_per_point, scissor);
+                }
+                Primitive::Callback(_) => {
+                    return Err(
+                        "Paint callbacks are not supported by the SoftwareTestRenderer".to_owned(),
+                    );
+                }
+            }
+        }
+
+        Ok(target.into_image())
+    }
+}
+
+impl crate::TestRenderer for SoftwareTestRenderer {
+    fn handle_delta(&mut self, delta: &TexturesDelta) {
+        for (id, delta) in &delta.set {
+            let pixels: Vec<Rgba> = match &delta.image {
+                ImageData::Color(image) => image.pixels.iter().copied().map(Rgba::from).collect(),
+                ImageData::Font(image) => image.srgba_pixels(None).map(Rgba::from).collect(),
+            };
+            let [width, height] = delta.image.size();
+
+            if let Some([x, y]) = delta.pos {
+                let texture = self
+                    .textures
+                    .get_mut(id)
+                    .expect("Tried to update a texture that has not been allocated yet.");
+                texture.options = delta.options;
+                for row in 0..height {
+                    let dst = (y + row) * texture.size[0] + x;
+                    texture.pixels[dst..dst + width]
+                        .copy_from_slice(&pixels[row * width..(row + 1) * width]);
+                }
+            } else {
+                self.textures.insert(
+                    *id,
+                    Texture {
+                        size: [width, height],
+                        pixels,
+                        options: delta.options,
+                    },
+                );
+            }
+        }
+
+        for id in &delta.free {
+            self.textures.remove(id);
+        }
+    }
+
+    /// Render the [`crate::Harness`] and return the resulting image.
+    fn render(
+        &mut self,
+        ctx: &egui::Context,
+        output: &egui::FullOutput,
+    ) -> Result<RgbaImage, String> {
+        let pixels_per_point = ctx.pixels_per_point();
+        let size = ctx.screen_rect().size() * pixels_per_point;
+        let size_in_pixels = [size.x.round() as usize, size.y.round() as usize];
+
+        let primitives = ctx.tessellate(output.shapes.clone(), pixels_per_point);
+
+        self.render_primitives(&primitives, pixels_per_point, size_in_pixels)
+    }
+}
+
+/// Pixel-space scissor rectangle, `min` inclusive, `max` exclusive.
+#[derive(Clone, Copy)]
+struct Scissor {
+    min: [usize; 2],
+    max: [usize; 2],
+}
+
+/// The framebuffer we render into.
+struct Target {
+    size: [usize; 2],
+
+    /// Gamma space, premultiplied alpha, in 0-1 range.
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Target {
+    fn new(size: [usize; 2]) -> Self {
+        Self {
+            size,
+            pixels: vec![[0.0; 4]; size[0] * size[1]],
+        }
+    }
+
+    /// Same rounding as `egui_wgpu`.
+    fn scissor(&self, clip_rect: Rect, pixels_per_point: f32) -> Scissor {
+        let round =
+            |v: f32, max: usize| ((v * pixels_per_point).round().max(0.0) as usize).min(max);
+        let min_x = round(clip_rect.min.x, self.size[0]);
+        let min_y = round(clip_rect.min.y, self.size[1]);
+        let max_x = round(clip_rect.max.x, self.size[0]).max(min_x);
+        let max_y = round(clip_rect.max.y, self.size[1]).max(min_y);
+        Scissor {
+            min: [min_x, min_y],
+            max: [max_x, max_y],
+        }
+    }
+
+    fn draw_mesh(
+        &mut self,
+        mesh: &Mesh,
+        texture: &Texture,
+        pixels_per_point: f32,
+        scissor: Scissor,
+    ) {
+        if scissor.min[0] >= scissor.max[0] || scissor.min[1] >= scissor.max[1] {
+            return;
+        }
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+            self.draw_triangle([a, b, c], texture, pixels_per_point, scissor);
+        }
+    }
+
+    fn draw_triangle(
+        &mut self,
+        vertices: [&Vertex; 3],
+        texture: &Texture,
+        pixels_per_point: f32,
+        scissor: Scissor,
+    ) {
+        let mut vertices = vertices;
+        let mut pos = vertices.map(|v| v.pos.to_vec2() * pixels_per_point);
+
+        let mut area = edge(pos[0], pos[1], pos[2]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            // egui doesn't care about winding order, but we want a consistent one:
+            vertices.swap(1, 2);
+            pos.swap(1, 2);
+            area = -area;
+        }
+
+        // Each edge is owned by exactly one of the triangles sharing it (the "top-left" rule),
+        // so we never blend the same pixel twice along a shared edge.
+        let owns_edge = |a: Vec2, b: Vec2| {
+            let d = b - a;
+            d.y > 0.0 || (d.y == 0.0 && d.x < 0.0)
+        };
+        let owned = [
+            owns_edge(pos[1], pos[2]),
+            owns_edge(pos[2], pos[0]),
+            owns_edge(pos[0], pos[1]),
+        ];
+
+        let colors = vertices.map(|v| {
+            let [r, g, b, a] = v.color.to_array();
+            [r, g, b, a].map(|c| c as f32 / 255.0)
+        });
+        let uvs = vertices.map(|v| v.uv);
+
+        // Pick the filter by comparing the texel area to the pixel area of the triangle:
+        let uv_area = edge(uvs[0].to_vec2(), uvs[1].to_vec2(), uvs[2].to_vec2()).abs()
+            * texture.size[0] as f32
+            * texture.size[1] as f32;
+        let filter = if uv_area > area {
+            texture.options.minification
+        } else {
+            texture.options.magnification
+        };
+
+        let min_x = pos.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+        let max_x = pos.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+        let min_y = pos.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_y = pos.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+
+        let x_range = pixel_range(min_x, max_x, scissor.min[0], scissor.max[0]);
+        let y_range = pixel_range(min_y, max_y, scissor.min[1], scissor.max[1]);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let p = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let w = [
+                    edge(pos[1], pos[2], p),
+                    edge(pos[2], pos[0], p),
+                    edge(pos[0], pos[1], p),
+                ];
+                let inside = (0..3).all(|i| w[i] > 0.0 || (w[i] == 0.0 && owned[i]));
+                if !inside {
+                    continue;
+                }
+                let w = w.map(|w| w / area);
+
+                let uv = Pos2::new(
+                    w[0] * uvs[0].x + w[1] * uvs[1].x + w[2] * uvs[2].x,
+                    w[0] * uvs[0].y + w[1] * uvs[1].y + w[2] * uvs[2].y,
+                );
+                let tex_linear = texture.sample(uv, filter);
+                let tex_gamma = [
+                    ecolor::gamma_from_linear(tex_linear.r()),
+                    ecolor::gamma_from_linear(tex_linear.g()),
+                    ecolor::gamma_from_linear(tex_linear.b()),
+                    tex_linear.a(),
+                ];
+
+                let src: [f32; 4] = std::array::from_fn(|c| {
+                    let color = w[0] * colors[0][c] + w[1] * colors[1][c] + w[2] * colors[2][c];
+                    color * tex_gamma[c]
+                });
+
+                // Premultiplied alpha blending:
+                let dst = &mut self.pixels[y * self.size[0] + x];
+                for c in 0..4 {
+                    dst[c] = src[c] + dst[c] * (1.0 - src[3]);
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let [width, height] = self.size;
+        let bytes = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8))
+            .collect();
+        RgbaImage::from_raw(width as u32, height as u32, bytes).expect("Failed to create image")
+    }
+}
+
+/// Signed area of the parallelogram spanned by `a->b` and `a->p`.
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// The pixels whose centers may fall within `min..=max`, limited to the scissor range.
+fn pixel_range(
+    min: f32,
+    max: f32,
+    scissor_min: usize,
+    scissor_max: usize,
+) -> std::ops::Range<usize> {
+    let start = ((min - 0.5).floor().max(0.0) as usize).max(scissor_min);
+    let end = ((max + 0.5).ceil().max(0.0) as usize).min(scissor_max);
+    start..end.max(start)
+}
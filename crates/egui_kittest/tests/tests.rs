@@ -81,3 +81,22 @@ fn should_wait_for_images() {
 
     harness.snapshot("should_wait_for_images");
 }
+
+#[cfg(feature = "software")]
+#[test]
+fn software_renderer() {
+    let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), Vec2::splat(8.0));
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(40.0, 40.0))
+        .with_pixels_per_point(2.0)
+        .software()
+        .build_ui(|ui| {
+            ui.painter().rect_filled(rect, 0.0, egui::Color32::RED);
+        });
+
+    let image = harness.render().expect("Failed to render");
+    assert_eq!(image.dimensions(), (80, 80));
+    assert_eq!(image.get_pixel(28, 48).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(0, 0), image.get_pixel(79, 79));
+    assert_ne!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+}
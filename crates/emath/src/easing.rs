@@ -224,3 +224,131 @@ pub fn bounce_in_out(t: f32) -> f32 {
         0.5 * bounce_out(t * 2. - 1.) + 0.5
     }
 }
+
+/// One of the easing functions in this module, as a value that can be stored, compared and serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Easing {
+    /// See [`linear`].
+    #[default]
+    Linear,
+
+    /// See [`quadratic_in`].
+    QuadraticIn,
+
+    /// See [`quadratic_out`].
+    QuadraticOut,
+
+    /// See [`quadratic_in_out`].
+    QuadraticInOut,
+
+    /// See [`cubic_in`].
+    CubicIn,
+
+    /// See [`cubic_out`].
+    CubicOut,
+
+    /// See [`cubic_in_out`].
+    CubicInOut,
+
+    /// See [`sin_in`].
+    SinIn,
+
+    /// See [`sin_out`].
+    SinOut,
+
+    /// See [`sin_in_out`].
+    SinInOut,
+
+    /// See [`circular_in`].
+    CircularIn,
+
+    /// See [`circular_out`].
+    CircularOut,
+
+    /// See [`circular_in_out`].
+    CircularInOut,
+
+    /// See [`exponential_in`].
+    ExponentialIn,
+
+    /// See [`exponential_out`].
+    ExponentialOut,
+
+    /// See [`exponential_in_out`].
+    ExponentialInOut,
+
+    /// See [`back_in`].
+    BackIn,
+
+    /// See [`back_out`].
+    BackOut,
+
+    /// See [`back_in_out`].
+    BackInOut,
+
+    /// See [`bounce_in`].
+    BounceIn,
+
+    /// See [`bounce_out`].
+    BounceOut,
+
+    /// See [`bounce_in_out`].
+    BounceInOut,
+}
+
+impl Easing {
+    pub const ALL: [Self; 22] = [
+        Self::Linear,
+        Self::QuadraticIn,
+        Self::QuadraticOut,
+        Self::QuadraticInOut,
+        Self::CubicIn,
+        Self::CubicOut,
+        Self::CubicInOut,
+        Self::SinIn,
+        Self::SinOut,
+        Self::SinInOut,
+        Self::CircularIn,
+        Self::CircularOut,
+        Self::CircularInOut,
+        Self::ExponentialIn,
+        Self::ExponentialOut,
+        Self::ExponentialInOut,
+        Self::BackIn,
+        Self::BackOut,
+        Self::BackInOut,
+        Self::BounceIn,
+        Self::BounceOut,
+        Self::BounceInOut,
+    ];
+
+    /// Apply the easing function to `t`, which should be in `[0, 1]`.
+    #[inline]
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => linear(t),
+            Self::QuadraticIn => quadratic_in(t),
+            Self::QuadraticOut => quadratic_out(t),
+            Self::QuadraticInOut => quadratic_in_out(t),
+            Self::CubicIn => cubic_in(t),
+            Self::CubicOut => cubic_out(t),
+            Self::CubicInOut => cubic_in_out(t),
+            Self::SinIn => sin_in(t),
+            Self::SinOut => sin_out(t),
+            Self::SinInOut => sin_in_out(t),
+            Self::CircularIn => circular_in(t),
+            Self::CircularOut => circular_out(t),
+            Self::CircularInOut => circular_in_out(t),
+            Self::ExponentialIn => exponential_in(t),
+            Self::ExponentialOut => exponential_out(t),
+            Self::ExponentialInOut => exponential_in_out(t),
+            Self::BackIn => back_in(t),
+            Self::BackOut => back_out(t),
+            Self::BackInOut => back_in_out(t),
+            Self::BounceIn => bounce_in(t),
+            Self::BounceOut => bounce_out(t),
+            Self::BounceInOut => bounce_in_out(t),
+        }
+    }
+}
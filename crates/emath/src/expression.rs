@@ -0,0 +1,391 @@
+//! Evaluate simple arithmetic expressions like `2*pi` or `10mm + 1.5cm`. Used by `DragValue`.
+
+use std::fmt::Display;
+
+/// Why an expression could not be evaluated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// The input was empty (or only whitespace).
+    Empty,
+
+    /// The expression ended where more was expected, e.g. `2 *`.
+    UnexpectedEnd,
+
+    /// A character that doesn't belong there, e.g. the `)` in `2)`.
+    UnexpectedCharacter(char),
+
+    /// Something that looks like a number but isn't, e.g. `1.2.3`.
+    InvalidNumber(String),
+
+    /// A name that is neither a known unit, constant, nor function.
+    UnknownName(String),
+
+    /// The result is infinite or NaN, e.g. from `1/0`.
+    NotFinite,
+
+    /// Too many nested parentheses, signs or powers, e.g. `((((…))))`.
+    TooDeeplyNested,
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Enter a value"),
+            Self::UnexpectedEnd => f.write_str("Incomplete expression"),
+            Self::UnexpectedCharacter(c) => write!(f, "Unexpected {c:?}"),
+            Self::InvalidNumber(text) => write!(f, "Invalid number {text:?}"),
+            Self::UnknownName(name) => write!(f, "Unknown unit or constant {name:?}"),
+            Self::NotFinite => f.write_str("The result is not a finite number"),
+            Self::TooDeeplyNested => f.write_str("The expression is too deeply nested"),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Evaluate an arithmetic expression.
+///
+/// Supports numbers (`1.5e3`), `+ - * / ^`, parentheses,
+/// the functions `abs sqrt exp ln log10 sin cos tan floor ceil round`,
+/// and names (e.g. units or constants) which are looked up with `lookup_name`.
+///
+/// A number or parenthesis directly followed by a name is multiplied with it,
+/// so `10mm` is the same as `10 * mm`.
+///
+/// Whitespace separates numbers and names, so `pi r` is `pi * r`, and `1 234` is an error.
+/// `−`, `×` and `÷` may be used instead of `-`, `*` and `/`.
+///
+/// ```
+/// # use emath::expression::{evaluate, ExpressionError};
+/// let lookup = |name: &str| match name {
+///     "pi" => Some(std::f64::consts::PI),
+///     "mm" => Some(0.001),
+///     "cm" => Some(0.01),
+///     _ => None,
+/// };
+/// assert_eq!(evaluate("1.5e3 + 20", lookup), Ok(1520.0));
+/// assert_eq!(evaluate("2*pi", lookup), Ok(std::f64::consts::TAU));
+/// assert_eq!(evaluate("10mm", lookup), Ok(0.01));
+/// assert_eq!(evaluate("2 +", lookup), Err(ExpressionError::UnexpectedEnd));
+/// ```
+///
+/// # Errors
+/// If the text is not a valid expression, uses an unknown name, or evaluates to a non-finite number.
+pub fn evaluate(
+    text: &str,
+    lookup_name: impl Fn(&str) -> Option<f64>,
+) -> Result<f64, ExpressionError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        depth: 0,
+        lookup_name: &lookup_name,
+    };
+
+    if parser.peek().is_none() {
+        return Err(ExpressionError::Empty);
+    }
+
+    let value = parser.sum()?;
+    if let Some(c) = parser.peek() {
+        return Err(ExpressionError::UnexpectedCharacter(c));
+    }
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(ExpressionError::NotFinite)
+    }
+}
+
+/// How deeply [`Parser::unary`] may recurse, so that e.g. a long run of `(` doesn't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+
+    /// Current recursion depth of [`Self::unary`].
+    depth: usize,
+
+    lookup_name: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '°' || c == '%'
+}
+
+impl Parser<'_> {
+    /// The next char that isn't whitespace.
+    fn peek(&mut self) -> Option<char> {
+        while self.peek_raw().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.peek_raw()
+    }
+
+    /// The next char, even if it is whitespace, e.g. to find the end of a number.
+    fn peek_raw(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<char, ExpressionError> {
+        let c = self.peek().ok_or(ExpressionError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn eat(&mut self, options: &[char]) -> Option<char> {
+        let c = self.peek().filter(|c| options.contains(c))?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    /// `product (('+' | '-') product)*`
+    fn sum(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.product()?;
+        while let Some(op) = self.eat(&['+', '-', '−']) {
+            let rhs = self.product()?;
+            if op == '+' {
+                value += rhs;
+            } else {
+                value -= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    /// `unary (('*' | '/') unary)*`
+    fn product(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.eat(&['*', '×', '/', '÷']) {
+            let rhs = self.unary()?;
+            if op == '*' || op == '×' {
+                value *= rhs;
+            } else {
+                value /= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    /// `('+' | '-')* power`
+    ///
+    /// All recursion goes through here, so this is where we limit the depth.
+    fn unary(&mut self) -> Result<f64, ExpressionError> {
+        if MAX_DEPTH <= self.depth {
+            return Err(ExpressionError::TooDeeplyNested);
+        }
+        self.depth += 1;
+        let value = match self.eat(&['+', '-', '−']) {
+            Some('+') => self.unary(),
+            Some(_) => self.unary().map(|value| -value),
+            None => self.power(),
+        };
+        self.depth -= 1;
+        value
+    }
+
+    /// `implicit_product ('^' unary)?`, right associative.
+    fn power(&mut self) -> Result<f64, ExpressionError> {
+        let base = self.implicit_product()?;
+        if self.eat(&['^']).is_some() {
+            Ok(base.powf(self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// `atom name*`, e.g. `10mm`.
+    fn implicit_product(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.atom()?;
+        while self.peek().is_some_and(is_name_char) {
+            value *= self.name_value()?;
+        }
+        Ok(value)
+    }
+
+    /// A number, a name, a function call, or a parenthesized expression.
+    fn atom(&mut self) -> Result<f64, ExpressionError> {
+        match self.peek() {
+            None => Err(ExpressionError::UnexpectedEnd),
+            Some('(') => self.parenthesized(),
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if is_name_char(c) => self.name_value(),
+            Some(c) => Err(ExpressionError::UnexpectedCharacter(c)),
+        }
+    }
+
+    fn parenthesized(&mut self) -> Result<f64, ExpressionError> {
+        let open = self.next()?;
+        if open != '(' {
+            return Err(ExpressionError::UnexpectedCharacter(open));
+        }
+        let value = self.sum()?;
+        match self.next()? {
+            ')' => Ok(value),
+            c => Err(ExpressionError::UnexpectedCharacter(c)),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ExpressionError> {
+        let start = self.pos;
+        while self
+            .peek_raw()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.')
+        {
+            self.pos += 1;
+        }
+
+        // Only treat `e` as an exponent if it is followed by digits,
+        // so that `2e` is two times the constant `e`:
+        if matches!(self.peek_raw(), Some('e' | 'E')) {
+            let sign = usize::from(matches!(self.chars.get(self.pos + 1), Some('+' | '-')));
+            if self
+                .chars
+                .get(self.pos + 1 + sign)
+                .is_some_and(char::is_ascii_digit)
+            {
+                self.pos += 1 + sign;
+                while self.peek_raw().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map_err(|_err| ExpressionError::InvalidNumber(text))
+    }
+
+    /// A unit, a constant, or a function call.
+    fn name_value(&mut self) -> Result<f64, ExpressionError> {
+        let start = self.pos;
+        while self
+            .peek_raw()
+            .is_some_and(|c| is_name_char(c) || c.is_ascii_digit())
+        {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+
+        if self.peek() == Some('(') {
+            let function: Option<fn(f64) -> f64> = match name.as_str() {
+                "abs" => Some(f64::abs),
+                "sqrt" => Some(f64::sqrt),
+                "exp" => Some(f64::exp),
+                "ln" => Some(f64::ln),
+                "log10" => Some(f64::log10),
+                "sin" => Some(f64::sin),
+                "cos" => Some(f64::cos),
+                "tan" => Some(f64::tan),
+                "floor" => Some(f64::floor),
+                "ceil" => Some(f64::ceil),
+                "round" => Some(f64::round),
+                _ => None,
+            };
+            if let Some(function) = function {
+                return Ok(function(self.parenthesized()?));
+            }
+        }
+
+        (self.lookup_name)(&name).ok_or(ExpressionError::UnknownName(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> Result<f64, ExpressionError> {
+        evaluate(text, |name| match name {
+            "e" => Some(std::f64::consts::E),
+            "pi" => Some(std::f64::consts::PI),
+            "mm" => Some(0.001),
+            "cm" | "%" => Some(0.01),
+            "m" => Some(1.0),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(eval("42"), Ok(42.0));
+        assert_eq!(eval(" 1.25 "), Ok(1.25));
+        assert_eq!(eval(".5"), Ok(0.5));
+        assert_eq!(eval("1.5e3"), Ok(1500.0));
+        assert_eq!(eval("1E-2"), Ok(0.01));
+        assert_eq!(eval("−3"), Ok(-3.0));
+        assert_eq!(
+            eval("1 234"),
+            Err(ExpressionError::UnexpectedCharacter('2'))
+        );
+        assert_eq!(
+            eval("1.5 e3"),
+            Err(ExpressionError::UnknownName("e3".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(eval("1.5e3 + 20"), Ok(1520.0));
+        assert_eq!(eval("2 + 3 * 4"), Ok(14.0));
+        assert_eq!(eval("(2 + 3) * 4"), Ok(20.0));
+        assert_eq!(
+            eval("2(3 + 4)"),
+            Err(ExpressionError::UnexpectedCharacter('('))
+        );
+        assert_eq!(eval("8 / 4 / 2"), Ok(1.0));
+        assert_eq!(eval("2 - 3 - 4"), Ok(-5.0));
+        assert_eq!(eval("2^3^2"), Ok(512.0));
+        assert_eq!(eval("-2^2"), Ok(-4.0));
+        assert_eq!(eval("3 × 4 ÷ 2"), Ok(6.0));
+        assert_eq!(eval("--1"), Ok(1.0));
+        assert_eq!(eval("sqrt(16) + abs(-1)"), Ok(5.0));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(eval("2*pi"), Ok(std::f64::consts::TAU));
+        assert_eq!(eval("2pi"), Ok(std::f64::consts::TAU));
+        assert_eq!(eval("2e"), Ok(2.0 * std::f64::consts::E));
+        assert_eq!(eval("10mm"), Ok(0.01));
+        assert_eq!(eval("1m + 50cm"), Ok(1.5));
+        assert_eq!(eval("(1 + 1)m"), Ok(2.0));
+        assert_eq!(eval("2 pi m"), Ok(std::f64::consts::TAU));
+        assert_eq!(eval("sqrt (4) m"), Ok(2.0));
+        assert_eq!(eval("50%"), Ok(0.5));
+        assert_eq!(
+            eval("10 inch"),
+            Err(ExpressionError::UnknownName("inch".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(eval(""), Err(ExpressionError::Empty));
+        assert_eq!(eval("  "), Err(ExpressionError::Empty));
+        assert_eq!(eval("2 *"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(eval("(2"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(eval("2)"), Err(ExpressionError::UnexpectedCharacter(')')));
+        assert_eq!(
+            eval("1..2"),
+            Err(ExpressionError::InvalidNumber("1..2".to_owned()))
+        );
+        assert_eq!(eval("1/0"), Err(ExpressionError::NotFinite));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        assert_eq!(
+            eval(&format!("{}1{}", "(".repeat(20), ")".repeat(20))),
+            Ok(1.0)
+        );
+        for deep in [
+            "(".repeat(100_000),
+            "-".repeat(100_000),
+            "2^".repeat(100_000),
+        ] {
+            assert_eq!(eval(&deep), Err(ExpressionError::TooDeeplyNested));
+        }
+    }
+}
@@ -27,6 +27,7 @@ use std::ops::{Add, Div, Mul, RangeInclusive, Sub};
 
 pub mod align;
 pub mod easing;
+pub mod expression;
 mod gui_rounding;
 mod history;
 mod numeric;
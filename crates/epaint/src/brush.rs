@@ -1,12 +1,13 @@
-use crate::{Rect, TextureId};
+use crate::{Color32, Pos2, Rect, TextureId, emath::lerp};
 
-/// Controls texturing of a [`crate::RectShape`].
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Controls texturing and gradients of filled shapes,
+/// i.e. [`crate::RectShape`], [`crate::CircleShape`], [`crate::EllipseShape`] and [`crate::PathShape`].
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Brush {
-    /// If the rect should be filled with a texture, which one?
+    /// If the shape should be filled with a texture, which one?
     ///
-    /// The texture is multiplied with [`crate::RectShape::fill`].
+    /// The texture is multiplied with the fill color of the shape.
     pub fill_texture_id: TextureId,
 
     /// What UV coordinates to use for the texture?
@@ -16,4 +17,343 @@ pub struct Brush {
     ///
     /// Use [`Rect::ZERO`] to turn off texturing.
     pub uv: Rect,
+
+    /// If set, the fill is painted with this gradient.
+    ///
+    /// The gradient is multiplied with the fill color of the shape (and the texture, if any),
+    /// so you usually want to set the fill color to [`Color32::WHITE`].
+    pub gradient: Option<Gradient>,
+}
+
+impl Default for Brush {
+    /// No texture and no gradient.
+    #[inline]
+    fn default() -> Self {
+        Self::texture(TextureId::default(), Rect::ZERO)
+    }
+}
+
+impl Brush {
+    /// Fill with a texture.
+    #[inline]
+    pub fn texture(fill_texture_id: TextureId, uv: Rect) -> Self {
+        Self {
+            fill_texture_id,
+            uv,
+            gradient: None,
+        }
+    }
+
+    /// Fill with a gradient, without any texture.
+    #[inline]
+    pub fn gradient(gradient: Gradient) -> Self {
+        Self {
+            fill_texture_id: TextureId::default(),
+            uv: Rect::ZERO,
+            gradient: Some(gradient),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A color at a specific offset along a [`Gradient`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct ColorStop {
+    /// Where along the gradient this color is, usually in the range `0..=1`.
+    pub offset: f32,
+
+    pub color: Color32,
+}
+
+impl ColorStop {
+    #[inline]
+    pub fn new(offset: f32, color: impl Into<Color32>) -> Self {
+        Self {
+            offset,
+            color: color.into(),
+        }
+    }
+}
+
+impl<C: Into<Color32>> From<(f32, C)> for ColorStop {
+    #[inline]
+    fn from((offset, color): (f32, C)) -> Self {
+        Self::new(offset, color)
+    }
+}
+
+/// The geometry of a [`Gradient`].
+///
+/// All positions are normalized to the bounding rectangle of the filled shape,
+/// so that `(0, 0)` is the top left corner and `(1, 1)` is the bottom right corner.
+/// This means the gradient follows the shape when it is moved or scaled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum GradientKind {
+    /// The color changes along the line from `start` (offset 0) to `end` (offset 1),
+    /// and is constant perpendicular to it.
+    Linear { start: Pos2, end: Pos2 },
+
+    /// The color changes with the distance from `center`, reaching offset 1 at `radius`.
+    ///
+    /// Since the coordinates are normalized, this is an ellipse for non-square shapes.
+    Radial { center: Pos2, radius: f32 },
+
+    /// The color changes with the angle around `center`, going clockwise one full turn
+    /// from offset 0 at `start_angle` (in radians, where zero is pointing right).
+    Conic { center: Pos2, start_angle: f32 },
+}
+
+/// A smooth transition between several colors, used to fill shapes via a [`Brush`].
+///
+/// Colors are interpolated in gamma space, like the rest of egui.
+/// Before the first stop and after the last stop the color is constant.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Gradient {
+    pub kind: GradientKind,
+
+    /// Sorted by [`ColorStop::offset`].
+    stops: Vec<ColorStop>,
+}
+
+impl Gradient {
+    /// The stops can be given in any order.
+    pub fn new(kind: GradientKind, stops: impl IntoIterator<Item = impl Into<ColorStop>>) -> Self {
+        let mut stops: Vec<ColorStop> = stops.into_iter().map(Into::into).collect();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Self { kind, stops }
+    }
+
+    /// The color stops, sorted by [`ColorStop::offset`].
+    #[inline]
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// A linear gradient from `start` to `end`, in coordinates normalized to the shape.
+    pub fn linear(
+        start: Pos2,
+        end: Pos2,
+        stops: impl IntoIterator<Item = impl Into<ColorStop>>,
+    ) -> Self {
+        Self::new(GradientKind::Linear { start, end }, stops)
+    }
+
+    /// A radial gradient around `center`, in coordinates normalized to the shape.
+    pub fn radial(
+        center: Pos2,
+        radius: f32,
+        stops: impl IntoIterator<Item = impl Into<ColorStop>>,
+    ) -> Self {
+        Self::new(GradientKind::Radial { center, radius }, stops)
+    }
+
+    /// A conic (sweep) gradient around `center`, in coordinates normalized to the shape.
+    pub fn conic(
+        center: Pos2,
+        start_angle: f32,
+        stops: impl IntoIterator<Item = impl Into<ColorStop>>,
+    ) -> Self {
+        Self::new(
+            GradientKind::Conic {
+                center,
+                start_angle,
+            },
+            stops,
+        )
+    }
+
+    /// From `top` at the top edge to `bottom` at the bottom edge.
+    pub fn vertical(top: impl Into<Color32>, bottom: impl Into<Color32>) -> Self {
+        Self::linear(
+            Pos2::new(0.5, 0.0),
+            Pos2::new(0.5, 1.0),
+            [(0.0, top.into()), (1.0, bottom.into())],
+        )
+    }
+
+    /// From `left` at the left edge to `right` at the right edge.
+    pub fn horizontal(left: impl Into<Color32>, right: impl Into<Color32>) -> Self {
+        Self::linear(
+            Pos2::new(0.0, 0.5),
+            Pos2::new(1.0, 0.5),
+            [(0.0, left.into()), (1.0, right.into())],
+        )
+    }
+
+    /// The color at the given offset along the gradient.
+    pub fn color_at_offset(&self, offset: f32) -> Color32 {
+        let Some(first) = self.stops().first() else {
+            return Color32::TRANSPARENT;
+        };
+        if offset.is_nan() || offset <= first.offset {
+            return first.color;
+        }
+        for pair in self.stops().windows(2) {
+            let [a, b] = [pair[0], pair[1]];
+            if offset <= b.offset {
+                let t = if b.offset > a.offset {
+                    (offset - a.offset) / (b.offset - a.offset)
+                } else {
+                    1.0
+                };
+                return a.color.lerp_to_gamma(b.color, t);
+            }
+        }
+        self.stops().last().map_or(first.color, |last| last.color)
+    }
+
+    /// The offset along the gradient of the given position,
+    /// where `rect` is the bounding rectangle of the filled shape.
+    pub fn offset_at(&self, rect: Rect, pos: Pos2) -> f32 {
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let start = rect.lerp_inside(start.to_vec2());
+                let dir = rect.lerp_inside(end.to_vec2()) - start;
+                let length_sq = dir.length_sq();
+                if length_sq > 0.0 {
+                    (pos - start).dot(dir) / length_sq
+                } else {
+                    0.0
+                }
+            }
+            GradientKind::Radial { center, radius } => {
+                let size = rect.size().max(crate::Vec2::splat(f32::EPSILON));
+                let normalized = (pos - rect.min) / size;
+                if radius > 0.0 {
+                    (normalized - center.to_vec2()).length() / radius
+                } else {
+                    0.0
+                }
+            }
+            GradientKind::Conic {
+                center,
+                start_angle,
+            } => {
+                let angle = (pos - rect.lerp_inside(center.to_vec2())).angle() - start_angle;
+                angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU
+            }
+        }
+    }
+
+    /// The color of the given position,
+    /// where `rect` is the bounding rectangle of the filled shape.
+    #[inline]
+    pub fn color_at(&self, rect: Rect, pos: Pos2) -> Color32 {
+        self.color_at_offset(self.offset_at(rect, pos))
+    }
+
+    /// Lines (as `normal · pos = offset`) along which the tessellator must split the fill,
+    /// so that linear interpolation of vertex colors within each piece is (close to) exact.
+    pub(crate) fn split_lines(&self, rect: Rect) -> Vec<(crate::Vec2, f32)> {
+        let mut lines = vec![];
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                // Between two stops the color is linear in the position, so we only need to
+                // split at the stops to be exact.
+                let start = rect.lerp_inside(start.to_vec2());
+                let dir = rect.lerp_inside(end.to_vec2()) - start;
+                let length = dir.length();
+                if length > 0.0 {
+                    let normal = dir / length;
+                    let start_offset = normal.dot(start.to_vec2());
+                    lines.extend(
+                        self.stops()
+                            .iter()
+                            .map(|stop| (normal, start_offset + stop.offset * length)),
+                    );
+                }
+            }
+            GradientKind::Radial { .. } | GradientKind::Conic { .. } => {
+                // Non-linear gradients are approximated with a grid:
+                const MAX_CELLS: f32 = 32.0;
+                const MIN_CELL_SIZE: f32 = 4.0;
+                for (axis, normal) in [(0, crate::Vec2::X), (1, crate::Vec2::Y)] {
+                    let (min, max) = (rect.min[axis], rect.max[axis]);
+                    let num_cells = ((max - min) / MIN_CELL_SIZE).clamp(1.0, MAX_CELLS).ceil();
+                    lines.extend(
+                        (1..num_cells as usize)
+                            .map(|i| (normal, lerp(min..=max, i as f32 / num_cells))),
+                    );
+                }
+
+                if let GradientKind::Conic {
+                    center,
+                    start_angle,
+                } = self.kind
+                {
+                    // Sectors around the center, starting with the seam at `start_angle`:
+                    const NUM_LINES: usize = 16;
+                    let center = rect.lerp_inside(center.to_vec2());
+                    lines.extend((0..NUM_LINES).map(|i| {
+                        let angle =
+                            start_angle + std::f32::consts::PI * i as f32 / NUM_LINES as f32;
+                        let normal = crate::Vec2::angled(angle).rot90();
+                        (normal, normal.dot(center.to_vec2()))
+                    }));
+                }
+            }
+        }
+        lines
+    }
+}
+
+#[test]
+fn gradient_color_at_offset() {
+    let gradient = Gradient::linear(
+        Pos2::ZERO,
+        Pos2::new(1.0, 0.0),
+        [
+            (0.0, Color32::BLACK),
+            (0.5, Color32::WHITE),
+            (1.0, Color32::RED),
+        ],
+    );
+    assert_eq!(gradient.color_at_offset(-1.0), Color32::BLACK);
+    assert_eq!(gradient.color_at_offset(0.0), Color32::BLACK);
+    assert_eq!(gradient.color_at_offset(0.5), Color32::WHITE);
+    assert_eq!(gradient.color_at_offset(1.0), Color32::RED);
+    assert_eq!(gradient.color_at_offset(2.0), Color32::RED);
+    assert_eq!(
+        gradient.color_at_offset(0.25),
+        Color32::BLACK.lerp_to_gamma(Color32::WHITE, 0.5)
+    );
+}
+
+#[test]
+fn gradient_offset_at() {
+    let rect = Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(30.0, 20.0));
+
+    let linear = Gradient::vertical(Color32::BLACK, Color32::WHITE);
+    assert_eq!(linear.offset_at(rect, Pos2::new(12.0, 10.0)), 0.0);
+    assert_eq!(linear.offset_at(rect, Pos2::new(25.0, 15.0)), 0.5);
+
+    let radial = Gradient::radial(Pos2::new(0.5, 0.5), 0.5, [(0.0, Color32::BLACK)]);
+    assert_eq!(radial.offset_at(rect, rect.center()), 0.0);
+    assert_eq!(radial.offset_at(rect, rect.right_center()), 1.0);
+    assert_eq!(radial.offset_at(rect, rect.center_bottom()), 1.0);
+
+    let conic = Gradient::conic(Pos2::new(0.5, 0.5), 0.0, [(0.0, Color32::BLACK)]);
+    assert_eq!(conic.offset_at(rect, rect.right_center()), 0.0);
+    assert_eq!(conic.offset_at(rect, rect.center_bottom()), 0.25);
+    assert_eq!(conic.offset_at(rect, rect.left_center()), 0.5);
+}
+
+#[test]
+fn gradient_stops() {
+    let gradient = Gradient::linear(
+        Pos2::ZERO,
+        Pos2::new(1.0, 0.0),
+        (0..20).rev().map(|i| (i as f32 / 20.0, Color32::BLACK)),
+    );
+    assert_eq!(gradient.stops().len(), 20);
+    assert!(
+        gradient
+            .stops()
+            .windows(2)
+            .all(|pair| pair[0].offset <= pair[1].offset)
+    );
 }
@@ -323,6 +323,8 @@ impl std::fmt::Debug for ColorImage {
 /// Each value represents "coverage", i.e. how much a texel is covered by a character.
 ///
 /// This is roughly interpreted as the opacity of a white image.
+///
+/// Color glyphs (e.g. emoji) are stored in [`Self::colors`] instead.
 #[derive(Clone, Default, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct FontImage {
@@ -333,6 +335,13 @@ pub struct FontImage {
     ///
     /// Often you want to use [`Self::srgba_pixels`] instead.
     pub pixels: Vec<f32>,
+
+    /// Premultiplied colors of texels belonging to color glyphs.
+    ///
+    /// Either empty (no color glyphs), or one value per pixel.
+    /// Texels that are [`Color32::TRANSPARENT`] here use the coverage in [`Self::pixels`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colors: Vec<Color32>,
 }
 
 impl FontImage {
@@ -340,6 +349,7 @@ impl FontImage {
         Self {
             size,
             pixels: vec![0.0; size[0] * size[1]],
+            colors: vec![],
         }
     }
 
@@ -364,7 +374,13 @@ impl FontImage {
         // Ideally we should do this in a shader instead, and use different computations
         // for different text colors.
         // See https://hikogui.org/2022/10/24/the-trouble-with-anti-aliasing.html for an in-depth analysis.
-        self.pixels.iter().map(move |coverage| {
+        self.pixels.iter().enumerate().map(move |(i, coverage)| {
+            if let Some(&color) = self.colors.get(i) {
+                if color != Color32::TRANSPARENT {
+                    return color;
+                }
+            }
+
             let alpha = if let Some(gamma) = gamma {
                 coverage.powf(gamma)
             } else {
@@ -398,9 +414,13 @@ impl FontImage {
         );
 
         let mut pixels = Vec::with_capacity(w * h);
+        let mut colors = Vec::with_capacity(if self.colors.is_empty() { 0 } else { w * h });
         for y in y..y + h {
             let offset = y * self.width() + x;
             pixels.extend(&self.pixels[offset..(offset + w)]);
+            if !self.colors.is_empty() {
+                colors.extend(&self.colors[offset..(offset + w)]);
+            }
         }
         assert_eq!(
             pixels.len(),
@@ -411,7 +431,21 @@ impl FontImage {
         Self {
             size: [w, h],
             pixels,
+            colors,
+        }
+    }
+
+    /// Set the premultiplied color of a texel belonging to a color glyph.
+    pub fn set_color(&mut self, (x, y): (usize, usize), color: Color32) {
+        let [w, h] = self.size;
+        assert!(x < w && y < h, "x: {x}, y: {y}, w: {w}, h: {h}");
+        if self.colors.is_empty() {
+            if color == Color32::TRANSPARENT {
+                return;
+            }
+            self.colors = vec![Color32::TRANSPARENT; self.pixels.len()];
         }
+        self.colors[y * w + x] = color;
     }
 }
 
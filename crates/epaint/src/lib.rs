@@ -46,7 +46,7 @@ pub mod util;
 mod viewport;
 
 pub use self::{
-    brush::Brush,
+    brush::{Brush, ColorStop, Gradient, GradientKind},
     color::ColorMode,
     corner_radius::CornerRadius,
     corner_radius_f32::CornerRadiusF32,
@@ -60,7 +60,7 @@ pub use self::{
         QuadraticBezierShape, RectShape, Shape, TextShape,
     },
     stats::PaintStats,
-    stroke::{PathStroke, Stroke, StrokeKind},
+    stroke::{DashPattern, LineCap, LineJoin, PathStroke, Stroke, StrokeKind, StrokeStyle},
     tessellator::{TessellationOptions, Tessellator},
     text::{FontFamily, FontId, Fonts, Galley},
     texture_atlas::TextureAtlas,
@@ -29,6 +29,7 @@ pub fn adjust_colors(
             closed: _,
             fill,
             stroke,
+            brush: _,
         })
         | Shape::QuadraticBezier(QuadraticBezierShape {
             points: _,
@@ -51,12 +52,16 @@ pub fn adjust_colors(
             radius: _,
             fill,
             stroke,
+            stroke_dash: _,
+            brush: _,
         })
         | Shape::Ellipse(EllipseShape {
             center: _,
             radius: _,
             fill,
             stroke,
+            stroke_dash: _,
+            brush: _,
         })
         | Shape::Rect(RectShape {
             rect: _,
@@ -64,6 +69,7 @@ pub fn adjust_colors(
             fill,
             stroke,
             stroke_kind: _,
+            stroke_dash: _,
             round_to_pixels: _,
             blur_width: _,
             brush: _,
@@ -70,6 +70,7 @@ impl CubicBezierShape {
                 closed: self.closed,
                 fill: self.fill,
                 stroke: self.stroke.clone(),
+                brush: None,
             };
             pathshapes.push(pathshape);
         }
@@ -434,6 +435,7 @@ impl QuadraticBezierShape {
             closed: self.closed,
             fill: self.fill,
             stroke: self.stroke.clone(),
+            brush: None,
         }
     }
 
@@ -1,13 +1,25 @@
-use crate::{Color32, Pos2, Rect, Shape, Stroke, Vec2};
+use std::sync::Arc;
+
+use crate::{Brush, Color32, DashPattern, Gradient, Pos2, Rect, Shape, Stroke, TextureId, Vec2};
 
 /// How to paint a circle.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct CircleShape {
     pub center: Pos2,
     pub radius: f32,
     pub fill: Color32,
     pub stroke: Stroke,
+
+    /// If set, the stroke is painted as dashes following this pattern.
+    ///
+    /// Since most strokes are solid, this is optional and in an `Arc`.
+    pub stroke_dash: Option<Arc<DashPattern>>,
+
+    /// Controls texturing and gradients of the fill, if any.
+    ///
+    /// Since most circles do not have a texture or gradient, this is optional and in an `Arc`.
+    pub brush: Option<Arc<Brush>>,
 }
 
 impl CircleShape {
@@ -18,6 +30,8 @@ impl CircleShape {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_dash: None,
+            brush: None,
         }
     }
 
@@ -28,9 +42,34 @@ impl CircleShape {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_dash: None,
+            brush: None,
         }
     }
 
+    /// Paint the stroke as dashes following the given pattern.
+    #[inline]
+    pub fn with_stroke_dash(mut self, dash: DashPattern) -> Self {
+        self.stroke_dash = Some(Arc::new(dash));
+        self
+    }
+
+    /// Set the [`Brush`] to use for the fill.
+    #[inline]
+    pub fn with_brush(mut self, brush: Brush) -> Self {
+        self.brush = Some(Arc::new(brush));
+        self
+    }
+
+    /// Fill the circle with a gradient, multiplied with [`Self::fill`].
+    ///
+    /// The gradient coordinates are normalized to the bounding square of the circle.
+    #[inline]
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        Arc::make_mut(self.brush.get_or_insert_with(Default::default)).gradient = Some(gradient);
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -42,6 +81,15 @@ impl CircleShape {
             )
         }
     }
+
+    /// The texture to use when painting this circle, if any.
+    ///
+    /// If no texture is set, this will return [`TextureId::default`].
+    pub fn fill_texture_id(&self) -> TextureId {
+        self.brush
+            .as_ref()
+            .map_or_else(TextureId::default, |brush| brush.fill_texture_id)
+    }
 }
 
 impl From<CircleShape> for Shape {
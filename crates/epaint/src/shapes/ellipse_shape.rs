@@ -1,7 +1,9 @@
+use std::sync::Arc;
+
 use crate::*;
 
 /// How to paint an ellipse.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct EllipseShape {
     pub center: Pos2,
@@ -10,6 +12,16 @@ pub struct EllipseShape {
     pub radius: Vec2,
     pub fill: Color32,
     pub stroke: Stroke,
+
+    /// If set, the stroke is painted as dashes following this pattern.
+    ///
+    /// Since most strokes are solid, this is optional and in an `Arc`.
+    pub stroke_dash: Option<Arc<DashPattern>>,
+
+    /// Controls texturing and gradients of the fill, if any.
+    ///
+    /// Since most ellipses do not have a texture or gradient, this is optional and in an `Arc`.
+    pub brush: Option<Arc<Brush>>,
 }
 
 impl EllipseShape {
@@ -20,6 +32,8 @@ impl EllipseShape {
             radius,
             fill: fill_color.into(),
             stroke: Default::default(),
+            stroke_dash: None,
+            brush: None,
         }
     }
 
@@ -30,9 +44,34 @@ impl EllipseShape {
             radius,
             fill: Default::default(),
             stroke: stroke.into(),
+            stroke_dash: None,
+            brush: None,
         }
     }
 
+    /// Paint the stroke as dashes following the given pattern.
+    #[inline]
+    pub fn with_stroke_dash(mut self, dash: DashPattern) -> Self {
+        self.stroke_dash = Some(Arc::new(dash));
+        self
+    }
+
+    /// Set the [`Brush`] to use for the fill.
+    #[inline]
+    pub fn with_brush(mut self, brush: Brush) -> Self {
+        self.brush = Some(Arc::new(brush));
+        self
+    }
+
+    /// Fill the ellipse with a gradient, multiplied with [`Self::fill`].
+    ///
+    /// The gradient coordinates are normalized to the bounding rectangle of the ellipse.
+    #[inline]
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        Arc::make_mut(self.brush.get_or_insert_with(Default::default)).gradient = Some(gradient);
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     pub fn visual_bounding_rect(&self) -> Rect {
         if self.fill == Color32::TRANSPARENT && self.stroke.is_empty() {
@@ -44,6 +83,15 @@ impl EllipseShape {
             )
         }
     }
+
+    /// The texture to use when painting this ellipse, if any.
+    ///
+    /// If no texture is set, this will return [`TextureId::default`].
+    pub fn fill_texture_id(&self) -> TextureId {
+        self.brush
+            .as_ref()
+            .map_or_else(TextureId::default, |brush| brush.fill_texture_id)
+    }
 }
 
 impl From<EllipseShape> for Shape {
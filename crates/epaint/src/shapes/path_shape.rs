@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::*;
 
 /// A path which can be stroked and/or filled (if closed).
@@ -16,8 +18,13 @@ pub struct PathShape {
 
     /// Color and thickness of the line.
     pub stroke: PathStroke,
-    // TODO(emilk): Add texture support either by supplying uv for each point,
-    // or by some transform from points to uv (e.g. a callback or a linear transform matrix).
+
+    /// Controls texturing and gradients of the fill, if any.
+    ///
+    /// The texture coordinates and gradient are relative to the bounding rectangle of [`Self::points`].
+    ///
+    /// Since most paths do not have a texture or gradient, this is optional and in an `Arc`.
+    pub brush: Option<Arc<Brush>>,
 }
 
 impl PathShape {
@@ -31,6 +38,7 @@ impl PathShape {
             closed: false,
             fill: Default::default(),
             stroke: stroke.into(),
+            brush: None,
         }
     }
 
@@ -42,6 +50,7 @@ impl PathShape {
             closed: true,
             fill: Default::default(),
             stroke: stroke.into(),
+            brush: None,
         }
     }
 
@@ -59,9 +68,26 @@ impl PathShape {
             closed: true,
             fill: fill.into(),
             stroke: stroke.into(),
+            brush: None,
         }
     }
 
+    /// Set the [`Brush`] to use for the fill.
+    #[inline]
+    pub fn with_brush(mut self, brush: Brush) -> Self {
+        self.brush = Some(Arc::new(brush));
+        self
+    }
+
+    /// Fill the polygon with a gradient, multiplied with [`Self::fill`].
+    ///
+    /// The gradient coordinates are normalized to the bounding rectangle of [`Self::points`].
+    #[inline]
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        Arc::make_mut(self.brush.get_or_insert_with(Default::default)).gradient = Some(gradient);
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
@@ -71,6 +97,15 @@ impl PathShape {
             Rect::from_points(&self.points).expand(self.stroke.width / 2.0)
         }
     }
+
+    /// The texture to use when painting this path, if any.
+    ///
+    /// If no texture is set, this will return [`TextureId::default`].
+    pub fn fill_texture_id(&self) -> TextureId {
+        self.brush
+            .as_ref()
+            .map_or_else(TextureId::default, |brush| brush.fill_texture_id)
+    }
 }
 
 impl From<PathShape> for Shape {
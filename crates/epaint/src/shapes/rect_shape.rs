@@ -34,6 +34,11 @@ pub struct RectShape {
     /// If you want to perfectly tile rectangles, use [`StrokeKind::Inside`].
     pub stroke_kind: StrokeKind,
 
+    /// If set, the stroke is painted as dashes following this pattern.
+    ///
+    /// Since most strokes are solid, this is optional and in an `Arc`.
+    pub stroke_dash: Option<Arc<DashPattern>>,
+
     /// Snap the rectangle to pixels?
     ///
     /// Rounding produces sharper rectangles.
@@ -49,9 +54,9 @@ pub struct RectShape {
     /// The blur is currently implemented using a simple linear blur in sRGBA gamma space.
     pub blur_width: f32,
 
-    /// Controls texturing, if any.
+    /// Controls texturing and gradients, if any.
     ///
-    /// Since most rectangles do not have a texture, this is optional and in an `Arc`,
+    /// Since most rectangles do not have a texture or gradient, this is optional and in an `Arc`,
     /// so that [`RectShape`] is kept small..
     pub brush: Option<Arc<Brush>>,
 }
@@ -60,7 +65,7 @@ pub struct RectShape {
 fn rect_shape_size() {
     assert_eq!(
         std::mem::size_of::<RectShape>(),
-        48,
+        56,
         "RectShape changed size! If it shrank - good! Update this test. If it grew - bad! Try to find a way to avoid it."
     );
     assert!(
@@ -85,6 +90,7 @@ impl RectShape {
             fill: fill_color.into(),
             stroke: stroke.into(),
             stroke_kind,
+            stroke_dash: None,
             round_to_pixels: None,
             blur_width: 0.0,
             brush: Default::default(),
@@ -124,6 +130,15 @@ impl RectShape {
         self
     }
 
+    /// Paint the stroke as dashes following the given pattern.
+    ///
+    /// The dashes follow the rounded corners of the rectangle.
+    #[inline]
+    pub fn with_stroke_dash(mut self, dash: DashPattern) -> Self {
+        self.stroke_dash = Some(Arc::new(dash));
+        self
+    }
+
     /// Snap the rectangle to pixels?
     ///
     /// Rounding produces sharper rectangles.
@@ -150,13 +165,24 @@ impl RectShape {
     /// Set the texture to use when painting this rectangle, if any.
     #[inline]
     pub fn with_texture(mut self, fill_texture_id: TextureId, uv: Rect) -> Self {
+        let gradient = self.brush.take().and_then(|brush| brush.gradient.clone());
         self.brush = Some(Arc::new(Brush {
             fill_texture_id,
             uv,
+            gradient,
         }));
         self
     }
 
+    /// Fill the rectangle with a gradient, multiplied with [`Self::fill`].
+    ///
+    /// The gradient follows the corner radius and feathering of the rectangle.
+    #[inline]
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        Arc::make_mut(self.brush.get_or_insert_with(Default::default)).gradient = Some(gradient);
+        self
+    }
+
     /// The visual bounding rectangle (includes stroke width)
     #[inline]
     pub fn visual_bounding_rect(&self) -> Rect {
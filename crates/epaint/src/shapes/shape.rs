@@ -6,7 +6,7 @@ use emath::{Align2, Pos2, Rangef, Rect, TSTransform, Vec2, pos2};
 
 use crate::{
     Color32, CornerRadius, Mesh, Stroke, StrokeKind, TextureId,
-    stroke::PathStroke,
+    stroke::{DashPattern, PathStroke},
     text::{FontId, Fonts, Galley},
 };
 
@@ -74,11 +74,11 @@ pub enum Shape {
 fn shape_size() {
     assert_eq!(
         std::mem::size_of::<Shape>(),
-        64,
+        80,
         "Shape changed size! If it shrank - good! Update this test. If it grew - bad! Try to find a way to avoid it."
     );
     assert!(
-        std::mem::size_of::<Shape>() <= 64,
+        std::mem::size_of::<Shape>() <= 80,
         "Shape is getting way too big!"
     );
 }
@@ -391,12 +391,13 @@ impl Shape {
 impl Shape {
     #[inline(always)]
     pub fn texture_id(&self) -> crate::TextureId {
-        if let Self::Mesh(mesh) = self {
-            mesh.texture_id
-        } else if let Self::Rect(rect_shape) = self {
-            rect_shape.fill_texture_id()
-        } else {
-            crate::TextureId::default()
+        match self {
+            Self::Mesh(mesh) => mesh.texture_id,
+            Self::Rect(rect_shape) => rect_shape.fill_texture_id(),
+            Self::Circle(circle_shape) => circle_shape.fill_texture_id(),
+            Self::Ellipse(ellipse_shape) => ellipse_shape.fill_texture_id(),
+            Self::Path(path_shape) => path_shape.fill_texture_id(),
+            _ => crate::TextureId::default(),
         }
     }
 
@@ -432,11 +433,13 @@ impl Shape {
                 circle_shape.center = transform * circle_shape.center;
                 circle_shape.radius *= transform.scaling;
                 circle_shape.stroke.width *= transform.scaling;
+                scale_dash(&mut circle_shape.stroke_dash, transform.scaling);
             }
             Self::Ellipse(ellipse_shape) => {
                 ellipse_shape.center = transform * ellipse_shape.center;
                 ellipse_shape.radius *= transform.scaling;
                 ellipse_shape.stroke.width *= transform.scaling;
+                scale_dash(&mut ellipse_shape.stroke_dash, transform.scaling);
             }
             Self::LineSegment { points, stroke } => {
                 for p in points {
@@ -449,11 +452,13 @@ impl Shape {
                     *p = transform * *p;
                 }
                 path_shape.stroke.width *= transform.scaling;
+                scale_path_stroke_dash(&mut path_shape.stroke, transform.scaling);
             }
             Self::Rect(rect_shape) => {
                 rect_shape.rect = transform * rect_shape.rect;
                 rect_shape.corner_radius *= transform.scaling;
                 rect_shape.stroke.width *= transform.scaling;
+                scale_dash(&mut rect_shape.stroke_dash, transform.scaling);
                 rect_shape.blur_width *= transform.scaling;
             }
             Self::Text(text_shape) => {
@@ -467,12 +472,14 @@ impl Shape {
                     *p = transform * *p;
                 }
                 bezier.stroke.width *= transform.scaling;
+                scale_path_stroke_dash(&mut bezier.stroke, transform.scaling);
             }
             Self::CubicBezier(bezier) => {
                 for p in &mut bezier.points {
                     *p = transform * *p;
                 }
                 bezier.stroke.width *= transform.scaling;
+                scale_path_stroke_dash(&mut bezier.stroke, transform.scaling);
             }
             Self::Callback(shape) => {
                 shape.rect = transform * shape.rect;
@@ -481,6 +488,22 @@ impl Shape {
     }
 }
 
+fn scale_dash(dash: &mut Option<Arc<DashPattern>>, factor: f32) {
+    if let Some(dash) = dash {
+        if factor != 1.0 {
+            Arc::make_mut(dash).scale(factor);
+        }
+    }
+}
+
+fn scale_path_stroke_dash(stroke: &mut PathStroke, factor: f32) {
+    if stroke.dash().is_some() && factor != 1.0 {
+        if let Some(dash) = &mut stroke.style_mut().dash {
+            dash.scale(factor);
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 /// Creates equally spaced filled circles from a line.
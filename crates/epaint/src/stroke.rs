@@ -116,10 +116,17 @@ pub enum StrokeKind {
 /// The default stroke is the same as [`Stroke::NONE`].
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct PathStroke {
     pub width: f32,
     pub color: ColorMode,
     pub kind: StrokeKind,
+
+    /// The caps, joins and dashes of the stroke.
+    ///
+    /// Since most strokes use the defaults, this is optional and in an `Arc`.
+    /// Use [`Self::with_cap`], [`Self::with_join`] and [`Self::with_dash`] to set it.
+    pub style: Option<Arc<StrokeStyle>>,
 }
 
 impl Default for PathStroke {
@@ -135,6 +142,7 @@ impl PathStroke {
         width: 0.0,
         color: ColorMode::TRANSPARENT,
         kind: StrokeKind::Middle,
+        style: None,
     };
 
     #[inline]
@@ -142,7 +150,7 @@ impl PathStroke {
         Self {
             width: width.into(),
             color: ColorMode::Solid(color.into()),
-            kind: StrokeKind::Middle,
+            ..Self::NONE
         }
     }
 
@@ -157,7 +165,7 @@ impl PathStroke {
         Self {
             width: width.into(),
             color: ColorMode::UV(Arc::new(callback)),
-            kind: StrokeKind::Middle,
+            ..Self::NONE
         }
     }
 
@@ -193,6 +201,58 @@ impl PathStroke {
         }
     }
 
+    /// How the ends of open paths are painted.
+    #[inline]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.style_mut().cap = cap;
+        self
+    }
+
+    /// How the corners of the path are painted.
+    ///
+    /// Use [`LineJoin::miter_limit`] to cut off sharp corners.
+    #[inline]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.style_mut().join = join;
+        self
+    }
+
+    /// Paint the stroke as dashes following the given pattern.
+    ///
+    /// Use [`DashPattern::dotted`] for a dotted line.
+    #[inline]
+    pub fn with_dash(mut self, dash: DashPattern) -> Self {
+        self.style_mut().dash = Some(dash);
+        self
+    }
+
+    /// How the ends of open paths are painted.
+    #[inline]
+    pub fn cap(&self) -> LineCap {
+        self.style
+            .as_ref()
+            .map_or_else(LineCap::default, |style| style.cap)
+    }
+
+    /// How the corners of the path are painted.
+    #[inline]
+    pub fn join(&self) -> LineJoin {
+        self.style
+            .as_ref()
+            .map_or_else(LineJoin::default, |style| style.join)
+    }
+
+    /// The dash pattern of the stroke, if any.
+    #[inline]
+    pub fn dash(&self) -> Option<&DashPattern> {
+        self.style.as_ref().and_then(|style| style.dash.as_ref())
+    }
+
+    /// Mutable access to the [`StrokeStyle`], which is created if needed.
+    pub fn style_mut(&mut self) -> &mut StrokeStyle {
+        Arc::make_mut(self.style.get_or_insert_with(Default::default))
+    }
+
     /// True if width is zero or color is solid and transparent
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -219,12 +279,200 @@ impl From<Stroke> for PathStroke {
             Self {
                 width: value.width,
                 color: ColorMode::Solid(value.color),
-                kind: StrokeKind::Middle,
+                ..Self::NONE
             }
         }
     }
 }
 
+// ----------------------------------------------------------------------------
+
+/// The caps, joins and dashes of a [`PathStroke`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StrokeStyle {
+    /// How the ends of open paths are painted.
+    pub cap: LineCap,
+
+    /// How the corners of the path are painted.
+    pub join: LineJoin,
+
+    /// If set, the stroke is broken up into dashes following this pattern.
+    pub dash: Option<DashPattern>,
+}
+
+/// How the ends of an open stroke, or of each dash in a dashed stroke, are painted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineCap {
+    /// The stroke ends exactly at the end point.
+    #[default]
+    Butt,
+
+    /// A half-circle with the diameter of the stroke width is added to the end.
+    Round,
+
+    /// The stroke is extended by half its width past the end point.
+    Square,
+}
+
+/// How the corners of a stroked path are painted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LineJoin {
+    /// Sharp corners, where the outer edges of the stroke are extended until they meet.
+    ///
+    /// This is how egui has always painted corners: closed paths get a full miter at every corner,
+    /// while open paths have corners sharper than a right angle cut off.
+    /// Use [`Self::miter_limit`] to choose the limit yourself.
+    #[default]
+    Miter,
+
+    /// Like [`Self::Miter`], but corners where the miter would stick out more than `limit`
+    /// half stroke widths are cut off at half the stroke width.
+    ///
+    /// This is the same as `stroke-miterlimit` in SVG: a limit of `2.0` cuts off
+    /// corners sharper than 60 degrees, and `4.0` cuts off corners sharper than 29 degrees.
+    /// Limits of `1.0` or less cut off all corners, and [`f32::INFINITY`] never cuts off any.
+    ///
+    /// See also [`Self::miter_limit`].
+    MiterClip { limit: f32 },
+
+    /// Rounded corners, with a radius of half the stroke width.
+    Round,
+
+    /// The corners are cut off straight across the outer edges of the stroke.
+    Bevel,
+}
+
+impl LineJoin {
+    /// Miter corners, cut off if the miter would stick out more than `limit` half stroke widths.
+    ///
+    /// Same as [`Self::MiterClip`].
+    #[inline]
+    pub fn miter_limit(limit: f32) -> Self {
+        Self::MiterClip { limit }
+    }
+}
+
+/// A repeating pattern of dashes and gaps, for painting dashed or dotted strokes.
+///
+/// The pattern follows the outline of the stroked shape, including around corners,
+/// and continues across the start point of closed paths.
+///
+/// See [`PathStroke::with_dash`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DashPattern {
+    /// The lengths of the dashes, in points.
+    dash_lengths: Vec<f32>,
+
+    /// The length of the gap following each dash, in points.
+    ///
+    /// Always as long as `dash_lengths`.
+    gap_lengths: Vec<f32>,
+
+    /// How far into the pattern the stroke starts, in points.
+    ///
+    /// Animate this to get "marching ants".
+    pub offset: f32,
+
+    /// How the ends of each dash are painted.
+    ///
+    /// Round and square caps extend each dash by half the stroke width at both ends.
+    pub cap: LineCap,
+}
+
+impl DashPattern {
+    /// Dashes of equal length with equal gaps between them.
+    #[inline]
+    pub fn new(dash_length: f32, gap_length: f32) -> Self {
+        Self::multi([(dash_length, gap_length)])
+    }
+
+    /// A pattern with several different dashes, e.g. for a dash-dot line.
+    ///
+    /// Each item is the length of a dash followed by the length of the gap after it.
+    /// Zero-length dashes are allowed, and combined with [`LineCap::Round`] they are painted as dots.
+    ///
+    /// ```
+    /// # use epaint::DashPattern;
+    /// let dash_dot = DashPattern::multi([(8.0, 2.0), (0.0, 2.0)]);
+    /// assert_eq!(dash_dot.dash_lengths(), &[8.0, 0.0]);
+    /// assert_eq!(dash_dot.gap_lengths(), &[2.0, 2.0]);
+    /// ```
+    pub fn multi(dashes: impl IntoIterator<Item = (f32, f32)>) -> Self {
+        let (dash_lengths, gap_lengths) = dashes.into_iter().unzip();
+        Self {
+            dash_lengths,
+            gap_lengths,
+            offset: 0.0,
+            cap: LineCap::Butt,
+        }
+    }
+
+    /// Round dots with the diameter of the stroke width, with `spacing` points between their centers.
+    #[inline]
+    pub fn dotted(spacing: f32) -> Self {
+        Self::new(0.0, spacing).with_cap(LineCap::Round)
+    }
+
+    /// How far into the pattern the stroke starts, in points.
+    #[inline]
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// How the ends of each dash are painted.
+    #[inline]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// The lengths of the dashes, in points.
+    #[inline]
+    pub fn dash_lengths(&self) -> &[f32] {
+        &self.dash_lengths
+    }
+
+    /// The length of the gap following each dash, in points.
+    #[inline]
+    pub fn gap_lengths(&self) -> &[f32] {
+        &self.gap_lengths
+    }
+
+    /// The length of one repetition of the pattern, in points.
+    pub fn period(&self) -> f32 {
+        self.dash_lengths().iter().sum::<f32>() + self.gap_lengths().iter().sum::<f32>()
+    }
+
+    /// Multiply all lengths (and the offset) by this factor.
+    pub fn scale(&mut self, factor: f32) {
+        for length in self.dash_lengths.iter_mut().chain(&mut self.gap_lengths) {
+            *length *= factor;
+        }
+        self.offset *= factor;
+    }
+
+    /// Can this pattern be used for painting?
+    ///
+    /// If not, the stroke is painted solid.
+    pub(crate) fn is_valid(&self) -> bool {
+        !self.dash_lengths.is_empty()
+            && self.dash_lengths.len() == self.gap_lengths.len()
+            && self
+                .dash_lengths()
+                .iter()
+                .chain(self.gap_lengths())
+                .all(|length| length.is_finite() && 0.0 <= *length)
+            && 0.0 < self.period()
+            && self.offset.is_finite()
+    }
+}
+
 /// Returns true if the nearest integer is odd.
 fn is_nearest_integer_odd(x: f32) -> bool {
     (x * 0.5 + 0.25).fract() > 0.5
@@ -242,3 +490,19 @@ fn test_is_nearest_integer_odd() {
     assert!(is_nearest_integer_odd(3.0));
     assert!(is_nearest_integer_odd(3.4));
 }
+
+#[test]
+fn test_miter_limit() {
+    for limit in [
+        1.0,
+        1.25,
+        std::f32::consts::SQRT_2,
+        2.0,
+        3.7,
+        10.05,
+        f32::INFINITY,
+    ] {
+        let stroke = PathStroke::new(1.0, Color32::WHITE).with_join(LineJoin::miter_limit(limit));
+        assert_eq!(stroke.join(), LineJoin::MiterClip { limit });
+    }
+}
@@ -10,8 +10,11 @@ use emath::{GuiRounding as _, NumExt as _, Pos2, Rect, Rot2, Vec2, pos2, remap,
 use crate::{
     CircleShape, ClippedPrimitive, ClippedShape, Color32, CornerRadiusF32, CubicBezierShape,
     EllipseShape, Mesh, PathShape, Primitive, QuadraticBezierShape, RectShape, Shape, Stroke,
-    StrokeKind, TextShape, TextureId, Vertex, WHITE_UV, color::ColorMode, emath,
-    stroke::PathStroke, texture_atlas::PreparedDisc,
+    StrokeKind, TextShape, TextureId, Vertex, WHITE_UV,
+    color::ColorMode,
+    emath,
+    stroke::{DashPattern, LineCap, LineJoin, PathStroke},
+    texture_atlas::PreparedDisc,
 };
 
 // ----------------------------------------------------------------------------
@@ -381,6 +384,18 @@ impl Path {
     }
 
     pub fn add_open_points(&mut self, points: &[Pos2]) {
+        self.add_open_points_joined(points, LineJoin::Miter, 0.0);
+    }
+
+    /// Like [`Self::add_open_points`], but the corners are shaped by [`PathStroke::join`].
+    pub fn add_open_points_with_joins(&mut self, points: &[Pos2], stroke: &PathStroke) {
+        self.add_open_points_joined(points, stroke.join(), 0.5 * stroke.width);
+    }
+
+    fn add_open_points_joined(&mut self, points: &[Pos2], join: LineJoin, half_width: f32) {
+        // Open paths have always had corners sharper than a right angle cut off:
+        let min_miter_length_sq = min_miter_length_sq(join).unwrap_or(0.5);
+
         let n = points.len();
         assert!(n >= 2, "A path needs at least two points, but got {n}");
 
@@ -401,21 +416,7 @@ impl Path {
                     n1 = n0;
                 }
 
-                let normal = (n0 + n1) / 2.0;
-                let length_sq = normal.length_sq();
-                let right_angle_length_sq = 0.5;
-                let sharper_than_a_right_angle = length_sq < right_angle_length_sq;
-                if sharper_than_a_right_angle {
-                    // cut off the sharp corner
-                    let center_normal = normal.normalized();
-                    let n0c = (n0 + center_normal) / 2.0;
-                    let n1c = (n1 + center_normal) / 2.0;
-                    self.add_point(points[i], n0c / n0c.length_sq());
-                    self.add_point(points[i], n1c / n1c.length_sq());
-                } else {
-                    // miter join
-                    self.add_point(points[i], normal / length_sq);
-                }
+                self.add_corner(points[i], n0, n1, join, min_miter_length_sq, half_width);
 
                 n0 = n1;
             }
@@ -427,6 +428,32 @@ impl Path {
     }
 
     pub fn add_line_loop(&mut self, points: &[Pos2]) {
+        // We can't just cut off corners for filled shapes like this,
+        // because the feather will both expand and contract the corner along the provided normals
+        // to make sure it doesn't grow, and the shrinking will make the inner points cross each other.
+        //
+        // A better approach is to shrink the vertices in by half the feather-width here
+        // and then only expand during feathering.
+        //
+        // See https://github.com/emilk/egui/issues/1226
+        //
+        // So we never cut corners here, and only do so in
+        // `add_line_loop_with_joins`, which is filled separately.
+        self.add_line_loop_joined(points, LineJoin::Miter, 0.0);
+    }
+
+    /// Like [`Self::add_line_loop`], but the corners are shaped by [`PathStroke::join`].
+    ///
+    /// If any corners are cut off or rounded, the path will have more points than `points`,
+    /// and should not be filled with [`Self::fill_and_stroke`] (see [`Self::add_line_loop`]).
+    pub fn add_line_loop_with_joins(&mut self, points: &[Pos2], stroke: &PathStroke) {
+        self.add_line_loop_joined(points, stroke.join(), 0.5 * stroke.width);
+    }
+
+    fn add_line_loop_joined(&mut self, points: &[Pos2], join: LineJoin, half_width: f32) {
+        // Closed paths have always had full miters:
+        let min_miter_length_sq = min_miter_length_sq(join).unwrap_or(0.0);
+
         let n = points.len();
         assert!(n >= 2, "A path needs at least two points, but got {n}");
         self.reserve(n);
@@ -444,34 +471,70 @@ impl Path {
                 n1 = n0;
             }
 
-            let normal = (n0 + n1) / 2.0;
-            let length_sq = normal.length_sq();
+            self.add_corner(points[i], n0, n1, join, min_miter_length_sq, half_width);
 
-            // We can't just cut off corners for filled shapes like this,
-            // because the feather will both expand and contract the corner along the provided normals
-            // to make sure it doesn't grow, and the shrinking will make the inner points cross each other.
-            //
-            // A better approach is to shrink the vertices in by half the feather-width here
-            // and then only expand during feathering.
-            //
-            // See https://github.com/emilk/egui/issues/1226
-            const CUT_OFF_SHARP_CORNERS: bool = false;
+            n0 = n1;
+        }
+    }
 
-            let right_angle_length_sq = 0.5;
-            let sharper_than_a_right_angle = length_sq < right_angle_length_sq;
-            if CUT_OFF_SHARP_CORNERS && sharper_than_a_right_angle {
+    /// Add the point(s) for a corner between a segment with normal `n0` and one with normal `n1`.
+    ///
+    /// Miter corners where the average normal is shorter than `sqrt(min_miter_length_sq)`
+    /// are cut off (see [`min_miter_length_sq`]).
+    fn add_corner(
+        &mut self,
+        pos: Pos2,
+        n0: Vec2,
+        n1: Vec2,
+        join: LineJoin,
+        min_miter_length_sq: f32,
+        half_width: f32,
+    ) {
+        let normal = (n0 + n1) / 2.0;
+        let length_sq = normal.length_sq();
+        let within_miter_limit = min_miter_length_sq <= length_sq;
+
+        // Corners this close to straight are not worth the extra vertices:
+        let almost_straight = 0.9999 < length_sq;
+
+        match join {
+            LineJoin::Miter | LineJoin::MiterClip { .. } if within_miter_limit => {
+                self.add_point(pos, normal / length_sq);
+            }
+            LineJoin::Miter | LineJoin::MiterClip { .. } => {
                 // cut off the sharp corner
                 let center_normal = normal.normalized();
                 let n0c = (n0 + center_normal) / 2.0;
                 let n1c = (n1 + center_normal) / 2.0;
-                self.add_point(points[i], n0c / n0c.length_sq());
-                self.add_point(points[i], n1c / n1c.length_sq());
-            } else {
-                // miter join
-                self.add_point(points[i], normal / length_sq);
+                self.add_point(pos, n0c / n0c.length_sq());
+                self.add_point(pos, n1c / n1c.length_sq());
+            }
+            LineJoin::Bevel if !almost_straight => {
+                self.add_point(pos, n0);
+                self.add_point(pos, n1);
+            }
+            LineJoin::Round if !almost_straight => {
+                // Enough segments to keep the outer edge within a tenth of a point from a true circle:
+                let tolerance = 0.1;
+                let max_angle_per_segment =
+                    2.0 * (1.0 - tolerance / half_width.at_least(tolerance)).acos();
+                let angle = (n0.x * n1.y - n0.y * n1.x).atan2(n0.dot(n1));
+                let segments = (angle.abs() / max_angle_per_segment.at_least(1e-3))
+                    .ceil()
+                    .at_least(1.0) as usize;
+                if segments == 1 {
+                    self.add_point(pos, normal / length_sq);
+                } else {
+                    self.reserve(segments + 1);
+                    for i in 0..=segments {
+                        let t = i as f32 / segments as f32;
+                        self.add_point(pos, Rot2::from_angle(t * angle) * n0);
+                    }
+                }
+            }
+            LineJoin::Bevel | LineJoin::Round => {
+                self.add_point(pos, normal / length_sq);
             }
-
-            n0 = n1;
         }
     }
 
@@ -486,7 +549,15 @@ impl Path {
         stroke: &PathStroke,
         out: &mut Mesh,
     ) {
-        stroke_and_fill_path(feathering, &mut self.0, PathType::Closed, stroke, fill, out);
+        stroke_and_fill_path(
+            feathering,
+            &mut self.0,
+            PathType::Closed,
+            stroke,
+            stroke.cap(),
+            fill,
+            out,
+        );
     }
 
     /// Open-ended.
@@ -530,6 +601,48 @@ impl Path {
     ) {
         fill_closed_path_with_uv(feathering, &mut self.0, color, texture_id, uv_from_pos, out);
     }
+
+    /// Like [`Self::fill`] but with the texture and/or gradient of a [`crate::Brush`].
+    ///
+    /// `rect` is the bounding rectangle of the shape,
+    /// used for the texture coordinates and the gradient geometry.
+    pub fn fill_with_brush(
+        &mut self,
+        feathering: f32,
+        color: Color32,
+        brush: &crate::Brush,
+        rect: Rect,
+        out: &mut Mesh,
+    ) {
+        let uv = brush.uv;
+        let uv_from_pos = |p: Pos2| {
+            pos2(
+                remap(p.x, rect.x_range(), uv.x_range()),
+                remap(p.y, rect.y_range(), uv.y_range()),
+            )
+        };
+        if let Some(gradient) = &brush.gradient {
+            fill_closed_path_with_gradient(
+                feathering,
+                &mut self.0,
+                color,
+                brush.fill_texture_id,
+                uv_from_pos,
+                gradient,
+                rect,
+                out,
+            );
+        } else {
+            fill_closed_path_with_uv(
+                feathering,
+                &mut self.0,
+                color,
+                brush.fill_texture_id,
+                uv_from_pos,
+                out,
+            );
+        }
+    }
 }
 
 pub mod path {
@@ -743,6 +856,18 @@ impl Default for TessellationOptions {
     }
 }
 
+/// For a [`LineJoin::MiterClip`]: the squared length of the average normal at a corner
+/// (`sin²(angle / 2)`) below which the corner is cut off.
+///
+/// Returns `None` if the join does not choose a limit itself.
+fn min_miter_length_sq(join: LineJoin) -> Option<f32> {
+    match join {
+        // The miter at a corner is `1 / sin(angle / 2)` half-widths long:
+        LineJoin::MiterClip { limit } => Some(limit.max(1.0).powi(-2)),
+        LineJoin::Miter | LineJoin::Round | LineJoin::Bevel => None,
+    }
+}
+
 fn cw_signed_area(path: &[PathPoint]) -> f64 {
     if let Some(last) = path.last() {
         let mut previous = last.pos;
@@ -903,6 +1028,182 @@ fn fill_closed_path_with_uv(
     }
 }
 
+/// Like [`fill_closed_path_with_uv`] but with per-vertex colors from a [`crate::Gradient`].
+///
+/// The convex area is split into pieces along [`crate::Gradient::split_lines`],
+/// so that the interpolation of vertex colors matches the gradient.
+#[expect(clippy::too_many_arguments)]
+fn fill_closed_path_with_gradient(
+    feathering: f32,
+    path: &mut [PathPoint],
+    color: Color32,
+    texture_id: TextureId,
+    uv_from_pos: impl Fn(Pos2) -> Pos2,
+    gradient: &crate::Gradient,
+    rect: Rect,
+    out: &mut Mesh,
+) {
+    if color == Color32::TRANSPARENT || path.len() < 3 {
+        return;
+    }
+
+    if out.is_empty() {
+        out.texture_id = texture_id;
+    } else {
+        assert_eq!(
+            out.texture_id, texture_id,
+            "Mixing different `texture_id` in the same "
+        );
+    }
+
+    if cw_signed_area(path) < 0.0 {
+        // Wrong winding order - fix:
+        path.reverse();
+        for point in &mut *path {
+            point.normal = -point.normal;
+        }
+    }
+
+    let color_at = |pos: Pos2| color * gradient.color_at(rect, pos);
+    let lines = gradient.split_lines(rect);
+
+    // The inner and outer edge of the feathering, with extra points where the split lines cross:
+    let mut inner: Vec<Pos2> = Vec::with_capacity(path.len());
+    let mut outer: Vec<Pos2> = Vec::with_capacity(path.len());
+    let mut crossings: Vec<f32> = vec![];
+    for (i0, p0) in path.iter().enumerate() {
+        let p1 = &path[(i0 + 1) % path.len()];
+        let dm0 = 0.5 * feathering * p0.normal;
+        let dm1 = 0.5 * feathering * p1.normal;
+        let (inner0, inner1) = (p0.pos - dm0, p1.pos - dm1);
+        let (outer0, outer1) = (p0.pos + dm0, p1.pos + dm1);
+
+        crossings.clear();
+        for &(normal, offset) in &lines {
+            let d0 = normal.dot(inner0.to_vec2()) - offset;
+            let d1 = normal.dot(inner1.to_vec2()) - offset;
+            if (d0 < 0.0) != (d1 < 0.0) && d0 != 0.0 && d1 != 0.0 {
+                crossings.push(d0 / (d0 - d1));
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+
+        inner.push(inner0);
+        outer.push(outer0);
+        for &t in &crossings {
+            inner.push(inner0.lerp(inner1, t));
+            outer.push(outer0.lerp(outer1, t));
+        }
+    }
+
+    // The fill:
+    let mut pieces = vec![inner.clone()];
+    for &(normal, offset) in &lines {
+        pieces = pieces
+            .into_iter()
+            .flat_map(|piece| split_convex_polygon(piece, normal, offset))
+            .collect();
+    }
+    for piece in &pieces {
+        let n = piece.len() as u32;
+        let centroid = piece
+            .iter()
+            .fold(Pos2::ZERO, |sum, p| sum + p.to_vec2() / n as f32);
+        let idx = out.vertices.len() as u32;
+        out.reserve_triangles(n as usize - 2);
+        out.vertices.extend(piece.iter().map(|&pos| Vertex {
+            pos,
+            uv: uv_from_pos(pos),
+            // Sample slightly inside the piece, so that we pick the right side of any discontinuity:
+            color: color_at(pos.lerp(centroid, 1e-3)),
+        }));
+        for i in 2..n {
+            out.add_triangle(idx, idx + i - 1, idx + i);
+        }
+    }
+
+    // The feathering:
+    if 0.0 < feathering {
+        let n = inner.len() as u32;
+        out.reserve_triangles(2 * n as usize);
+        out.reserve_vertices(2 * n as usize);
+        let idx_inner = out.vertices.len() as u32;
+        let idx_outer = idx_inner + 1;
+
+        let mut i0 = n - 1;
+        for i1 in 0..n {
+            let (pos_inner, pos_outer) = (inner[i1 as usize], outer[i1 as usize]);
+            out.vertices.push(Vertex {
+                pos: pos_inner,
+                uv: uv_from_pos(pos_inner),
+                color: color_at(pos_inner),
+            });
+            out.vertices.push(Vertex {
+                pos: pos_outer,
+                uv: uv_from_pos(pos_outer),
+                color: Color32::TRANSPARENT,
+            });
+            out.add_triangle(idx_inner + i1 * 2, idx_inner + i0 * 2, idx_outer + 2 * i0);
+            out.add_triangle(idx_outer + i0 * 2, idx_outer + i1 * 2, idx_inner + 2 * i1);
+            i0 = i1;
+        }
+    }
+}
+
+/// Split a convex polygon in two along the line `normal · pos = offset`.
+///
+/// Returns the non-empty halves.
+fn split_convex_polygon(polygon: Vec<Pos2>, normal: Vec2, offset: f32) -> Vec<Vec<Pos2>> {
+    // Points this close to the line are considered to be on it, to avoid slivers.
+    const EPS: f32 = 1e-3;
+
+    let distances: Vec<f32> = polygon
+        .iter()
+        .map(|p| {
+            let d = normal.dot(p.to_vec2()) - offset;
+            if d.abs() <= EPS { 0.0 } else { d }
+        })
+        .collect();
+
+    if distances.iter().all(|&d| d <= 0.0) || distances.iter().all(|&d| d >= 0.0) {
+        return vec![polygon];
+    }
+
+    let mut below = Vec::with_capacity(polygon.len() + 2);
+    let mut above = Vec::with_capacity(polygon.len() + 2);
+    for i0 in 0..polygon.len() {
+        let i1 = (i0 + 1) % polygon.len();
+        let (p0, d0) = (polygon[i0], distances[i0]);
+        let d1 = distances[i1];
+
+        if d0 <= 0.0 {
+            below.push(p0);
+        }
+        if d0 >= 0.0 {
+            above.push(p0);
+        }
+        if (d0 < 0.0 && 0.0 < d1) || (d1 < 0.0 && 0.0 < d0) {
+            let crossing = p0.lerp(polygon[i1], d0 / (d0 - d1));
+            below.push(crossing);
+            above.push(crossing);
+        }
+    }
+
+    [below, above]
+        .into_iter()
+        .filter(|piece| piece.len() >= 3)
+        .collect()
+}
+
+/// The stroke of the outline of a [`RectShape`], [`CircleShape`] or [`EllipseShape`].
+fn outline_stroke(stroke: Stroke, kind: StrokeKind, dash: Option<&DashPattern>) -> PathStroke {
+    let path_stroke = PathStroke::from(stroke).with_kind(kind);
+    match dash {
+        Some(dash) if !path_stroke.is_empty() => path_stroke.with_dash(dash.clone()),
+        _ => path_stroke,
+    }
+}
+
 /// Tessellate the given path as a stroke with thickness.
 fn stroke_path(
     feathering: f32,
@@ -912,11 +1213,13 @@ fn stroke_path(
     out: &mut Mesh,
 ) {
     let fill = Color32::TRANSPARENT;
-    stroke_and_fill_path(feathering, path, path_type, stroke, fill, out);
+    stroke_and_fill_path(feathering, path, path_type, stroke, stroke.cap(), fill, out);
 }
 
 /// Tessellate the given path as a stroke with thickness, with optional fill color.
 ///
+/// The `cap` is only used for open paths.
+///
 /// Calling this may reverse the vertices in the path if they are wrong winding order.
 ///
 /// The preferred winding order is clockwise.
@@ -925,6 +1228,7 @@ fn stroke_and_fill_path(
     path: &mut [PathPoint],
     path_type: PathType,
     stroke: &PathStroke,
+    cap: LineCap,
     color_fill: Color32,
     out: &mut Mesh,
 ) {
@@ -939,6 +1243,23 @@ fn stroke_and_fill_path(
         return fill_closed_path(feathering, path, color_fill, out);
     }
 
+    if let Some(dash) = stroke.dash() {
+        if dash.is_valid() {
+            return stroke_and_fill_dashed_path(
+                feathering, path, path_type, stroke, dash, color_fill, out,
+            );
+        }
+    }
+
+    if path_type == PathType::Open && cap == LineCap::Square {
+        // Extend both ends by half the stroke width:
+        let half_width = 0.5 * stroke.width;
+        let first = &mut path[0];
+        first.pos += first.normal.rot90() * half_width;
+        let last = &mut path[n as usize - 1];
+        last.pos -= last.normal.rot90() * half_width;
+    }
+
     if color_fill != Color32::TRANSPARENT && cw_signed_area(path) < 0.0 {
         // Wrong winding order - fix:
         path.reverse();
@@ -1133,11 +1454,13 @@ fn stroke_and_fill_path(
                     //   |    |  que  |    |
                     //   |    |       |    |
 
-                    // (in the future it would be great with an option to add a circular end instead)
+                    // With round caps we instead add a feathered half-disc to each end.
 
                     // TODO(emilk): we should probably shrink before adding the line caps,
                     // so that we don't add to the area of the line.
-                    // TODO(emilk): make line caps optional.
+
+                    let round_cap = cap == LineCap::Round;
+                    let feather_ends = if round_cap { 0.0 } else { feathering };
 
                     out.reserve_triangles(6 * n as usize + 4);
                     out.reserve_vertices(4 * n as usize);
@@ -1146,7 +1469,7 @@ fn stroke_and_fill_path(
                         let end = path[0];
                         let p = end.pos;
                         let n = end.normal;
-                        let back_extrude = n.rot90() * feathering;
+                        let back_extrude = n.rot90() * feather_ends;
                         out.colored_vertex(p + n * outer_rad + back_extrude, color_outer);
                         out.colored_vertex(
                             p + n * inner_rad,
@@ -1195,7 +1518,7 @@ fn stroke_and_fill_path(
                         let end = path[i1 as usize];
                         let p = end.pos;
                         let n = end.normal;
-                        let back_extrude = -n.rot90() * feathering;
+                        let back_extrude = -n.rot90() * feather_ends;
                         out.colored_vertex(p + n * outer_rad + back_extrude, color_outer);
                         out.colored_vertex(
                             p + n * inner_rad,
@@ -1220,6 +1543,14 @@ fn stroke_and_fill_path(
                         out.add_triangle(idx + 4 * i1 + 0, idx + 4 * i1 + 1, idx + 4 * i1 + 2);
                         out.add_triangle(idx + 4 * i1 + 0, idx + 4 * i1 + 2, idx + 4 * i1 + 3);
                     }
+
+                    if round_cap {
+                        let color = |pos: Pos2| get_color(color_middle, pos);
+                        let [first, last] = [path[0], path[n as usize - 1]];
+                        let (first_dir, last_dir) = (first.normal.rot90(), -last.normal.rot90());
+                        add_round_cap(out, first, first_dir, inner_rad, outer_rad, color);
+                        add_round_cap(out, last, last_dir, inner_rad, outer_rad, color);
+                    }
                 }
             }
         }
@@ -1275,6 +1606,16 @@ fn stroke_and_fill_path(
             }
         }
 
+        if path_type == PathType::Open && cap == LineCap::Round {
+            let radius = stroke.width.max(feathering) / 2.0;
+            let opacity = (stroke.width / feathering).min(1.0);
+            let color = |pos: Pos2| mul_color(get_color(&stroke.color, pos), opacity);
+            let [first, last] = [path[0], path[n as usize - 1]];
+            let (first_dir, last_dir) = (first.normal.rot90(), -last.normal.rot90());
+            add_round_cap(out, first, first_dir, radius, radius, color);
+            add_round_cap(out, last, last_dir, radius, radius, color);
+        }
+
         if color_fill != Color32::TRANSPARENT {
             // We Need to create new vertices, because the ones we used for the stroke
             // has the wrong color.
@@ -1289,6 +1630,284 @@ fn stroke_and_fill_path(
     }
 }
 
+/// Adds a half-disc to the end of an open stroke, for [`LineCap::Round`].
+///
+/// `dir` is the unit direction pointing away from the stroke.
+/// The disc is opaque out to `inner_rad`, and then fades out to `outer_rad`.
+fn add_round_cap(
+    out: &mut Mesh,
+    end: PathPoint,
+    dir: Vec2,
+    inner_rad: f32,
+    outer_rad: f32,
+    color: impl Fn(Pos2) -> Color32,
+) {
+    use std::f32::consts::PI;
+
+    let PathPoint { pos, normal } = end;
+    let feathered = inner_rad < outer_rad;
+
+    // Keep the deviation from a true circle well below a pixel (= feathering width):
+    let tolerance = if feathered {
+        0.1 * (outer_rad - inner_rad)
+    } else {
+        0.1
+    };
+    let segments = (PI * (outer_rad / (8.0 * tolerance)).sqrt())
+        .ceil()
+        .clamp(2.0, 64.0) as u32;
+
+    let stride = if feathered { 2 } else { 1 };
+    out.reserve_vertices(1 + stride as usize * (segments as usize + 1));
+    out.reserve_triangles((1 + 2 * (stride as usize - 1)) * segments as usize);
+
+    let idx = out.vertices.len() as u32;
+    out.colored_vertex(pos, color(pos));
+    for i in 0..=segments {
+        let angle = PI * i as f32 / segments as f32;
+        let v = normal * angle.cos() + dir * angle.sin();
+        out.colored_vertex(pos + inner_rad * v, color(pos + inner_rad * v));
+        if feathered {
+            out.colored_vertex(pos + outer_rad * v, Color32::TRANSPARENT);
+        }
+    }
+    for i in 0..segments {
+        let i0 = idx + 1 + stride * i;
+        let i1 = i0 + stride;
+        out.add_triangle(idx, i0, i1);
+        if feathered {
+            out.add_triangle(i0, i0 + 1, i1);
+            out.add_triangle(i0 + 1, i1, i1 + 1);
+        }
+    }
+}
+
+/// Tessellate a path with [`PathStroke::dash`] set.
+///
+/// The fill (if any) is painted first, up to the inside of the stroke,
+/// and then each dash is stroked as an open path with the caps of the pattern.
+fn stroke_and_fill_dashed_path(
+    feathering: f32,
+    path: &mut [PathPoint],
+    path_type: PathType,
+    stroke: &PathStroke,
+    dash: &DashPattern,
+    color_fill: Color32,
+    out: &mut Mesh,
+) {
+    if color_fill != Color32::TRANSPARENT {
+        if cw_signed_area(path) < 0.0 {
+            // Wrong winding order - fix:
+            path.reverse();
+            for point in &mut *path {
+                point.normal = -point.normal;
+            }
+        }
+
+        // The gaps between the dashes should show what is behind the shape,
+        // so only fill up to the inside of the stroke:
+        let inset = match stroke.kind {
+            StrokeKind::Inside => stroke.width,
+            StrokeKind::Middle => 0.5 * stroke.width,
+            StrokeKind::Outside => 0.0,
+        };
+        let mut fill_path: Vec<PathPoint> = path
+            .iter()
+            .map(|p| PathPoint {
+                pos: p.pos - inset * p.normal,
+                normal: p.normal,
+            })
+            .collect();
+        fill_closed_path(feathering, &mut fill_path, color_fill, out);
+    }
+
+    if stroke.color == ColorMode::TRANSPARENT {
+        return;
+    }
+
+    // Move the points so that the stroke is on middle of the path.
+    let offset = match stroke.kind {
+        StrokeKind::Inside => -0.5 * stroke.width,
+        StrokeKind::Middle => 0.0,
+        StrokeKind::Outside => 0.5 * stroke.width,
+    };
+    if offset != 0.0 {
+        for point in &mut *path {
+            point.pos += offset * point.normal;
+        }
+    }
+
+    // The UV callback should see the bounding box of the whole path, not of each dash:
+    let color = match &stroke.color {
+        ColorMode::Solid(color) => ColorMode::Solid(*color),
+        ColorMode::UV(fun) => {
+            let uv_bbox = Rect::from_points(&path.iter().map(|p| p.pos).collect::<Vec<Pos2>>())
+                .expand((stroke.width / 2.0) + feathering);
+            let fun = fun.clone();
+            ColorMode::UV(std::sync::Arc::new(move |_, pos| fun(uv_bbox, pos)))
+        }
+    };
+    let dash_stroke = PathStroke {
+        width: stroke.width,
+        color,
+        ..PathStroke::NONE
+    }
+    .with_join(stroke.join());
+
+    for_each_dash(path, path_type, dash, |dash_path, dash_path_type| {
+        stroke_and_fill_path(
+            feathering,
+            dash_path,
+            dash_path_type,
+            &dash_stroke,
+            dash.cap,
+            Color32::TRANSPARENT,
+            out,
+        );
+    });
+}
+
+/// Split a path into the dashes of a [`DashPattern`], calling `add_dash` with each of them.
+///
+/// The pattern must be valid (see [`DashPattern::is_valid`]).
+///
+/// Each dash is an open path with at least two points.
+/// A dash that crosses the start of a closed path is given as one continuous dash.
+/// If a single dash covers the whole closed path, it is given as a closed path instead.
+fn for_each_dash(
+    path: &[PathPoint],
+    path_type: PathType,
+    pattern: &DashPattern,
+    mut add_dash: impl FnMut(&mut [PathPoint], PathType),
+) {
+    /// More dashes than this is most likely a mistake (e.g. a pattern in pixels instead of points),
+    /// and would also be too slow. We paint a solid stroke instead.
+    const MAX_DASHES: f32 = 100_000.0;
+
+    let dash_lengths = pattern.dash_lengths();
+    let gap_lengths = pattern.gap_lengths();
+
+    let n = path.len();
+    let num_segments = match path_type {
+        PathType::Open => n - 1,
+        PathType::Closed => n,
+    };
+
+    let total_length: f32 = (0..num_segments)
+        .map(|i| path[i].pos.distance(path[(i + 1) % n].pos))
+        .sum();
+    if MAX_DASHES * pattern.period() < total_length {
+        return add_dash(&mut path.to_vec(), path_type);
+    }
+
+    // Where in the pattern we are:
+    let mut step = 0;
+    let mut in_dash = true;
+    let mut remaining = dash_lengths[0];
+    let advance = |step: &mut usize, in_dash: &mut bool, remaining: &mut f32| {
+        if *in_dash {
+            *remaining = gap_lengths[*step];
+        } else {
+            *step = (*step + 1) % dash_lengths.len();
+            *remaining = dash_lengths[*step];
+        }
+        *in_dash = !*in_dash;
+    };
+
+    // Skip into the pattern:
+    let mut skip = pattern.offset.rem_euclid(pattern.period());
+    while remaining <= skip {
+        skip -= remaining;
+        advance(&mut step, &mut in_dash, &mut remaining);
+    }
+    remaining -= skip;
+
+    // For closed paths, a dash starting at the first point
+    // is joined with the last dash if it reaches all the way around.
+    let mut first_dash: Option<Vec<PathPoint>> = None;
+    let mut first_dash_pending = path_type == PathType::Closed && in_dash;
+
+    let mut current: Vec<PathPoint> = Vec::new();
+    let mut started = false;
+
+    for i in 0..num_segments {
+        let a = path[i];
+        let b = path[(i + 1) % n];
+        let vector = b.pos - a.pos;
+        let length = vector.length();
+
+        if length <= 0.0 {
+            // Duplicated point (e.g. a cut-off corner): keep its normal if we are in a dash.
+            if in_dash && !current.is_empty() {
+                current.push(b);
+            }
+            continue;
+        }
+
+        let dir = vector / length;
+        let mut normal = dir.rot90();
+        if normal.dot(a.normal + b.normal) < 0.0 {
+            normal = -normal;
+        }
+
+        if !started {
+            started = true;
+            if in_dash {
+                current.push(PathPoint { pos: a.pos, normal });
+            }
+        }
+
+        let mut t = 0.0;
+        while remaining <= length - t {
+            t += remaining;
+            let point = PathPoint {
+                pos: a.pos + t * dir,
+                normal,
+            };
+            current.push(point);
+            if in_dash {
+                if first_dash_pending {
+                    first_dash = Some(std::mem::take(&mut current));
+                    first_dash_pending = false;
+                } else {
+                    add_dash(&mut current, PathType::Open);
+                    current.clear();
+                }
+            }
+            advance(&mut step, &mut in_dash, &mut remaining);
+        }
+        remaining -= length - t;
+
+        if in_dash {
+            let is_last_point = path_type == PathType::Open && i + 1 == num_segments;
+            current.push(if is_last_point {
+                PathPoint { pos: b.pos, normal }
+            } else {
+                b
+            });
+        }
+    }
+
+    if first_dash_pending {
+        // The first dash never ended - the whole path is one dash.
+        return add_dash(&mut path.to_vec(), PathType::Closed);
+    }
+
+    if in_dash {
+        if let Some(first_dash) = first_dash.take() {
+            // Join with the first dash, skipping its duplicated start point:
+            current.extend_from_slice(&first_dash[1..]);
+        }
+        if 2 <= current.len() {
+            add_dash(&mut current, PathType::Open);
+        }
+    }
+
+    if let Some(mut first_dash) = first_dash {
+        add_dash(&mut first_dash, PathType::Open);
+    }
+}
+
 fn mul_color(color: Color32, factor: f32) -> Color32 {
     // The fast gamma-space multiply also happens to be perceptually better.
     // Win-win!
@@ -1489,6 +2108,8 @@ impl Tessellator {
             radius,
             mut fill,
             stroke,
+            stroke_dash,
+            brush,
         } = shape;
 
         if radius <= 0.0 {
@@ -1504,7 +2125,7 @@ impl Tessellator {
             return;
         }
 
-        if self.options.prerasterized_discs && fill != Color32::TRANSPARENT {
+        if self.options.prerasterized_discs && fill != Color32::TRANSPARENT && brush.is_none() {
             let radius_px = radius * self.pixels_per_point;
             // strike the right balance between some circles becoming too blurry, and some too sharp.
             let cutoff_radius = radius_px * 2.0_f32.powf(0.25);
@@ -1528,11 +2149,16 @@ impl Tessellator {
             }
         }
 
-        let path_stroke = PathStroke::from(stroke).outside();
+        let path_stroke = outline_stroke(stroke, StrokeKind::Outside, stroke_dash.as_deref());
         self.scratchpad_path.clear();
         self.scratchpad_path.add_circle(center, radius);
-        self.scratchpad_path
-            .fill_and_stroke(self.feathering, fill, &path_stroke, out);
+        if let Some(brush) = brush {
+            let rect = Rect::from_center_size(center, Vec2::splat(2.0 * radius));
+            self.fill_and_stroke_with_brush(fill, &brush, rect, &path_stroke, out);
+        } else {
+            self.scratchpad_path
+                .fill_and_stroke(self.feathering, fill, &path_stroke, out);
+        }
     }
 
     /// Tessellate a single [`EllipseShape`] into a [`Mesh`].
@@ -1545,6 +2171,8 @@ impl Tessellator {
             radius,
             fill,
             stroke,
+            stroke_dash,
+            brush,
         } = shape;
 
         if radius.x <= 0.0 || radius.y <= 0.0 {
@@ -1595,11 +2223,32 @@ impl Tessellator {
         points.push(center + Vec2::new(0.0, -radius.y));
         points.extend(quarter.iter().rev().map(|p| center + Vec2::new(p.x, -p.y)));
 
-        let path_stroke = PathStroke::from(stroke).outside();
+        let path_stroke = outline_stroke(stroke, StrokeKind::Outside, stroke_dash.as_deref());
         self.scratchpad_path.clear();
         self.scratchpad_path.add_line_loop(&points);
-        self.scratchpad_path
-            .fill_and_stroke(self.feathering, fill, &path_stroke, out);
+        if let Some(brush) = brush {
+            let rect = Rect::from_center_size(center, 2.0 * radius);
+            self.fill_and_stroke_with_brush(fill, &brush, rect, &path_stroke, out);
+        } else {
+            self.scratchpad_path
+                .fill_and_stroke(self.feathering, fill, &path_stroke, out);
+        }
+    }
+
+    /// Fill [`Self::scratchpad_path`] with a [`crate::Brush`], then stroke it.
+    fn fill_and_stroke_with_brush(
+        &mut self,
+        fill: Color32,
+        brush: &crate::Brush,
+        rect: Rect,
+        stroke: &PathStroke,
+        out: &mut Mesh,
+    ) {
+        let path = &mut self.scratchpad_path;
+        path.fill_with_brush(self.feathering, fill, brush, rect, out);
+        if stroke.width > 0.0 && stroke.color != ColorMode::TRANSPARENT {
+            path.stroke_closed(self.feathering, stroke, out);
+        }
     }
 
     /// Tessellate a single [`Mesh`] into a [`Mesh`].
@@ -1728,23 +2377,70 @@ impl Tessellator {
             closed,
             fill,
             stroke,
+            brush,
         } = path_shape;
 
-        self.scratchpad_path.clear();
-
-        if *closed {
-            self.scratchpad_path.add_line_loop(points);
-
-            self.scratchpad_path
-                .fill_and_stroke(self.feathering, *fill, stroke, out);
-        } else {
+        if !*closed {
             debug_assert_eq!(
                 *fill,
                 Color32::TRANSPARENT,
                 "You asked to fill a path that is not closed. That makes no sense."
             );
+        }
+
+        self.tessellate_polyline(points, *closed, *fill, brush.as_deref(), stroke, out);
+    }
 
-            self.scratchpad_path.add_open_points(points);
+    /// Stroke (and fill, if closed) the polyline through `points`,
+    /// with the corners and ends given by the stroke.
+    fn tessellate_polyline(
+        &mut self,
+        points: &[Pos2],
+        closed: bool,
+        fill: Color32,
+        brush: Option<&crate::Brush>,
+        stroke: &PathStroke,
+        out: &mut Mesh,
+    ) {
+        self.scratchpad_path.clear();
+
+        if closed {
+            if stroke.is_empty() {
+                self.scratchpad_path.add_line_loop(points);
+            } else {
+                self.scratchpad_path
+                    .add_line_loop_with_joins(points, stroke);
+            }
+
+            let has_fill = fill != Color32::TRANSPARENT || brush.is_some();
+            let cut_corners = self.scratchpad_path.0.len() != points.len();
+
+            if has_fill && cut_corners {
+                // The cut off (or rounded) corners don't work with the feathering of the fill
+                // (see `Path::add_line_loop`), so we fill the plain outline first,
+                // and then stroke on top of it:
+                let mut stroke_path = std::mem::take(&mut self.scratchpad_path);
+                self.scratchpad_path.add_line_loop(points);
+                if let Some(brush) = brush {
+                    let rect = Rect::from_points(points);
+                    self.fill_and_stroke_with_brush(fill, brush, rect, &PathStroke::NONE, out);
+                } else {
+                    self.scratchpad_path.fill(self.feathering, fill, out);
+                }
+                if !stroke.is_empty() {
+                    stroke_path.stroke_closed(self.feathering, stroke, out);
+                }
+                self.scratchpad_path = stroke_path; // reuse the allocation
+            } else if let Some(brush) = brush {
+                let rect = Rect::from_points(points);
+                self.fill_and_stroke_with_brush(fill, brush, rect, stroke, out);
+            } else {
+                self.scratchpad_path
+                    .fill_and_stroke(self.feathering, fill, stroke, out);
+            }
+        } else {
+            self.scratchpad_path
+                .add_open_points_with_joins(points, stroke);
 
             self.scratchpad_path
                 .stroke(self.feathering, PathType::Open, stroke, out);
@@ -1763,12 +2459,14 @@ impl Tessellator {
         }
 
         let brush = rect_shape.brush.as_ref();
+        let stroke_dash = rect_shape.stroke_dash.as_deref();
         let RectShape {
             mut rect,
             corner_radius,
             mut fill,
             mut stroke,
             mut stroke_kind,
+            stroke_dash: _, // stroke_dash is extracted on its own, because it is not Copy
             round_to_pixels,
             mut blur_width,
             brush: _, // brush is extracted on its own, because it is not Copy
@@ -1786,7 +2484,7 @@ impl Tessellator {
         rect.min = rect.min.at_least(pos2(-1e7, -1e7));
         rect.max = rect.max.at_most(pos2(1e7, 1e7));
 
-        if !stroke.is_empty() {
+        if !stroke.is_empty() && stroke_dash.is_none() {
             // Check if the stroke covers the whole rectangle
             let rect_with_stroke = match stroke_kind {
                 StrokeKind::Inside => rect,
@@ -1807,7 +2505,10 @@ impl Tessellator {
             }
         }
 
-        if stroke.is_empty() && out.texture_id == TextureId::default() {
+        if stroke.is_empty()
+            && out.texture_id == TextureId::default()
+            && brush.is_none_or(|brush| brush.gradient.is_none())
+        {
             // Approximate thin rectangles with line segments.
             // This is important so that thin rectangles look good.
             if rect.width() <= 2.0 * self.feathering {
@@ -1941,10 +2642,10 @@ impl Tessellator {
         path::rounded_rectangle(&mut self.scratchpad_points, rect, corner_radius);
         path.add_line_loop(&self.scratchpad_points);
 
-        let path_stroke = PathStroke::from(stroke).with_kind(stroke_kind);
+        let path_stroke = outline_stroke(stroke, stroke_kind, stroke_dash);
 
         if let Some(brush) = brush {
-            // Textured fill
+            // Textured and/or gradient fill
 
             let fill_rect = match stroke_kind {
                 StrokeKind::Inside => rect.shrink(stroke.width),
@@ -1953,17 +2654,7 @@ impl Tessellator {
             };
 
             if fill_rect.is_positive() {
-                let crate::Brush {
-                    fill_texture_id,
-                    uv,
-                } = **brush;
-                let uv_from_pos = |p: Pos2| {
-                    pos2(
-                        remap(p.x, rect.x_range(), uv.x_range()),
-                        remap(p.y, rect.y_range(), uv.y_range()),
-                    )
-                };
-                path.fill_with_uv(self.feathering, fill, fill_texture_id, uv_from_pos, out);
+                path.fill_with_brush(self.feathering, fill, brush, rect, out);
             }
 
             if !stroke.is_empty() {
@@ -2171,24 +2862,15 @@ impl Tessellator {
             return;
         }
 
-        self.scratchpad_path.clear();
-        if closed {
-            self.scratchpad_path.add_line_loop(points);
-
-            self.scratchpad_path
-                .fill_and_stroke(self.feathering, fill, stroke, out);
-        } else {
+        if !closed {
             debug_assert_eq!(
                 fill,
                 Color32::TRANSPARENT,
                 "You asked to fill a bezier path that is not closed. That makes no sense."
             );
-
-            self.scratchpad_path.add_open_points(points);
-
-            self.scratchpad_path
-                .stroke(self.feathering, PathType::Open, stroke, out);
         }
+
+        self.tessellate_polyline(points, closed, fill, None, stroke, out);
     }
 }
 
@@ -2411,3 +3093,313 @@ fn path_bounding_box() {
         );
     }
 }
+
+#[test]
+fn gradient_fill() {
+    use crate::*;
+
+    /// Every vertex (except the outer edge of the feathering) should have the color
+    /// of the gradient at its position, where `rect` is the bounding rectangle of the shape.
+    fn check_gradient(shape: impl Into<Shape>, gradient: &Gradient, rect: Rect) {
+        let shape = shape.into();
+        let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_shape(shape.clone(), &mut mesh);
+        assert!(mesh.is_valid());
+
+        let opaque: Vec<&Vertex> = mesh
+            .vertices
+            .iter()
+            .filter(|vertex| vertex.color != Color32::TRANSPARENT)
+            .collect();
+        assert!(
+            opaque.iter().any(|vertex| vertex.color != opaque[0].color),
+            "No gradient in {shape:?}"
+        );
+
+        for vertex in opaque {
+            let expected = gradient.color_at(rect, vertex.pos);
+            let max_diff = (0..4)
+                .map(|i| vertex.color[i].abs_diff(expected[i]))
+                .max()
+                .unwrap_or_default();
+            assert!(
+                max_diff <= 2,
+                "Wrong color at {:?}: {:?}, expected {expected:?}",
+                vertex.pos,
+                vertex.color
+            );
+        }
+    }
+
+    let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(10.0, 20.0));
+    let vertical = Gradient::vertical(Color32::RED, Color32::BLUE);
+    let horizontal = Gradient::horizontal(Color32::YELLOW, Color32::GREEN);
+    let radial = Gradient::radial(
+        pos2(0.5, 0.5),
+        0.5,
+        [(0.0, Color32::WHITE), (1.0, Color32::BLACK)],
+    );
+
+    check_gradient(
+        RectShape::filled(rect, 4.0, Color32::WHITE).with_gradient(vertical.clone()),
+        &vertical,
+        rect,
+    );
+
+    let center = pos2(5.0, 10.0);
+    check_gradient(
+        CircleShape::filled(center, 5.0, Color32::WHITE).with_gradient(radial.clone()),
+        &radial,
+        Rect::from_center_size(center, Vec2::splat(10.0)),
+    );
+
+    check_gradient(
+        EllipseShape::filled(center, vec2(5.0, 10.0), Color32::WHITE)
+            .with_gradient(horizontal.clone()),
+        &horizontal,
+        rect,
+    );
+
+    let triangle = vec![pos2(0.0, 0.0), pos2(10.0, 20.0), pos2(0.0, 20.0)];
+    check_gradient(
+        PathShape::convex_polygon(triangle.clone(), Color32::WHITE, Stroke::NONE)
+            .with_gradient(vertical.clone()),
+        &vertical,
+        rect,
+    );
+
+    // With round joins the fill is painted separately from the stroke:
+    let stroke = PathStroke::new(1.0, Color32::WHITE).with_join(LineJoin::Round);
+    let shape = PathShape::convex_polygon(triangle, Color32::WHITE, stroke).with_gradient(vertical);
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+    let mut mesh = Mesh::default();
+    tessellator.tessellate_path(&shape, &mut mesh);
+    assert!(mesh.is_valid());
+    assert!(
+        mesh.vertices
+            .iter()
+            .any(|vertex| ![Color32::WHITE, Color32::TRANSPARENT].contains(&vertex.color)),
+        "The gradient fill is missing"
+    );
+}
+
+#[test]
+fn dashes() {
+    let mut path = Path::default();
+    path.add_open_points(&[pos2(0.0, 0.0), pos2(10.0, 0.0), pos2(10.0, 10.0)]);
+
+    let dash_ranges = |path: &[PathPoint], path_type, pattern: &DashPattern| {
+        let mut dashes = vec![];
+        for_each_dash(path, path_type, pattern, |dash, dash_path_type| {
+            assert!(dash_path_type == PathType::Open);
+            let first = dash.first().unwrap().pos;
+            let last = dash.last().unwrap().pos;
+            dashes.push([first, last]);
+        });
+        dashes
+    };
+
+    // The dashes follow the corner:
+    let dashes = dash_ranges(&path.0, PathType::Open, &DashPattern::new(4.0, 2.0));
+    assert_eq!(
+        dashes,
+        vec![
+            [pos2(0.0, 0.0), pos2(4.0, 0.0)],
+            [pos2(6.0, 0.0), pos2(10.0, 0.0)],
+            [pos2(10.0, 2.0), pos2(10.0, 6.0)],
+            [pos2(10.0, 8.0), pos2(10.0, 10.0)],
+        ]
+    );
+
+    // The offset shifts the pattern along the path:
+    let pattern = DashPattern::new(4.0, 2.0).with_offset(5.0);
+    let dashes = dash_ranges(&path.0, PathType::Open, &pattern);
+    assert_eq!(dashes[0], [pos2(1.0, 0.0), pos2(5.0, 0.0)]);
+
+    // A dash crossing the start of a closed path is joined into one:
+    let mut path = Path::default();
+    path.add_line_loop(&[
+        pos2(0.0, 0.0),
+        pos2(10.0, 0.0),
+        pos2(10.0, 10.0),
+        pos2(0.0, 10.0),
+    ]);
+    let pattern = DashPattern::new(4.0, 1.0).with_offset(2.0);
+    let dashes = dash_ranges(&path.0, PathType::Closed, &pattern);
+    assert_eq!(dashes.len(), 8);
+    assert_eq!(dashes.last(), Some(&[pos2(0.0, 2.0), pos2(2.0, 0.0)]));
+}
+
+#[test]
+fn dashed_stroke() {
+    use crate::*;
+
+    let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(20.0, 20.0));
+    let mut points = vec![];
+    path::rounded_rectangle(&mut points, rect, CornerRadiusF32::same(4.0));
+    let stroke = PathStroke::new(2.0, Color32::WHITE);
+
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+    let mut solid = Mesh::default();
+    tessellator.tessellate_path(
+        &PathShape::closed_line(points.clone(), stroke.clone()),
+        &mut solid,
+    );
+
+    for pattern in [
+        DashPattern::new(4.0, 2.0),
+        DashPattern::new(4.0, 2.0).with_cap(LineCap::Square),
+        DashPattern::dotted(4.0),
+    ] {
+        let shape =
+            PathShape::closed_line(points.clone(), stroke.clone().with_dash(pattern.clone()));
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_path(&shape, &mut mesh);
+        assert!(mesh.is_valid());
+        assert!(
+            solid.vertices.len() < mesh.vertices.len(),
+            "Expected many dashes for {pattern:?}"
+        );
+
+        // The dashes follow the outline (but the caps may stick out around the corners):
+        let bounds = mesh.calc_bounds();
+        assert!(
+            rect.expand(stroke.width).contains_rect(bounds),
+            "{pattern:?}: {bounds:?}"
+        );
+    }
+
+    // An invalid pattern gives a solid stroke:
+    let shape = PathShape::closed_line(points, stroke.with_dash(DashPattern::new(0.0, 0.0)));
+    let mut mesh = Mesh::default();
+    tessellator.tessellate_path(&shape, &mut mesh);
+    assert_eq!(mesh.vertices.len(), solid.vertices.len());
+}
+
+#[test]
+fn dashed_outlines() {
+    use crate::*;
+
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+    let stroke = Stroke::new(2.0, Color32::WHITE);
+
+    // A rounded rectangle, with a dash-dot-dash pattern:
+    let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(40.0, 20.0));
+    let solid = RectShape::stroke(rect, 6.0, stroke, StrokeKind::Inside);
+    let dashed =
+        solid
+            .clone()
+            .with_stroke_dash(DashPattern::multi([(6.0, 2.0), (0.0, 2.0), (2.0, 2.0)]));
+    let mut solid_mesh = Mesh::default();
+    tessellator.tessellate_rect(&solid, &mut solid_mesh);
+    let mut dashed_mesh = Mesh::default();
+    tessellator.tessellate_rect(&dashed, &mut dashed_mesh);
+    assert!(dashed_mesh.is_valid());
+    assert!(solid_mesh.vertices.len() < dashed_mesh.vertices.len());
+    assert!(rect.expand(1.0).contains_rect(dashed_mesh.calc_bounds()));
+
+    // A circle:
+    let center = pos2(50.0, 50.0);
+    let solid = CircleShape::stroke(center, 20.0, stroke);
+    let dashed =
+        solid
+            .clone()
+            .with_stroke_dash(DashPattern::multi([(6.0, 6.0), (0.0, 6.0), (2.0, 6.0)]));
+    let mut solid_mesh = Mesh::default();
+    tessellator.tessellate_circle(solid, &mut solid_mesh);
+    let mut dashed_mesh = Mesh::default();
+    tessellator.tessellate_circle(dashed, &mut dashed_mesh);
+    assert!(dashed_mesh.is_valid());
+    let bounds = Rect::from_center_size(center, Vec2::splat(2.0 * (20.0 + stroke.width)));
+    assert!(bounds.expand(1.0).contains_rect(dashed_mesh.calc_bounds()));
+
+    // The dashes start at the rightmost point of the circle, and go clockwise
+    // along the middle of the stroke:
+    let covers = |mesh: &Mesh, arc_length: f32| {
+        let pos = center + 21.0 * Vec2::angled(arc_length / 21.0);
+        mesh.indices.chunks_exact(3).any(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].pos);
+            let sides = [(a, b), (b, c), (c, a)].map(|(p, q)| (q - p).rot90().dot(pos - p));
+            sides.iter().all(|side| *side > 0.0) || sides.iter().all(|side| *side < 0.0)
+        })
+    };
+    assert!(covers(&solid_mesh, 3.0) && covers(&solid_mesh, 9.0));
+    assert!(covers(&dashed_mesh, 3.0), "The first dash is missing");
+    assert!(!covers(&dashed_mesh, 9.0), "The first gap is painted");
+}
+
+#[test]
+fn line_joins() {
+    // A sharp (60 degree) corner:
+    let points = [pos2(0.0, 0.0), pos2(10.0, 0.0), pos2(5.0, 8.66)];
+    let stroke = PathStroke::new(4.0, Color32::WHITE);
+
+    let corner = |stroke: &PathStroke| {
+        let mut path = Path::default();
+        path.add_open_points_with_joins(&points, stroke);
+        let path = path.0;
+        path[1..path.len() - 1].to_vec()
+    };
+
+    // The default is the same as `add_open_points`:
+    let mut path = Path::default();
+    path.add_open_points(&points);
+    assert_eq!(path.0[1..path.0.len() - 1], corner(&stroke));
+
+    // With a high enough limit we get a single miter point, two half-widths out:
+    let miter = corner(&stroke.clone().with_join(LineJoin::miter_limit(4.0)));
+    assert_eq!(miter.len(), 1);
+    assert!((miter[0].normal.length() - 2.0).abs() < 1e-3);
+
+    // Otherwise the corner is cut off:
+    assert_eq!(
+        corner(&stroke.clone().with_join(LineJoin::miter_limit(1.5))).len(),
+        2
+    );
+
+    // The limit is not rounded, so even a tiny bit below the miter length cuts off the corner:
+    let with_limit = |limit| stroke.clone().with_join(LineJoin::miter_limit(limit));
+    assert_eq!(corner(&with_limit(2.01)).len(), 1);
+    assert_eq!(corner(&with_limit(1.99)).len(), 2);
+
+    // Closed paths get full miters by default, just like `add_line_loop`:
+    let mut line_loop = Path::default();
+    line_loop.add_line_loop(&points);
+    let mut joined_loop = Path::default();
+    joined_loop.add_line_loop_with_joins(&points, &stroke);
+    assert_eq!(line_loop.0, joined_loop.0);
+
+    let bevel = corner(&stroke.clone().with_join(LineJoin::Bevel));
+    assert_eq!(bevel.len(), 2);
+    assert!(bevel.iter().all(|p| (p.normal.length() - 1.0).abs() < 1e-3));
+
+    let round = corner(&stroke.clone().with_join(LineJoin::Round));
+    assert!(3 <= round.len());
+    assert!(round.iter().all(|p| (p.normal.length() - 1.0).abs() < 1e-3));
+}
+
+#[test]
+fn line_caps() {
+    use emath::Rangef;
+
+    let points = vec![pos2(10.0, 10.0), pos2(30.0, 10.0)];
+    let mut tessellator = Tessellator::new(1.0, Default::default(), [1024, 1024], vec![]);
+
+    let mut bounds = |cap: LineCap| {
+        let stroke = PathStroke::new(4.0, Color32::WHITE).with_cap(cap);
+        let mut mesh = Mesh::default();
+        tessellator.tessellate_path(&PathShape::line(points.clone(), stroke), &mut mesh);
+        assert!(mesh.is_valid());
+        mesh.calc_bounds()
+    };
+
+    // The butt cap only extends by the feathering (one pixel):
+    assert_eq!(bounds(LineCap::Butt).x_range(), Rangef::new(9.0, 31.0));
+
+    // The others extend by half the stroke width, plus feathering:
+    assert_eq!(bounds(LineCap::Square).x_range(), Rangef::new(7.0, 33.0));
+
+    // …but the round cap has its feathering centered on the edge:
+    assert_eq!(bounds(LineCap::Round).x_range(), Rangef::new(7.5, 32.5));
+}
@@ -0,0 +1,563 @@
+//! Rasterization of color glyphs (e.g. emoji), using `COLR` layers or `CBDT`/`sbix` bitmaps.
+
+use ecolor::Color32;
+use emath::Vec2;
+
+/// A rasterized color glyph.
+pub(crate) struct ColorGlyphImage {
+    /// Top left corner in physical pixels, relative to the glyph origin (on the baseline).
+    ///
+    /// Y is down.
+    pub min: Vec2,
+
+    /// Width and height in physical pixels.
+    pub size: [usize; 2],
+
+    /// Premultiplied colors, row by row, top to bottom.
+    pub pixels: Vec<Color32>,
+
+    /// Coverage of the layers to paint in the text color, beneath [`Self::pixels`].
+    ///
+    /// Same layout as [`Self::pixels`], or empty if there are no such layers.
+    pub text_color_coverage: Vec<f32>,
+}
+
+/// Does this font have any color glyphs we know how to rasterize?
+#[cfg(feature = "system_fonts")]
+pub(crate) fn has_color_glyphs(font_data: &[u8], face_index: u32) -> bool {
+    ttf_parser::Face::parse(font_data, face_index).is_ok_and(|face| {
+        let tables = face.tables();
+        tables.colr.is_some() || tables.cbdt.is_some() || tables.sbix.is_some()
+    })
+}
+
+/// Rasterize a glyph in color, if it is a color glyph.
+///
+/// `scale_in_pixels` is the same scale as used by `ab_glyph`,
+/// and `offset` is the position of the glyph origin in physical pixels.
+/// `face` must be parsed from the data of `font`.
+pub(crate) fn rasterize_color_glyph(
+    font: &ab_glyph::FontArc,
+    face: &ttf_parser::Face<'_>,
+    glyph_id: ab_glyph::GlyphId,
+    scale_in_pixels: f32,
+    offset: Vec2,
+) -> Option<ColorGlyphImage> {
+    use ab_glyph::Font as _;
+
+    let ttf_glyph_id = ttf_parser::GlyphId(glyph_id.0);
+
+    if face.is_color_glyph(ttf_glyph_id) {
+        let layers = color_layers(face, ttf_glyph_id)?;
+        rasterize_layers(font, &layers, scale_in_pixels, offset)
+    } else {
+        let pixels_per_em = scale_in_pixels * face.units_per_em() as f32 / font.height_unscaled();
+        let raster = face.glyph_raster_image(ttf_glyph_id, pixels_per_em.round() as u16)?;
+        rasterize_bitmap(&raster, pixels_per_em, offset)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// The color of a layer of a `COLR` glyph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LayerColor {
+    /// A color from the palette of the font.
+    Palette(Color32),
+
+    /// The text color, with this opacity.
+    Text { alpha: u8 },
+}
+
+/// The layers of a `COLR` glyph, from bottom to top.
+fn color_layers(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: ttf_parser::GlyphId,
+) -> Option<Vec<(ab_glyph::GlyphId, LayerColor)>> {
+    // The layers in the text color get whatever foreground color we paint with,
+    // so paint with two different ones to tell them apart from the palette colors:
+    let black = paint_layers(face, glyph_id, ttf_parser::RgbaColor::new(0, 0, 0, 255))?;
+    let white = paint_layers(
+        face,
+        glyph_id,
+        ttf_parser::RgbaColor::new(255, 255, 255, 255),
+    )?;
+    if black.len() != white.len() {
+        return None;
+    }
+
+    let layers = black
+        .into_iter()
+        .zip(white)
+        .map(|((glyph_id, on_black), (_, on_white))| {
+            let color = if on_black == on_white {
+                LayerColor::Palette(on_black)
+            } else {
+                LayerColor::Text {
+                    alpha: on_black.a(),
+                }
+            };
+            (glyph_id, color)
+        })
+        .collect();
+    Some(layers)
+}
+
+fn paint_layers(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: ttf_parser::GlyphId,
+    foreground: ttf_parser::RgbaColor,
+) -> Option<Vec<(ab_glyph::GlyphId, Color32)>> {
+    let mut collector = LayerCollector::default();
+    face.paint_color_glyph(glyph_id, 0, foreground, &mut collector)?;
+    (!collector.unsupported && !collector.layers.is_empty()).then_some(collector.layers)
+}
+
+/// Collects the layers of a color glyph.
+///
+/// We support `COLR` v0 (solid colored layers),
+/// and the subset of v1 that is equivalent to it.
+#[derive(Default)]
+struct LayerCollector {
+    outline: Option<ttf_parser::GlyphId>,
+    clip: Option<ttf_parser::GlyphId>,
+    layers: Vec<(ab_glyph::GlyphId, Color32)>,
+
+    /// Gradients, transforms, compositing, …
+    unsupported: bool,
+}
+
+impl<'a> ttf_parser::colr::Painter<'a> for LayerCollector {
+    fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+        self.outline = Some(glyph_id);
+    }
+
+    fn paint(&mut self, paint: ttf_parser::colr::Paint<'a>) {
+        let glyph_id = self.clip.or(self.outline);
+        match (paint, glyph_id) {
+            (ttf_parser::colr::Paint::Solid(color), Some(glyph_id)) => {
+                let color = Color32::from_rgba_unmultiplied(
+                    color.red,
+                    color.green,
+                    color.blue,
+                    color.alpha,
+                );
+                self.layers.push((ab_glyph::GlyphId(glyph_id.0), color));
+            }
+            _ => self.unsupported = true,
+        }
+    }
+
+    fn push_clip(&mut self) {
+        if self.clip.is_some() {
+            self.unsupported = true; // nested clips
+        }
+        self.clip = self.outline;
+    }
+
+    fn push_clip_box(&mut self, _clipbox: ttf_parser::colr::ClipBox) {
+        // The clip box only bounds the glyph, so we can ignore it.
+    }
+
+    fn pop_clip(&mut self) {
+        self.clip = None;
+    }
+
+    fn push_layer(&mut self, _mode: ttf_parser::colr::CompositeMode) {
+        self.unsupported = true;
+    }
+
+    fn pop_layer(&mut self) {}
+
+    fn push_transform(&mut self, _transform: ttf_parser::Transform) {
+        self.unsupported = true;
+    }
+
+    fn pop_transform(&mut self) {}
+}
+
+/// Draw each layer outline in its color, on top of the previous layers.
+///
+/// The layers in the text color go into a separate coverage image, to be painted beneath the others.
+/// To still show them on top of earlier layers, they erase whatever is beneath them in the color image.
+fn rasterize_layers(
+    font: &ab_glyph::FontArc,
+    layers: &[(ab_glyph::GlyphId, LayerColor)],
+    scale_in_pixels: f32,
+    offset: Vec2,
+) -> Option<ColorGlyphImage> {
+    use ab_glyph::Font as _;
+
+    let outlined: Vec<(ab_glyph::OutlinedGlyph, LayerColor)> = layers
+        .iter()
+        .filter_map(|&(glyph_id, color)| {
+            let glyph = glyph_id
+                .with_scale_and_position(scale_in_pixels, ab_glyph::point(offset.x, offset.y));
+            Some((font.outline_glyph(glyph)?, color))
+        })
+        .collect();
+
+    let bb = outlined
+        .iter()
+        .map(|(glyph, _)| glyph.px_bounds())
+        .reduce(|a, b| ab_glyph::Rect {
+            min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+            max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+        })?;
+
+    let [w, h] = [bb.width() as usize, bb.height() as usize];
+    if w == 0 || h == 0 {
+        return None;
+    }
+
+    let mut pixels = vec![[0.0_f32; 4]; w * h];
+    let mut text_color_coverage = vec![0.0_f32; w * h];
+    for (glyph, color) in &outlined {
+        let color = *color;
+        let glyph_bb = glyph.px_bounds();
+        let dx = (glyph_bb.min.x - bb.min.x) as usize;
+        let dy = (glyph_bb.min.y - bb.min.y) as usize;
+        glyph.draw(|x, y, coverage| {
+            let (x, y) = (dx + x as usize, dy + y as usize);
+            if 0.0 < coverage && x < w && y < h {
+                let dst = &mut pixels[y * w + x];
+                match color {
+                    LayerColor::Palette(color) => {
+                        let src = color.to_array().map(|c| c as f32 / 255.0 * coverage);
+                        *dst = std::array::from_fn(|i| src[i] + dst[i] * (1.0 - src[3]));
+                    }
+                    LayerColor::Text { alpha } => {
+                        let coverage = coverage * alpha as f32 / 255.0;
+                        *dst = dst.map(|c| c * (1.0 - coverage));
+                        let text = &mut text_color_coverage[y * w + x];
+                        *text = coverage + *text * (1.0 - coverage);
+                    }
+                }
+            }
+        });
+    }
+
+    let has_text_color = outlined
+        .iter()
+        .any(|(_, color)| matches!(color, LayerColor::Text { .. }));
+    if !has_text_color {
+        text_color_coverage.clear();
+    }
+
+    Some(ColorGlyphImage {
+        min: Vec2::new(bb.min.x, bb.min.y),
+        size: [w, h],
+        pixels: pixels.into_iter().map(color_from_f32).collect(),
+        text_color_coverage,
+    })
+}
+
+fn color_from_f32(rgba: [f32; 4]) -> Color32 {
+    let [r, g, b, a] = rgba.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8);
+    Color32::from_rgba_premultiplied(r, g, b, a)
+}
+
+// ----------------------------------------------------------------------------
+
+/// Scale a bitmap glyph (from a bitmap strike of some fixed size) to the size we want.
+fn rasterize_bitmap(
+    raster: &ttf_parser::RasterGlyphImage<'_>,
+    pixels_per_em: f32,
+    offset: Vec2,
+) -> Option<ColorGlyphImage> {
+    let (size, pixels) = decode_bitmap(raster)?;
+    if size[0] == 0 || size[1] == 0 || raster.pixels_per_em == 0 {
+        return None;
+    }
+
+    let scale = pixels_per_em / raster.pixels_per_em as f32;
+    let target_size = size.map(|s| ((s as f32 * scale).round() as usize).max(1));
+    let pixels = resample(size, &pixels, target_size);
+
+    // The raster offset is to the bottom left corner of the image, with Y up:
+    let min = Vec2::new(
+        raster.x as f32 * scale,
+        -(raster.y as f32 + size[1] as f32) * scale,
+    ) + offset;
+
+    Some(ColorGlyphImage {
+        min: min.round(),
+        size: target_size,
+        pixels,
+        text_color_coverage: vec![],
+    })
+}
+
+/// Returns the size and premultiplied pixels of the bitmap.
+fn decode_bitmap(raster: &ttf_parser::RasterGlyphImage<'_>) -> Option<([usize; 2], Vec<Color32>)> {
+    match raster.format {
+        ttf_parser::RasterImageFormat::PNG => decode_png(raster.data),
+        ttf_parser::RasterImageFormat::BitmapPremulBgra32 => {
+            let size = [raster.width as usize, raster.height as usize];
+            let pixels: Vec<Color32> = raster
+                .data
+                .chunks_exact(4)
+                .map(|bgra| Color32::from_rgba_premultiplied(bgra[2], bgra[1], bgra[0], bgra[3]))
+                .collect();
+            (pixels.len() == size[0] * size[1]).then_some((size, pixels))
+        }
+        _ => None, // Monochrome bitmaps aren't color glyphs
+    }
+}
+
+fn decode_png(data: &[u8]) -> Option<([usize; 2], Vec<Color32>)> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let bytes = &buffer[..info.buffer_size()];
+
+    let pixels: Vec<Color32> = match info.color_type {
+        png::ColorType::Rgba => bytes
+            .chunks_exact(4)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
+            .collect(),
+        png::ColorType::Rgb => bytes
+            .chunks_exact(3)
+            .map(|p| Color32::from_rgb(p[0], p[1], p[2]))
+            .collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks_exact(2)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[0], p[0], p[1]))
+            .collect(),
+        png::ColorType::Grayscale => bytes.iter().map(|&l| Color32::from_gray(l)).collect(),
+        png::ColorType::Indexed => return None, // expanded by `normalize_to_color8`
+    };
+
+    let size = [info.width as usize, info.height as usize];
+    (pixels.len() == size[0] * size[1]).then_some((size, pixels))
+}
+
+/// Resize a premultiplied image by averaging all source pixels covering each target pixel.
+fn resample(size: [usize; 2], pixels: &[Color32], target_size: [usize; 2]) -> Vec<Color32> {
+    if size == target_size {
+        return pixels.to_vec();
+    }
+
+    // The range of source pixels covering the target pixel `i`:
+    let source_range = |i: usize, axis: usize| {
+        let scale = size[axis] as f32 / target_size[axis] as f32;
+        let start = ((i as f32 * scale) as usize).min(size[axis] - 1);
+        let end = (((i + 1) as f32 * scale).ceil() as usize).clamp(start + 1, size[axis]);
+        start..end
+    };
+
+    let mut out = Vec::with_capacity(target_size[0] * target_size[1]);
+    for ty in 0..target_size[1] {
+        let ys = source_range(ty, 1);
+        for tx in 0..target_size[0] {
+            let xs = source_range(tx, 0);
+            let mut sum = [0_u32; 4];
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let color = pixels[y * size[0] + x].to_array();
+                    for (s, c) in sum.iter_mut().zip(color) {
+                        *s += c as u32;
+                    }
+                }
+            }
+            let count = (ys.len() * xs.len()) as u32;
+            let [r, g, b, a] = sum.map(|s| ((s + count / 2) / count) as u8);
+            out.push(Color32::from_rgba_premultiplied(r, g, b, a));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let red = Color32::RED;
+        let blue = Color32::BLUE;
+        let pixels = [red, red, blue, blue, red, red, blue, blue];
+        assert_eq!(resample([4, 2], &pixels, [2, 1]), vec![red, blue]);
+        assert_eq!(
+            resample([4, 2], &pixels, [1, 1]),
+            vec![Color32::from_rgba_premultiplied(128, 0, 128, 255)]
+        );
+        assert_eq!(resample([1, 1], &[red], [2, 2]), vec![red; 4]);
+    }
+
+    fn be_u16(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    fn be_u32(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    /// A font file with these tables.
+    fn font_file(mut tables: Vec<(&[u8; 4], Vec<u8>)>) -> Vec<u8> {
+        tables.sort_by_key(|(tag, _)| **tag); // Looked up with a binary search
+
+        let mut file = be_u32(&[0x0001_0000]);
+        file.extend(be_u16(&[tables.len() as u16, 0, 0, 0]));
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, data) in &tables {
+            file.extend(*tag);
+            file.extend(be_u32(&[0, offset as u32, data.len() as u32]));
+            offset += data.len().next_multiple_of(4);
+        }
+        for (_, data) in &tables {
+            file.extend(data);
+            file.resize(file.len().next_multiple_of(4), 0);
+        }
+        file
+    }
+
+    /// The required tables for a font with 1000 units per em, and an ascent of 1000 units.
+    fn required_tables(num_glyphs: u16) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let mut head = vec![0; 54];
+        head[0..4].copy_from_slice(&be_u32(&[0x0001_0000]));
+        head[18..20].copy_from_slice(&be_u16(&[1000])); // units per em
+        head[50..52].copy_from_slice(&be_u16(&[1])); // 32-bit `loca` offsets
+
+        let mut hhea = vec![0; 36];
+        hhea[0..4].copy_from_slice(&be_u32(&[0x0001_0000]));
+        hhea[4..6].copy_from_slice(&be_u16(&[1000])); // ascent
+        hhea[34..36].copy_from_slice(&be_u16(&[num_glyphs]));
+
+        let maxp = be_u16(&[0, 0x5000, num_glyphs]);
+        let hmtx = be_u16(&[1000, 0].repeat(num_glyphs as usize));
+
+        vec![
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"maxp", maxp),
+            (b"hmtx", hmtx),
+        ]
+    }
+
+    /// `glyf` and `loca` tables with a rectangle as the outline of each glyph, if any.
+    fn outline_tables(rects: &[Option<[i16; 4]>]) -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let mut glyf = vec![];
+        let mut loca = vec![0];
+        for rect in rects {
+            if let Some([x0, y0, x1, y1]) = *rect {
+                let header = [1, x0, y0, x1, y1, 3, 0]; // One contour with four points, no instructions
+                glyf.extend(be_u16(&header.map(|v| v as u16)));
+                glyf.extend([1; 4]); // On curve, with 16 bit deltas
+                glyf.extend(be_u16(&[x0, x1 - x0, 0, x0 - x1].map(|v| v as u16)));
+                glyf.extend(be_u16(&[y0, 0, y1 - y0, 0].map(|v| v as u16)));
+            }
+            loca.push(glyf.len() as u32);
+        }
+        vec![(b"glyf", glyf), (b"loca", be_u32(&loca))]
+    }
+
+    #[test]
+    fn test_colr_glyph() {
+        // Glyph 1 is red with a layer in the text color on the right, and glyph 4 is only red:
+        let colr = [
+            be_u16(&[0, 2]),
+            be_u32(&[14, 26]),
+            be_u16(&[3]),
+            be_u16(&[1, 0, 2, 4, 2, 1]),
+            be_u16(&[2, 0, 3, 0xFFFF, 2, 0]),
+        ]
+        .concat();
+        let cpal = [
+            be_u16(&[0, 1, 1, 1]),
+            be_u32(&[14]),
+            be_u16(&[0]),
+            vec![0, 0, 255, 255],
+        ]
+        .concat();
+
+        let mut tables = required_tables(5);
+        tables.extend(outline_tables(&[
+            None,
+            None,
+            Some([0, 0, 1000, 800]),
+            Some([480, 0, 1000, 800]),
+            None,
+        ]));
+        tables.extend([(b"COLR", colr), (b"CPAL", cpal)]);
+        let font_data = font_file(tables);
+
+        let font = ab_glyph::FontArc::try_from_vec(font_data.clone()).unwrap();
+        let face = ttf_parser::Face::parse(&font_data, 0).unwrap();
+        let rasterize = |glyph_id| {
+            rasterize_color_glyph(&font, &face, ab_glyph::GlyphId(glyph_id), 125.0, Vec2::ZERO)
+                .unwrap()
+        };
+
+        let image = rasterize(1);
+        assert_eq!(image.size, [125, 100]);
+        assert_eq!(image.min, Vec2::new(0.0, -100.0));
+        let (left, right) = (50 * 125 + 10, 50 * 125 + 100);
+        assert_eq!(image.pixels[left], Color32::RED);
+        assert_eq!(image.text_color_coverage[left], 0.0);
+        // The layer in the text color is on top of the red one:
+        assert_eq!(image.pixels[right], Color32::TRANSPARENT);
+        assert!(0.99 < image.text_color_coverage[right]);
+
+        let image = rasterize(4);
+        assert_eq!(image.pixels[right], Color32::RED);
+        assert!(image.text_color_coverage.is_empty());
+    }
+
+    #[test]
+    fn test_cbdt_glyph() {
+        let png = {
+            let rgba = [Color32::RED, Color32::GREEN, Color32::BLUE, Color32::WHITE];
+            let mut png = vec![];
+            let mut encoder = png::Encoder::new(&mut png, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&rgba.map(|c| c.to_array()).concat())
+                .unwrap();
+            writer.finish().unwrap();
+            png
+        };
+
+        // A 20 pixels per em strike with a PNG image for glyph 1, 2 pixels above the baseline:
+        let cblc = [
+            be_u32(&[0x0003_0000, 1]),
+            be_u32(&[56, 24, 1, 0]),
+            vec![0; 24],
+            be_u16(&[1, 1]),
+            vec![20, 20, 32, 1],
+            be_u16(&[1, 1]),
+            be_u32(&[8]),
+            be_u16(&[1, 17]),
+            be_u32(&[4, 0, 9 + png.len() as u32]),
+        ]
+        .concat();
+        let cbdt = [
+            be_u32(&[0x0003_0000]),
+            vec![2, 2, 0, 2, 2],
+            be_u32(&[png.len() as u32]),
+            png,
+        ]
+        .concat();
+
+        let mut tables = required_tables(2);
+        tables.extend([(b"CBLC", cblc), (b"CBDT", cbdt)]);
+        let font_data = font_file(tables);
+
+        let font = ab_glyph::FontArc::try_from_vec(font_data.clone()).unwrap();
+        let face = ttf_parser::Face::parse(&font_data, 0).unwrap();
+        let image =
+            rasterize_color_glyph(&font, &face, ab_glyph::GlyphId(1), 40.0, Vec2::ZERO).unwrap();
+
+        // Scaled up to 40 pixels per em:
+        assert_eq!(image.size, [4, 4]);
+        assert_eq!(image.min, Vec2::new(0.0, -4.0));
+        assert_eq!(image.pixels[0], Color32::RED);
+        assert_eq!(image.pixels[3], Color32::GREEN);
+        assert_eq!(image.pixels[12], Color32::BLUE);
+        assert_eq!(image.pixels[15], Color32::WHITE);
+        assert!(image.text_color_coverage.is_empty());
+    }
+}
@@ -9,6 +9,9 @@ use crate::{
     text::FontTweak,
 };
 
+#[cfg(feature = "system_fonts")]
+use crate::text::system_fonts::SystemFonts;
+
 // ----------------------------------------------------------------------------
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -26,6 +29,18 @@ pub struct UvRect {
 
     /// Bottom right corner (exclusive).
     pub max: [u16; 2],
+
+    /// The texels are already colored (e.g. an emoji),
+    /// and should not be tinted by the text color.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub colored: bool,
+
+    /// Some layers of this [`Self::colored`] glyph are painted in the text color.
+    ///
+    /// Their coverage is stored in a rectangle of the same size right next to this one in the texture,
+    /// and is painted beneath the colored texels.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub text_color_layer: bool,
 }
 
 impl UvRect {
@@ -62,6 +77,31 @@ impl Default for GlyphInfo {
 
 // ----------------------------------------------------------------------------
 
+#[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+#[cfg_attr(feature = "text_shaping", expect(clippy::mem_forget))] // In the code generated by `yoke::Yokeable`
+mod parsed_face {
+    use std::sync::Arc;
+
+    /// A parsed font face together with the font that owns its data,
+    /// so it only needs to be parsed once per [`super::FontImpl`].
+    pub(super) type ParsedFace<Y> = yoke::Yoke<Y, Arc<ab_glyph::FontArc>>;
+
+    /// A `rustybuzz` face borrowing the font data it was parsed from.
+    #[cfg(feature = "text_shaping")]
+    #[derive(yoke::Yokeable)]
+    pub(super) struct RustybuzzFace<'a>(pub rustybuzz::Face<'a>);
+
+    /// A `ttf-parser` face borrowing the font data it was parsed from.
+    #[cfg(feature = "color_glyphs")]
+    #[derive(yoke::Yokeable)]
+    pub(super) struct TtfParserFace<'a>(pub ttf_parser::Face<'a>);
+}
+
+#[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+use parsed_face::ParsedFace;
+
+// ----------------------------------------------------------------------------
+
 /// A specific font with a size.
 /// The interface uses points as the unit for everything.
 pub struct FontImpl {
@@ -79,6 +119,23 @@ pub struct FontImpl {
     ascent: f32,
     pixels_per_point: f32,
     glyph_info_cache: RwLock<ahash::HashMap<char, GlyphInfo>>, // TODO(emilk): standard Mutex
+
+    /// Index of the face in the font file (for font collections), needed for shaping and color glyphs.
+    #[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+    face_index: u32,
+
+    /// Shaped glyph clusters that have been rasterized, keyed on glyph ids and pixel offsets.
+    #[cfg(feature = "text_shaping")]
+    cluster_cache: RwLock<ahash::HashMap<Vec<(u16, i32, i32)>, UvRect>>,
+
+    /// The font parsed by `rustybuzz`, built on the first call to [`Self::shape`].
+    #[cfg(feature = "text_shaping")]
+    shaping_face: std::sync::OnceLock<Option<ParsedFace<parsed_face::RustybuzzFace<'static>>>>,
+
+    /// The font parsed by `ttf-parser`, built the first time a glyph is rasterized.
+    #[cfg(feature = "color_glyphs")]
+    color_face: std::sync::OnceLock<Option<ParsedFace<parsed_face::TtfParserFace<'static>>>>,
+
     atlas: Arc<Mutex<TextureAtlas>>,
 }
 
@@ -135,10 +192,25 @@ impl FontImpl {
             ascent: ascent + baseline_offset,
             pixels_per_point,
             glyph_info_cache: Default::default(),
+            #[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+            face_index: 0,
+            #[cfg(feature = "text_shaping")]
+            cluster_cache: Default::default(),
+            #[cfg(feature = "text_shaping")]
+            shaping_face: Default::default(),
+            #[cfg(feature = "color_glyphs")]
+            color_face: Default::default(),
             atlas,
         }
     }
 
+    /// Which face in the font file this is, for font collections (`.ttc`).
+    #[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+    pub(crate) fn with_face_index(mut self, face_index: u32) -> Self {
+        self.face_index = face_index;
+        self
+    }
+
     /// Code points that will always be replaced by the replacement character.
     ///
     /// See also [`invisible_char`].
@@ -227,6 +299,130 @@ impl FontImpl {
         }
     }
 
+    /// Does this font have a glyph for this character?
+    #[cfg(feature = "text_shaping")]
+    fn supports(&self, c: char) -> bool {
+        use ab_glyph::Font as _;
+        !self.ignore_character(c) && self.ab_glyph_font.glyph_id(c).0 != 0
+    }
+
+    /// Shape a run of text with a single direction using `rustybuzz`.
+    ///
+    /// Returns one [`GlyphInfo`] per `char` in `text`, in logical order.
+    /// The advance width of a cluster of characters (e.g. a ligature) is split evenly between them,
+    /// and its glyphs are rasterized together into the `uv_rect` of the visually left-most character.
+    ///
+    /// Returns `None` if the font could not be parsed for shaping.
+    #[cfg(feature = "text_shaping")]
+    fn shape(&self, text: &str, rtl: bool) -> Option<Vec<GlyphInfo>> {
+        use ab_glyph::{Font as _, ScaleFont as _};
+
+        let face = self
+            .shaping_face
+            .get_or_init(|| {
+                yoke::Yoke::try_attach_to_cart(Arc::new(self.ab_glyph_font.clone()), |font| {
+                    rustybuzz::Face::from_slice(font.font_data(), self.face_index)
+                        .map(parsed_face::RustybuzzFace)
+                        .ok_or(())
+                })
+                .ok()
+            })
+            .as_ref()?;
+        let face = &face.get().0;
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.set_direction(if rtl {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+        buffer.guess_segment_properties();
+        let output = rustybuzz::shape(face, &[], buffer);
+        let infos = output.glyph_infos();
+        let positions = output.glyph_positions();
+
+        let scaled = self.ab_glyph_font.as_scaled(self.scale_in_pixels as f32);
+        let pixels_per_unit = vec2(scaled.h_scale_factor(), scaled.v_scale_factor());
+
+        let char_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        let mut cluster_starts: Vec<usize> =
+            infos.iter().map(|info| info.cluster as usize).collect();
+        cluster_starts.sort_unstable();
+        cluster_starts.dedup();
+
+        let mut glyph_infos = vec![GlyphInfo::default(); char_starts.len()];
+
+        // The glyphs come out in visual order, with all glyphs of a cluster next to each other:
+        let mut pen_x = 0;
+        let mut i = 0;
+        while i < infos.len() {
+            let cluster = infos[i].cluster as usize;
+            let cluster_x = pen_x;
+            let mut glyphs = vec![];
+            while i < infos.len() && infos[i].cluster as usize == cluster {
+                let position = &positions[i];
+                let offset = vec2(
+                    (pen_x - cluster_x + position.x_offset) as f32 * pixels_per_unit.x,
+                    -position.y_offset as f32 * pixels_per_unit.y,
+                );
+                glyphs.push((ab_glyph::GlyphId(infos[i].glyph_id as u16), offset));
+                pen_x += position.x_advance;
+                i += 1;
+            }
+
+            let cluster_end = cluster_starts
+                .get(cluster_starts.partition_point(|&start| start <= cluster))
+                .copied()
+                .unwrap_or(text.len());
+            let first_char = char_starts.partition_point(|&start| start < cluster);
+            let end_char = char_starts.partition_point(|&start| start < cluster_end);
+            if end_char <= first_char {
+                continue;
+            }
+
+            let advance_width =
+                (pen_x - cluster_x) as f32 * pixels_per_unit.x / self.pixels_per_point;
+            let advance_width = advance_width / (end_char - first_char) as f32;
+            for glyph_info in &mut glyph_infos[first_char..end_char] {
+                *glyph_info = GlyphInfo {
+                    id: glyphs[0].0,
+                    advance_width,
+                    uv_rect: UvRect::default(),
+                };
+            }
+
+            let visually_first_char = if rtl { end_char - 1 } else { first_char };
+            glyph_infos[visually_first_char].uv_rect = self.cluster_uv_rect(&glyphs);
+        }
+
+        Some(glyph_infos)
+    }
+
+    /// Rasterize (or look up) a shaped cluster of glyphs.
+    ///
+    /// The offsets are in pixels, relative to the start of the cluster.
+    #[cfg(feature = "text_shaping")]
+    fn cluster_uv_rect(&self, glyphs: &[(ab_glyph::GlyphId, Vec2)]) -> UvRect {
+        let glyphs: Vec<(ab_glyph::GlyphId, Vec2)> = glyphs
+            .iter()
+            .filter(|(id, _)| id.0 != 0)
+            .map(|&(id, offset)| (id, offset.round()))
+            .collect();
+        let key: Vec<(u16, i32, i32)> = glyphs
+            .iter()
+            .map(|(id, offset)| (id.0, offset.x as i32, offset.y as i32))
+            .collect();
+
+        if let Some(uv_rect) = self.cluster_cache.read().get(&key) {
+            return *uv_rect;
+        }
+
+        let uv_rect = self.rasterize(&glyphs);
+        self.cluster_cache.write().insert(key, uv_rect);
+        uv_rect
+    }
+
     #[inline]
     pub fn pair_kerning(
         &self,
@@ -265,46 +461,7 @@ impl FontImpl {
         assert!(glyph_id.0 != 0, "Can't allocate glyph for id 0");
         use ab_glyph::{Font as _, ScaleFont as _};
 
-        let glyph = glyph_id.with_scale_and_position(
-            self.scale_in_pixels as f32,
-            ab_glyph::Point { x: 0.0, y: 0.0 },
-        );
-
-        let uv_rect = self.ab_glyph_font.outline_glyph(glyph).map(|glyph| {
-            let bb = glyph.px_bounds();
-            let glyph_width = bb.width() as usize;
-            let glyph_height = bb.height() as usize;
-            if glyph_width == 0 || glyph_height == 0 {
-                UvRect::default()
-            } else {
-                let glyph_pos = {
-                    let atlas = &mut self.atlas.lock();
-                    let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
-                    glyph.draw(|x, y, v| {
-                        if 0.0 < v {
-                            let px = glyph_pos.0 + x as usize;
-                            let py = glyph_pos.1 + y as usize;
-                            image[(px, py)] = v;
-                        }
-                    });
-                    glyph_pos
-                };
-
-                let offset_in_pixels = vec2(bb.min.x, bb.min.y);
-                let offset =
-                    offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
-                UvRect {
-                    offset,
-                    size: vec2(glyph_width as f32, glyph_height as f32) / self.pixels_per_point,
-                    min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
-                    max: [
-                        (glyph_pos.0 + glyph_width) as u16,
-                        (glyph_pos.1 + glyph_height) as u16,
-                    ],
-                }
-            }
-        });
-        let uv_rect = uv_rect.unwrap_or_default();
+        let uv_rect = self.rasterize(&[(glyph_id, Vec2::ZERO)]);
 
         let advance_width_in_points = self
             .ab_glyph_font
@@ -318,6 +475,148 @@ impl FontImpl {
             uv_rect,
         }
     }
+
+    /// The font parsed by `ttf-parser`, for rasterizing color glyphs.
+    #[cfg(feature = "color_glyphs")]
+    fn color_face(&self) -> Option<&ttf_parser::Face<'_>> {
+        let face = self
+            .color_face
+            .get_or_init(|| {
+                yoke::Yoke::try_attach_to_cart(Arc::new(self.ab_glyph_font.clone()), |font| {
+                    use ab_glyph::Font as _;
+                    ttf_parser::Face::parse(font.font_data(), self.face_index)
+                        .map(parsed_face::TtfParserFace)
+                })
+                .ok()
+            })
+            .as_ref()?;
+        Some(&face.get().0)
+    }
+
+    /// Rasterize one or more glyphs into a single image in the atlas.
+    ///
+    /// Each glyph is positioned at the given offset, in whole pixels.
+    fn rasterize(&self, glyphs: &[(ab_glyph::GlyphId, Vec2)]) -> UvRect {
+        use ab_glyph::Font as _;
+
+        #[cfg(feature = "color_glyphs")]
+        if let ([(glyph_id, offset)], Some(face)) = (glyphs, self.color_face()) {
+            if let Some(image) = super::color_glyph::rasterize_color_glyph(
+                &self.ab_glyph_font,
+                face,
+                *glyph_id,
+                self.scale_in_pixels as f32,
+                *offset,
+            ) {
+                return self.allocate_color_glyph(&image);
+            }
+        }
+
+        let outlined: Vec<ab_glyph::OutlinedGlyph> = glyphs
+            .iter()
+            .filter_map(|&(glyph_id, offset)| {
+                let glyph = glyph_id.with_scale_and_position(
+                    self.scale_in_pixels as f32,
+                    ab_glyph::Point {
+                        x: offset.x,
+                        y: offset.y,
+                    },
+                );
+                self.ab_glyph_font.outline_glyph(glyph)
+            })
+            .collect();
+
+        let Some(bb) = outlined
+            .iter()
+            .map(|glyph| glyph.px_bounds())
+            .reduce(|a, b| ab_glyph::Rect {
+                min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            })
+        else {
+            return UvRect::default();
+        };
+
+        let glyph_width = bb.width() as usize;
+        let glyph_height = bb.height() as usize;
+        if glyph_width == 0 || glyph_height == 0 {
+            return UvRect::default();
+        }
+
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let (glyph_pos, image) = atlas.allocate((glyph_width, glyph_height));
+            for glyph in &outlined {
+                let glyph_bb = glyph.px_bounds();
+                let dx = (glyph_bb.min.x - bb.min.x) as usize;
+                let dy = (glyph_bb.min.y - bb.min.y) as usize;
+                glyph.draw(|x, y, v| {
+                    if 0.0 < v {
+                        let px = glyph_pos.0 + dx + x as usize;
+                        let py = glyph_pos.1 + dy + y as usize;
+                        image[(px, py)] = image[(px, py)].max(v);
+                    }
+                });
+            }
+            glyph_pos
+        };
+
+        self.uv_rect_in_atlas(
+            vec2(bb.min.x, bb.min.y),
+            glyph_pos,
+            [glyph_width, glyph_height],
+            false,
+        )
+    }
+
+    /// Copy a rasterized color glyph into the atlas.
+    ///
+    /// The coverage of the layers in the text color (if any) goes to the right of the colored texels.
+    #[cfg(feature = "color_glyphs")]
+    fn allocate_color_glyph(&self, image: &super::color_glyph::ColorGlyphImage) -> UvRect {
+        let [width, height] = image.size;
+        let text_color_layer = !image.text_color_coverage.is_empty();
+        let glyph_pos = {
+            let atlas = &mut self.atlas.lock();
+            let allocated_width = if text_color_layer { 2 * width } else { width };
+            let (glyph_pos, atlas_image) = atlas.allocate((allocated_width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    let pos = (glyph_pos.0 + x, glyph_pos.1 + y);
+                    atlas_image[pos] = 0.0;
+                    atlas_image.set_color(pos, image.pixels[y * width + x]);
+                    if text_color_layer {
+                        atlas_image[(pos.0 + width, pos.1)] =
+                            image.text_color_coverage[y * width + x];
+                    }
+                }
+            }
+            glyph_pos
+        };
+        UvRect {
+            text_color_layer,
+            ..self.uv_rect_in_atlas(image.min, glyph_pos, image.size, true)
+        }
+    }
+
+    /// `offset_in_pixels` is the top left corner relative to the glyph origin.
+    fn uv_rect_in_atlas(
+        &self,
+        offset_in_pixels: Vec2,
+        glyph_pos: (usize, usize),
+        [width, height]: [usize; 2],
+        colored: bool,
+    ) -> UvRect {
+        let offset = offset_in_pixels / self.pixels_per_point + self.y_offset_in_points * Vec2::Y;
+        UvRect {
+            offset,
+            size: vec2(width as f32, height as f32) / self.pixels_per_point,
+            min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+            max: [(glyph_pos.0 + width) as u16, (glyph_pos.1 + height) as u16],
+            colored,
+            text_color_layer: false,
+        }
+    }
 }
 
 type FontIndex = usize;
@@ -334,6 +633,10 @@ pub struct Font {
     pixels_per_point: f32,
     row_height: f32,
     glyph_info_cache: ahash::HashMap<char, (FontIndex, GlyphInfo)>,
+
+    /// Where to look for glyphs not in [`Self::fonts`], and at what size (in points).
+    #[cfg(feature = "system_fonts")]
+    system_fonts: Option<(Arc<Mutex<SystemFonts>>, f32)>,
 }
 
 impl Font {
@@ -346,6 +649,8 @@ impl Font {
                 pixels_per_point: 1.0,
                 row_height: 0.0,
                 glyph_info_cache: Default::default(),
+                #[cfg(feature = "system_fonts")]
+                system_fonts: None,
             };
         }
 
@@ -359,6 +664,8 @@ impl Font {
             pixels_per_point,
             row_height,
             glyph_info_cache: Default::default(),
+            #[cfg(feature = "system_fonts")]
+            system_fonts: None,
         };
 
         const PRIMARY_REPLACEMENT_CHAR: char = '◻'; // white medium square
@@ -379,6 +686,17 @@ impl Font {
         slf
     }
 
+    /// Fall back to system fonts for characters not in any of our fonts.
+    #[cfg(feature = "system_fonts")]
+    pub(crate) fn with_system_fonts(
+        mut self,
+        system_fonts: Arc<Mutex<SystemFonts>>,
+        scale_in_points: f32,
+    ) -> Self {
+        self.system_fonts = Some((system_fonts, scale_in_points));
+        self
+    }
+
     pub fn preload_characters(&mut self, s: &str) {
         for c in s.chars() {
             self.glyph_info(c);
@@ -466,6 +784,73 @@ impl Font {
         (Some(font_impl), glyph_info)
     }
 
+    #[inline]
+    pub(crate) fn font_impl(&self, font_index: usize) -> Option<&FontImpl> {
+        self.fonts.get(font_index).map(|font_impl| &**font_impl)
+    }
+
+    /// Shape a piece of text, given the bidi embedding level of each `char`.
+    ///
+    /// The text is split into runs of the same [`FontImpl`] and direction, and each run is shaped separately.
+    ///
+    /// Returns one entry per `char` in `text`, with the index of the [`FontImpl`] used.
+    /// `None` means the character wasn't shaped (e.g. tabs and newlines,
+    /// or characters not in any font), and should be handled by [`Self::font_impl_and_glyph_info`] instead.
+    #[cfg(feature = "text_shaping")]
+    pub(crate) fn shape(&mut self, text: &str, levels: &[u8]) -> Vec<Option<(usize, GlyphInfo)>> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let level_of = |i: usize| levels.get(i).copied().unwrap_or(0);
+
+        // Which font (if any) to shape each char with:
+        let mut char_fonts: Vec<Option<FontIndex>> = Vec::with_capacity(chars.len());
+        for &(_, c) in &chars {
+            let font_index = if matches!(c, '\u{200C}' | '\u{200D}') {
+                // ZERO WIDTH (NON-)JOINER affect the shaping of their neighbors, so keep them in the run:
+                char_fonts.last().copied().flatten()
+            } else if c == '\t' || c == '\u{2009}' || invisible_char(c) {
+                None // Special-cased by `FontImpl::glyph_info`
+            } else {
+                self.fonts
+                    .iter()
+                    .position(|font_impl| font_impl.supports(c))
+                    .or_else(|| self.add_system_font(c))
+            };
+            char_fonts.push(font_index);
+        }
+
+        let mut shaped = vec![None; chars.len()];
+        let mut run_start = 0;
+        while run_start < chars.len() {
+            let font_index = char_fonts[run_start];
+            let level = level_of(run_start);
+            let mut run_end = run_start + 1;
+            while run_end < chars.len()
+                && char_fonts[run_end] == font_index
+                && level_of(run_end) == level
+            {
+                run_end += 1;
+            }
+
+            if let Some(font_index) = font_index {
+                let byte_start = chars[run_start].0;
+                let byte_end = chars.get(run_end).map_or(text.len(), |&(i, _)| i);
+                let rtl = level % 2 == 1;
+                if let Some(glyph_infos) =
+                    self.fonts[font_index].shape(&text[byte_start..byte_end], rtl)
+                {
+                    for (out, glyph_info) in shaped[run_start..run_end].iter_mut().zip(glyph_infos)
+                    {
+                        *out = Some((font_index, glyph_info));
+                    }
+                }
+            }
+
+            run_start = run_end;
+        }
+
+        shaped
+    }
+
     pub(crate) fn ascent(&self) -> f32 {
         if let Some(first) = self.fonts.first() {
             first.ascent()
@@ -481,6 +866,34 @@ impl Font {
                 return Some((font_index, glyph_info));
             }
         }
+
+        let font_index = self.add_system_font(c)?;
+        let glyph_info = self.fonts[font_index].glyph_info(c)?;
+        self.glyph_info_cache.insert(c, (font_index, glyph_info));
+        Some((font_index, glyph_info))
+    }
+
+    /// Add a system font with this character as a new fallback, if there is one.
+    #[cfg(feature = "system_fonts")]
+    fn add_system_font(&mut self, c: char) -> Option<FontIndex> {
+        let (system_fonts, scale_in_points) = self.system_fonts.as_ref()?;
+        let font_impl = system_fonts.lock().font_impl(*scale_in_points, c)?;
+        if self
+            .fonts
+            .iter()
+            .any(|existing| existing.name == font_impl.name)
+        {
+            // Already a fallback, so it didn't have the glyph after all.
+            return None;
+        }
+        self.fonts.push(font_impl);
+        self.characters = None;
+        Some(self.fonts.len() - 1)
+    }
+
+    #[cfg(not(feature = "system_fonts"))]
+    #[expect(clippy::unused_self, clippy::needless_pass_by_ref_mut)]
+    fn add_system_font(&mut self, _c: char) -> Option<FontIndex> {
         None
     }
 }
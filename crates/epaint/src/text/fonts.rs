@@ -8,6 +8,9 @@ use crate::{
         font::{Font, FontImpl},
     },
 };
+
+#[cfg(feature = "system_fonts")]
+use crate::text::system_fonts::{SystemFontFallback, SystemFonts};
 use emath::{NumExt as _, OrderedFloat};
 
 #[cfg(feature = "default_fonts")]
@@ -261,6 +264,13 @@ pub struct FontDefinitions {
     /// the first font and then move to the second, and so on.
     /// So the first font is the primary, and then comes a list of fallbacks in order of priority.
     pub families: BTreeMap<FontFamily, Vec<String>>,
+
+    /// If set, fonts installed on the system are used for characters
+    /// not found in any of the fonts in [`Self::families`].
+    ///
+    /// Off by default.
+    #[cfg(feature = "system_fonts")]
+    pub system_fallback: Option<SystemFontFallback>,
 }
 
 #[derive(Debug, Clone)]
@@ -372,6 +382,8 @@ impl Default for FontDefinitions {
         Self {
             font_data,
             families,
+            #[cfg(feature = "system_fonts")]
+            system_fallback: None,
         }
     }
 }
@@ -386,6 +398,8 @@ impl FontDefinitions {
         Self {
             font_data: Default::default(),
             families,
+            #[cfg(feature = "system_fonts")]
+            system_fallback: None,
         }
     }
 
@@ -464,6 +478,11 @@ impl Fonts {
             };
         }
 
+        #[cfg(feature = "system_fonts")]
+        if !needs_recreate && fonts_and_cache.fonts.refresh_system_fonts() {
+            fonts_and_cache.galley_cache = Default::default();
+        }
+
         fonts_and_cache.galley_cache.flush_cache();
     }
 
@@ -633,6 +652,8 @@ pub struct FontsImpl {
     definitions: FontDefinitions,
     atlas: Arc<Mutex<TextureAtlas>>,
     font_impl_cache: FontImplCache,
+    #[cfg(feature = "system_fonts")]
+    system_fonts: Option<Arc<Mutex<SystemFonts>>>,
     sized_family: ahash::HashMap<(OrderedFloat<f32>, FontFamily), Font>,
 }
 
@@ -658,12 +679,23 @@ impl FontsImpl {
         let font_impl_cache =
             FontImplCache::new(atlas.clone(), pixels_per_point, &definitions.font_data);
 
+        #[cfg(feature = "system_fonts")]
+        let system_fonts = definitions.system_fallback.clone().map(|config| {
+            Arc::new(Mutex::new(SystemFonts::new(
+                config,
+                atlas.clone(),
+                pixels_per_point,
+            )))
+        });
+
         Self {
             pixels_per_point,
             max_texture_side,
             definitions,
             atlas,
             font_impl_cache,
+            #[cfg(feature = "system_fonts")]
+            system_fonts,
             sized_family: Default::default(),
         }
     }
@@ -696,10 +728,33 @@ impl FontsImpl {
                     .map(|font_name| self.font_impl_cache.font_impl(size, font_name))
                     .collect();
 
-                Font::new(fonts)
+                let font = Font::new(fonts);
+                #[cfg(feature = "system_fonts")]
+                let font = if let Some(system_fonts) = &self.system_fonts {
+                    font.with_system_fonts(system_fonts.clone(), size)
+                } else {
+                    font
+                };
+                font
             })
     }
 
+    /// If the system fonts finished scanning after some character was missing from all fonts,
+    /// forget all glyphs looked up so far so that character can be found this time.
+    ///
+    /// Returns `true` if the glyphs were forgotten.
+    #[cfg(feature = "system_fonts")]
+    fn refresh_system_fonts(&mut self) -> bool {
+        let scanned = self
+            .system_fonts
+            .as_ref()
+            .is_some_and(|system_fonts| system_fonts.lock().scan_finished_after_miss());
+        if scanned {
+            self.sized_family.clear();
+        }
+        scanned
+    }
+
     /// Width of this character in points.
     fn glyph_width(&mut self, font_id: &FontId, c: char) -> f32 {
         self.font(font_id).glyph_width(c)
@@ -891,6 +946,7 @@ impl GalleyCache {
                     leading_space,
                     byte_range: section_range,
                     format,
+                    inline_box,
                 } = section;
 
                 // `start` and `end` are the byte range of the current paragraph.
@@ -921,6 +977,7 @@ impl GalleyCache {
                         },
                         byte_range: new_range,
                         format: format.clone(),
+                        inline_box: *inline_box,
                     });
                 }
             }
@@ -976,10 +1033,10 @@ fn should_cache_each_paragraph_individually(job: &LayoutJob) -> bool {
 
 // ----------------------------------------------------------------------------
 
-struct FontImplCache {
+pub(crate) struct FontImplCache {
     atlas: Arc<Mutex<TextureAtlas>>,
     pixels_per_point: f32,
-    ab_glyph_fonts: BTreeMap<String, (FontTweak, ab_glyph::FontArc)>,
+    ab_glyph_fonts: BTreeMap<String, (FontTweak, ab_glyph::FontArc, u32)>,
 
     /// Map font pixel sizes and names to the cached [`FontImpl`].
     cache: ahash::HashMap<(u32, String), Arc<FontImpl>>,
@@ -996,7 +1053,7 @@ impl FontImplCache {
             .map(|(name, font_data)| {
                 let tweak = font_data.tweak;
                 let ab_glyph = ab_glyph_font_from_font_data(name, font_data);
-                (name.clone(), (tweak, ab_glyph))
+                (name.clone(), (tweak, ab_glyph, font_data.index))
             })
             .collect();
 
@@ -1008,10 +1065,22 @@ impl FontImplCache {
         }
     }
 
+    /// Add a font after construction.
+    #[cfg(feature = "system_fonts")]
+    pub fn insert(&mut self, name: String, font_data: &FontData) {
+        let ab_glyph = ab_glyph_font_from_font_data(&name, font_data);
+        self.ab_glyph_fonts
+            .insert(name, (font_data.tweak, ab_glyph, font_data.index));
+    }
+
     pub fn font_impl(&mut self, scale_in_points: f32, font_name: &str) -> Arc<FontImpl> {
         use ab_glyph::Font as _;
 
-        let (tweak, ab_glyph_font) = self
+        #[cfg_attr(
+            not(any(feature = "text_shaping", feature = "color_glyphs")),
+            expect(unused_variables)
+        )]
+        let (tweak, ab_glyph_font, face_index) = self
             .ab_glyph_fonts
             .get(font_name)
             .unwrap_or_else(|| panic!("No font data found for {font_name:?}"))
@@ -1032,14 +1101,17 @@ impl FontImplCache {
                 font_name.to_owned(),
             ))
             .or_insert_with(|| {
-                Arc::new(FontImpl::new(
+                let font_impl = FontImpl::new(
                     self.atlas.clone(),
                     self.pixels_per_point,
                     font_name.to_owned(),
                     ab_glyph_font,
                     scale_in_pixels,
                     tweak,
-                ))
+                );
+                #[cfg(any(feature = "text_shaping", feature = "color_glyphs"))]
+                let font_impl = font_impl.with_face_index(face_index);
+                Arc::new(font_impl)
             })
             .clone()
     }
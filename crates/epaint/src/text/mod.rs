@@ -1,8 +1,12 @@
 //! Everything related to text, fonts, text layout, cursors etc.
 
+#[cfg(feature = "color_glyphs")]
+mod color_glyph;
 pub mod cursor;
 mod font;
 mod fonts;
+#[cfg(feature = "system_fonts")]
+mod system_fonts;
 mod text_layout;
 mod text_layout_types;
 
@@ -18,5 +22,11 @@ pub use {
     text_layout_types::*,
 };
 
+#[cfg(feature = "system_fonts")]
+pub use system_fonts::SystemFontFallback;
+
 /// Suggested character to use to replace those in password text fields.
 pub const PASSWORD_REPLACEMENT_CHAR: char = '•';
+
+/// Stands in for an [`InlineBox`] in the text of a [`LayoutJob`].
+pub const OBJECT_REPLACEMENT_CHAR: char = '\u{FFFC}';
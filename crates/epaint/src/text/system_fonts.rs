@@ -0,0 +1,525 @@
+//! Fallback to fonts installed on the system, for characters not covered by [`crate::text::FontDefinitions`].
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{
+    TextureAtlas,
+    mutex::Mutex,
+    text::{FontData, font::FontImpl, fonts::FontImplCache},
+};
+
+/// Use fonts installed on the system as a last fallback,
+/// for characters that none of the fonts in [`crate::text::FontDefinitions`] cover.
+///
+/// The directories are scanned (recursively) for `.ttf`, `.otf`, `.ttc` and `.otc` files
+/// on a background thread, starting when the fonts are created (e.g. by `egui::Context::set_fonts`).
+/// The result of the scan is shared between clones, so it only happens once.
+/// Until it is done, characters missing from all configured fonts show up as the replacement character,
+/// and are looked up again in the first pass after the scan finishes.
+/// `egui` repaints when that happens (see [`Self::on_scan_finished`]).
+/// Use [`Self::wait_for_scan`] if you need the system fonts from the very first frame.
+///
+/// A face is only loaded once it is needed for a character,
+/// and is then added as a fallback for that [`crate::text::FontId`].
+///
+/// This lets you render e.g. CJK text without bundling large font files with your app.
+/// Requires the `system_fonts` feature, and does nothing on the web.
+///
+/// ```
+/// # use epaint::text::{FontDefinitions, SystemFontFallback};
+/// let mut fonts = FontDefinitions::default();
+/// fonts.system_fallback = Some(SystemFontFallback::default());
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SystemFontFallback {
+    /// Directories to look for font files in.
+    ///
+    /// [`Self::default`] uses the standard font directories of the current platform.
+    pub directories: Vec<PathBuf>,
+
+    /// Scanned on a background thread, and shared between clones so we only scan once.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scan: Arc<Scan>,
+}
+
+#[derive(Default)]
+struct Scan {
+    started: AtomicBool,
+    faces: OnceLock<Vec<SystemFace>>,
+
+    /// Called once [`Self::faces`] is set.
+    on_finished: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Scan {
+    fn finish(&self, faces: impl FnOnce() -> Vec<SystemFace>) {
+        self.faces.get_or_init(faces);
+        let callbacks = std::mem::take(&mut *self.on_finished.lock());
+        for callback in callbacks {
+            callback();
+        }
+    }
+}
+
+impl Default for SystemFontFallback {
+    fn default() -> Self {
+        Self::from_directories(default_font_directories())
+    }
+}
+
+impl std::fmt::Debug for SystemFontFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SystemFontFallback")
+            .field("directories", &self.directories)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for SystemFontFallback {
+    fn eq(&self, other: &Self) -> bool {
+        self.directories == other.directories
+    }
+}
+
+impl SystemFontFallback {
+    /// Look for fonts in these directories (and their subdirectories).
+    pub fn from_directories(directories: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            directories: directories.into_iter().map(Into::into).collect(),
+            scan: Default::default(),
+        }
+    }
+
+    /// Block until the directories have been scanned.
+    ///
+    /// Normally the scan runs in the background, so there is no need to call this.
+    /// It is useful in tests, or to have system fonts available in the first frame.
+    pub fn wait_for_scan(&self) {
+        self.start_scan();
+        self.scan.finish(|| scan_directories(&self.directories));
+    }
+
+    /// Call the given function once the directories have been scanned,
+    /// or right away if that has already happened.
+    ///
+    /// This starts the scan if it hasn't started yet.
+    /// The function may be called from the background thread doing the scan.
+    ///
+    /// `egui` uses this to repaint, so that characters that were missing during the scan show up.
+    pub fn on_scan_finished(&self, callback: impl FnOnce() + Send + 'static) {
+        {
+            let mut on_finished = self.scan.on_finished.lock();
+            if self.scan.faces.get().is_none() {
+                on_finished.push(Box::new(callback));
+                drop(on_finished);
+                self.start_scan();
+                return;
+            }
+        }
+        callback();
+    }
+
+    /// Start scanning the directories on a background thread, unless that has already happened.
+    fn start_scan(&self) {
+        if self.scan.started.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        if cfg!(target_arch = "wasm32") {
+            // No threads or file system to look in
+            self.scan.finish(Vec::new);
+            return;
+        }
+
+        let scan = self.scan.clone();
+        let directories = self.directories.clone();
+        let spawned = std::thread::Builder::new()
+            .name("epaint_system_fonts".to_owned())
+            .spawn(move || {
+                scan.finish(|| scan_directories(&directories));
+            });
+        if let Err(_err) = spawned {
+            #[cfg(feature = "log")]
+            log::warn!("Failed to spawn thread for scanning system fonts: {_err}");
+            self.scan.finish(Vec::new);
+        }
+    }
+
+    /// The faces found by the scan, or `None` if it is still running.
+    fn faces(&self) -> Option<&[SystemFace]> {
+        self.scan.faces.get().map(Vec::as_slice)
+    }
+}
+
+/// The standard font directories of the current platform.
+///
+/// On Linux and other free unixes these are the same directories fontconfig looks in by default.
+fn default_font_directories() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut directories = vec![];
+
+    if cfg!(target_os = "macos") {
+        directories.push(PathBuf::from("/System/Library/Fonts"));
+        directories.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = &home {
+            directories.push(home.join("Library/Fonts"));
+        }
+    } else if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        directories.push(PathBuf::from(windir).join("Fonts"));
+        if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+            directories.push(PathBuf::from(local_app_data).join("Microsoft\\Windows\\Fonts"));
+        }
+    } else if cfg!(target_os = "android") {
+        directories.push(PathBuf::from("/system/fonts"));
+    } else if cfg!(unix) {
+        // See https://specifications.freedesktop.org/basedir-spec/latest/
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+        if let Some(data_home) = data_home {
+            directories.push(data_home.join("fonts"));
+        }
+        if let Some(home) = &home {
+            directories.push(home.join(".fonts"));
+        }
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_owned());
+        for data_dir in data_dirs.split(':').filter(|dir| !dir.is_empty()) {
+            directories.push(Path::new(data_dir).join("fonts"));
+        }
+    }
+
+    directories
+}
+
+// ----------------------------------------------------------------------------
+
+/// A font face found on disk, and which characters it covers.
+struct SystemFace {
+    path: PathBuf,
+    index: u32,
+
+    /// Sorted, non-overlapping, inclusive ranges of covered characters.
+    coverage: Vec<(char, char)>,
+}
+
+impl SystemFace {
+    fn covers(&self, c: char) -> bool {
+        let i = self.coverage.partition_point(|&(_, last)| last < c);
+        self.coverage.get(i).is_some_and(|&(first, _)| first <= c)
+    }
+
+    fn name(&self) -> String {
+        format!("{}#{}", self.path.display(), self.index)
+    }
+}
+
+fn scan_directories(directories: &[PathBuf]) -> Vec<SystemFace> {
+    profiling::function_scope!();
+
+    let mut paths = vec![];
+    for directory in directories {
+        find_font_files(directory, 0, &mut paths);
+    }
+
+    // The same file may be reachable from several directories (e.g. via symlinks):
+    let mut seen = ahash::HashSet::default();
+    paths.retain(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+
+    paths.iter().flat_map(|path| scan_file(path)).collect()
+}
+
+fn find_font_files(directory: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    const MAX_DEPTH: usize = 8; // Protect against symlink cycles
+
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| Some(e.ok()?.path())).collect();
+    entries.sort(); // Deterministic fallback order
+
+    for path in entries {
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                find_font_files(&path, depth + 1, paths);
+            }
+        } else if is_font_file(&path) {
+            paths.push(path);
+        }
+    }
+}
+
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["ttf", "otf", "ttc", "otc"]
+                .iter()
+                .any(|font_ext| ext.eq_ignore_ascii_case(font_ext))
+        })
+}
+
+/// All the usable faces in a font file (collections can contain several).
+fn scan_file(path: &Path) -> Vec<SystemFace> {
+    use ab_glyph::Font as _;
+
+    const MAX_FACES: u32 = 256;
+
+    let Ok(bytes) = std::fs::read(path) else {
+        return vec![];
+    };
+
+    let mut faces = vec![];
+    for index in 0..MAX_FACES {
+        let Ok(font) = ab_glyph::FontRef::try_from_slice_and_index(&bytes, index) else {
+            break;
+        };
+
+        let mut chars: Vec<(ab_glyph::GlyphId, char)> = font.codepoint_ids().collect();
+
+        // Skip faces we can't rasterize (e.g. bitmap-only color emoji fonts without `color_glyphs`):
+        let has_outlines = chars
+            .iter()
+            .filter(|(_, c)| !c.is_whitespace())
+            .take(64)
+            .any(|&(glyph_id, _)| font.outline(glyph_id).is_some());
+        #[cfg(feature = "color_glyphs")]
+        let has_outlines = has_outlines || super::color_glyph::has_color_glyphs(&bytes, index);
+        if !has_outlines {
+            continue;
+        }
+
+        chars.sort_by_key(|&(_, c)| c);
+
+        let mut coverage: Vec<(char, char)> = vec![];
+        for (_, c) in chars {
+            match coverage.last_mut() {
+                Some((_, last)) if *last == c || char::from_u32(*last as u32 + 1) == Some(c) => {
+                    *last = c;
+                }
+                _ => coverage.push((c, c)),
+            }
+        }
+
+        faces.push(SystemFace {
+            path: path.to_owned(),
+            index,
+            coverage,
+        });
+    }
+    faces
+}
+
+// ----------------------------------------------------------------------------
+
+/// The faces loaded from [`SystemFontFallback`] for one [`crate::text::FontsImpl`].
+pub(crate) struct SystemFonts {
+    config: SystemFontFallback,
+    font_impl_cache: FontImplCache,
+
+    /// Indices of faces we've already tried loading, and whether it worked.
+    loaded: ahash::HashMap<usize, bool>,
+
+    /// Did we look for a character before the scan was done?
+    missed_while_scanning: bool,
+}
+
+impl SystemFonts {
+    pub fn new(
+        config: SystemFontFallback,
+        atlas: Arc<Mutex<TextureAtlas>>,
+        pixels_per_point: f32,
+    ) -> Self {
+        config.start_scan();
+        Self {
+            config,
+            font_impl_cache: FontImplCache::new(atlas, pixels_per_point, &Default::default()),
+            loaded: Default::default(),
+            missed_while_scanning: false,
+        }
+    }
+
+    /// Returns `true` (once) if the scan finished after [`Self::font_impl`] was called without a result,
+    /// meaning that any characters missing in the meantime should be looked up again.
+    pub fn scan_finished_after_miss(&mut self) -> bool {
+        if self.missed_while_scanning && self.config.faces().is_some() {
+            self.missed_while_scanning = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// A system font face that has a glyph for this character, if any.
+    ///
+    /// Never blocks: returns `None` if the directories are still being scanned.
+    pub fn font_impl(&mut self, scale_in_points: f32, c: char) -> Option<Arc<FontImpl>> {
+        let Some(faces) = self.config.faces() else {
+            self.missed_while_scanning = true;
+            return None;
+        };
+        for (face_index, face) in faces.iter().enumerate() {
+            if !face.covers(c) {
+                continue;
+            }
+            let loaded = *self.loaded.entry(face_index).or_insert_with(|| {
+                let Some(font_data) = load_face(face) else {
+                    return false;
+                };
+                self.font_impl_cache.insert(face.name(), &font_data);
+                true
+            });
+            if loaded {
+                return Some(
+                    self.font_impl_cache
+                        .font_impl(scale_in_points, &face.name()),
+                );
+            }
+        }
+        None
+    }
+}
+
+fn load_face(face: &SystemFace) -> Option<FontData> {
+    let bytes = std::fs::read(&face.path)
+        .inspect_err(|_err| {
+            #[cfg(feature = "log")]
+            log::warn!("Failed to load system font {:?}: {_err}", face.path);
+        })
+        .ok()?;
+
+    // The file may have changed since we scanned it:
+    ab_glyph::FontRef::try_from_slice_and_index(&bytes, face.index).ok()?;
+
+    Some(FontData {
+        font: std::borrow::Cow::Owned(bytes),
+        index: face.index,
+        tweak: Default::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let face = SystemFace {
+            path: PathBuf::new(),
+            index: 0,
+            coverage: vec![('a', 'c'), ('x', 'x')],
+        };
+        assert!(face.covers('a'));
+        assert!(face.covers('b'));
+        assert!(face.covers('c'));
+        assert!(!face.covers('d'));
+        assert!(face.covers('x'));
+        assert!(!face.covers('y'));
+        assert!(!face.covers(' '));
+    }
+
+    #[test]
+    fn test_lookup_while_scanning() {
+        let config = SystemFontFallback::from_directories(Vec::<PathBuf>::new());
+        config.scan.started.store(true, Ordering::Relaxed); // Pretend the scan is running
+        let atlas = Arc::new(Mutex::new(TextureAtlas::new([1024, 64])));
+        let mut system_fonts = SystemFonts::new(config.clone(), atlas, 1.0);
+
+        assert!(!system_fonts.scan_finished_after_miss());
+        assert!(system_fonts.font_impl(14.0, 'a').is_none());
+        assert!(!system_fonts.scan_finished_after_miss(), "Still scanning");
+
+        config.scan.faces.set(vec![]).ok();
+        assert!(system_fonts.scan_finished_after_miss());
+        assert!(!system_fonts.scan_finished_after_miss(), "Only once");
+    }
+
+    /// Contains the default font files, next to some license files.
+    #[cfg(feature = "default_fonts")]
+    const FONT_DIRECTORY: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../epaint_default_fonts/fonts");
+
+    /// The default fonts, without the ones covering emojis.
+    #[cfg(feature = "default_fonts")]
+    fn definitions_without_emojis() -> crate::text::FontDefinitions {
+        let mut definitions = crate::text::FontDefinitions::default();
+        for fonts in definitions.families.values_mut() {
+            fonts.retain(|name| name != "NotoEmoji-Regular" && name != "emoji-icon-font");
+        }
+        definitions
+    }
+
+    #[cfg(feature = "default_fonts")]
+    #[test]
+    fn test_system_fallback() {
+        use crate::text::{FontFamily, FontId, FontsImpl};
+
+        let mut definitions = definitions_without_emojis();
+        let font_id = FontId::new(14.0, FontFamily::Proportional);
+
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions.clone());
+        assert!(!fonts.has_glyph(&font_id, '🐱'));
+
+        let system_fallback = SystemFontFallback::from_directories([FONT_DIRECTORY]);
+        system_fallback.wait_for_scan();
+        definitions.system_fallback = Some(system_fallback);
+        let mut fonts = FontsImpl::new(1.0, 1024, definitions);
+        assert!(fonts.has_glyph(&font_id, 'a'));
+        assert!(fonts.has_glyph(&font_id, '🐱'));
+        assert!(!fonts.has_glyph(&font_id, '\u{10FFFD}'));
+
+        let characters = fonts.font(&font_id).characters();
+        assert!(characters[&'🐱'][0].ends_with("NotoEmoji-Regular.ttf#0"));
+    }
+
+    #[cfg(feature = "default_fonts")]
+    #[test]
+    fn test_missing_glyph_found_after_scan() {
+        use crate::text::{FontFamily, FontId, Fonts};
+
+        let system_fallback = SystemFontFallback::from_directories([FONT_DIRECTORY]);
+        system_fallback.scan.started.store(true, Ordering::Relaxed); // We scan on this thread, below
+
+        let finished = Arc::new(AtomicBool::new(false));
+        system_fallback.on_scan_finished({
+            let finished = finished.clone();
+            move || finished.store(true, Ordering::Relaxed)
+        });
+        assert!(!finished.load(Ordering::Relaxed));
+
+        let mut definitions = definitions_without_emojis();
+        definitions.system_fallback = Some(system_fallback.clone());
+        let fonts = Fonts::new(1.0, 1024, definitions);
+        let font_id = FontId::new(14.0, FontFamily::Proportional);
+        fonts.begin_pass(1.0, 1024);
+        assert!(!fonts.has_glyph(&font_id, '🐱'), "Still scanning");
+
+        system_fallback.wait_for_scan();
+        assert!(finished.load(Ordering::Relaxed));
+
+        // Nothing else changed, but the next pass finds the character:
+        fonts.begin_pass(1.0, 1024);
+        assert!(fonts.has_glyph(&font_id, '🐱'));
+
+        let called = Arc::new(AtomicBool::new(false));
+        system_fallback.on_scan_finished({
+            let called = called.clone();
+            move || called.store(true, Ordering::Relaxed)
+        });
+        assert!(
+            called.load(Ordering::Relaxed),
+            "Called right away once done"
+        );
+    }
+}
@@ -87,9 +87,18 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
 
     // For most of this we ignore the y coordinate:
 
+    let bidi_levels = bidi_levels(&job.text);
+
     let mut paragraphs = vec![Paragraph::from_section_index(0)];
     for (section_index, section) in job.sections.iter().enumerate() {
-        layout_section(fonts, &job, section_index as u32, section, &mut paragraphs);
+        layout_section(
+            fonts,
+            &job,
+            &bidi_levels,
+            section_index as u32,
+            section,
+            &mut paragraphs,
+        );
     }
 
     let point_scale = PointScale::new(fonts.pixels_per_point());
@@ -123,14 +132,49 @@ pub fn layout(fonts: &mut FontsImpl, job: Arc<LayoutJob>) -> Galley {
         }
     }
 
+    if !bidi_levels.is_empty() {
+        for placed_row in &mut rows {
+            reorder_bidi_row(point_scale, placed_row);
+        }
+    }
+
     // Calculate the Y positions and tessellate the text:
     galley_from_rows(point_scale, job, rows, elided)
 }
 
+/// The Unicode bidi embedding level of each byte of the text.
+///
+/// Returns an empty vector if all the text is left-to-right.
+fn bidi_levels(text: &str) -> Vec<u8> {
+    #[cfg(feature = "text_shaping")]
+    {
+        profiling::function_scope!();
+
+        if text.is_ascii() {
+            return vec![];
+        }
+
+        let bidi_info = unicode_bidi::BidiInfo::new(text, None);
+        if bidi_info.has_rtl() {
+            return bidi_info
+                .levels
+                .iter()
+                .map(|level| level.number())
+                .collect();
+        }
+    }
+
+    #[cfg(not(feature = "text_shaping"))]
+    let _ = text;
+
+    vec![]
+}
+
 // Ignores the Y coordinate.
 fn layout_section(
     fonts: &mut FontsImpl,
     job: &LayoutJob,
+    bidi_levels: &[u8],
     section_index: u32,
     section: &LayoutSection,
     out_paragraphs: &mut Vec<Paragraph>,
@@ -139,6 +183,7 @@ fn layout_section(
         leading_space,
         byte_range,
         format,
+        inline_box,
     } = section;
     let font = fonts.font(&format.font_id);
     let line_height = section
@@ -154,18 +199,62 @@ fn layout_section(
 
     paragraph.cursor_x += leading_space;
 
+    let text = &job.text[byte_range.clone()];
+
+    // The bidi level of each char:
+    let levels: Vec<u8> = if bidi_levels.is_empty() {
+        vec![]
+    } else {
+        text.char_indices()
+            .map(|(i, _)| bidi_levels[byte_range.start + i])
+            .collect()
+    };
+
+    if let Some(inline_box) = inline_box {
+        for (i, chr) in text.chars().enumerate() {
+            paragraph.glyphs.push(Glyph {
+                chr,
+                pos: pos2(paragraph.cursor_x, f32::NAN),
+                advance_width: inline_box.size.x,
+                line_height: inline_box.size.y,
+                font_impl_height: inline_box.size.y,
+                font_impl_ascent: inline_box.ascent(),
+                font_height: inline_box.size.y,
+                font_ascent: inline_box.ascent(),
+                uv_rect: Default::default(),
+                bidi_level: levels.get(i).copied().unwrap_or(0),
+                section_index,
+            });
+            paragraph.cursor_x += inline_box.size.x;
+            paragraph.cursor_x = font.round_to_pixel(paragraph.cursor_x);
+        }
+        return;
+    }
+
+    #[cfg(feature = "text_shaping")]
+    let shaped = font.shape(text, &levels);
+    #[cfg(not(feature = "text_shaping"))]
+    let shaped: Vec<Option<(usize, super::font::GlyphInfo)>> = vec![];
+
     let mut last_glyph_id = None;
 
-    for chr in job.text[byte_range.clone()].chars() {
+    for (i, chr) in text.chars().enumerate() {
         if job.break_on_newline && chr == '\n' {
             out_paragraphs.push(Paragraph::from_section_index(section_index));
             paragraph = out_paragraphs.last_mut().unwrap();
             paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
         } else {
-            let (font_impl, glyph_info) = font.font_impl_and_glyph_info(chr);
+            let shaped_glyph = shaped.get(i).copied().flatten();
+            let (font_impl, glyph_info) = match shaped_glyph {
+                Some((font_index, glyph_info)) => (font.font_impl(font_index), glyph_info),
+                None => font.font_impl_and_glyph_info(chr),
+            };
             if let Some(font_impl) = font_impl {
                 if let Some(last_glyph_id) = last_glyph_id {
-                    paragraph.cursor_x += font_impl.pair_kerning(last_glyph_id, glyph_info.id);
+                    if shaped_glyph.is_none() {
+                        // Shaped glyphs already include kerning.
+                        paragraph.cursor_x += font_impl.pair_kerning(last_glyph_id, glyph_info.id);
+                    }
                     paragraph.cursor_x += extra_letter_spacing;
                 }
             }
@@ -180,6 +269,7 @@ fn layout_section(
                 font_height: font.row_height(),
                 font_ascent: font.ascent(),
                 uv_rect: glyph_info.uv_rect,
+                bidi_level: levels.get(i).copied().unwrap_or(0),
                 section_index,
             });
 
@@ -416,6 +506,7 @@ fn replace_last_glyph_with_overflow_character(
             font_height: font.row_height(),
             font_ascent: font.ascent(),
             uv_rect: replacement_glyph_info.uv_rect,
+            bidi_level: last_glyph.bidi_level,
             section_index,
         });
     } else {
@@ -438,6 +529,7 @@ fn replace_last_glyph_with_overflow_character(
             font_height: font.row_height(),
             font_ascent: font.ascent(),
             uv_rect: replacement_glyph_info.uv_rect,
+            bidi_level: 0,
             section_index,
         });
     }
@@ -604,6 +696,75 @@ fn halign_and_justify_row(
     row.size.x = target_max_x - target_min_x;
 }
 
+/// Move the glyphs of a row containing right-to-left text into visual order.
+///
+/// The glyphs stay in logical order in [`Row::glyphs`]; only their x coordinates change.
+///
+/// Ignores the Y coordinate.
+fn reorder_bidi_row(point_scale: PointScale, placed_row: &mut PlacedRow) {
+    if !placed_row.row.has_rtl() {
+        return;
+    }
+
+    let row = Arc::make_mut(&mut placed_row.row);
+    let glyphs = &mut row.glyphs;
+
+    // The space taken up by each glyph, including kerning, letter spacing and justification:
+    let widths: Vec<f32> = (0..glyphs.len())
+        .map(|i| match glyphs.get(i + 1) {
+            Some(next) => next.pos.x - glyphs[i].pos.x,
+            None => glyphs[i].advance_width,
+        })
+        .collect();
+
+    let levels: Vec<u8> = glyphs.iter().map(|glyph| glyph.bidi_level).collect();
+
+    let mut x = glyphs[0].pos.x;
+    for i in visual_order(&levels) {
+        glyphs[i].pos.x = point_scale.round_to_pixel(x);
+        x += widths[i];
+    }
+}
+
+/// Given the bidi embedding levels of some characters in logical order,
+/// returns their indices in left-to-right order, following rule L2 of the Unicode bidi algorithm.
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+
+    let max_level = levels.iter().copied().max().unwrap_or(0);
+    let Some(min_odd_level) = levels.iter().copied().filter(|level| level % 2 == 1).min() else {
+        return order;
+    };
+
+    // From the highest level down to the lowest odd level,
+    // reverse every sequence of characters at that level or higher:
+    for level in (min_odd_level..=max_level).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && level <= levels[order[i]] {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    order
+}
+
+/// The glyphs of the row, sorted from left to right.
+fn glyphs_left_to_right(row: &Row) -> Vec<&Glyph> {
+    let mut glyphs: Vec<&Glyph> = row.glyphs.iter().collect();
+    if row.has_rtl() {
+        glyphs.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+    }
+    glyphs
+}
+
 /// Calculate the Y positions and tessellate the text.
 fn galley_from_rows(
     point_scale: PointScale,
@@ -622,22 +783,48 @@ fn galley_from_rows(
         for glyph in &row.glyphs {
             max_row_height = max_row_height.max(glyph.line_height);
         }
-        max_row_height = point_scale.round_to_pixel(max_row_height);
 
-        // Now position each glyph vertically:
-        for glyph in &mut row.glyphs {
-            let format = &job.sections[glyph.section_index as usize].format;
+        let has_inline_boxes = row.glyphs.iter().any(|glyph| {
+            job.sections[glyph.section_index as usize]
+                .inline_box
+                .is_some()
+        });
 
-            glyph.pos.y = glyph.font_impl_ascent
+        if has_inline_boxes {
+            // Put everything on a common baseline, so the boxes line up with the text:
+            let ascent = row.glyphs.iter().map(|g| g.font_ascent).fold(0.0, f32::max);
+            let descent = row
+                .glyphs
+                .iter()
+                .map(|g| g.line_height - g.font_ascent)
+                .fold(0.0, f32::max);
+            max_row_height = point_scale.round_to_pixel(max_row_height.max(ascent + descent));
+            let baseline = max_row_height - descent;
+
+            for glyph in &mut row.glyphs {
+                glyph.pos.y = baseline - glyph.font_ascent
+                    + glyph.font_impl_ascent
+                    + 0.5 * (glyph.font_height - glyph.font_impl_height);
+                glyph.pos.y = point_scale.round_to_pixel(glyph.pos.y);
+            }
+        } else {
+            max_row_height = point_scale.round_to_pixel(max_row_height);
+
+            // Now position each glyph vertically:
+            for glyph in &mut row.glyphs {
+                let format = &job.sections[glyph.section_index as usize].format;
 
-                // Apply valign to the different in height of the entire row, and the height of this `Font`:
-                + format.valign.to_factor() * (max_row_height - glyph.line_height)
+                glyph.pos.y = glyph.font_impl_ascent
 
-                // When mixing different `FontImpl` (e.g. latin and emojis),
-                // we always center the difference:
-                + 0.5 * (glyph.font_height - glyph.font_impl_height);
+                    // Apply valign to the different in height of the entire row, and the height of this `Font`:
+                    + format.valign.to_factor() * (max_row_height - glyph.line_height)
 
-            glyph.pos.y = point_scale.round_to_pixel(glyph.pos.y);
+                    // When mixing different `FontImpl` (e.g. latin and emojis),
+                    // we always center the difference:
+                    + 0.5 * (glyph.font_height - glyph.font_impl_height);
+
+                glyph.pos.y = point_scale.round_to_pixel(glyph.pos.y);
+            }
         }
 
         placed_row.pos.y = cursor_y;
@@ -727,9 +914,12 @@ fn tessellate_row(
 
     let glyph_index_start = mesh.indices.len();
     let glyph_vertex_start = mesh.vertices.len();
-    tessellate_glyphs(point_scale, job, row, &mut mesh);
+    tessellate_glyphs(point_scale, job, row, &mut mesh, false);
     let glyph_vertex_end = mesh.vertices.len();
 
+    // Color glyphs go outside of `glyph_vertex_range`, so they keep their colors when the text color is overridden:
+    tessellate_glyphs(point_scale, job, row, &mut mesh, true);
+
     if format_summary.any_underline {
         add_row_hline(point_scale, row, &mut mesh, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
@@ -777,7 +967,7 @@ fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh
     let mut run_start = None;
     let mut last_rect = Rect::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in glyphs_left_to_right(row) {
         let format = &job.sections[glyph.section_index as usize].format;
         let color = format.background;
         let rect = glyph.logical_rect();
@@ -805,54 +995,83 @@ fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh
     end_run(run_start.take(), last_rect.right());
 }
 
-fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
+/// Either the regular glyphs, tinted by the text color, or the `colored` ones (e.g. emoji).
+///
+/// The layers of `colored` glyphs that use the text color are painted along with the regular glyphs.
+fn tessellate_glyphs(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    row: &Row,
+    mesh: &mut Mesh,
+    colored: bool,
+) {
     for glyph in &row.glyphs {
         let uv_rect = glyph.uv_rect;
-        if !uv_rect.is_nothing() {
-            let mut left_top = glyph.pos + uv_rect.offset;
-            left_top.x = point_scale.round_to_pixel(left_top.x);
-            left_top.y = point_scale.round_to_pixel(left_top.y);
-
-            let rect = Rect::from_min_max(left_top, left_top + uv_rect.size);
-            let uv = Rect::from_min_max(
-                pos2(uv_rect.min[0] as f32, uv_rect.min[1] as f32),
-                pos2(uv_rect.max[0] as f32, uv_rect.max[1] as f32),
-            );
+        if uv_rect.is_nothing() {
+            continue;
+        }
 
-            let format = &job.sections[glyph.section_index as usize].format;
+        // The layers of a color glyph that are painted in the text color
+        // are stored next to it in the texture, and go beneath it:
+        let uv_offset = if uv_rect.colored == colored {
+            0.0
+        } else if !colored && uv_rect.text_color_layer {
+            (uv_rect.max[0] - uv_rect.min[0]) as f32
+        } else {
+            continue;
+        };
 
-            let color = format.color;
+        let mut left_top = glyph.pos + uv_rect.offset;
+        left_top.x = point_scale.round_to_pixel(left_top.x);
+        left_top.y = point_scale.round_to_pixel(left_top.y);
 
-            if format.italics {
-                let idx = mesh.vertices.len() as u32;
-                mesh.add_triangle(idx, idx + 1, idx + 2);
-                mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+        let rect = Rect::from_min_max(left_top, left_top + uv_rect.size);
+        let uv = Rect::from_min_max(
+            pos2(uv_rect.min[0] as f32, uv_rect.min[1] as f32),
+            pos2(uv_rect.max[0] as f32, uv_rect.max[1] as f32),
+        )
+        .translate(vec2(uv_offset, 0.0));
 
-                let top_offset = rect.height() * 0.25 * Vec2::X;
+        let format = &job.sections[glyph.section_index as usize].format;
 
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_top() + top_offset,
-                    uv: uv.left_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_top() + top_offset,
-                    uv: uv.right_top(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.left_bottom(),
-                    uv: uv.left_bottom(),
-                    color,
-                });
-                mesh.vertices.push(Vertex {
-                    pos: rect.right_bottom(),
-                    uv: uv.right_bottom(),
-                    color,
-                });
-            } else {
-                mesh.add_rect_with_uv(rect, uv, color);
-            }
+        let color = if !colored {
+            format.color
+        } else if format.color == Color32::PLACEHOLDER {
+            Color32::WHITE
+        } else {
+            // Only use the opacity of the text color:
+            Color32::from_white_alpha(format.color.a())
+        };
+
+        if format.italics {
+            let idx = mesh.vertices.len() as u32;
+            mesh.add_triangle(idx, idx + 1, idx + 2);
+            mesh.add_triangle(idx + 2, idx + 1, idx + 3);
+
+            let top_offset = rect.height() * 0.25 * Vec2::X;
+
+            mesh.vertices.push(Vertex {
+                pos: rect.left_top() + top_offset,
+                uv: uv.left_top(),
+                color,
+            });
+            mesh.vertices.push(Vertex {
+                pos: rect.right_top() + top_offset,
+                uv: uv.right_top(),
+                color,
+            });
+            mesh.vertices.push(Vertex {
+                pos: rect.left_bottom(),
+                uv: uv.left_bottom(),
+                color,
+            });
+            mesh.vertices.push(Vertex {
+                pos: rect.right_bottom(),
+                uv: uv.right_bottom(),
+                color,
+            });
+        } else {
+            mesh.add_rect_with_uv(rect, uv, color);
         }
     }
 }
@@ -879,7 +1098,7 @@ fn add_row_hline(
     let mut line_start = None;
     let mut last_right_x = f32::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in glyphs_left_to_right(row) {
         let (stroke, mut y) = stroke_and_y(glyph);
         stroke.round_center_to_pixel(point_scale.pixels_per_point, &mut y);
 
@@ -1140,4 +1359,146 @@ mod tests {
         assert_eq!(row.pos, Pos2::ZERO);
         assert_eq!(row.rect().max.x, row.glyphs.last().unwrap().max_x());
     }
+
+    #[test]
+    fn test_visual_order() {
+        assert_eq!(visual_order(&[0, 0, 0]), vec![0, 1, 2]);
+        assert_eq!(visual_order(&[1, 1, 1]), vec![2, 1, 0]);
+        assert_eq!(visual_order(&[0, 0, 1, 1, 1, 0]), vec![0, 1, 4, 3, 2, 5]);
+        assert_eq!(visual_order(&[1, 1, 2, 2, 1]), vec![4, 2, 3, 1, 0]);
+    }
+
+    #[cfg(feature = "text_shaping")]
+    #[test]
+    fn test_bidi() {
+        use emath::Rangef;
+
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let layout_job = LayoutJob::single_section("ab אבג cd".into(), TextFormat::default());
+        let galley = layout(&mut fonts, layout_job.into());
+        assert_eq!(galley.rows.len(), 1);
+
+        let row = &galley.rows[0];
+        assert_eq!(row.text(), "ab אבג cd", "Glyphs are kept in logical order");
+        assert!(row.has_rtl());
+        assert!(!row.glyphs[2].is_rtl());
+        assert!(row.glyphs[3].is_rtl());
+
+        let x: Vec<f32> = row.glyphs.iter().map(|glyph| glyph.pos.x).collect();
+        assert!(x[0] < x[1] && x[1] < x[2], "Latin is left-to-right");
+        assert!(
+            x[2] < x[5] && x[5] < x[4] && x[4] < x[3],
+            "Hebrew is right-to-left"
+        );
+        assert!(
+            x[3] < x[6] && x[6] < x[7] && x[7] < x[8],
+            "Latin is left-to-right"
+        );
+
+        // Visual cursor movement, through the Hebrew word from its left end:
+        let right = |index| {
+            galley
+                .cursor_right_one_character(&cursor::CCursor::new(index))
+                .index
+        };
+        let left = |index| {
+            galley
+                .cursor_left_one_character(&cursor::CCursor::new(index))
+                .index
+        };
+        assert_eq!(right(2), 5);
+        assert_eq!(right(5), 4);
+        assert_eq!(right(4), 3);
+        assert_eq!(right(3), 7);
+        assert_eq!(left(7), 6);
+        assert_eq!(left(6), 4);
+        assert_eq!(left(0), 0);
+        assert_eq!(right(9), 9);
+
+        // Clicking between ב and ג:
+        assert_eq!(row.char_at(row.glyphs[4].pos.x), 5);
+
+        // Selecting "b א" covers two separate ranges:
+        let x_ranges = row.x_ranges(1..4);
+        assert_eq!(x_ranges.len(), 2);
+        assert_eq!(x_ranges[0], Rangef::new(x[1], row.glyphs[2].max_x()));
+        assert_eq!(x_ranges[1], Rangef::new(x[3], row.glyphs[3].max_x()));
+    }
+
+    #[cfg(feature = "text_shaping")]
+    #[test]
+    fn test_ligature() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let layout_job = LayoutJob::single_section("fi".into(), TextFormat::default());
+        let galley = layout(&mut fonts, layout_job.into());
+
+        // The default proportional font has an "fi" ligature:
+        let glyphs = &galley.rows[0].glyphs;
+        assert_eq!(
+            glyphs.len(),
+            2,
+            "There should still be one glyph per character"
+        );
+        assert!(!glyphs[0].uv_rect.is_nothing());
+        assert!(
+            glyphs[1].uv_rect.is_nothing(),
+            "The ligature is painted by the first character"
+        );
+        assert_eq!(glyphs[0].advance_width, glyphs[1].advance_width);
+    }
+
+    #[test]
+    fn test_inline_box() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_job = LayoutJob::default();
+        layout_job.append("Hello ", 0.0, TextFormat::default());
+        layout_job.append_inline_box(InlineBox::new(vec2(20.0, 30.0)), TextFormat::default());
+        layout_job.append(" world", 0.0, TextFormat::default());
+        layout_job.append_inline_box(
+            InlineBox::new(vec2(10.0, 10.0)).with_descent(2.0),
+            TextFormat::default(),
+        );
+        let galley = layout(&mut fonts, layout_job.into());
+
+        assert_eq!(galley.rows.len(), 1);
+        let row = &galley.rows[0];
+        let baseline = row.glyphs[0].pos.y;
+        assert!(
+            row.glyphs.iter().all(|glyph| glyph.pos.y == baseline),
+            "Everything should be on the same baseline"
+        );
+        assert!(30.0 < row.size.y, "The row should fit the box and the text");
+
+        let boxes = galley.inline_boxes();
+        assert_eq!(boxes.len(), 2);
+
+        let (section_index, rect) = boxes[0];
+        assert_eq!(section_index, 1);
+        assert_eq!(rect.size(), vec2(20.0, 30.0));
+        assert_eq!(rect.bottom(), baseline);
+        assert_eq!(rect.left(), row.glyphs[6].pos.x);
+
+        let (section_index, rect) = boxes[1];
+        assert_eq!(section_index, 3);
+        assert_eq!(rect.size(), vec2(10.0, 10.0));
+        assert_eq!(rect.bottom(), baseline + 2.0);
+    }
+
+    #[test]
+    fn test_inline_box_wrapping() {
+        let mut fonts = FontsImpl::new(1.0, 1024, FontDefinitions::default());
+        let mut layout_job = LayoutJob::default();
+        layout_job.append("Hello\nworld ", 0.0, TextFormat::default());
+        layout_job.append_inline_box(InlineBox::new(vec2(40.0, 10.0)), TextFormat::default());
+        layout_job.wrap.max_width = 50.0;
+        let galley = layout(&mut fonts, layout_job.into());
+
+        assert_eq!(galley.rows.len(), 3);
+        let boxes = galley.inline_boxes();
+        assert_eq!(boxes.len(), 1);
+        let (_, rect) = boxes[0];
+        assert_eq!(rect.left(), 0.0, "The box should be wrapped to a new row");
+        let row = &galley.rows[2];
+        assert!(row.pos.y <= rect.top() && rect.bottom() <= row.pos.y + row.size.y);
+    }
 }
@@ -5,11 +5,14 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use super::{
+    OBJECT_REPLACEMENT_CHAR,
     cursor::{CCursor, LayoutCursor},
     font::UvRect,
 };
 use crate::{Color32, FontId, Mesh, Stroke};
-use emath::{Align, GuiRounding as _, NumExt as _, OrderedFloat, Pos2, Rect, Vec2, pos2, vec2};
+use emath::{
+    Align, GuiRounding as _, NumExt as _, OrderedFloat, Pos2, Rangef, Rect, Vec2, pos2, vec2,
+};
 
 /// Describes the task of laying out text.
 ///
@@ -107,6 +110,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format: TextFormat::simple(font_id, color),
+                inline_box: None,
             }],
             text,
             wrap: TextWrapping {
@@ -126,6 +130,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format,
+                inline_box: None,
             }],
             text,
             break_on_newline: true,
@@ -141,6 +146,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format: TextFormat::simple(font_id, color),
+                inline_box: None,
             }],
             text,
             wrap: Default::default(),
@@ -156,6 +162,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format,
+                inline_box: None,
             }],
             text,
             wrap: Default::default(),
@@ -178,6 +185,25 @@ impl LayoutJob {
             leading_space,
             byte_range,
             format,
+            inline_box: None,
+        });
+    }
+
+    /// Add a box of the given size, placed inline with the text.
+    ///
+    /// This can be used to put e.g. an image or a custom widget in wrapping text.
+    /// The box is represented by [`OBJECT_REPLACEMENT_CHAR`] in the text.
+    /// Use [`Galley::inline_boxes`] to find where it ended up.
+    ///
+    /// The `format` decides e.g. the background, and the font the box is aligned with.
+    pub fn append_inline_box(&mut self, inline_box: InlineBox, format: TextFormat) {
+        let start = self.text.len();
+        self.text.push(OBJECT_REPLACEMENT_CHAR);
+        self.sections.push(LayoutSection {
+            leading_space: 0.0,
+            byte_range: start..self.text.len(),
+            format,
+            inline_box: Some(inline_box),
         });
     }
 
@@ -242,6 +268,13 @@ pub struct LayoutSection {
     pub byte_range: Range<usize>,
 
     pub format: TextFormat,
+
+    /// If set, each character in this section is laid out as a box of this size,
+    /// instead of as a glyph.
+    ///
+    /// See [`LayoutJob::append_inline_box`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inline_box: Option<InlineBox>,
 }
 
 impl std::hash::Hash for LayoutSection {
@@ -251,10 +284,59 @@ impl std::hash::Hash for LayoutSection {
             leading_space,
             byte_range,
             format,
+            inline_box,
         } = self;
         OrderedFloat(*leading_space).hash(state);
         byte_range.hash(state);
         format.hash(state);
+        inline_box.hash(state);
+    }
+}
+
+/// A box placed inline with the text, e.g. for an icon, image or custom widget.
+///
+/// Rows containing inline boxes align all glyphs and boxes on a common baseline.
+///
+/// See [`LayoutJob::append_inline_box`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct InlineBox {
+    /// Width and height, in points.
+    pub size: Vec2,
+
+    /// How far the box extends below the baseline of the text, in points.
+    ///
+    /// The default, `0.0`, puts the bottom of the box on the baseline.
+    pub descent: f32,
+}
+
+impl InlineBox {
+    #[inline]
+    pub fn new(size: Vec2) -> Self {
+        Self { size, descent: 0.0 }
+    }
+
+    /// How far the box extends below the baseline of the text, in points.
+    #[inline]
+    pub fn with_descent(mut self, descent: f32) -> Self {
+        self.descent = descent;
+        self
+    }
+
+    /// The height above the baseline.
+    #[inline]
+    pub fn ascent(&self) -> f32 {
+        self.size.y - self.descent
+    }
+}
+
+impl std::hash::Hash for InlineBox {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let Self { size, descent } = self;
+        OrderedFloat(size.x).hash(state);
+        OrderedFloat(size.y).hash(state);
+        OrderedFloat(*descent).hash(state);
     }
 }
 
@@ -693,8 +775,17 @@ pub struct Glyph {
     pub font_impl_height: f32,
 
     /// Position and size of the glyph in the font texture, in texels.
+    ///
+    /// With text shaping, a cluster of characters (e.g. a ligature) is painted as one image,
+    /// and the other characters of the cluster have an empty `uv_rect`.
     pub uv_rect: UvRect,
 
+    /// The Unicode bidi embedding level of this character.
+    ///
+    /// Even levels are left-to-right, odd levels are right-to-left.
+    /// This is always zero unless the `text_shaping` feature is enabled.
+    pub bidi_level: u8,
+
     /// Index into [`LayoutJob::sections`]. Decides color etc.
     ///
     /// Only used during layout, then set to an invalid value in order to
@@ -719,6 +810,24 @@ impl Glyph {
     pub fn logical_rect(&self) -> Rect {
         Rect::from_min_size(self.pos - vec2(0.0, self.font_ascent), self.size())
     }
+
+    /// Is this character laid out right-to-left (e.g. Arabic or Hebrew)?
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.bidi_level % 2 == 1
+    }
+
+    /// The x coordinate of the cursor placed before this character.
+    ///
+    /// This is the left edge for left-to-right text, and the right edge for right-to-left text.
+    #[inline]
+    pub fn leading_x(&self) -> f32 {
+        if self.is_rtl() {
+            self.max_x()
+        } else {
+            self.pos.x
+        }
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -741,9 +850,28 @@ impl Row {
         self.glyphs.len() + (self.ends_with_newline as usize)
     }
 
+    /// Does this row contain any right-to-left text?
+    ///
+    /// If so, the glyphs are not ordered left-to-right, and the cursor positions must take this into account.
+    #[inline]
+    pub fn has_rtl(&self) -> bool {
+        self.glyphs.iter().any(|glyph| glyph.is_rtl())
+    }
+
     /// Closest char at the desired x coordinate in row-relative coordinates.
     /// Returns something in the range `[0, char_count_excluding_newline()]`.
     pub fn char_at(&self, desired_x: f32) -> usize {
+        if self.has_rtl() {
+            // The glyphs are not sorted by x, so pick the closest cursor position:
+            return (0..=self.char_count_excluding_newline())
+                .min_by(|&a, &b| {
+                    let a = (self.x_offset(a) - desired_x).abs();
+                    let b = (self.x_offset(b) - desired_x).abs();
+                    a.total_cmp(&b)
+                })
+                .unwrap_or_default();
+        }
+
         for (i, glyph) in self.glyphs.iter().enumerate() {
             if desired_x < glyph.logical_rect().center().x {
                 return i;
@@ -752,14 +880,49 @@ impl Row {
         self.char_count_excluding_newline()
     }
 
+    /// The x coordinate of a cursor placed before the character at the given column.
     pub fn x_offset(&self, column: usize) -> f32 {
         if let Some(glyph) = self.glyphs.get(column) {
-            glyph.pos.x
+            glyph.leading_x()
+        } else if let Some(last) = self.glyphs.last().filter(|glyph| glyph.is_rtl()) {
+            // After the last character, which is right-to-left, i.e. on its left side.
+            last.pos.x
         } else {
             self.size.x
         }
     }
 
+    /// The horizontal ranges covered by the characters in the given columns, sorted left-to-right.
+    ///
+    /// For left-to-right text this is a single range, but bidirectional text
+    /// can result in several disjoint ranges. Useful for painting text selection.
+    pub fn x_ranges(&self, columns: Range<usize>) -> Vec<Rangef> {
+        if !self.has_rtl() {
+            return vec![Rangef::new(
+                self.x_offset(columns.start),
+                self.x_offset(columns.end),
+            )];
+        }
+
+        let mut visual_order: Vec<usize> = (0..self.glyphs.len()).collect();
+        visual_order.sort_by(|&a, &b| self.glyphs[a].pos.x.total_cmp(&self.glyphs[b].pos.x));
+
+        let mut ranges: Vec<Rangef> = vec![];
+        let mut previous_selected = false;
+        for i in visual_order {
+            let selected = columns.contains(&i);
+            if selected {
+                let glyph = &self.glyphs[i];
+                match ranges.last_mut() {
+                    Some(last) if previous_selected => last.max = glyph.max_x(),
+                    _ => ranges.push(Rangef::new(glyph.pos.x, glyph.max_x())),
+                }
+            }
+            previous_selected = selected;
+        }
+        ranges
+    }
+
     #[inline]
     pub fn height(&self) -> f32 {
         self.size.y
@@ -795,6 +958,54 @@ impl Galley {
         self.rect.size()
     }
 
+    /// Where the inline boxes ended up (see [`LayoutJob::append_inline_box`]).
+    ///
+    /// Returns the index of the [`LayoutSection`] of each box, and its rect relative to the galley position,
+    /// in the order they appear in the text.
+    /// Boxes that were elided are not included.
+    pub fn inline_boxes(&self) -> Vec<(usize, Rect)> {
+        // The character index of each box:
+        let mut boxes = vec![];
+        let mut char_index = 0;
+        let mut byte_index = 0;
+        for (section_index, section) in self.job.sections.iter().enumerate() {
+            if section.inline_box.is_none() {
+                continue;
+            }
+            let byte_range = &section.byte_range;
+            char_index += self.job.text[byte_index.min(byte_range.start)..byte_range.start]
+                .chars()
+                .count();
+            for chr in self.job.text[byte_range.clone()].chars() {
+                boxes.push((char_index, chr, section_index));
+                char_index += 1;
+            }
+            byte_index = byte_range.end;
+        }
+
+        let mut rects = Vec::with_capacity(boxes.len());
+        let mut boxes = boxes.into_iter().peekable();
+        let mut row_start = 0;
+        for placed_row in &self.rows {
+            let row_end = row_start + placed_row.char_count_excluding_newline();
+            while let Some(&(char_index, chr, section_index)) = boxes.peek() {
+                if row_end <= char_index {
+                    break;
+                }
+                boxes.next();
+                let glyph = char_index
+                    .checked_sub(row_start)
+                    .and_then(|i| placed_row.glyphs.get(i));
+                if let Some(glyph) = glyph.filter(|glyph| glyph.chr == chr) {
+                    let rect = glyph.logical_rect().translate(placed_row.pos.to_vec2());
+                    rects.push((section_index, rect));
+                }
+            }
+            row_start += placed_row.char_count_including_newline();
+        }
+        rects
+    }
+
     pub(crate) fn round_output_to_gui(&mut self) {
         for placed_row in &mut self.rows {
             // Optimization: only call `make_mut` if necessary (can cause a deep clone)
@@ -1085,9 +1296,13 @@ impl Galley {
 
 /// ## Cursor positions
 impl Galley {
-    #[expect(clippy::unused_self)]
+    /// Move the cursor one character to the left, visually.
+    ///
+    /// In right-to-left text this moves the cursor forward in the text.
     pub fn cursor_left_one_character(&self, cursor: &CCursor) -> CCursor {
-        if cursor.index == 0 {
+        if let Some(new_cursor) = self.cursor_move_visually(cursor, false) {
+            new_cursor
+        } else if cursor.index == 0 {
             Default::default()
         } else {
             CCursor {
@@ -1097,13 +1312,85 @@ impl Galley {
         }
     }
 
+    /// Move the cursor one character to the right, visually.
+    ///
+    /// In right-to-left text this moves the cursor backwards in the text.
     pub fn cursor_right_one_character(&self, cursor: &CCursor) -> CCursor {
+        if let Some(new_cursor) = self.cursor_move_visually(cursor, true) {
+            return new_cursor;
+        }
+
         CCursor {
             index: (cursor.index + 1).min(self.end().index),
             prefer_next_row: true, // default to this when navigating. It is more often useful to put cursor at the beginning of a row than at the end.
         }
     }
 
+    /// Move the cursor to the closest cursor position to the left or right of it on the same row,
+    /// or to the start/end of the next/previous row if there is none.
+    ///
+    /// Returns `None` for rows without right-to-left text, where this is the same as moving one character.
+    fn cursor_move_visually(&self, cursor: &CCursor, rightwards: bool) -> Option<CCursor> {
+        let layout_cursor = self.layout_from_cursor(*cursor);
+        let row = self.rows.get(layout_cursor.row)?;
+        if !row.has_rtl() {
+            return None;
+        }
+
+        let num_columns = row.char_count_excluding_newline();
+        let column = layout_cursor.column.at_most(num_columns);
+        let x = row.x_offset(column);
+
+        // Ignore cursor positions that are visually the same as the current one:
+        const MIN_MOVE: f32 = 0.5;
+
+        let closest = (0..=num_columns)
+            .filter(|&c| {
+                let dx = row.x_offset(c) - x;
+                if rightwards {
+                    MIN_MOVE < dx
+                } else {
+                    dx < -MIN_MOVE
+                }
+            })
+            .min_by(|&a, &b| {
+                let dist_a = (row.x_offset(a) - x).abs();
+                let dist_b = (row.x_offset(b) - x).abs();
+                if (dist_a - dist_b).abs() < MIN_MOVE {
+                    // Same visual position: prefer the one closest in the text.
+                    a.abs_diff(column).cmp(&b.abs_diff(column))
+                } else {
+                    dist_a.total_cmp(&dist_b)
+                }
+            });
+
+        let new_layout_cursor = if let Some(column) = closest {
+            LayoutCursor {
+                row: layout_cursor.row,
+                column,
+            }
+        } else if rightwards {
+            if layout_cursor.row + 1 < self.rows.len() {
+                LayoutCursor {
+                    row: layout_cursor.row + 1,
+                    column: 0,
+                }
+            } else {
+                return Some(*cursor);
+            }
+        } else if 0 < layout_cursor.row {
+            let row = layout_cursor.row - 1;
+            LayoutCursor {
+                row,
+                column: self.rows[row].char_count_excluding_newline(),
+            }
+        } else {
+            return Some(*cursor);
+        };
+
+        Some(self.cursor_from_layout(new_layout_cursor))
+    }
+
     pub fn clamp_cursor(&self, cursor: &CCursor) -> CCursor {
         self.cursor_from_layout(self.layout_from_cursor(*cursor))
     }
@@ -1,6 +1,6 @@
 use emath::{Rect, remap_clamp};
 
-use crate::{FontImage, ImageDelta};
+use crate::{Color32, FontImage, ImageDelta};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct Rectu {
@@ -261,6 +261,11 @@ fn resize_to_min_height(image: &mut FontImage, required_height: usize) -> bool {
 
     if image.width() * image.height() > image.pixels.len() {
         image.pixels.resize(image.width() * image.height(), 0.0);
+        if !image.colors.is_empty() {
+            image
+                .colors
+                .resize(image.width() * image.height(), Color32::TRANSPARENT);
+        }
         true
     } else {
         false